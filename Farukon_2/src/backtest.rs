@@ -5,20 +5,23 @@
 
 use anyhow::Context;
 
-use crate::strategy_loader;
+use crate::scheduler;
+use crate::event_journal;
 
 /// Main backtesting controller.
 /// Manages the lifecycle of a single strategy backtest.
-/// Integrates all components: data handler, dynamic strategy, portfolio, and execution engine.
+/// Integrates all components: data handler, strategy, portfolio, and execution engine.
 pub struct Backtest {
     mode: String,   // Operational mode: "Debug", "Optimize", "Visual"
     strategy_settings: farukon_core::settings::StrategySettings,    // Strategy-specific config
     strategy_instruments_info: std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,   // Metadata for all traded instruments
     data_handler: Box<dyn farukon_core::data_handler::DataHandler>, // Source of market data (FlatBuffers or CSV)
-    event_receiver: std::sync::mpsc::Receiver<Box<dyn farukon_core::event::Event>>, // Channel to receive events from strategy
-    dynamic_strategy: Box<strategy_loader::DynamicStratagy>,    // Dynamically loaded strategy library
+    event_queue: farukon_core::event::EventQueue, // Queue shared with all components (events are sent by strategy, portfolio, execution)
+    strategy: Box<dyn farukon_core::strategy::Strategy>,    // Trading logic: a plugin strategy or one compiled into the engine
     portfolio: Box<dyn farukon_core::portfolio::PortfolioHandler>,  // Manages positions, equity, and risk
     execution_handler: Box<dyn farukon_core::execution::ExecutionHandler>,  // Simulates order execution with slippage/commission
+    timer_scheduler: scheduler::TimerScheduler, // Strategy timers (TimerCommand → TimerEvent)
+    event_journal: Option<event_journal::EventJournal>,  // Optional JSONL record of every processed event
 }
 
 impl Backtest {
@@ -29,8 +32,8 @@ impl Backtest {
     /// * `strategy_settings` - Configuration for this specific strategy
     /// * `strategy_instruments_info` - Metadata for all instruments traded by this strategy
     /// * `data_handler` - Abstract interface to market data
-    /// * `event_receiver` - Receiver end of the event channel (events are sent by strategy)
-    /// * `dynamic_strategy` - Loaded dynamic library implementing the trading logic
+    /// * `event_queue` - Event queue shared with all components (events are sent by strategy)
+    /// * `strategy` - The trading logic (see `strategy_loader::create_strategy`)
     /// * `portfolio` - Handles position tracking, equity, and margin
     /// * `execution_handler` - Simulates market execution (fills, slippage, commission)
     pub fn new(
@@ -38,8 +41,8 @@ impl Backtest {
         strategy_settings: &farukon_core::settings::StrategySettings,
         strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
         data_handler: Box<dyn farukon_core::data_handler::DataHandler>,
-        event_receiver: std::sync::mpsc::Receiver<Box<dyn farukon_core::event::Event>>,
-        dynamic_strategy: Box<strategy_loader::DynamicStratagy>,
+        event_queue: farukon_core::event::EventQueue,
+        strategy: Box<dyn farukon_core::strategy::Strategy>,
         portfolio: Box<dyn farukon_core::portfolio::PortfolioHandler>,
         execution_handler: Box<dyn farukon_core::execution::ExecutionHandler>,
     ) -> Self {
@@ -48,89 +51,182 @@ impl Backtest {
             strategy_settings: strategy_settings.clone(),
             strategy_instruments_info: strategy_instruments_info.clone(),
             data_handler,
-            event_receiver,
-            dynamic_strategy,
+            event_queue,
+            strategy,
             portfolio,
             execution_handler,
+            timer_scheduler: scheduler::TimerScheduler::new(strategy_settings),
+            event_journal: None,
         }
     }
 
+    /// Records every processed event of this backtest into `event_journal`.
+    pub fn with_event_journal(mut self, event_journal: event_journal::EventJournal) -> Self {
+        self.event_journal = Some(event_journal);
+        self
+    }
+
     fn process_pending_events(&mut self) -> anyhow::Result<()> {
-        loop {
-            match self.event_receiver.try_recv() {
-                Ok(event_box) => {
-                    match event_box.event_type() {
-                        "MARKET" => {
-                            // Debug: Print current state before strategy runs
-                            if self.mode == "Debug".to_string() {
-                                print!("Start event, {:?}, ", event_box);
-                                for symbol in &self.strategy_settings.symbols {
-                                    print!("{}, {:?}, ", symbol, self.data_handler.get_latest_bar(symbol))
-                                }
-                                println!();
-                                println!("Start_all position, {:?}", self.portfolio.get_all_positions());
-                                println!("Start_all holdings, {:?}", self.portfolio.get_all_holdings());
-                            }
-                        }
-                        "SIGNAL" => {
-                            if self.mode == "Debug".to_string() {
-                                println!("Start event, {:?}, ", event_box);
-                            }
-
-                            // Signal → create order
-                            self.portfolio.update_signal(
-                                event_box.get_signal_event_params().unwrap(),
-                                &self.data_handler,
-                            );
-                            
-                            if self.mode == "Debug".to_string() {
-                                println!("Finish event, {:?}, ", event_box);
-                            }
-                        }
-                        "ORDER" => {
-                            if self.mode == "Debug".to_string() {
-                                println!("Start event, {:?}, ", event_box);
-                            }
-
-                            // Order → simulate execution (slippage, commission)
-                            self.execution_handler.execute_order(
-                                event_box.get_order_event_params().unwrap(),
-                                &self.strategy_instruments_info,
-                                &self.strategy_settings,
-                                &*self.data_handler
-                            )?;
-
-                            if self.mode == "Debug".to_string() {
-                                println!("Finish event, {:?}, ", event_box);
-                            }
-                        }
-                        "FILL" => {
-                            if self.mode == "Debug".to_string() {
-                                println!("Start event, {:?}, ", event_box);
-                            }
-                            
-                            self.portfolio.update_fill(
-                                event_box.get_fill_event_params().unwrap(),
-                                &self.data_handler,
-                            );
-
-                            if self.mode == "Debug".to_string() {
-                                println!("Finish event, {:?}, ", event_box);
-                            }
-                        }
-                        _ => {
-                            println!("Received unknown event type: {}", event_box.event_type());
+        while let Some(event) = self.event_queue.try_recv() {
+            if let Some(event_journal) = self.event_journal.as_mut() {
+                event_journal.record_event(&event, &*self.data_handler, &self.strategy_settings.symbols)?;
+            }
+
+            match &event {
+                farukon_core::event::EngineEvent::Market(_) => {
+                    // Timer commands after this event come from this bar's fills and rejections
+                    self.timer_scheduler.begin_bar();
+
+                    // Debug: Print current state before strategy runs
+                    if self.mode == "Debug".to_string() {
+                        print!("Start event, {:?}, ", event);
+                        for symbol in &self.strategy_settings.symbols {
+                            print!("{}, {:?}, ", symbol, self.data_handler.get_latest_bar(symbol))
                         }
+                        println!();
+                        println!("Start_all position, {:?}", self.portfolio.get_all_positions());
+                        println!("Start_all holdings, {:?}", self.portfolio.get_all_holdings());
                     }
                 }
-                Err(std::sync::mpsc::TryRecvError::Empty) => {
-                    break;
+                farukon_core::event::EngineEvent::Signal(signal_event) => {
+                    if self.mode == "Debug".to_string() {
+                        println!("Start event, {:?}, ", event);
+                    }
+
+                    // Signal → create order
+                    self.portfolio.update_signal(
+                        signal_event,
+                        &self.data_handler,
+                    );
+                    
+                    if self.mode == "Debug".to_string() {
+                        println!("Finish event, {:?}, ", event);
+                    }
                 }
-                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
-                    eprintln!("Event channel disconnected.");
-                    self.data_handler.set_continue_backtest(false);
-                    break;
+                farukon_core::event::EngineEvent::Order(order_event) => {
+                    if self.mode == "Debug".to_string() {
+                        println!("Start event, {:?}, ", event);
+                    }
+
+                    // Order → simulate execution (slippage, commission)
+                    self.execution_handler.execute_order(
+                        order_event,
+                        &self.strategy_instruments_info,
+                        &self.strategy_settings,
+                        &*self.data_handler
+                    )?;
+
+                    if self.mode == "Debug".to_string() {
+                        println!("Finish event, {:?}, ", event);
+                    }
                 }
+                farukon_core::event::EngineEvent::Fill(fill_event) => {
+                    if self.mode == "Debug".to_string() {
+                        println!("Start event, {:?}, ", event);
+                    }
+                    
+                    self.portfolio.update_fill(
+                        fill_event,
+                        &self.data_handler,
+                    );
+
+                    // Fill → notify strategy
+                    if let Some(latest_holdings) = self.portfolio.get_latest_holdings()
+                        && let Err(e) = self.strategy.on_fill(
+                            fill_event,
+                            &*self.data_handler,
+                            self.portfolio.get_current_positions(),
+                            latest_holdings,
+                        )
+                    {
+                        eprintln!("Error in Strategy::on_fill: {}", e);
+                        self.data_handler.set_continue_backtest(false);
+                    }
+
+                    if self.mode == "Debug".to_string() {
+                        println!("Finish event, {:?}, ", event);
+                    }
+                }
+                farukon_core::event::EngineEvent::OrderRejected(rejected_event) => {
+                    if self.mode == "Debug" {
+                        println!("Start event, {:?}, ", event);
+                    }
+
+                    // Rejected signal → notify strategy
+                    if let Some(latest_holdings) = self.portfolio.get_latest_holdings()
+                        && let Err(e) = self.strategy.on_order_rejected(
+                            rejected_event,
+                            &*self.data_handler,
+                            self.portfolio.get_current_positions(),
+                            latest_holdings,
+                        )
+                    {
+                        eprintln!("Error in Strategy::on_order_rejected: {}", e);
+                        self.data_handler.set_continue_backtest(false);
+                    }
+                }
+                farukon_core::event::EngineEvent::MarginCall(margin_call_event) => {
+                    if self.mode == "Debug" {
+                        println!("Start event, {:?}, ", event);
+                    }
+
+                    // Margin call → notify strategy (forced EXIT signals follow in the queue)
+                    if let Some(latest_holdings) = self.portfolio.get_latest_holdings()
+                        && let Err(e) = self.strategy.on_margin_call(
+                            margin_call_event,
+                            &*self.data_handler,
+                            self.portfolio.get_current_positions(),
+                            latest_holdings,
+                        )
+                    {
+                        eprintln!("Error in Strategy::on_margin_call: {}", e);
+                        self.data_handler.set_continue_backtest(false);
+                    }
+                }
+                farukon_core::event::EngineEvent::Timer(timer_command) => {
+                    if self.mode == "Debug" {
+                        println!("Start event, {:?}, ", event);
+                    }
+
+                    // Timer command → register / cancel the strategy timer
+                    let now = self.current_datetime();
+                    self.timer_scheduler.apply(timer_command, now)?;
+                }
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Datetime of the current timeline step: the most recent bar across the strategy's symbols.
+    fn current_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.strategy_settings.symbols
+            .iter()
+            .filter_map(|symbol| self.data_handler.get_latest_bar_datetime(symbol))
+            .max()
+    }
+
+    /// Delivers the strategy timers that came due on the current bar to `on_timer`, in firing order.
+    fn fire_timers(&mut self) -> anyhow::Result<()> {
+        let Some(now) = self.current_datetime() else {
+            return anyhow::Ok(());
+        };
+
+        for timer_event in self.timer_scheduler.advance(now) {
+            if self.mode == "Debug" {
+                println!("Start event, {:?}, ", timer_event);
+            }
+            if let Some(event_journal) = self.event_journal.as_mut() {
+                event_journal.record_timer(&timer_event)?;
+            }
+
+            if let Some(latest_holdings) = self.portfolio.get_latest_holdings() {
+                self.strategy.on_timer(
+                    &timer_event,
+                    &*self.data_handler,
+                    self.portfolio.get_current_positions(),
+                    latest_holdings,
+                )?;
             }
         }
 
@@ -148,7 +244,7 @@ impl Backtest {
     /// On negative capital, stops backtest immediately.
     fn run_backtest(&mut self) -> anyhow::Result<()> {
         // let symbol_list = &self.strategy_settings.symbols;
-        // let mut fill_flag: Option<Box<dyn farukon_core::event::Event>> = None;
+        // let mut fill_flag: Option<farukon_core::event::EngineEvent> = None;
 
         loop {
             // Advance data: load next bar for all symbols
@@ -161,7 +257,17 @@ impl Backtest {
 
             // Run strategy logic on new market data
             if let Some(latest_holdings) = self.portfolio.get_latest_holdings() {
-                if let Err(e) = self.dynamic_strategy.calculate_signals(
+                if let Err(e) = self.strategy.on_bar(
+                    &*self.data_handler,
+                    self.portfolio.get_current_positions(),
+                    latest_holdings,
+                ) {
+                    eprintln!("Error in Strategy::on_bar: {}", e);
+                    self.data_handler.set_continue_backtest(false);
+                    break;
+                }
+
+                if let Err(e) = self.strategy.calculate_signals(
                     &*self.data_handler,
                     self.portfolio.get_current_positions(),
                     latest_holdings,
@@ -173,8 +279,18 @@ impl Backtest {
                 }
             }
 
+            // Fire strategy timers due on this bar
+            if let Err(e) = self.fire_timers() {
+                eprintln!("Error in Strategy::on_timer: {}", e);
+                self.data_handler.set_continue_backtest(false);
+                break;
+            }
+
             // Update portfolio time index (equity, positions, holdings)
             self.portfolio.update_timeindex(&self.data_handler);
+            if let Some(event_journal) = self.event_journal.as_mut() {
+                event_journal.record_time_index(&*self.data_handler, &self.strategy_settings.symbols)?;
+            }
 
             // Risk check: stop if capital becomes negative
             if let Some(holdings) = self.portfolio.get_latest_holdings() {
@@ -234,9 +350,24 @@ impl Backtest {
             println!("Starting backtest simulation...");
         }
 
+        self.strategy.on_start()
+            .context("Strategy on_start failed")?;
+
         self.run_backtest()
             .context("Backtest simulation failed")?;
 
+        if let Some(latest_holdings) = self.portfolio.get_latest_holdings() {
+            self.strategy.on_end(
+                &*self.data_handler,
+                self.portfolio.get_current_positions(),
+                latest_holdings,
+            ).context("Strategy on_end failed")?;
+        }
+
+        if let Some(event_journal) = self.event_journal.as_mut() {
+            event_journal.finish()?;
+        }
+
         if self.mode == "Debug" {
             println!("all_positions: {:#?}", self.portfolio.get_all_positions());
             println!("all_holdings: {:#?}", self.portfolio.get_all_holdings());
@@ -0,0 +1,228 @@
+// Farukon_2_0/src/checkpoint.rs
+
+//! Checkpoints of long optimizations, so that an interrupted run can continue with `--resume`.
+//! The checkpoint directory of a strategy holds:
+//! * `checkpoint.json` - the fingerprint of the strategy settings and the strategy build that started the checkpoint;
+//! * `results.jsonl` - one `OptimizationResult` per line, appended as soon as its backtest ends;
+//! * `ga_state.json` - the Genetic Algorithm state after its last completed generation.
+//!
+//! On resume, parameter sets already in `results.jsonl` are not backtested again, and the Genetic Algorithm
+//! continues from its last generation with the same population, fitness cache and random generator.
+
+use anyhow::Context;
+use std::io::Write;
+
+use crate::hot_reload;
+use crate::result_cache;
+
+const MANIFEST_FILE: &str = "checkpoint.json";
+const RESULTS_FILE: &str = "results.jsonl";
+const GA_STATE_FILE: &str = "ga_state.json";
+
+/// Settings that do not change the results, and may differ between a run and its resume.
+const IGNORED_SETTINGS: [&str; 3] = ["threads", "checkpoint", "result_cache"];
+
+/// Content of `checkpoint.json`.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CheckpointManifest {
+    strategy_name: String,
+    settings_fingerprint: String,
+    /// Content hash of `strategy_path` (or of the engine, for built-in strategies). Empty in older checkpoints.
+    #[serde(default)]
+    strategy_build: String,
+}
+
+/// Checkpoint of the optimization of one strategy.
+#[derive(Debug)]
+pub struct Checkpoint {
+    dir: std::path::PathBuf,
+    /// `results.jsonl`, shared by the threads of the optimization.
+    results_writer: std::sync::Mutex<std::fs::File>,
+    /// Results restored on resume, by `ParameterSet::cache_key`.
+    completed: std::collections::HashMap<String, farukon_core::optimization::OptimizationResult>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint of a strategy.
+    /// Without `resume` any previous checkpoint in the directory is discarded; with it, the completed
+    /// results are loaded, provided the checkpoint was started with the same strategy settings and strategy build.
+    /// # Arguments
+    /// * `strategy_settings` - Settings of the optimized strategy.
+    /// * `checkpoint_settings` - The `checkpoint` block of the strategy.
+    /// * `resume` - `true` to continue the checkpoint of an interrupted run.
+    /// # Returns
+    /// * `anyhow::Result<Checkpoint>` - The checkpoint, or an error if it cannot be read or belongs to other settings or another build.
+    pub fn open(
+        strategy_settings: &farukon_core::settings::StrategySettings,
+        checkpoint_settings: &farukon_core::settings::CheckpointSettings,
+        resume: bool,
+    ) -> anyhow::Result<Self> {
+        let dir = match &checkpoint_settings.path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => std::path::Path::new(&strategy_settings.exit_results_path).join("checkpoint"),
+        };
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create checkpoint directory {}", dir.display()))?;
+
+        let manifest = CheckpointManifest {
+            strategy_name: strategy_settings.strategy_name.clone(),
+            settings_fingerprint: settings_fingerprint(strategy_settings)?,
+            strategy_build: result_cache::strategy_build(strategy_settings)?,
+        };
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let results_path = dir.join(RESULTS_FILE);
+
+        let completed = if resume && manifest_path.exists() {
+            let saved_manifest: CheckpointManifest = serde_json::from_str(
+                &std::fs::read_to_string(&manifest_path)
+                    .with_context(|| format!("Failed to read {}", manifest_path.display()))?,
+            ).with_context(|| format!("Failed to parse {}", manifest_path.display()))?;
+            if saved_manifest.settings_fingerprint != manifest.settings_fingerprint {
+                anyhow::bail!(
+                    "Checkpoint {} was started with other settings of {}; run without --resume to start afresh",
+                    dir.display(),
+                    saved_manifest.strategy_name,
+                );
+            }
+            if saved_manifest.strategy_build != manifest.strategy_build {
+                anyhow::bail!(
+                    "Checkpoint {} was started with another build of {}; run without --resume to start afresh",
+                    dir.display(),
+                    saved_manifest.strategy_name,
+                );
+            }
+            load_results(&results_path)?
+        } else {
+            if resume {
+                println!("No checkpoint in {}: {} starts afresh", dir.display(), strategy_settings.strategy_name);
+            }
+            std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+                .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+            std::fs::File::create(&results_path)
+                .with_context(|| format!("Failed to create {}", results_path.display()))?;
+            let ga_state_path = dir.join(GA_STATE_FILE);
+            if ga_state_path.exists() {
+                std::fs::remove_file(&ga_state_path)
+                    .with_context(|| format!("Failed to remove {}", ga_state_path.display()))?;
+            }
+            std::collections::HashMap::new()
+        };
+
+        let results_file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&results_path)
+            .with_context(|| format!("Failed to open {}", results_path.display()))?;
+
+        anyhow::Ok(Checkpoint {
+            dir,
+            results_writer: std::sync::Mutex::new(results_file),
+            completed,
+        })
+    }
+
+    /// Returns the checkpoint directory.
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    /// Returns the number of results restored from the checkpoint.
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Returns the restored result of a parameter set, if its backtest completed before the interruption.
+    pub fn completed_result(
+        &self,
+        params: &farukon_core::optimization::ParameterSet,
+    ) -> Option<&farukon_core::optimization::OptimizationResult> {
+        self.completed.get(&params.cache_key())
+    }
+
+    /// Appends a completed result to `results.jsonl` and flushes it, so it survives a crash of the run.
+    pub fn record_result(&self, result: &farukon_core::optimization::OptimizationResult) -> anyhow::Result<()> {
+        let line = format!("{}\n", serde_json::to_string(result)?);
+        let mut results_writer = self.results_writer.lock().unwrap();
+        results_writer.write_all(line.as_bytes())
+            .and_then(|_| results_writer.flush())
+            .with_context(|| format!("Failed to write {}", self.dir.join(RESULTS_FILE).display()))
+    }
+
+    /// Saves the Genetic Algorithm state. The file is written aside and renamed, so a crash never leaves a partial state.
+    pub fn save_ga_state(&self, state: &farukon_core::optimization::GAState) -> anyhow::Result<()> {
+        let path = self.dir.join(GA_STATE_FILE);
+        let tmp_path = self.dir.join(format!("{}.tmp", GA_STATE_FILE));
+        std::fs::write(&tmp_path, serde_json::to_string(state)?)
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to replace {}", path.display()))
+    }
+
+    /// Loads the Genetic Algorithm state of the checkpoint, if a generation was completed.
+    pub fn load_ga_state(&self) -> anyhow::Result<Option<farukon_core::optimization::GAState>> {
+        let path = self.dir.join(GA_STATE_FILE);
+        if !path.exists() {
+            return anyhow::Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let state = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        anyhow::Ok(Some(state))
+    }
+
+}
+
+/// Fingerprint of the strategy settings, without the settings in `IGNORED_SETTINGS`.
+fn settings_fingerprint(strategy_settings: &farukon_core::settings::StrategySettings) -> anyhow::Result<String> {
+    // JSON objects keep their keys sorted, so the fingerprint does not depend on the order of the settings file.
+    let mut settings = serde_json::to_value(strategy_settings)?;
+    if let Some(settings) = settings.as_object_mut() {
+        for key in IGNORED_SETTINGS {
+            settings.remove(key);
+        }
+    }
+    anyhow::Ok(hot_reload::content_hash(settings.to_string().as_bytes()))
+}
+
+/// Loads the results of `results.jsonl`, by `ParameterSet::cache_key`.
+fn load_results(
+    results_path: &std::path::Path,
+) -> anyhow::Result<std::collections::HashMap<String, farukon_core::optimization::OptimizationResult>> {
+    let results: Vec<farukon_core::optimization::OptimizationResult> = read_jsonl(results_path)?;
+    anyhow::Ok(
+        results
+            .into_iter()
+            .map(|result| (result.get_parameters().cache_key(), result))
+            .collect()
+    )
+}
+
+/// Reads an append-only JSONL file (missing file: no records).
+/// A run killed while writing leaves a partial last line: it is cut from the file, and its record is lost.
+pub fn read_jsonl<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> anyhow::Result<Vec<T>> {
+    if !path.exists() {
+        return anyhow::Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let complete_len = content.rfind('\n').map_or(0, |newline_pos| newline_pos + 1);
+    if complete_len < content.len() {
+        println!("Dropping the partial last line of {}", path.display());
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_len(complete_len as u64))
+            .with_context(|| format!("Failed to truncate {}", path.display()))?;
+    }
+
+    content[..complete_len]
+        .lines()
+        .enumerate()
+        .filter(|(_line_idx, line)| !line.trim().is_empty())
+        .map(|(line_idx, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse line {} of {}", line_idx + 1, path.display()))
+        })
+        .collect()
+}
@@ -1,23 +1,79 @@
 // Farukon_2_0/src/cli.rs
 
 /// Command-line argument parser using `clap`.
-/// Parses the `--config` flag to locate the strategy configuration file.
+/// Parses the `--config` flag to locate the strategy configuration file,
+/// or one of the utility subcommands (e.g. `convert`).
 #[derive(Debug)]
 pub struct Args {
-    pub config: std::path::PathBuf,
+    /// Path to the settings.json file. Required when no subcommand is given.
+    pub config: Option<std::path::PathBuf>,
+    /// Resumes the optimizations from their checkpoints instead of starting afresh.
+    pub resume: bool,
+    /// Optional utility subcommand.
+    pub command: Option<Command>,
+}
+
+/// Utility subcommands that do not run a backtest.
+#[derive(Debug)]
+pub enum Command {
+    /// Converts `<DATE>;<TIME>;...` CSV/TXT files into FlatBuffers `.bin` + bincode `.idx` files.
+    Convert {
+        /// Source file or directory with `.csv`/`.txt` files.
+        input: std::path::PathBuf,
+        /// Directory where `.bin` and `.idx` files are written.
+        output: std::path::PathBuf,
+        /// Number of threads for directory conversion. Defaults to all logical cores.
+        threads: Option<usize>,
+    },
+    /// Checks `.bin`/`.idx` data integrity and writes a per-symbol data-quality report.
+    /// With `--config`, checks the data of every strategy in the portfolio instead.
+    CheckData {
+        /// Directory with `.bin`/`.idx` files. Ignored when `--config` is given.
+        data_path: Option<String>,
+        /// Symbols to check. Defaults to every `.bin` file in `data_path`.
+        symbols: Vec<String>,
+        /// Directory where reports are written.
+        report_dir: Option<std::path::PathBuf>,
+        /// Instruments file mapping the symbols to their exchanges. Defaults to `instruments_info.json`.
+        instruments_path: Option<std::path::PathBuf>,
+        /// Session calendar file. Defaults to `sessions.json` next to the instruments file.
+        sessions_path: Option<std::path::PathBuf>,
+        /// Number of threads. Defaults to all logical cores.
+        threads: Option<usize>,
+    },
+    /// Recomputes a strategy's metrics from an event journal (needs `--config`).
+    Replay {
+        /// The `.jsonl` journal to replay.
+        journal: std::path::PathBuf,
+        /// Strategy id in the config. Optional when the config has a single strategy.
+        strategy_id: Option<String>,
+    },
+    /// Reports the first entry where two event journals differ.
+    DiffJournals {
+        left: std::path::PathBuf,
+        right: std::path::PathBuf,
+    },
+    /// Lists the strategies exported by strategy libraries.
+    /// Without `--plugin`, lists the libraries of every strategy in `--config`,
+    /// or the strategies compiled into the engine when there is no config either.
+    ListStrategies {
+        /// Strategy library (.so/.dylib/.dll) or Rhai script (.rhai).
+        plugin: Option<String>,
+    },
 }
 
 impl Args {
     /// Parses command-line arguments.
-    /// Expects exactly one required argument: `--config <path>`
+    /// Expects either `--config <path>` or a subcommand.
     /// # Returns
-    /// * `Args` struct with parsed config path
-    /// * Panics if required argument is missing (clap handles this)    
+    /// * `Args` struct with parsed config path and subcommand
+    /// * Panics if required argument is missing (clap handles this)
     pub fn parse() -> Self {
         let matches = clap::Command::new("Farukon")
             .version("2.0.0")
             .author("AndyDar")
             .about("Event-driven Backtester")
+            .subcommand_negates_reqs(true)
             .arg(
                 clap::Arg::new("config")
                 .short('c')
@@ -26,10 +82,166 @@ impl Args {
                 .required(true)
                 .num_args(1),
             )
+            .arg(
+                clap::Arg::new("resume")
+                .long("resume")
+                .help("Resume the optimizations from their checkpoints (strategies with a checkpoint block)")
+                .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(
+                clap::Command::new("check-data")
+                .about("Check .bin/.idx data integrity and write per-symbol data-quality reports")
+                .arg(
+                    clap::Arg::new("data_path")
+                    .short('d')
+                    .long("data-path")
+                    .help("Directory with .bin/.idx files (required unless --config is given)")
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("symbols")
+                    .short('s')
+                    .long("symbols")
+                    .help("Comma-separated symbols to check (default: all .bin files)")
+                    .value_delimiter(',')
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("report_dir")
+                    .short('o')
+                    .long("output")
+                    .help("Output directory for reports (default: <data-path>/data_reports)")
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("instruments")
+                    .long("instruments")
+                    .help("Instruments file mapping symbols to exchanges (default: instruments_info.json)")
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("sessions")
+                    .long("sessions")
+                    .help("Session calendar, so that breaks and closed days are not gaps (default: sessions.json next to the instruments file)")
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("threads")
+                    .short('t')
+                    .long("threads")
+                    .help("Number of threads")
+                    .value_parser(clap::value_parser!(usize))
+                    .num_args(1),
+                ),
+            )
+            .subcommand(
+                clap::Command::new("convert")
+                .about("Convert CSV/TXT bars into FlatBuffers .bin + .idx files")
+                .arg(
+                    clap::Arg::new("input")
+                    .short('i')
+                    .long("input")
+                    .help("Source .csv/.txt file or directory with such files")
+                    .required(true)
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Output directory for .bin and .idx files")
+                    .required(true)
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("threads")
+                    .short('t')
+                    .long("threads")
+                    .help("Number of threads for directory conversion")
+                    .value_parser(clap::value_parser!(usize))
+                    .num_args(1),
+                ),
+            )
+            .subcommand(
+                clap::Command::new("replay")
+                .about("Recompute strategy metrics from an event journal (uses --config)")
+                .arg(
+                    clap::Arg::new("journal")
+                    .short('j')
+                    .long("journal")
+                    .help("Event journal (.jsonl) to replay")
+                    .required(true)
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("strategy")
+                    .short('s')
+                    .long("strategy")
+                    .help("Strategy id in the config (default: the only strategy)")
+                    .num_args(1),
+                ),
+            )
+            .subcommand(
+                clap::Command::new("diff-journals")
+                .about("Find the first entry where two event journals diverge")
+                .arg(
+                    clap::Arg::new("left")
+                    .help("First journal")
+                    .required(true)
+                    .num_args(1),
+                )
+                .arg(
+                    clap::Arg::new("right")
+                    .help("Second journal")
+                    .required(true)
+                    .num_args(1),
+                ),
+            )
+            .subcommand(
+                clap::Command::new("list-strategies")
+                .about("List the strategies exported by a strategy library, used by --config, or compiled into the engine")
+                .arg(
+                    clap::Arg::new("plugin")
+                    .short('p')
+                    .long("plugin")
+                    .help("Strategy library (.so/.dylib/.dll) or Rhai script (.rhai); without it and --config, lists the built-in strategies")
+                    .num_args(1),
+                ),
+            )
             .get_matches();
 
+        let command = match matches.subcommand() {
+            Some(("convert", sub_matches)) => Some(Command::Convert {
+                input: sub_matches.get_one::<String>("input").unwrap().clone().into(),
+                output: sub_matches.get_one::<String>("output").unwrap().clone().into(),
+                threads: sub_matches.get_one::<usize>("threads").copied(),
+            }),
+            Some(("check-data", sub_matches)) => Some(Command::CheckData {
+                data_path: sub_matches.get_one::<String>("data_path").cloned(),
+                symbols: sub_matches.get_many::<String>("symbols").unwrap_or_default().cloned().collect(),
+                report_dir: sub_matches.get_one::<String>("report_dir").map(|path| path.clone().into()),
+                instruments_path: sub_matches.get_one::<String>("instruments").map(|path| path.clone().into()),
+                sessions_path: sub_matches.get_one::<String>("sessions").map(|path| path.clone().into()),
+                threads: sub_matches.get_one::<usize>("threads").copied(),
+            }),
+            Some(("replay", sub_matches)) => Some(Command::Replay {
+                journal: sub_matches.get_one::<String>("journal").unwrap().clone().into(),
+                strategy_id: sub_matches.get_one::<String>("strategy").cloned(),
+            }),
+            Some(("diff-journals", sub_matches)) => Some(Command::DiffJournals {
+                left: sub_matches.get_one::<String>("left").unwrap().clone().into(),
+                right: sub_matches.get_one::<String>("right").unwrap().clone().into(),
+            }),
+            Some(("list-strategies", sub_matches)) => Some(Command::ListStrategies {
+                plugin: sub_matches.get_one::<String>("plugin").cloned(),
+            }),
+            _ => None,
+        };
+
         Args {
-            config: matches.get_one::<String>("config").unwrap().clone().into(),
+            config: matches.get_one::<String>("config").map(|path| path.clone().into()),
+            resume: matches.get_flag("resume"),
+            command,
         }
     }
 
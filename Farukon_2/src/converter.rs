@@ -0,0 +1,226 @@
+// Farukon_2_0/src/converter.rs
+
+//! Built-in CSV/TXT → FlatBuffers converter (`convert` subcommand).
+//! Reads Finam/MetaStock-style `<DATE>;<TIME>;<OPEN>;<HIGH>;<LOW>;<CLOSE>;<VOL>` exports
+//! and writes the `.bin` (OHLCVList FlatBuffer) + `.idx` (bincode `FullIndex`) pair
+//! consumed by `HistoricFlatBuffersDataHandlerZC`.
+//!
+//! Keeping the writer next to the reader guarantees both sides agree on the format.
+
+use anyhow::Context;
+use rayon::prelude::*;
+
+use crate::data_handler;
+use crate::ohlcv_generated;
+
+/// Resample windows stored in `FullIndex::timeframe_index`, keyed like the existing `.idx` files.
+const TIMEFRAME_INDEX_KEYS: &[(&str, u64)] = &[
+    ("1m", 60),
+    ("2m", 120),
+    ("3m", 180),
+    ("4m", 240),
+    ("5m", 300),
+];
+
+/// Converts a single file or every `.csv`/`.txt` file in a directory.
+/// Directory conversion runs in parallel on a dedicated Rayon pool.
+/// # Arguments
+/// * `input` - Source file or directory.
+/// * `output_dir` - Directory for the generated `.bin`/`.idx` files (created if missing).
+/// * `threads` - Number of threads to use. Defaults to all logical cores.
+/// # Returns
+/// * `anyhow::Result<usize>` - Number of converted files.
+pub fn convert_path(
+    input: &std::path::Path,
+    output_dir: &std::path::Path,
+    threads: Option<usize>,
+) -> anyhow::Result<usize> {
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create output directory {}", output_dir.display()))?;
+
+    let files = collect_source_files(input)?;
+    if files.is_empty() {
+        anyhow::bail!("No .csv/.txt files found in {}", input.display());
+    }
+
+    let threads = threads.unwrap_or(num_cpus::get()).max(1);
+    let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()
+        .context("Failed to create custom Rayon thread pool")?;
+
+    let converted: anyhow::Result<Vec<()>> = thread_pool.install(|| {
+        files
+            .par_iter()
+            .map(|file| {
+                let start_time = std::time::Instant::now();
+                let bars_count = convert_file(file, output_dir)?;
+                println!(
+                    "Converted {} ({} bars) in {:.3} seconds",
+                    file.display(),
+                    bars_count,
+                    start_time.elapsed().as_secs_f64(),
+                );
+                anyhow::Ok(())
+            })
+            .collect()
+    });
+
+    anyhow::Ok(converted?.len())
+}
+
+/// Converts one CSV/TXT file into `<output_dir>/<file_stem>.bin` and `<output_dir>/<file_stem>.idx`.
+/// # Returns
+/// * `anyhow::Result<usize>` - Number of bars written.
+pub fn convert_file(
+    csv_file_path: &std::path::Path,
+    output_dir: &std::path::Path,
+) -> anyhow::Result<usize> {
+    let symbol = csv_file_path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name {}", csv_file_path.display()))?;
+
+    let bars = read_csv_bars(csv_file_path)?;
+
+    let bin_file_path = output_dir.join(format!("{}.bin", symbol));
+    let idx_file_path = output_dir.join(format!("{}.idx", symbol));
+
+    std::fs::write(&bin_file_path, build_ohlcv_buffer(&bars))
+        .with_context(|| format!("Failed to write .bin file {}", bin_file_path.display()))?;
+
+    let idx_data = bincode::serialize(&build_full_index(&bars))
+        .with_context(|| format!("Failed to serialize index for {}", symbol))?;
+    std::fs::write(&idx_file_path, idx_data)
+        .with_context(|| format!("Failed to write .idx file {}", idx_file_path.display()))?;
+
+    anyhow::Ok(bars.len())
+}
+
+fn collect_source_files(input: &std::path::Path) -> anyhow::Result<Vec<std::path::PathBuf>> {
+    // A single file is converted as-is; a directory is scanned (non-recursively) for .csv/.txt.
+    if input.is_file() {
+        return anyhow::Ok(vec![input.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(input)
+        .with_context(|| format!("Failed to read input directory {}", input.display()))?
+    {
+        let path = entry?.path();
+        let is_source = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("txt"))
+            .unwrap_or(false);
+
+        if path.is_file() && is_source {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    anyhow::Ok(files)
+}
+
+fn read_csv_bars(csv_file_path: &std::path::Path) -> anyhow::Result<Vec<farukon_core::data_handler::MarketBar>> {
+    // Parses the file, sorts bars chronologically and drops duplicated timestamps (last one wins).
+    let contents = std::fs::read_to_string(csv_file_path)
+        .with_context(|| format!("Failed to read {}", csv_file_path.display()))?;
+    let header = contents.lines().next().unwrap_or_default();
+    let delimiter = if header.contains(';') {
+        b';'
+    } else if header.contains('\t') {
+        b'\t'
+    } else {
+        b','
+    };
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+
+    let mut bars: Vec<farukon_core::data_handler::MarketBar> = Vec::new();
+    for (line_number, result) in csv_reader.deserialize().enumerate() {
+        let record: data_handler::CSVRecord = result
+            .with_context(|| format!("Failed to parse row {} in {}", line_number + 2, csv_file_path.display()))?;
+        bars.push(record.to_market_bar()?);
+    }
+
+    bars.sort_by_key(|bar| bar.datetime);
+    let mut deduplicated: Vec<farukon_core::data_handler::MarketBar> = Vec::with_capacity(bars.len());
+    for bar in bars {
+        match deduplicated.last_mut() {
+            Some(last) if last.datetime == bar.datetime => *last = bar,
+            _ => deduplicated.push(bar),
+        }
+    }
+
+    anyhow::Ok(deduplicated)
+}
+
+fn build_ohlcv_buffer(bars: &[farukon_core::data_handler::MarketBar]) -> Vec<u8> {
+    // Serializes bars into an OHLCVList FlatBuffer root.
+    let mut builder = flatbuffers::FlatBufferBuilder::with_capacity(bars.len() * 64 + 1024);
+
+    let items: Vec<flatbuffers::WIPOffset<ohlcv_generated::OHLCV>> = bars
+        .iter()
+        .map(|bar| ohlcv_generated::OHLCV::create(&mut builder, &ohlcv_generated::OHLCVArgs {
+            timestamp: bar.datetime.timestamp() as u64,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+        }))
+        .collect();
+
+    let items = builder.create_vector(&items);
+    let root = ohlcv_generated::OHLCVList::create(&mut builder, &ohlcv_generated::OHLCVListArgs {
+        items: Some(items),
+    });
+    ohlcv_generated::finish_ohlcvlist_buffer(&mut builder, root);
+
+    builder.finished_data().to_vec()
+}
+
+/// Builds the `.idx` contents for bars in file order. Also used by `data_checker` to cross-check existing indices.
+pub(crate) fn build_full_index(bars: &[farukon_core::data_handler::MarketBar]) -> farukon_core::index::FullIndex {
+    // time_index: one entry per bar.
+    // daily_index: inclusive [start_index, end_index] per calendar date.
+    // timeframe_index: timestamps of bars that open a resample window, per timeframe.
+    let time_index: Vec<farukon_core::index::TimeIndexEntry> = bars
+        .iter()
+        .enumerate()
+        .map(|(index, bar)| farukon_core::index::TimeIndexEntry {
+            timestamp: bar.datetime.timestamp() as u64,
+            index: index as u64,
+        })
+        .collect();
+
+    let mut daily_index: Vec<farukon_core::index::DailyIndexEntry> = Vec::new();
+    for (index, bar) in bars.iter().enumerate() {
+        let date = bar.datetime.format("%Y-%m-%d").to_string();
+        match daily_index.last_mut() {
+            Some(last) if last.date == date => last.end_index = index as u64,
+            _ => daily_index.push(farukon_core::index::DailyIndexEntry {
+                date,
+                start_index: index as u64,
+                end_index: index as u64,
+            }),
+        }
+    }
+
+    let mut timeframe_index: std::collections::HashMap<String, Vec<u64>> = std::collections::HashMap::new();
+    for (key, timeframe_sec) in TIMEFRAME_INDEX_KEYS {
+        let window_starts: Vec<u64> = time_index
+            .iter()
+            .map(|entry| entry.timestamp)
+            .filter(|timestamp| timestamp % timeframe_sec == 0)
+            .collect();
+        timeframe_index.insert(key.to_string(), window_starts);
+    }
+
+    farukon_core::index::FullIndex {
+        time_index,
+        daily_index,
+        timeframe_index,
+    }
+}
@@ -0,0 +1,503 @@
+// Farukon_2_0/src/data_checker.rs
+
+//! Data integrity checker (`check-data` subcommand and optional pre-run check).
+//! Verifies the FlatBuffer `.bin`, cross-checks the `.idx` against the bars and
+//! scans bars for unsorted/duplicate timestamps, broken prices, gaps, volume spikes
+//! and price outliers. Gaps are judged against the session calendar when one is configured.
+//! Results are written as one `;`-separated report per symbol.
+
+use anyhow::Context;
+use rayon::prelude::*;
+
+use crate::converter;
+use crate::ohlcv_generated;
+
+/// Severity of a data issue. Errors make data unusable, warnings need a human look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "ERROR"),
+            Severity::Warning => write!(f, "WARNING"),
+        }
+    }
+}
+
+/// A single finding of the checker.
+#[derive(Debug, Clone)]
+pub struct DataIssue {
+    pub severity: Severity,
+    pub kind: &'static str,
+    pub bar_index: Option<usize>,
+    pub timestamp: Option<u64>,
+    pub details: String,
+}
+
+/// Data-quality report for one symbol.
+#[derive(Debug, Clone)]
+pub struct SymbolReport {
+    pub symbol: String,
+    pub bars_count: usize,
+    pub issues: Vec<DataIssue>,
+}
+
+impl SymbolReport {
+    fn new(symbol: &str) -> Self {
+        SymbolReport {
+            symbol: symbol.to_string(),
+            bars_count: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: &'static str,
+        bar_index: Option<usize>,
+        timestamp: Option<u64>,
+        details: String,
+    ) {
+        self.issues.push(DataIssue { severity, kind, bar_index, timestamp, details });
+    }
+
+    pub fn errors_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+
+    pub fn warnings_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning).count()
+    }
+
+    /// One-line summary with issue counts per kind, e.g. `Si-3.24: 57406 bars, 0 errors, 3 warnings [gap: 3]`.
+    pub fn summary(&self) -> String {
+        let mut counts_by_kind: std::collections::BTreeMap<&'static str, usize> = std::collections::BTreeMap::new();
+        for issue in &self.issues {
+            *counts_by_kind.entry(issue.kind).or_insert(0) += 1;
+        }
+        let kinds: Vec<String> = counts_by_kind
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .collect();
+
+        format!(
+            "{}: {} bars, {} errors, {} warnings [{}]",
+            self.symbol,
+            self.bars_count,
+            self.errors_count(),
+            self.warnings_count(),
+            kinds.join(", "),
+        )
+    }
+
+    /// Writes the report to `<report_dir>/<symbol>_data_report.csv`.
+    /// # Returns
+    /// * `anyhow::Result<std::path::PathBuf>` - Path of the written report.
+    pub fn save(&self, report_dir: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+        std::fs::create_dir_all(report_dir)
+            .with_context(|| format!("Failed to create report directory {}", report_dir.display()))?;
+        let report_path = report_dir.join(format!("{}_data_report.csv", self.symbol));
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_path(&report_path)
+            .with_context(|| format!("Failed to create report {}", report_path.display()))?;
+
+        writer.write_record(["severity", "kind", "bar_index", "datetime", "details"])?;
+        for issue in &self.issues {
+            let datetime = issue.timestamp
+                .and_then(|timestamp| chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp as i64, 0))
+                .map(|datetime| datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+
+            writer.write_record([
+                issue.severity.to_string(),
+                issue.kind.to_string(),
+                issue.bar_index.map(|index| index.to_string()).unwrap_or_default(),
+                datetime,
+                issue.details.clone(),
+            ])?;
+        }
+        writer.flush()?;
+
+        anyhow::Ok(report_path)
+    }
+}
+
+/// Checks a list of symbols in parallel and writes one report per symbol.
+/// # Arguments
+/// * `fbs_dir` - Directory with `.bin`/`.idx` files.
+/// * `symbols` - Symbols (file stems) to check.
+/// * `check_settings` - Thresholds for gaps, spikes and outliers.
+/// * `session_calendar` - Optional session calendar; gaps are then measured in trading minutes.
+/// * `report_dir` - Directory where reports are written.
+/// * `threads` - Number of threads. Defaults to all logical cores.
+/// # Returns
+/// * `anyhow::Result<Vec<SymbolReport>>` - Reports in the order of `symbols`.
+pub fn check_symbols(
+    fbs_dir: &str,
+    symbols: &[String],
+    check_settings: &farukon_core::settings::DataCheckSettings,
+    session_calendar: Option<&farukon_core::sessions::SessionCalendar>,
+    report_dir: &std::path::Path,
+    threads: Option<usize>,
+) -> anyhow::Result<Vec<SymbolReport>> {
+    let threads = threads.unwrap_or(num_cpus::get()).max(1);
+    let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()
+        .context("Failed to create custom Rayon thread pool")?;
+
+    let reports: Vec<SymbolReport> = thread_pool.install(|| {
+        symbols
+            .par_iter()
+            .map(|symbol| {
+                let exchange_calendar = session_calendar.and_then(|calendar| calendar.for_symbol(symbol));
+                check_symbol(fbs_dir, symbol, check_settings, exchange_calendar)
+            })
+            .collect()
+    });
+
+    for report in &reports {
+        let report_path = report.save(report_dir)?;
+        println!("{} -> {}", report.summary(), report_path.display());
+    }
+
+    anyhow::Ok(reports)
+}
+
+/// Lists symbols (file stems of `.bin` files) available in a data directory.
+pub fn list_symbols(fbs_dir: &str) -> anyhow::Result<Vec<String>> {
+    let mut symbols = Vec::new();
+    for entry in std::fs::read_dir(fbs_dir)
+        .with_context(|| format!("Failed to read data directory {}", fbs_dir))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("bin")
+            && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            symbols.push(stem.to_string());
+        }
+    }
+    symbols.sort();
+
+    anyhow::Ok(symbols)
+}
+
+/// Loads the session calendar of a standalone data check (without `--config`).
+/// The instruments file maps the symbols to their exchanges; the calendar is `sessions_path` if given,
+/// otherwise `sessions.json` next to the instruments file. Missing default files are not an error:
+/// the check then runs without a calendar.
+/// # Arguments
+/// * `instruments_path` - Instruments file. Defaults to `instruments_info.json`.
+/// * `sessions_path` - Session calendar file.
+/// * `symbols` - Symbols to check.
+/// # Returns
+/// * `anyhow::Result<Option<SessionCalendar>>` - The calendar, if both files were found.
+pub fn load_session_calendar(
+    instruments_path: Option<&std::path::Path>,
+    sessions_path: Option<&std::path::Path>,
+    symbols: &[String],
+) -> anyhow::Result<Option<farukon_core::sessions::SessionCalendar>> {
+    let default_instruments_path = std::path::Path::new("instruments_info.json");
+    let instruments_file = instruments_path.unwrap_or(default_instruments_path);
+    let sessions_file = sessions_path
+        .map(std::path::Path::to_path_buf)
+        .unwrap_or_else(|| instruments_file.with_file_name("sessions.json"));
+
+    if instruments_path.is_none() && !instruments_file.exists() || sessions_path.is_none() && !sessions_file.exists() {
+        println!("No session calendar found (see --instruments, --sessions): gaps are measured in calendar time");
+        return anyhow::Ok(None);
+    }
+
+    let instruments_info = farukon_core::instruments_info::InstrumentsInfoRegistry::load_file(instruments_file)
+        .with_context(|| format!("Failed to load instruments {}", instruments_file.display()))?;
+    let session_calendar = farukon_core::sessions::SessionCalendar::load_for_symbols(&sessions_file, &instruments_info, symbols)?;
+    for symbol in symbols {
+        if session_calendar.for_symbol(symbol).is_none() {
+            println!("{}: no session calendar for its exchange, gaps are measured in calendar time", symbol);
+        }
+    }
+
+    anyhow::Ok(Some(session_calendar))
+}
+
+/// Runs every check for one symbol. Never fails: load problems are reported as errors.
+/// With an exchange calendar, bars outside sessions are flagged and gaps count only trading minutes;
+/// without one, only intraday gaps are reported.
+pub fn check_symbol(
+    fbs_dir: &str,
+    symbol: &str,
+    check_settings: &farukon_core::settings::DataCheckSettings,
+    exchange_calendar: Option<&farukon_core::sessions::ExchangeCalendar>,
+) -> SymbolReport {
+    let mut report = SymbolReport::new(symbol);
+    let bin_file_path = format!("{}/{}.bin", fbs_dir, symbol);
+    let idx_file_path = format!("{}/{}.idx", fbs_dir, symbol);
+
+    // --- FlatBuffer ---
+    let bin_data = match std::fs::read(&bin_file_path) {
+        Ok(bin_data) => bin_data,
+        Err(e) => {
+            report.push(Severity::Error, "bin_file", None, None, format!("Failed to read {}: {}", bin_file_path, e));
+            return report;
+        }
+    };
+    let bars = match read_bars(&bin_data) {
+        Ok(bars) => bars,
+        Err(e) => {
+            report.push(Severity::Error, "flatbuffer", None, None, format!("{:#}", e));
+            return report;
+        }
+    };
+    report.bars_count = bars.len();
+    if bars.is_empty() {
+        report.push(Severity::Error, "flatbuffer", None, None, "OHLCVList contains no bars".to_string());
+        return report;
+    }
+
+    // --- Bars ---
+    check_bars(&bars, check_settings, exchange_calendar, &mut report);
+
+    // --- Index ---
+    match std::fs::read(&idx_file_path)
+        .with_context(|| format!("Failed to read {}", idx_file_path))
+        .and_then(|idx_data| bincode::deserialize::<farukon_core::index::FullIndex>(&idx_data)
+            .with_context(|| format!("Failed to deserialize {}", idx_file_path)))
+    {
+        Ok(full_index) => check_index(&bars, &full_index, &mut report),
+        Err(e) => report.push(Severity::Error, "index_file", None, None, format!("{:#}", e)),
+    }
+
+    report
+}
+
+fn read_bars(bin_data: &[u8]) -> anyhow::Result<Vec<farukon_core::data_handler::MarketBar>> {
+    // `root_as_ohlcvlist` runs the FlatBuffers verifier, so a truncated or corrupt file fails here.
+    let ohlcv_list = ohlcv_generated::root_as_ohlcvlist(bin_data)
+        .context("FlatBuffer verification failed")?;
+    let items = ohlcv_list.items()
+        .ok_or_else(|| anyhow::anyhow!("OHLCVList has no items vector"))?;
+
+    let mut bars = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(item.timestamp() as i64, 0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid timestamp {} at bar {}", item.timestamp(), index))?;
+        bars.push(farukon_core::data_handler::MarketBar {
+            datetime,
+            open: item.open(),
+            high: item.high(),
+            low: item.low(),
+            close: item.close(),
+            volume: item.volume(),
+            is_filled: false,
+        });
+    }
+
+    anyhow::Ok(bars)
+}
+
+fn check_bars(
+    bars: &[farukon_core::data_handler::MarketBar],
+    check_settings: &farukon_core::settings::DataCheckSettings,
+    exchange_calendar: Option<&farukon_core::sessions::ExchangeCalendar>,
+    report: &mut SymbolReport,
+) {
+    let window = check_settings.rolling_window;
+    let max_gap_sec = check_settings.max_gap_minutes * 60;
+
+    // Rolling sums over the previous `window` bars (the current bar is never part of its own baseline).
+    let mut volumes: std::collections::VecDeque<f64> = std::collections::VecDeque::with_capacity(window + 1);
+    let mut volume_sum = 0.0;
+    let mut returns: std::collections::VecDeque<f64> = std::collections::VecDeque::with_capacity(window + 1);
+    let mut returns_sum = 0.0;
+    let mut returns_sq_sum = 0.0;
+
+    for (index, bar) in bars.iter().enumerate() {
+        let timestamp = bar.datetime.timestamp() as u64;
+        let prices = [bar.open, bar.high, bar.low, bar.close];
+        let prices_valid = prices.iter().all(|price| price.is_finite() && *price > 0.0);
+
+        if !prices_valid {
+            report.push(Severity::Error, "non_positive_price", Some(index), Some(timestamp),
+                format!("O={} H={} L={} C={}", bar.open, bar.high, bar.low, bar.close));
+        }
+        if bar.high < bar.low {
+            report.push(Severity::Error, "high_below_low", Some(index), Some(timestamp),
+                format!("H={} < L={}", bar.high, bar.low));
+        } else if bar.open > bar.high || bar.open < bar.low || bar.close > bar.high || bar.close < bar.low {
+            report.push(Severity::Error, "ohlc_inconsistent", Some(index), Some(timestamp),
+                format!("O={} C={} outside [L={}, H={}]", bar.open, bar.close, bar.low, bar.high));
+        }
+
+        if let Some(exchange_calendar) = exchange_calendar
+            && !exchange_calendar.is_trading_time(bar.datetime)
+        {
+            report.push(Severity::Warning, "outside_session", Some(index), Some(timestamp),
+                "Bar outside trading sessions".to_string());
+        }
+
+        if index > 0 {
+            let prev_bar = &bars[index - 1];
+            let prev_timestamp = prev_bar.datetime.timestamp() as u64;
+            let same_day = bar.datetime.date_naive() == prev_bar.datetime.date_naive();
+
+            if timestamp == prev_timestamp {
+                report.push(Severity::Error, "duplicate_timestamp", Some(index), Some(timestamp),
+                    "Same timestamp as previous bar".to_string());
+            } else if timestamp < prev_timestamp {
+                report.push(Severity::Error, "unsorted_timestamp", Some(index), Some(timestamp),
+                    format!("Earlier than previous bar by {} s", prev_timestamp - timestamp));
+            } else if timestamp - prev_timestamp > max_gap_sec {
+                match exchange_calendar {
+                    Some(exchange_calendar) => {
+                        // Only minutes the exchange was supposed to trade count as missing.
+                        let missing_minutes = exchange_calendar.trading_minutes_between(
+                            prev_bar.datetime + chrono::Duration::minutes(1),
+                            bar.datetime,
+                        );
+                        if missing_minutes as u64 > check_settings.max_gap_minutes {
+                            report.push(Severity::Warning, "gap", Some(index), Some(timestamp),
+                                format!("{} trading min without bars", missing_minutes));
+                        }
+                    },
+                    None if same_day => {
+                        // Overnight gaps are expected; only intraday holes are reported.
+                        report.push(Severity::Warning, "gap", Some(index), Some(timestamp),
+                            format!("{} min without bars", (timestamp - prev_timestamp) / 60));
+                    },
+                    None => {},
+                }
+            }
+
+            let prev_close = prev_bar.close;
+            if prices_valid && prev_close.is_finite() && prev_close > 0.0 {
+                // Price outlier: the bar's largest excursion from the previous close vs. rolling return volatility.
+                // Opening gaps of a new day are legitimate and not judged.
+                if same_day && returns.len() == window {
+                    let mean = returns_sum / window as f64;
+                    let std = (returns_sq_sum / window as f64 - mean * mean).max(0.0).sqrt();
+                    let max_move = (bar.high / prev_close).ln().abs()
+                        .max((bar.low / prev_close).ln().abs());
+                    if std > 0.0 && max_move > check_settings.price_outlier_factor * std {
+                        report.push(Severity::Warning, "price_outlier", Some(index), Some(timestamp),
+                            format!("Move {:.5} vs rolling std {:.5} (prev close {}, H={}, L={})",
+                                max_move, std, prev_close, bar.high, bar.low));
+                    }
+                }
+
+                let close_return = (bar.close / prev_close).ln();
+                returns.push_back(close_return);
+                returns_sum += close_return;
+                returns_sq_sum += close_return * close_return;
+                if returns.len() > window {
+                    let old = returns.pop_front().unwrap_or_default();
+                    returns_sum -= old;
+                    returns_sq_sum -= old * old;
+                }
+            }
+        }
+
+        // Volume spike vs. rolling mean volume.
+        let volume = bar.volume as f64;
+        if volumes.len() == window {
+            let mean_volume = volume_sum / window as f64;
+            if mean_volume > 0.0 && volume > check_settings.volume_spike_factor * mean_volume {
+                report.push(Severity::Warning, "volume_spike", Some(index), Some(timestamp),
+                    format!("Volume {} vs rolling mean {:.1}", bar.volume, mean_volume));
+            }
+        }
+        volumes.push_back(volume);
+        volume_sum += volume;
+        if volumes.len() > window {
+            volume_sum -= volumes.pop_front().unwrap_or_default();
+        }
+    }
+}
+
+fn check_index(
+    bars: &[farukon_core::data_handler::MarketBar],
+    full_index: &farukon_core::index::FullIndex,
+    report: &mut SymbolReport,
+) {
+    // The expected index is rebuilt from the bars exactly as `convert` would write it.
+    let expected_index = converter::build_full_index(bars);
+
+    // time_index
+    if full_index.time_index.len() != expected_index.time_index.len() {
+        report.push(Severity::Error, "time_index", None, None,
+            format!("{} entries for {} bars", full_index.time_index.len(), bars.len()));
+    }
+    let mismatches: Vec<usize> = full_index.time_index
+        .iter()
+        .zip(expected_index.time_index.iter())
+        .enumerate()
+        .filter(|(_, (actual, expected))| actual.index != expected.index || actual.timestamp != expected.timestamp)
+        .map(|(position, _)| position)
+        .collect();
+    if let Some(&first) = mismatches.first() {
+        let actual = &full_index.time_index[first];
+        report.push(Severity::Error, "time_index", Some(first), Some(bars[first].datetime.timestamp() as u64),
+            format!("{} entries disagree with bars, first: index {} timestamp {}",
+                mismatches.len(), actual.index, actual.timestamp));
+    }
+
+    // daily_index
+    if full_index.daily_index.len() != expected_index.daily_index.len() {
+        report.push(Severity::Error, "daily_index", None, None,
+            format!("{} days in index, {} days in bars",
+                full_index.daily_index.len(), expected_index.daily_index.len()));
+    }
+    for (actual, expected) in full_index.daily_index.iter().zip(expected_index.daily_index.iter()) {
+        if actual.date != expected.date || actual.start_index != expected.start_index || actual.end_index != expected.end_index {
+            report.push(Severity::Error, "daily_index", Some(actual.start_index as usize), None,
+                format!("{} [{}, {}] expected {} [{}, {}]",
+                    actual.date, actual.start_index, actual.end_index,
+                    expected.date, expected.start_index, expected.end_index));
+            break;
+        }
+    }
+
+    // timeframe_index
+    let mut timeframe_keys: Vec<&String> = expected_index.timeframe_index.keys().collect();
+    timeframe_keys.sort();
+    for key in timeframe_keys {
+        let expected = &expected_index.timeframe_index[key];
+        match full_index.timeframe_index.get(key) {
+            Some(actual) if actual == expected => {},
+            Some(actual) => report.push(Severity::Error, "timeframe_index", None, None,
+                format!("'{}' has {} window starts, bars give {}", key, actual.len(), expected.len())),
+            None => report.push(Severity::Warning, "timeframe_index", None, None,
+                format!("'{}' is missing", key)),
+        }
+    }
+}
+
+/// Checks the data of one strategy using its `data.check_data` thresholds (or defaults).
+/// Reports go to `report_dir` or `<exit_results_path>/data_reports`.
+/// # Returns
+/// * `anyhow::Result<usize>` - Total number of errors over all symbols.
+pub fn check_strategy_data(
+    strategy_settings: &farukon_core::settings::StrategySettings,
+    report_dir: Option<&std::path::Path>,
+) -> anyhow::Result<usize> {
+    let check_settings = strategy_settings.data.check_data.clone().unwrap_or_default();
+    let report_dir = match report_dir {
+        Some(report_dir) => report_dir.to_path_buf(),
+        None => std::path::Path::new(&strategy_settings.exit_results_path).join("data_reports"),
+    };
+
+    let reports = check_symbols(
+        &strategy_settings.data.data_path,
+        &strategy_settings.symbols,
+        &check_settings,
+        strategy_settings.session_calendar.as_ref(),
+        &report_dir,
+        strategy_settings.threads,
+    )?;
+
+    anyhow::Ok(reports.iter().map(|report| report.errors_count()).sum())
+}
@@ -14,9 +14,10 @@ use rayon::prelude::*;
 use crate:: ohlcv_generated;    // Generated by FlatBuffers compiler
 
 // --- CSV DATA HANDLER (LEGACY, NOT USED IN PRODUCTION) ---
-#[allow(dead_code)]
+/// A single row of a Finam/MetaStock-style `<DATE>;<TIME>;<OPEN>;...` export.
+/// Shared by the legacy CSV handler and the `convert` subcommand.
 #[derive(serde::Deserialize)]
-struct CSVRecord {
+pub(crate) struct CSVRecord {
     #[serde(rename = "<DATE>")]
     date: String,
     #[serde(rename = "<TIME>")]
@@ -33,12 +34,11 @@ struct CSVRecord {
     volume: u64,
 }
 
-#[allow(dead_code)]
 impl CSVRecord {
     /// Converts a CSV record into a MarketBar.
     /// # Errors
     /// * If date/time format is invalid
-    fn to_market_bar(&self) -> anyhow::Result<farukon_core::data_handler::MarketBar> {
+    pub(crate) fn to_market_bar(&self) -> anyhow::Result<farukon_core::data_handler::MarketBar> {
         let naive_date = chrono::NaiveDate::parse_from_str(&self.date, "%Y%m%d")?;
         let naive_time = chrono::NaiveTime::parse_from_str(&self.time, "%H%M%S")?;
         let naive_datetime = chrono::NaiveDateTime::new(naive_date, naive_time);
@@ -51,6 +51,7 @@ impl CSVRecord {
             low: self.low,
             close: self.close,
             volume: self.volume,
+            is_filled: false,
         })
     }
 
@@ -91,6 +92,7 @@ fn align_data_to_common_index(
                         low: last_bar.low,
                         close: last_bar.close,
                         volume: last_bar.volume,
+                        is_filled: true,
                     };
                     aligned_bars.push(padded_bar);
                 } else {
@@ -101,6 +103,7 @@ fn align_data_to_common_index(
                         low: core::f64::NAN,
                         close: core::f64::NAN,
                         volume: 0,
+                        is_filled: true,
                     };
                     aligned_bars.push(placeholder_bar);
                 }
@@ -132,7 +135,7 @@ fn create_combined_datetime_list(
 
 #[allow(dead_code)]
 pub struct HistoricCSVDataHandler {
-    event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+    event_sender: farukon_core::event::EventQueue,
     csv_dir: String,
     symbol_list: Vec<String>,
     symbol_data_iterators: std::collections::HashMap<String, std::vec::IntoIter<farukon_core::data_handler::MarketBar>>,
@@ -143,7 +146,7 @@ pub struct HistoricCSVDataHandler {
 #[allow(dead_code)]
 impl HistoricCSVDataHandler {
     pub fn new(
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        event_sender: farukon_core::event::EventQueue,
         csv_dir: String,
         symbol_list: Vec<String>,
     ) -> anyhow::Result<Self> {
@@ -290,7 +293,7 @@ impl farukon_core::data_handler::DataHandler for HistoricCSVDataHandler {
         if !has_data {
             self.continue_backtest = false;
         } else {
-            if let Err(e) = self.event_sender.send(Box::new(farukon_core::event::MarketEvent::new())) {
+            if let Err(e) = self.event_sender.send(farukon_core::event::MarketEvent::new()) {
                 eprintln!("Error sending MarketEvent: {}", e);
                 self.continue_backtest = false;
             }
@@ -310,7 +313,7 @@ impl farukon_core::data_handler::DataHandler for HistoricCSVDataHandler {
 // --- FLATBUFFERS DATA HANDLER (NON-ZERO-COPY) ---
 #[allow(dead_code)]
 pub struct HistoricFlatBuffersDataHandler {
-    event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+    event_sender: farukon_core::event::EventQueue,
     fbs_dir: String,
     symbol_list: Vec<String>,
     symbol_data_fb: std::collections::HashMap<String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>>,
@@ -323,7 +326,7 @@ pub struct HistoricFlatBuffersDataHandler {
 #[allow(dead_code)]
 impl HistoricFlatBuffersDataHandler {
     pub fn new(
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        event_sender: farukon_core::event::EventQueue,
         fbs_dir: String,
         symbol_list: Vec<String>,
     ) -> anyhow::Result<Self> {
@@ -400,6 +403,7 @@ impl HistoricFlatBuffersDataHandler {
                 low: ohlcv.low(),
                 close: ohlcv.close(),
                 volume: ohlcv.volume(),
+                is_filled: false,
             };
             bars.push(market_bar);
         }
@@ -519,7 +523,7 @@ impl farukon_core::data_handler::DataHandler for HistoricFlatBuffersDataHandler
         if !has_data {
             self.continue_backtest = false;
         } else {
-            if let Err(e) = self.event_sender.send(Box::new(farukon_core::event::MarketEvent::new())) {
+            if let Err(e) = self.event_sender.send(farukon_core::event::MarketEvent::new()) {
                 eprintln!("Error sending MarketEvent: {}", e);
                 self.continue_backtest = false;
             }
@@ -603,6 +607,7 @@ impl FbSymbolIteratorState {
                 low,
                 close: self.aggregated_close,
                 volume: self.aggregated_volume,
+                is_filled: false,
             })
         } else {
             None
@@ -611,111 +616,52 @@ impl FbSymbolIteratorState {
 
 }
 
-#[derive(Debug, Clone)]
-pub struct HistoricFlatBuffersDataHandlerZC {
-    event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+/// Symbol name, memory-mapped OHLCVList and FullIndex of one loaded symbol.
+type LoadedSymbolData = (String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>, std::sync::Arc<farukon_core::index::FullIndex>);
+/// Symbol name, published bars and published bar counts per timeline step.
+type ResampledSymbolData = (String, Vec<farukon_core::data_handler::MarketBar>, Vec<usize>);
+
+/// Immutable market data of one strategy, shared by every backtest of an optimization run.
+/// Holds the memory-mapped `.bin` files, the `.idx` indices, the session-filtered timeline
+/// and the resampled (gap-filled) bar series of each symbol.
+/// Loaded once per strategy; backtests read it through `HistoricFlatBuffersDataHandlerZC::from_store`.
+/// Only `data` settings shape the store, so it is valid for any parameter set of the strategy.
+#[derive(Debug)]
+pub struct MarketDataStore {
     /// Maps symbol names to Arc-wrapped (memory-mapped FlatBuffer data, FlatBuffer root object).
-    symbol_data_fb: std::collections::HashMap<String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>>,
+    /// Kept mapped for the lifetime of the store.
+    _symbol_data_fb: std::collections::HashMap<String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>>,
     /// Unused field, likely for future indexing needs.
     _symbol_indices: std::collections::HashMap<String, std::sync::Arc<farukon_core::index::FullIndex>>,
-    /// The unified timeline of all aggregated timestamps across all symbols, shared across instances.
-    combined_aggregated_datetime_list: std::sync::Arc<Vec<chrono::DateTime<chrono::Utc>>>,
-    /// Maps symbol names to their respective aggregation state machine.
-    symbol_iterator_states: std::collections::HashMap<String, FbSymbolIteratorState>,
-    /// Stores the latest aggregated bars for each symbol, used by the DataHandler trait methods.
-    latest_symbol_data: std::collections::HashMap<String, Vec<farukon_core::data_handler::MarketBar>>,
-    continue_backtest: bool,
-    /// The strategy-specific settings containing data path and target timeframe.
-    strategy_settings: farukon_core::settings::StrategySettings,
+    /// The unified timeline of all aggregated timestamps across all symbols.
+    combined_aggregated_datetime_list: Vec<chrono::DateTime<chrono::Utc>>,
+    /// Resampled bars per symbol, in the order they are published to the strategy.
+    symbol_bars: std::collections::HashMap<String, Vec<farukon_core::data_handler::MarketBar>>,
+    /// Per symbol: number of published bars after each timeline step.
+    /// Differs from `step + 1` only for symbols with the `skip` gap policy.
+    symbol_published_counts: std::collections::HashMap<String, Vec<usize>>,
+    /// Indicator series over `symbol_bars`, computed on first use and shared by every backtest on this store.
+    indicator_cache: farukon_core::indicator_cache::IndicatorCache,
 }
 
-impl HistoricFlatBuffersDataHandlerZC {
-    pub fn new_with_sequential_load(
-        mode: &String,
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
-        strategy_settings: &farukon_core::settings::StrategySettings,
-    ) -> anyhow::Result<Self> {
-        // Sequential loading — loads each symbol one after another.
-        // Slower but uses less memory. Good for single-threaded backtests.
-        let start_time = std::time::Instant::now();
-        let resample = &strategy_settings.data.timeframe;
-        let resample_timeframe_sec = Self::resample_timeframe_sec(resample);
-        let fbs_dir = &strategy_settings.data.data_path;
-        let symbol_list = &strategy_settings.symbols;
-
-        let mode_desc = format!("{} Resample", resample);
-
-        if mode == "Debug" {
-            println!("Starting to load and process FlatBuffer files ({}) from {}...", mode_desc, fbs_dir);
-        }
-
-        let mut loaded_data = Vec::new();
-        for symbol in symbol_list {
-            let result = Self::load_single_symbol(fbs_dir.clone(), symbol.clone())?;
-            loaded_data.push(result);
-        }
-
-        let mut all_aggregated_timestamps: std::collections::BTreeSet<chrono::DateTime<chrono::Utc>> = std::collections::BTreeSet::new();
-        for (symbol, _fb_data, index_data) in &loaded_data {            
-            for time_entry in &index_data.time_index {
-                let raw_timestamp = time_entry.timestamp;
-                let aggregated_window_start = raw_timestamp - (raw_timestamp % resample_timeframe_sec);
-                
-                let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(aggregated_window_start as i64, 0)
-                .ok_or_else(|| anyhow::anyhow!("Invalid timestamp {} in index for symbol {}", aggregated_window_start, symbol))?;
-            all_aggregated_timestamps.insert(datetime);
-            }
-        }
-    
-        let combined_aggregated_datetime_list: Vec<chrono::DateTime<chrono::Utc>> = all_aggregated_timestamps.into_iter().collect();
-        let combined_aggregated_datetime_list = std::sync::Arc::new(combined_aggregated_datetime_list);
-        
-        let mut symbol_data_fb: std::collections::HashMap<String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>> = std::collections::HashMap::new();
-        let mut symbol_indices: std::collections::HashMap<String, std::sync::Arc<farukon_core::index::FullIndex>> = std::collections::HashMap::new();
-        let mut symbol_iterator_states: std::collections::HashMap<String, FbSymbolIteratorState> = std::collections::HashMap::new();
-        let mut latest_symbol_data: std::collections::HashMap<String, Vec<farukon_core::data_handler::MarketBar>> = std::collections::HashMap::new();
-        for (symbol, fb_data, index_data) in loaded_data {
-            symbol_data_fb.insert(symbol.clone(), fb_data);
-            symbol_indices.insert(symbol.clone(), index_data);
-            symbol_iterator_states.insert(symbol.clone(), FbSymbolIteratorState::new());
-            latest_symbol_data.insert(symbol.clone(), Vec::new());
-        }
-
-        let duration = start_time.elapsed();
-
-        if mode == "Debug" {
-            println!(
-                "Finished loading and processing FlatBuffer files ({}) in {:.3} seconds.",
-                mode_desc, duration.as_secs_f64()
-            );
-        }
-
-        anyhow::Ok(HistoricFlatBuffersDataHandlerZC {
-            event_sender,
-            symbol_data_fb,
-            _symbol_indices: symbol_indices,
-            combined_aggregated_datetime_list,
-            symbol_iterator_states,
-            latest_symbol_data,
-            continue_backtest: true,
-            strategy_settings: strategy_settings.clone(),
-        })
-    }
-
-    #[allow(dead_code)]
+impl MarketDataStore {
+    /// Loads and resamples all symbols of a strategy in parallel.
+    /// Uses `strategy_settings.threads` threads (all logical cores if not set).
+    /// # Arguments
+    /// * `mode` - The operational mode (affects verbosity).
+    /// * `strategy_settings` - The strategy settings containing data path, timeframe, sessions and gap policies.
+    /// # Returns
+    /// * `anyhow::Result<std::sync::Arc<MarketDataStore>>` - The shared store.
     pub fn new_with_parallel_load(
-        mode: &String,
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        mode: &str,
         strategy_settings: &farukon_core::settings::StrategySettings,
-    ) -> anyhow::Result<Self> {
+    ) -> anyhow::Result<std::sync::Arc<Self>> {
         // Parallel loading using Rayon — faster for many symbols.
         // Uses custom thread pool to avoid system overload.
         let start_time = std::time::Instant::now();
-        let threads_to_use = strategy_settings.threads.unwrap();
+        let threads_to_use = strategy_settings.threads.unwrap_or(num_cpus::get());
         let resample = &strategy_settings.data.timeframe;
-        let resample_timeframe_sec = Self::resample_timeframe_sec(resample);
         let fbs_dir = &strategy_settings.data.data_path;
-        let symbol_list = &strategy_settings.symbols;
 
         let mode_desc = format!("{} Resample", resample);
 
@@ -724,68 +670,121 @@ impl HistoricFlatBuffersDataHandlerZC {
         }
 
         let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(threads_to_use).build()
-            .context("Failed to create custom Rayon thread pool: {}")?;
+            .context("Failed to create custom Rayon thread pool")?;
 
         let loaded_data: anyhow::Result<Vec<_>> = thread_pool.install( || {
-            symbol_list
+            strategy_settings.symbols
                 .par_iter()
                 .map(|symbol| Self::load_single_symbol(fbs_dir.clone(), symbol.clone()))
                 .collect()
         });
-
         let loaded_data = loaded_data?;
 
-        let mut all_aggregated_timestamps: std::collections::BTreeSet<chrono::DateTime<chrono::Utc>> = std::collections::BTreeSet::new();
-        for (symbol, _fb_data, index_data) in &loaded_data {            
-            for time_entry in &index_data.time_index {
-                let raw_timestamp = time_entry.timestamp;
-                let aggregated_window_start = raw_timestamp - (raw_timestamp % resample_timeframe_sec);
-                
-                let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(aggregated_window_start as i64, 0)
-                .ok_or_else(|| anyhow::anyhow!("Invalid timestamp {} in index for symbol {}", aggregated_window_start, symbol))?;
-            all_aggregated_timestamps.insert(datetime);
-            }
+        let combined_aggregated_datetime_list = Self::build_timeline(&loaded_data, strategy_settings)?;
+
+        let resampled_data: Vec<_> = thread_pool.install(|| {
+            loaded_data
+                .par_iter()
+                .map(|(symbol, fb_data, _index_data)| {
+                    let (bars, published_counts) = Self::resample_symbol(symbol, fb_data, &combined_aggregated_datetime_list, strategy_settings);
+                    (symbol.clone(), bars, published_counts)
+                })
+                .collect()
+        });
+
+        let store = Self::from_loaded_data(loaded_data, resampled_data, combined_aggregated_datetime_list, resample);
+
+        if mode == "Debug" {
+            println!(
+                "Finished loading and processing FlatBuffer files ({}) using {:?} threads in {:.3} seconds.",
+                mode_desc, threads_to_use, start_time.elapsed().as_secs_f64()
+            );
         }
-    
-        let combined_aggregated_datetime_list: Vec<chrono::DateTime<chrono::Utc>> = all_aggregated_timestamps.into_iter().collect();
-        let combined_aggregated_datetime_list = std::sync::Arc::new(combined_aggregated_datetime_list);
-        
+
+        anyhow::Ok(std::sync::Arc::new(store))
+    }
+
+    fn from_loaded_data(
+        loaded_data: Vec<LoadedSymbolData>,
+        resampled_data: Vec<ResampledSymbolData>,
+        combined_aggregated_datetime_list: Vec<chrono::DateTime<chrono::Utc>>,
+        timeframe: &str,
+    ) -> Self {
         let mut symbol_data_fb: std::collections::HashMap<String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>> = std::collections::HashMap::new();
         let mut symbol_indices: std::collections::HashMap<String, std::sync::Arc<farukon_core::index::FullIndex>> = std::collections::HashMap::new();
-        let mut symbol_iterator_states: std::collections::HashMap<String, FbSymbolIteratorState> = std::collections::HashMap::new();
-        let mut latest_symbol_data: std::collections::HashMap<String, Vec<farukon_core::data_handler::MarketBar>> = std::collections::HashMap::new();
         for (symbol, fb_data, index_data) in loaded_data {
             symbol_data_fb.insert(symbol.clone(), fb_data);
-            symbol_indices.insert(symbol.clone(), index_data);
-            symbol_iterator_states.insert(symbol.clone(), FbSymbolIteratorState::new());
-            latest_symbol_data.insert(symbol.clone(), Vec::new());
+            symbol_indices.insert(symbol, index_data);
         }
 
-        let duration = start_time.elapsed();
-
-        if mode == "Debug" {
-            println!(
-                "Finished loading and processing FlatBuffer files ({}) using {:?} threads in {:.3} seconds.",
-                mode_desc, threads_to_use, duration.as_secs_f64()
-            );
+        let mut symbol_bars: std::collections::HashMap<String, Vec<farukon_core::data_handler::MarketBar>> = std::collections::HashMap::new();
+        let mut symbol_published_counts: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
+        for (symbol, bars, published_counts) in resampled_data {
+            symbol_bars.insert(symbol.clone(), bars);
+            symbol_published_counts.insert(symbol, published_counts);
         }
 
-        anyhow::Ok(HistoricFlatBuffersDataHandlerZC {
-            event_sender,
-            symbol_data_fb,
+        MarketDataStore {
+            _symbol_data_fb: symbol_data_fb,
             _symbol_indices: symbol_indices,
             combined_aggregated_datetime_list,
-            symbol_iterator_states,
-            latest_symbol_data,
-            continue_backtest: true,
-            strategy_settings: strategy_settings.clone(),
-        })
+            symbol_bars,
+            symbol_published_counts,
+            indicator_cache: farukon_core::indicator_cache::IndicatorCache::new(timeframe),
+        }
+    }
+
+    /// Number of steps in the combined timeline.
+    pub fn timeline_len(&self) -> usize {
+        self.combined_aggregated_datetime_list.len()
+    }
+
+    /// Bars of `symbol` published after `steps` timeline steps, oldest first.
+    /// # Arguments
+    /// * `symbol` - Symbol name.
+    /// * `steps` - Number of timeline steps already played (0 = nothing published yet).
+    /// # Returns
+    /// * `&[MarketBar]` - Empty for unknown symbols.
+    pub fn published_bars(&self, symbol: &str, steps: usize) -> &[farukon_core::data_handler::MarketBar] {
+        if steps == 0 {
+            return &[];
+        }
+        match (self.symbol_bars.get(symbol), self.symbol_published_counts.get(symbol)) {
+            (Some(bars), Some(published_counts)) => {
+                let count = published_counts.get(steps - 1).copied().unwrap_or(bars.len());
+                &bars[..count]
+            }
+            _ => &[],
+        }
+    }
+
+    /// Returns the value of an indicator at bar `index` of a symbol, from the shared indicator cache.
+    /// # Arguments
+    /// * `symbol` - The symbol.
+    /// * `spec` - The indicator and its parameters.
+    /// * `index` - Index of the bar among all bars of the symbol.
+    /// # Returns
+    /// * `Option<f64>` - The value, or `None` during warm-up or for an unknown symbol.
+    pub fn indicator_value(&self, symbol: &str, spec: &farukon_core::indicator_cache::IndicatorSpec, index: usize) -> Option<f64> {
+        let bars = self.symbol_bars.get(symbol)?;
+        self.indicator_cache.value_at(symbol, spec, bars, index)
+    }
+
+    /// Returns the series of an indicator over all bars of a symbol, from the shared indicator cache.
+    /// # Arguments
+    /// * `symbol` - The symbol.
+    /// * `spec` - The indicator and its parameters.
+    /// # Returns
+    /// * `Option<Series>` - One value per bar (NaN during warm-up), or `None` for an unknown symbol.
+    pub fn indicator_series(&self, symbol: &str, spec: &farukon_core::indicator_cache::IndicatorSpec) -> Option<farukon_core::indicator_cache::Series> {
+        let bars = self.symbol_bars.get(symbol)?;
+        Some(self.indicator_cache.series(symbol, spec, bars))
     }
 
     fn load_single_symbol(
         fbs_dir: String,
         symbol: String,
-    ) -> anyhow::Result<(String, std::sync::Arc<(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>)>, std::sync::Arc<farukon_core::index::FullIndex>)> {
+    ) -> anyhow::Result<LoadedSymbolData> {
         // Loads one symbol’s .bin and .idx files.
         // Uses memory mapping (mmap) for zero-copy access.
         // Returns symbol name, memory-mapped OHLCVList, and FullIndex.
@@ -816,22 +815,77 @@ impl HistoricFlatBuffersDataHandlerZC {
         anyhow::Ok((symbol.clone(), fb_data, index_data))
     }
 
-    fn get_next_bar(&mut self, symbol: &str) -> Option<farukon_core::data_handler::MarketBar> {
-        // Zero-copy, on-demand resampling during backtest.
-        // Uses .idx file to find target time window.
-        // Aggregates raw bars into desired timeframe (e.g., 1min → 5min) without pre-loading.
-        let resample_timeframe = Self::resample_timeframe_sec(
-            &self.strategy_settings.data.timeframe);
-        let iterator_state = self.symbol_iterator_states.get_mut(symbol)?;
-        if iterator_state.current_aggregated_index_in_timeline >= self.combined_aggregated_datetime_list.len() {
+    fn build_timeline(
+        loaded_data: &[LoadedSymbolData],
+        strategy_settings: &farukon_core::settings::StrategySettings,
+    ) -> anyhow::Result<Vec<chrono::DateTime<chrono::Utc>>> {
+        // Builds the union of aggregated window starts across all symbols.
+        let resample_timeframe_sec = Self::resample_timeframe_sec(&strategy_settings.data.timeframe);
+
+        let mut all_aggregated_timestamps: std::collections::BTreeSet<chrono::DateTime<chrono::Utc>> = std::collections::BTreeSet::new();
+        for (symbol, _fb_data, index_data) in loaded_data {
+            for time_entry in &index_data.time_index {
+                let raw_timestamp = time_entry.timestamp;
+                // Bars outside the selected sessions (data.session_filter) never create a timeline slot
+                if strategy_settings.data.session_filter.is_some() {
+                    let raw_datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(raw_timestamp as i64, 0)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid timestamp {} in index for symbol {}", raw_timestamp, symbol))?;
+                    if !farukon_core::sessions::is_in_selected_sessions(strategy_settings, symbol, raw_datetime) {
+                        continue;
+                    }
+                }
+                let aggregated_window_start = raw_timestamp - (raw_timestamp % resample_timeframe_sec);
+
+                let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(aggregated_window_start as i64, 0)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid timestamp {} in index for symbol {}", aggregated_window_start, symbol))?;
+                all_aggregated_timestamps.insert(datetime);
+            }
+        }
+
+        anyhow::Ok(all_aggregated_timestamps.into_iter().collect())
+    }
+
+    fn resample_symbol(
+        symbol: &str,
+        fb_data: &(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>),
+        combined_aggregated_datetime_list: &[chrono::DateTime<chrono::Utc>],
+        strategy_settings: &farukon_core::settings::StrategySettings,
+    ) -> (Vec<farukon_core::data_handler::MarketBar>, Vec<usize>) {
+        // Walks the whole timeline once and returns the bars to publish plus,
+        // for every timeline step, how many of them are published by then.
+        let skip_filled = strategy_settings.data.gap_policy_for(symbol) == farukon_core::settings::GapPolicy::Skip;
+        let mut iterator_state = FbSymbolIteratorState::new();
+        let mut bars: Vec<farukon_core::data_handler::MarketBar> = Vec::with_capacity(combined_aggregated_datetime_list.len());
+        let mut published_counts: Vec<usize> = Vec::with_capacity(combined_aggregated_datetime_list.len());
+
+        while let Some(bar) = Self::next_resampled_bar(&mut iterator_state, symbol, fb_data, combined_aggregated_datetime_list, strategy_settings) {
+            // Skip policy: the latest bar stays the last real one
+            if !(bar.is_filled && skip_filled) {
+                bars.push(bar);
+            }
+            published_counts.push(bars.len());
+        }
+
+        (bars, published_counts)
+    }
+
+    fn next_resampled_bar(
+        iterator_state: &mut FbSymbolIteratorState,
+        symbol: &str,
+        fb_data: &(memmap2::Mmap, ohlcv_generated::OHLCVList<'static>),
+        combined_aggregated_datetime_list: &[chrono::DateTime<chrono::Utc>],
+        strategy_settings: &farukon_core::settings::StrategySettings,
+    ) -> Option<farukon_core::data_handler::MarketBar> {
+        // Uses the combined timeline to find the target time window.
+        // Aggregates raw bars into desired timeframe (e.g., 1min → 5min).
+        let resample_timeframe = Self::resample_timeframe_sec(&strategy_settings.data.timeframe);
+        if iterator_state.current_aggregated_index_in_timeline >= combined_aggregated_datetime_list.len() {
             return None;
         }
 
-        let target_datetime = self.combined_aggregated_datetime_list[iterator_state.current_aggregated_index_in_timeline];
+        let target_datetime = combined_aggregated_datetime_list[iterator_state.current_aggregated_index_in_timeline];
         let target_timestamp = target_datetime.timestamp() as u64;
 
-        let fb_data_arc = self.symbol_data_fb.get(symbol)?;
-        let fb_data = &**fb_data_arc;
         let ohlcv_list = &fb_data.1;
         let bars_vector = ohlcv_list.items().unwrap_or_default();
 
@@ -843,6 +897,15 @@ impl HistoricFlatBuffersDataHandlerZC {
             let raw_bar_timestamp =current_raw_bar.timestamp();
 
             if raw_bar_timestamp >= target_timestamp && raw_bar_timestamp <= target_window_end {
+                // Skip raw bars outside the selected sessions (data.session_filter)
+                if strategy_settings.data.session_filter.is_some() {
+                    let raw_bar_datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(raw_bar_timestamp as i64, 0)
+                        .unwrap_or_default();
+                    if !farukon_core::sessions::is_in_selected_sessions(strategy_settings, symbol, raw_bar_datetime) {
+                        iterator_state.next_raw_bar_index_in_vector += 1;
+                        continue;
+                    }
+                }
                 if iterator_state.aggregated_open.is_none() {
                     iterator_state.aggregated_open = Some(current_raw_bar.open());
                 }
@@ -864,14 +927,29 @@ impl HistoricFlatBuffersDataHandlerZC {
             final_bar = aggregated_bar.clone();
             iterator_state.last_known_bar_cache = Some(aggregated_bar);
         } else {
+            // No raw bars in this window: fill according to the symbol's gap policy.
+            // `Skip` bars are built like `ForwardFill` and dropped in `resample_symbol`.
+            let gap_policy = strategy_settings.data.gap_policy_for(symbol);
             if let Some(last_bar) = &iterator_state.last_known_bar_cache {
-                final_bar = farukon_core::data_handler::MarketBar {
-                    datetime: target_datetime,
-                    open: last_bar.open,
-                    high: last_bar.high,
-                    low: last_bar.low,
-                    close: last_bar.close,
-                    volume: 0,
+                final_bar = match gap_policy {
+                    farukon_core::settings::GapPolicy::Synthetic => farukon_core::data_handler::MarketBar {
+                        datetime: target_datetime,
+                        open: last_bar.close,
+                        high: last_bar.close,
+                        low: last_bar.close,
+                        close: last_bar.close,
+                        volume: 0,
+                        is_filled: true,
+                    },
+                    farukon_core::settings::GapPolicy::ForwardFill | farukon_core::settings::GapPolicy::Skip => farukon_core::data_handler::MarketBar {
+                        datetime: target_datetime,
+                        open: last_bar.open,
+                        high: last_bar.high,
+                        low: last_bar.low,
+                        close: last_bar.close,
+                        volume: 0,
+                        is_filled: true,
+                    },
                 };
             } else {
                 final_bar = farukon_core::data_handler::MarketBar {
@@ -881,6 +959,7 @@ impl HistoricFlatBuffersDataHandlerZC {
                     low: std::f64::NAN,
                     close: std::f64::NAN,
                     volume: 0,
+                    is_filled: true,
                 };
             }
         }
@@ -909,25 +988,49 @@ impl HistoricFlatBuffersDataHandlerZC {
 
 }
 
+/// Cursor over a shared `MarketDataStore`.
+/// Creating one is cheap: no file access, no resampling — only an `Arc` clone.
+#[derive(Debug, Clone)]
+pub struct HistoricFlatBuffersDataHandlerZC {
+    event_sender: farukon_core::event::EventQueue,
+    /// Shared, immutable market data of the strategy.
+    market_data_store: std::sync::Arc<MarketDataStore>,
+    /// Number of timeline steps already published.
+    steps_published: usize,
+    continue_backtest: bool,
+}
+
+impl HistoricFlatBuffersDataHandlerZC {
+    /// Creates a cursor positioned before the first bar of `market_data_store`.
+    /// # Arguments
+    /// * `market_data_store` - Data loaded once per strategy.
+    /// * `event_sender` - Channel for MARKET events.
+    /// # Returns
+    /// * `HistoricFlatBuffersDataHandlerZC` - The data handler for one backtest.
+    pub fn from_store(
+        market_data_store: std::sync::Arc<MarketDataStore>,
+        event_sender: farukon_core::event::EventQueue,
+    ) -> Self {
+        HistoricFlatBuffersDataHandlerZC {
+            event_sender,
+            market_data_store,
+            steps_published: 0,
+            continue_backtest: true,
+        }
+    }
+
+}
+
 impl farukon_core::data_handler::DataHandler for HistoricFlatBuffersDataHandlerZC {
     // Standard DataHandler interface — used by Backtest and Strategy.
     fn get_latest_bar(&self, symbol: &str) -> Option<&farukon_core::data_handler::MarketBar> {
-        self.latest_symbol_data.get(symbol)?.last()
+        self.market_data_store.published_bars(symbol, self.steps_published).last()
     }
 
     fn get_latest_bars(&self, symbol: &str, n: usize) -> Vec<&farukon_core::data_handler::MarketBar> {
-        match self.latest_symbol_data.get(symbol) {
-            Some(bars) => {
-                let len = bars.len();
-                if len == 0 {
-                    vec![]
-                } else {
-                    let start = if n > len { 0 } else { len - n };
-                    bars[start..].iter().collect()
-                }
-            }
-            None => vec![]
-        }
+        let bars = self.market_data_store.published_bars(symbol, self.steps_published);
+        let start = bars.len().saturating_sub(n);
+        bars[start..].iter().collect()
     }
 
     fn get_latest_bar_datetime(&self, symbol: &str) -> Option<chrono::DateTime<chrono::offset::Utc>> {
@@ -965,29 +1068,25 @@ impl farukon_core::data_handler::DataHandler for HistoricFlatBuffersDataHandlerZ
             .collect()
     }
 
-    fn update_bars(&mut self) {
-        // Advances all symbols by one aggregated bar.
-        // Sends MARKET event when at least one bar is available.
-        let mut has_data = false;
-        let symbols:Vec<String> = self.strategy_settings.symbols.clone();
+    fn get_indicator_value(&self, symbol: &str, spec: &farukon_core::indicator_cache::IndicatorSpec, shift: usize) -> Option<f64> {
+        // The published bars are a prefix of the store's bars, so the latest one has the same index in both.
+        let published = self.market_data_store.published_bars(symbol, self.steps_published).len();
+        let index = published.checked_sub(shift + 1)?;
+        self.market_data_store.indicator_value(symbol, spec, index)
+    }
 
-        for symbol in &symbols {
-            if let Some(bar) = self.get_next_bar(symbol) {
-                has_data = true;
-                self.latest_symbol_data
-                    .get_mut(symbol)
-                    .unwrap()
-                    .push(bar);
-            }
+    fn update_bars(&mut self) {
+        // Advances the cursor by one timeline step.
+        // Sends MARKET event while the timeline is not exhausted.
+        if self.steps_published >= self.market_data_store.timeline_len() {
+            self.continue_backtest = false;
+            return;
         }
 
-        if !has_data {
+        self.steps_published += 1;
+        if let Err(e) = self.event_sender.send(farukon_core::event::MarketEvent::new()) {
+            eprintln!("Error sending MarketEvent: {}", e);
             self.continue_backtest = false;
-        } else {
-            if let Err(e) = self.event_sender.send(Box::new(farukon_core::event::MarketEvent::new())) {
-                eprintln!("Error sending MarketEvent: {}", e);
-                self.continue_backtest = false;
-            }
         }
     }
 
@@ -999,4 +1098,4 @@ impl farukon_core::data_handler::DataHandler for HistoricFlatBuffersDataHandlerZ
         self.continue_backtest = value;
     }
 
-}
\ No newline at end of file
+}
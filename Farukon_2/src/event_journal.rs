@@ -0,0 +1,471 @@
+// Farukon_2_0/src/event_journal.rs
+
+//! Event journal: records every MARKET, SIGNAL, ORDER and FILL of a backtest as JSONL.
+//! Each line is one `JournalEntry` with a sequence number and a timestamp.
+//!
+//! The journal is self-contained: MARKET entries carry the bars published on that step,
+//! so `replay_journal` can re-drive the `Portfolio` and its metrics without data files or the strategy.
+//! `diff_journals` finds the first entry where two runs diverged.
+
+use anyhow::Context;
+use std::io::{BufRead, Write};
+
+use crate::portfolio;
+
+/// OHLCV bar as stored in the journal.
+/// Prices of placeholder bars (no history yet) are NaN, which JSON stores as `null`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct JournalBar {
+    pub datetime: chrono::DateTime<chrono::Utc>,
+    #[serde(deserialize_with = "deserialize_price")]
+    pub open: f64,
+    #[serde(deserialize_with = "deserialize_price")]
+    pub high: f64,
+    #[serde(deserialize_with = "deserialize_price")]
+    pub low: f64,
+    #[serde(deserialize_with = "deserialize_price")]
+    pub close: f64,
+    pub volume: u64,
+    pub is_filled: bool,
+}
+
+impl From<&farukon_core::data_handler::MarketBar> for JournalBar {
+    fn from(bar: &farukon_core::data_handler::MarketBar) -> Self {
+        JournalBar {
+            datetime: bar.datetime,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            is_filled: bar.is_filled,
+        }
+    }
+}
+
+impl From<JournalBar> for farukon_core::data_handler::MarketBar {
+    fn from(bar: JournalBar) -> Self {
+        farukon_core::data_handler::MarketBar {
+            datetime: bar.datetime,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            is_filled: bar.is_filled,
+        }
+    }
+}
+
+fn deserialize_price<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    // `null` is how serde_json writes NaN.
+    let price: Option<f64> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(price.unwrap_or(f64::NAN))
+}
+
+/// Payload of one journal line.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", content = "event")]
+pub enum JournalRecord {
+    /// A new timeline step; `bars` holds the bars published on it (symbols without a new bar are absent).
+    #[serde(rename = "MARKET")]
+    Market { bars: std::collections::BTreeMap<String, JournalBar> },
+    #[serde(rename = "SIGNAL")]
+    Signal(farukon_core::event::SignalEvent),
+    #[serde(rename = "ORDER")]
+    Order(farukon_core::event::OrderEvent),
+    #[serde(rename = "FILL")]
+    Fill(farukon_core::event::FillEvent),
+    /// The portfolio rejected a signal.
+    #[serde(rename = "ORDER_REJECTED")]
+    OrderRejected(farukon_core::event::OrderRejectedEvent),
+    /// Margin call: every position is being closed.
+    #[serde(rename = "MARGIN_CALL")]
+    MarginCall(farukon_core::event::MarginCallEvent),
+    /// A strategy scheduled or cancelled a timer.
+    #[serde(rename = "TIMER_COMMAND")]
+    TimerCommand(farukon_core::event::TimerCommand),
+    /// A strategy timer fired.
+    #[serde(rename = "TIMER")]
+    Timer(farukon_core::event::TimerEvent),
+    /// The portfolio was marked to market (`PortfolioHandler::update_timeindex`).
+    #[serde(rename = "TIMEINDEX")]
+    TimeIndex,
+}
+
+/// One line of the journal.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct JournalEntry {
+    /// 1-based position in the journal.
+    pub seq: u64,
+    /// Bar time of the event.
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub record: JournalRecord,
+}
+
+/// JSONL journal writer owned by a `Backtest`.
+pub struct EventJournal {
+    path: std::path::PathBuf,
+    writer: std::io::BufWriter<std::fs::File>,
+    /// Sequence number of the last written entry.
+    seq: u64,
+    /// Datetime of the last journaled bar per symbol; a MARKET entry only carries bars newer than this.
+    last_bar_datetimes: std::collections::HashMap<String, chrono::DateTime<chrono::Utc>>,
+}
+
+impl EventJournal {
+    /// Creates (truncates) the journal file for one backtest.
+    /// The file is `<dir>/<strategy_name>_<params>.jsonl`, where `<dir>` is `event_journal.path`
+    /// or `<exit_results_path>/journals`, so runs with the same parameters overwrite each other.
+    /// # Arguments
+    /// * `strategy_settings` - Settings of the backtest (single parameter set).
+    /// * `journal_settings` - The `event_journal` block of the strategy.
+    /// # Returns
+    /// * `anyhow::Result<EventJournal>` - The journal writer.
+    pub fn create(
+        strategy_settings: &farukon_core::settings::StrategySettings,
+        journal_settings: &farukon_core::settings::EventJournalSettings,
+    ) -> anyhow::Result<Self> {
+        let journal_dir = match &journal_settings.path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => std::path::Path::new(&strategy_settings.exit_results_path).join("journals"),
+        };
+        std::fs::create_dir_all(&journal_dir)
+            .with_context(|| format!("Failed to create journal directory {}", journal_dir.display()))?;
+
+        let path = journal_dir.join(format!("{}.jsonl", journal_file_stem(strategy_settings)));
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create journal {}", path.display()))?;
+
+        anyhow::Ok(EventJournal {
+            path,
+            writer: std::io::BufWriter::new(file),
+            seq: 0,
+            last_bar_datetimes: std::collections::HashMap::new(),
+        })
+    }
+
+    /// Records an event when the backtest loop dequeues it.
+    /// MARKET events are stored with the bars the data handler published on this step.
+    pub fn record_event(
+        &mut self,
+        event: &farukon_core::event::EngineEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        symbols: &[String],
+    ) -> anyhow::Result<()> {
+        let (timestamp, record) = match event {
+            farukon_core::event::EngineEvent::Market(_) => {
+                let mut bars = std::collections::BTreeMap::new();
+                for symbol in symbols {
+                    if let Some(bar) = data_handler.get_latest_bar(symbol)
+                        && self.last_bar_datetimes.get(symbol) != Some(&bar.datetime)
+                    {
+                        self.last_bar_datetimes.insert(symbol.clone(), bar.datetime);
+                        bars.insert(symbol.clone(), JournalBar::from(bar));
+                    }
+                }
+                (latest_datetime(data_handler, symbols), JournalRecord::Market { bars })
+            },
+            farukon_core::event::EngineEvent::Signal(signal_event) => (signal_event.timeindex, JournalRecord::Signal(signal_event.clone())),
+            farukon_core::event::EngineEvent::Order(order_event) => (order_event.timeindex, JournalRecord::Order(order_event.clone())),
+            farukon_core::event::EngineEvent::Fill(fill_event) => (fill_event.timeindex, JournalRecord::Fill(fill_event.clone())),
+            farukon_core::event::EngineEvent::OrderRejected(rejected_event) => (rejected_event.timeindex, JournalRecord::OrderRejected(rejected_event.clone())),
+            farukon_core::event::EngineEvent::MarginCall(margin_call_event) => (margin_call_event.timeindex, JournalRecord::MarginCall(margin_call_event.clone())),
+            farukon_core::event::EngineEvent::Timer(timer_command) => (latest_datetime(data_handler, symbols), JournalRecord::TimerCommand(timer_command.clone())),
+        };
+
+        self.write_entry(timestamp, record)
+    }
+
+    /// Records a fired strategy timer.
+    pub fn record_timer(&mut self, timer_event: &farukon_core::event::TimerEvent) -> anyhow::Result<()> {
+        self.write_entry(timer_event.timeindex, JournalRecord::Timer(timer_event.clone()))
+    }
+
+    /// Records that the portfolio was marked to market.
+    pub fn record_time_index(
+        &mut self,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        symbols: &[String],
+    ) -> anyhow::Result<()> {
+        self.write_entry(latest_datetime(data_handler, symbols), JournalRecord::TimeIndex)
+    }
+
+    /// Flushes buffered entries to disk.
+    pub fn finish(&mut self) -> anyhow::Result<()> {
+        self.writer.flush()
+            .with_context(|| format!("Failed to flush journal {}", self.path.display()))
+    }
+
+    fn write_entry(
+        &mut self,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        record: JournalRecord,
+    ) -> anyhow::Result<()> {
+        self.seq += 1;
+        let entry = JournalEntry { seq: self.seq, timestamp, record };
+        serde_json::to_writer(&mut self.writer, &entry)
+            .with_context(|| format!("Failed to write journal entry #{} to {}", self.seq, self.path.display()))?;
+        self.writer.write_all(b"\n")?;
+        anyhow::Ok(())
+    }
+
+}
+
+fn latest_datetime(
+    data_handler: &dyn farukon_core::data_handler::DataHandler,
+    symbols: &[String],
+) -> chrono::DateTime<chrono::Utc> {
+    // Current timeline step = the most recent bar across symbols.
+    symbols
+        .iter()
+        .filter_map(|symbol| data_handler.get_latest_bar_datetime(symbol))
+        .max()
+        .unwrap_or_default()
+}
+
+fn journal_file_stem(strategy_settings: &farukon_core::settings::StrategySettings) -> String {
+    // <strategy_name>_<param>=<value>_..._<pos_sizer>=<value>_slippage=<value>, parameters sorted by name.
+    let mut strategy_params: Vec<(&String, &Vec<serde_json::Value>)> = strategy_settings.strategy_params.iter().collect();
+    strategy_params.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut parts = vec![strategy_settings.strategy_name.clone()];
+    for (name, values) in strategy_params {
+        if let Some(value) = values.first() {
+            parts.push(format!("{}={}", name, value));
+        }
+    }
+    if let Some(pos_sizer_value) = strategy_settings.pos_sizer_params.pos_sizer_value.first() {
+        parts.push(format!("{}={}", strategy_settings.pos_sizer_params.pos_sizer_name, pos_sizer_value));
+    }
+    if let Some(slippage) = strategy_settings.slippage.first() {
+        parts.push(format!("slippage={}", slippage));
+    }
+
+    parts
+        .join("_")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '=' | '.' | '-' | '_') { c } else { '_' })
+        .collect()
+}
+
+/// Reads all entries of a journal file.
+pub fn read_journal(journal_path: &std::path::Path) -> anyhow::Result<Vec<JournalEntry>> {
+    let file = std::fs::File::open(journal_path)
+        .with_context(|| format!("Failed to open journal {}", journal_path.display()))?;
+
+    let mut entries = Vec::new();
+    for (line_number, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: JournalEntry = serde_json::from_str(&line)
+            .with_context(|| format!("Failed to parse line {} of {}", line_number + 1, journal_path.display()))?;
+        entries.push(entry);
+    }
+
+    anyhow::Ok(entries)
+}
+
+// --- REPLAY ---
+
+/// Data handler fed from journal MARKET entries instead of data files.
+/// Each `update_bars` call publishes the bars of the next MARKET entry.
+struct JournalDataHandler {
+    pending_steps: std::collections::VecDeque<std::collections::BTreeMap<String, JournalBar>>,
+    bars: std::collections::HashMap<String, Vec<farukon_core::data_handler::MarketBar>>,
+    continue_backtest: bool,
+}
+
+impl farukon_core::data_handler::DataHandler for JournalDataHandler {
+    fn get_latest_bar(&self, symbol: &str) -> Option<&farukon_core::data_handler::MarketBar> {
+        self.bars.get(symbol)?.last()
+    }
+
+    fn get_latest_bars(&self, symbol: &str, n: usize) -> Vec<&farukon_core::data_handler::MarketBar> {
+        match self.bars.get(symbol) {
+            Some(bars) => bars[bars.len().saturating_sub(n)..].iter().collect(),
+            None => vec![],
+        }
+    }
+
+    fn get_latest_bar_datetime(&self, symbol: &str) -> Option<chrono::DateTime<chrono::offset::Utc>> {
+        self.get_latest_bar(symbol).map(|bar| bar.datetime)
+    }
+
+    fn get_latest_bar_value(&self, symbol: &str, val_type: &str) -> Option<f64> {
+        self.get_latest_bar(symbol).map(|bar| match val_type {
+            "open" => bar.open,
+            "high" => bar.high,
+            "low" => bar.low,
+            "close" => bar.close,
+            "volume" => bar.volume as f64,
+            _ => {
+                eprintln!("Warning: Unknown value type '{}' for symbol '{}'", val_type, symbol);
+                0.0
+            }
+        })
+    }
+
+    fn get_latest_bars_values(&self, symbol: &str, val_type: &str, n: usize) -> Vec<f64> {
+        self.get_latest_bars(symbol, n)
+            .into_iter()
+            .map(|bar| match val_type {
+                "open" => bar.open,
+                "high" => bar.high,
+                "low" => bar.low,
+                "close" => bar.close,
+                "volume" => bar.volume as f64,
+                _ => {
+                    eprintln!("Warning: Unknown value type '{}' in get_latest_bars_values for symbol '{}'", val_type, symbol);
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    fn update_bars(&mut self) {
+        match self.pending_steps.pop_front() {
+            Some(step_bars) => {
+                for (symbol, bar) in step_bars {
+                    self.bars.entry(symbol).or_default().push(bar.into());
+                }
+            },
+            None => self.continue_backtest = false,
+        }
+    }
+
+    fn get_continue_backtest(&self) -> bool {
+        self.continue_backtest
+    }
+
+    fn set_continue_backtest(&mut self, value: bool) {
+        self.continue_backtest = value;
+    }
+
+}
+
+/// Re-drives a `Portfolio` from a journal: bars from MARKET entries, fills from FILL entries,
+/// mark-to-market on TIMEINDEX entries. The other entries are informational.
+/// The strategy and the execution simulator are not involved.
+/// # Arguments
+/// * `journal_path` - The `.jsonl` journal.
+/// * `mode` - The operational mode.
+/// * `initial_capital_for_strategy` - Starting capital, as in the recorded run.
+/// * `strategy_settings` - Settings of the recorded strategy.
+/// * `strategy_instruments_info` - Metadata for the instruments traded.
+/// # Returns
+/// * `anyhow::Result<PerformanceMetrics>` - Metrics recomputed from the journal.
+pub fn replay_journal(
+    journal_path: &std::path::Path,
+    mode: &String,
+    initial_capital_for_strategy: &f64,
+    strategy_settings: &farukon_core::settings::StrategySettings,
+    strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+) -> anyhow::Result<farukon_core::performance::PerformanceMetrics> {
+    use farukon_core::portfolio::PortfolioHandler;
+
+    let entries = read_journal(journal_path)?;
+
+    // Orders and margin-call signals the portfolio emits are not used: the journal already has the fills.
+    let event_queue = farukon_core::event::EventQueue::new();
+    let mut portfolio = portfolio::Portfolio::new(
+        mode,
+        initial_capital_for_strategy,
+        event_queue.clone(),
+        strategy_settings,
+        strategy_instruments_info,
+    )?;
+    let pending_steps = entries
+        .iter()
+        .filter_map(|entry| match &entry.record {
+            JournalRecord::Market { bars } => Some(bars.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut data_handler: Box<dyn farukon_core::data_handler::DataHandler> = Box::new(JournalDataHandler {
+        pending_steps,
+        bars: std::collections::HashMap::new(),
+        continue_backtest: true,
+    });
+
+    for entry in entries {
+        match entry.record {
+            JournalRecord::Market { .. } => data_handler.update_bars(),
+            JournalRecord::Fill(fill_event) => portfolio.update_fill(&fill_event, &data_handler),
+            JournalRecord::TimeIndex => {
+                portfolio.update_timeindex(&data_handler);
+                while event_queue.try_recv().is_some() {}
+            },
+            JournalRecord::Signal(_)
+            | JournalRecord::Order(_)
+            | JournalRecord::OrderRejected(_)
+            | JournalRecord::MarginCall(_)
+            | JournalRecord::TimerCommand(_)
+            | JournalRecord::Timer(_) => {},
+        }
+    }
+
+    portfolio.calculate_final_performance();
+    let metrics = portfolio.output_summary_stats()?.clone();
+
+    anyhow::Ok(metrics)
+}
+
+// --- DIFF ---
+
+/// First difference between two journals.
+#[derive(Debug)]
+pub struct JournalDivergence {
+    /// 1-based line number where the journals differ.
+    pub line: usize,
+    /// Line of the first journal (None if it ended earlier).
+    pub left: Option<String>,
+    /// Line of the second journal (None if it ended earlier).
+    pub right: Option<String>,
+}
+
+/// Compares two journals entry by entry (as JSON values, so formatting does not matter).
+/// # Returns
+/// * `anyhow::Result<Option<JournalDivergence>>` - `None` if the journals are identical.
+pub fn diff_journals(
+    left_path: &std::path::Path,
+    right_path: &std::path::Path,
+) -> anyhow::Result<Option<JournalDivergence>> {
+    let read_lines = |path: &std::path::Path| -> anyhow::Result<Vec<String>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read journal {}", path.display()))?;
+        anyhow::Ok(contents.lines().filter(|line| !line.trim().is_empty()).map(str::to_string).collect())
+    };
+    let left_lines = read_lines(left_path)?;
+    let right_lines = read_lines(right_path)?;
+
+    for line_index in 0..left_lines.len().max(right_lines.len()) {
+        let left = left_lines.get(line_index);
+        let right = right_lines.get(line_index);
+        let same = match (left, right) {
+            (Some(left), Some(right)) => {
+                let left_value: serde_json::Value = serde_json::from_str(left)
+                    .with_context(|| format!("Failed to parse line {} of {}", line_index + 1, left_path.display()))?;
+                let right_value: serde_json::Value = serde_json::from_str(right)
+                    .with_context(|| format!("Failed to parse line {} of {}", line_index + 1, right_path.display()))?;
+                left_value == right_value
+            },
+            _ => false,
+        };
+        if !same {
+            return anyhow::Ok(Some(JournalDivergence {
+                line: line_index + 1,
+                left: left.cloned(),
+                right: right.cloned(),
+            }));
+        }
+    }
+
+    anyhow::Ok(None)
+}
@@ -4,18 +4,18 @@
 /// It mimics broker behavior by applying slippage and commission,
 /// and generates `FILL` events which are sent to the event channel.
 pub struct SimulatedExecutionHandler {
-    /// Channel for sending events (in this case, `FillEvent`).
-    pub event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+    /// Event queue for sending events (in this case, `FillEvent`).
+    pub event_sender: farukon_core::event::EventQueue,
 }
 
 impl SimulatedExecutionHandler {
     /// Creates a new instance of `SimulatedExecutionHandler`.
     /// # Arguments
-    /// * `event_sender` - The sender for the event channel, which will be used to send `FILL` events.
+    /// * `event_sender` - The event queue, which will be used to send `FILL` events.
     /// # Returns
     /// * `anyhow::Result<Self>` - A new instance of `SimulatedExecutionHandler` or an error.
     pub fn new(
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        event_sender: farukon_core::event::EventQueue,
     ) -> anyhow::Result<Self> {
         // Always returns Ok with a new instance, as creating the struct itself cannot fail.
         anyhow::Ok(
@@ -67,15 +67,15 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                 .ok_or_else(|| anyhow::anyhow!("No bar for {}", symbol))?; // Return an error if the bar is unavailable.
 
             // Determine the execution price based on the order type.
-            let execution_price = match event.order_type.as_str() {
-                "MKT" => {
+            let execution_price = match event.order_type {
+                farukon_core::event::OrderType::Market => {
                     // Market order: slippage is applied.
                     // Buy at High + slippage, Sell at Low - slippage.
                     if strategy_settings.slippage.len() == 1 {
                         // Check the order direction (BUY/SELL).
-                        match event.direction.as_deref() {
+                        match event.direction {
                             // For a buy order, use the bar's High and add slippage.
-                            Some("BUY") => {
+                            Some(farukon_core::event::Direction::Buy) => {
                                 let market_price = (1.0 + strategy_settings.slippage[0]) * current_bar.high;
                                 if current_bar.low <= market_price {
                                     if current_bar.high <= market_price {
@@ -90,7 +90,7 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                                 }
                             }
                             // For a sell order, use the bar's Low and subtract slippage.
-                            Some("SELL") => {
+                            Some(farukon_core::event::Direction::Sell) => {
                                 let market_price = (1.0 - strategy_settings.slippage[0]) * current_bar.low;
                                 if current_bar.high >= market_price {
                                     if current_bar.low >= market_price {
@@ -104,7 +104,7 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                                     return anyhow::Ok(());
                                 }
                             }
-                            // If the direction is not specified or unknown, return a price of 0.0.
+                            // If the direction is not specified, return a price of 0.0.
                             _ => 0.0,
                         }
                     } else {
@@ -113,14 +113,14 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                         anyhow::bail!("Wrong len of slippage vector!!");
                     }
                 },
-                "LMT" => {
+                farukon_core::event::OrderType::Limit => {
                     // Limit order: execution occurs only if the price was reached during the bar.
                     // Use the specified limit price, or the bar's close price if the limit price is not specified.
                     let limit_price = event.limit_price.unwrap_or(current_bar.close);
                     // Check the direction of the limit order.
-                    match event.direction.as_deref() {
+                    match event.direction {
                         // For a buy order, check if the bar's Low was less than or equal to the limit price.
-                        Some("BUY") => {
+                        Some(farukon_core::event::Direction::Buy) => {
                             if current_bar.low <= limit_price {
                                 if current_bar.open <= limit_price {
                                     current_bar.open
@@ -134,7 +134,7 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                             }
                         }
                         // For a sell order, check if the bar's High was greater than or equal to the limit price.
-                        Some("SELL") => {
+                        Some(farukon_core::event::Direction::Sell) => {
                             if current_bar.high >= limit_price {
                                 if current_bar.open >= limit_price {
                                     current_bar.open
@@ -147,12 +147,10 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                                 return anyhow::Ok(());
                             }
                         }
-                        // If the direction is not specified or unknown, the order is not executed.
+                        // If the direction is not specified, the order is not executed.
                         _ => return anyhow::Ok(()),
                     }
                 }
-                // If the order type is neither "MKT" nor "LMT", the order is not executed.
-                _ => return anyhow::Ok(()),
             };
 
             // Get the exchange name from the instrument information.
@@ -172,14 +170,14 @@ impl farukon_core::execution::ExecutionHandler for SimulatedExecutionHandler {
                 symbol.clone(),         // The instrument symbol.
                 exchange.clone(),       // The exchange name.
                 event.quantity,         // The number of contracts.
-                event.direction.clone(), // The direction of the trade (BUY/SELL).
+                event.direction, // The direction of the trade (BUY/SELL).
                 Some(execution_price),  // The price at which the order was executed.
                 total_commission,       // The total commission for the trade.
-                event.signal_name.clone(), // The name of the signal that generated the order.
+                event.signal_name, // The name of the signal that generated the order.
             );
 
             // Send the FillEvent to the event channel.
-            self.event_sender.send(Box::new(fill_event))
+            self.event_sender.send(fill_event)
                 .map_err(|e| anyhow::anyhow!("Failed to send FillEvent: {}", e))?; // Wrap the send error in an anyhow::Error.
     
         // Return Ok(()) upon successful order execution and FillEvent sending.
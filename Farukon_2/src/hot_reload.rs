@@ -0,0 +1,185 @@
+// Farukon_2_0/src/hot_reload.rs
+
+//! Hot reload of strategy libraries (and scripts) during long optimizations.
+//! Every build of `strategy_path` is identified by the hash of its content and copied to a private file
+//! named after that hash; backtests load the copy, never `strategy_path` itself.
+//! A rebuild in the middle of a Grid Search chunk or a GA generation therefore cannot change the strategy
+//! of the backtests already running: the optimizer checks for a new build only between chunks and generations,
+//! and tags every result with the build that produced it.
+
+use anyhow::Context;
+
+use crate::strategy_loader;
+
+/// One build of a strategy library or script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyBuild {
+    /// First 16 hex digits of the SHA-256 of the file.
+    pub hash: String,
+    /// Private copy of the build loaded by the backtests.
+    pub path: String,
+}
+
+/// Size and modification time of the watched file, to skip hashing it when nothing changed.
+type FileStamp = (u64, Option<std::time::SystemTime>);
+
+/// Watches `strategy_path` for new builds.
+#[derive(Debug)]
+pub struct LibraryWatcher {
+    /// The strategy settings, used to check that a new build can create the strategy.
+    strategy_settings: farukon_core::settings::StrategySettings,
+    source_path: String,
+    /// Directory of the private copies; removed with the watcher.
+    builds_dir: std::path::PathBuf,
+    current_build: StrategyBuild,
+    /// Stamp of the file when it was last checked.
+    checked_stamp: Option<FileStamp>,
+}
+
+impl LibraryWatcher {
+    /// Snapshots the current build of `strategy_path`.
+    /// # Arguments
+    /// * `strategy_settings` - Settings of the strategy; `strategy_path` is watched.
+    /// # Returns
+    /// * `anyhow::Result<Self>` - Error if there is no `strategy_path` or it cannot be read or copied.
+    pub fn new(strategy_settings: &farukon_core::settings::StrategySettings) -> anyhow::Result<Self> {
+        let source_path = strategy_settings.strategy_path.clone()
+            .ok_or_else(|| anyhow::anyhow!("hot_reload needs a strategy_path: built-in strategies cannot be reloaded"))?;
+        let builds_dir = std::env::temp_dir().join(format!("farukon_builds_{}_{}", std::process::id(), strategy_settings.strategy_name));
+        std::fs::create_dir_all(&builds_dir)
+            .with_context(|| format!("Failed to create directory for strategy builds {}", builds_dir.display()))?;
+
+        let (content, stamp) = read_stable(&source_path)?
+            .ok_or_else(|| anyhow::anyhow!("Strategy library {} is being written, try again", source_path))?;
+        let current_build = save_build(&builds_dir, &source_path, &content)?;
+
+        anyhow::Ok(Self {
+            strategy_settings: strategy_settings.clone(),
+            source_path,
+            builds_dir,
+            current_build,
+            checked_stamp: Some(stamp),
+        })
+    }
+
+    /// The build the next backtests use.
+    pub fn current_build(&self) -> &StrategyBuild {
+        &self.current_build
+    }
+
+    /// Checks `strategy_path` for a new build and switches to it.
+    /// A file still being written is checked again next time; a build that cannot create the strategy
+    /// (failed to load, wrong ABI, script error) or declares another parameter schema is reported
+    /// and the current build is kept.
+    /// # Returns
+    /// * `Option<&StrategyBuild>` - The new build, if the watcher switched to one.
+    pub fn poll(&mut self) -> Option<&StrategyBuild> {
+        let stamp = file_stamp(&self.source_path).ok()?;
+        if self.checked_stamp == Some(stamp) {
+            return None;
+        }
+
+        let (content, stamp) = match read_stable(&self.source_path) {
+            Ok(Some(content_and_stamp)) => content_and_stamp,
+            Ok(None) => return None,   // Still being written
+            Err(e) => {
+                println!("Warning: cannot read strategy library {}: {:#}", self.source_path, e);
+                return None;
+            },
+        };
+        self.checked_stamp = Some(stamp);
+        if content_hash(&content) == self.current_build.hash {
+            return None;
+        }
+
+        let new_build = match save_build(&self.builds_dir, &self.source_path, &content)
+            .and_then(|new_build| self.check_build(&new_build).map(|_| new_build))
+        {
+            Ok(new_build) => new_build,
+            Err(e) => {
+                println!(
+                    "Warning: strategy library {} changed, but the new build cannot be used; keeping build {}: {:#}",
+                    self.source_path, self.current_build.hash, e,
+                );
+                return None;
+            },
+        };
+
+        println!(
+            "Strategy library {} changed: build {} -> {}, the remaining backtests use the new build",
+            self.source_path, self.current_build.hash, new_build.hash,
+        );
+        self.current_build = new_build;
+        Some(&self.current_build)
+    }
+
+    /// Checks that `build` provides the strategy of the settings, with the parameter schema
+    /// the `strategy_params` were validated against when the run started.
+    fn check_build(&self, build: &StrategyBuild) -> anyhow::Result<()> {
+        let mut build_settings = self.strategy_settings.clone();
+        build_settings.strategy_path = Some(build.path.clone());
+        let param_schema = strategy_loader::strategy_param_schema(&build_settings)?;
+        if param_schema != self.strategy_settings.param_schema {
+            anyhow::bail!("its parameter schema differs from the one the strategy_params were validated against; restart the run to use it");
+        }
+        anyhow::Ok(())
+    }
+
+}
+
+impl Drop for LibraryWatcher {
+    fn drop(&mut self) {
+        // The copies are only needed while the optimization runs.
+        let _ = std::fs::remove_dir_all(&self.builds_dir);
+    }
+}
+
+/// Content hash identifying a build: the first 16 hex digits of the SHA-256.
+pub fn content_hash(content: &[u8]) -> String {
+    use sha2::Digest;
+    let digest = sha2::Sha256::digest(content);
+    digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn file_stamp(path: &str) -> anyhow::Result<FileStamp> {
+    let metadata = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read metadata of {}", path))?;
+    anyhow::Ok((metadata.len(), metadata.modified().ok()))
+}
+
+/// Reads the file, or returns `None` if it changed while being read (a build still being written).
+fn read_stable(path: &str) -> anyhow::Result<Option<(Vec<u8>, FileStamp)>> {
+    let stamp_before = file_stamp(path)?;
+    let content = std::fs::read(path)
+        .with_context(|| format!("Failed to read strategy library {}", path))?;
+    let stamp_after = file_stamp(path)?;
+
+    if stamp_before != stamp_after || content.len() as u64 != stamp_after.0 {
+        return anyhow::Ok(None);
+    }
+    anyhow::Ok(Some((content, stamp_after)))
+}
+
+/// Writes the private copy of a build: `<builds_dir>/<file stem>-<hash>.<extension>`.
+/// The extension is kept, so a `.rhai` copy is still run as a script.
+fn save_build(builds_dir: &std::path::Path, source_path: &str, content: &[u8]) -> anyhow::Result<StrategyBuild> {
+    let hash = content_hash(content);
+    let source = std::path::Path::new(source_path);
+    let stem = source.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match source.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, hash, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, hash),
+    };
+    let path = builds_dir.join(file_name);
+
+    // Several chunks may come back to the same build; its copy is already there.
+    if !path.exists() {
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to copy strategy build to {}", path.display()))?;
+    }
+
+    anyhow::Ok(StrategyBuild {
+        hash,
+        path: path.to_string_lossy().into_owned(),
+    })
+}
@@ -6,23 +6,123 @@
 //! or single backtest (not implemented yet — optimization only).
 
 mod cli;
+mod converter;
 mod risks;
 mod backtest;
+mod data_checker;
+mod event_journal;
 mod portfolio;
+mod scheduler;
 mod execution;
 mod optimizers;
 mod data_handler;
 mod ohlcv_generated;
 mod strategy_loader;
+mod script_strategy;
+mod hot_reload;
+mod checkpoint;
+mod result_cache;
+
+// Links the example strategies into the binary; they register themselves in `farukon_core::registry`.
+use strategy_lib as _;
 
 fn main() -> anyhow::Result<()>{
     let start_time = std::time::Instant::now();
     
-    let args = cli::Args::parse();  // Parse --config path
+    let args = cli::Args::parse();  // Parse --config path or subcommand
+
+    // Utility subcommands run instead of the backtest
+    match &args.command {
+        Some(cli::Command::Convert { input, output, threads }) => {
+            let converted_files = converter::convert_path(input, output, *threads)?;
+            println!("Converted {} file(s) in {:.3} seconds", converted_files, start_time.elapsed().as_secs_f64());
+            return anyhow::Ok(());
+        },
+        Some(cli::Command::CheckData { data_path, symbols, report_dir, instruments_path, sessions_path, threads }) if args.config.is_none() => {
+            let data_path = data_path.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("check-data needs --data-path or --config"))?;
+            let symbols = if symbols.is_empty() {
+                data_checker::list_symbols(data_path)?
+            } else {
+                symbols.clone()
+            };
+            let report_dir = report_dir.clone()
+                .unwrap_or_else(|| std::path::Path::new(data_path).join("data_reports"));
+            let session_calendar = data_checker::load_session_calendar(
+                instruments_path.as_deref(),
+                sessions_path.as_deref(),
+                &symbols,
+            )?;
+
+            let reports = data_checker::check_symbols(
+                data_path,
+                &symbols,
+                &farukon_core::settings::DataCheckSettings::default(),
+                session_calendar.as_ref(),
+                &report_dir,
+                *threads,
+            )?;
+            let errors_count: usize = reports.iter().map(|report| report.errors_count()).sum();
+            if errors_count > 0 {
+                anyhow::bail!("Data check found {} error(s), see reports in {}", errors_count, report_dir.display());
+            }
+            return anyhow::Ok(());
+        },
+        Some(cli::Command::DiffJournals { left, right }) => {
+            match event_journal::diff_journals(left, right)? {
+                Some(divergence) => {
+                    println!("Journals diverge at line {}", divergence.line);
+                    println!("< {}", divergence.left.as_deref().unwrap_or("<end of journal>"));
+                    println!("> {}", divergence.right.as_deref().unwrap_or("<end of journal>"));
+                    anyhow::bail!("Journals {} and {} differ", left.display(), right.display());
+                },
+                None => println!("Journals are identical"),
+            }
+            return anyhow::Ok(());
+        },
+        Some(cli::Command::ListStrategies { plugin }) if args.config.is_none() => {
+            match plugin {
+                Some(plugin) => print_strategy_library(plugin, &[])?,
+                None => print_builtin_strategies(&[])?,
+            }
+            return anyhow::Ok(());
+        },
+        _ => {}
+    }
+
+    let config_path = args.config.clone()
+        .ok_or_else(|| anyhow::anyhow!("--config is required when no subcommand is given"))?;
 
     // Load full settings (common + portfolio)
-    let mut all_settings = farukon_core::settings::Settings::load(args.config)?;
+    let mut all_settings = farukon_core::settings::Settings::load(config_path)?;
     let mode = &all_settings.common.mode.clone();
+
+    // `list-strategies` with a config lists the library of every strategy (or the built-in strategies) and exits
+    if let Some(cli::Command::ListStrategies { plugin }) = &args.command {
+        // `None` groups the strategies without `strategy_path`
+        let mut strategies_by_path: std::collections::BTreeMap<Option<&str>, Vec<(&str, &str)>> = std::collections::BTreeMap::new();
+        if let Some(plugin) = plugin {
+            strategies_by_path.insert(Some(plugin.as_str()), Vec::new());
+        }
+        for (strategy_id, strategy_settings) in &all_settings.portfolio {
+            let strategy_path = strategy_settings.strategy_path.as_deref();
+            if plugin.is_some() && plugin.as_deref() != strategy_path {
+                continue;
+            }
+            strategies_by_path
+                .entry(strategy_path)
+                .or_default()
+                .push((strategy_id.as_str(), strategy_settings.strategy_name.as_str()));
+        }
+        for (strategy_path, configured_strategies) in &mut strategies_by_path {
+            configured_strategies.sort();
+            match strategy_path {
+                Some(strategy_path) => print_strategy_library(strategy_path, configured_strategies)?,
+                None => print_builtin_strategies(configured_strategies)?,
+            }
+        }
+        return anyhow::Ok(());
+    }
     
     // Load global instrument metadata
     let instruments_info = &farukon_core::instruments_info::InstrumentsInfoRegistry::load(&all_settings)?;
@@ -30,8 +130,66 @@ fn main() -> anyhow::Result<()>{
     // Load commission plans
     let _commission_plans = farukon_core::commission_plans::CommissionPlans::load(&mut all_settings, instruments_info)?;
 
+    // Load trading session calendar (optional)
+    let _session_calendar = farukon_core::sessions::SessionCalendar::load(&mut all_settings, instruments_info)?;
+
+    // `check-data` with a config checks every strategy's data and exits
+    if let Some(cli::Command::CheckData { report_dir, .. }) = &args.command {
+        let mut errors_count = 0;
+        for (strategy_id, strategy_settings) in &all_settings.portfolio {
+            println!("Checking data of {}...", strategy_id);
+            errors_count += data_checker::check_strategy_data(strategy_settings, report_dir.as_deref())?;
+        }
+        if errors_count > 0 {
+            anyhow::bail!("Data check found {} error(s)", errors_count);
+        }
+        return anyhow::Ok(());
+    }
+
+    // `replay` recomputes the metrics of one strategy from its event journal and exits
+    if let Some(cli::Command::Replay { journal, strategy_id }) = &args.command {
+        let (strategy_id, strategy_settings) = match strategy_id {
+            Some(strategy_id) => all_settings.portfolio.get_key_value(strategy_id)
+                .ok_or_else(|| anyhow::anyhow!("Strategy {} not found in config", strategy_id))?,
+            None if all_settings.portfolio.len() == 1 => all_settings.portfolio.iter().next().unwrap(),
+            None => {
+                let mut strategy_ids: Vec<&String> = all_settings.portfolio.keys().collect();
+                strategy_ids.sort();
+                anyhow::bail!("Config has several strategies, choose one with --strategy: {:?}", strategy_ids);
+            },
+        };
+        let strategy_instruments_info = &instruments_info.get_instrument_info_for_strategy(&strategy_settings.symbols)?;
+        let initial_capital_for_strategy = strategy_settings.strategy_weight * all_settings.common.initial_capital;
+
+        let metrics = event_journal::replay_journal(
+            journal,
+            mode,
+            &initial_capital_for_strategy,
+            strategy_settings,
+            strategy_instruments_info,
+        )?;
+
+        println!("Replayed {} from {}", strategy_id, journal.display());
+        for (name, value) in metrics.to_stats_list() {
+            println!("{}: {}", name, value);
+        }
+        println!("The main programm is finished in {:.3} seconds", start_time.elapsed().as_secs_f64());
+        return anyhow::Ok(());
+    }
+
+    // Check strategy parameters against the schemas declared by the strategies
+    strategy_loader::load_param_schemas(&mut all_settings)?;
+
     // For each strategy in portfolio, run optimization
-    for (_strategy_id, strategy_settings) in all_settings.portfolio {
+    for (strategy_id, strategy_settings) in all_settings.portfolio {
+        // Optional pre-run data-quality check
+        if let Some(check_data) = &strategy_settings.data.check_data {
+            let errors_count = data_checker::check_strategy_data(&strategy_settings, None)?;
+            if errors_count > 0 && check_data.abort_on_errors {
+                anyhow::bail!("Data check for {} found {} error(s), aborting run", strategy_id, errors_count);
+            }
+        }
+
         let strategy_instruments_info = &instruments_info.get_instrument_info_for_strategy(&strategy_settings.symbols)?;
         let initial_capital_for_strategy = strategy_settings.strategy_weight * all_settings.common.initial_capital;
 
@@ -41,7 +199,8 @@ fn main() -> anyhow::Result<()>{
                 &initial_capital_for_strategy,
                 &strategy_settings,
                 strategy_instruments_info,
-            );
+                args.resume,
+            )?;
 
             match &strategy_settings.optimizer_type {
                 farukon_core::settings::OptimizerType::GridSearch => {
@@ -53,11 +212,45 @@ fn main() -> anyhow::Result<()>{
                         .get_grid_search_optimizer()
                         .get_config()
                         .generate_all_combinations_vec();
-                    
+
+                    // Combinations breaking the parameter constraints are not backtested
+                    let skipped_combinations = total_combinations - combinations_to_grid_search.len();
+                    if skipped_combinations > 0 {
+                        println!(
+                            "{}: skipping {} of {} combinations that break the parameter constraints of {}",
+                            strategy_id, skipped_combinations, total_combinations, strategy_settings.strategy_name,
+                        );
+                    }
+
+                    // Only the best combinations of the vectorized pass run the event-driven backtest
+                    let combinations_to_grid_search = match &strategy_settings.prefilter {
+                        Some(prefilter) => optimization_runner.prefilter_combinations(prefilter, combinations_to_grid_search)?,
+                        None => combinations_to_grid_search,
+                    };
+
+                    let results = optimization_runner
+                        .run_grid_search(combinations_to_grid_search.len(), combinations_to_grid_search);
+                    optimization_runner.save_grid_search_optimization_results(&results)?;
+                },
+                farukon_core::settings::OptimizerType::RandomSearch { sampling_params } => {
+                    let results = optimization_runner
+                        .run_sampling_search(farukon_core::optimization::SamplingMethod::Random, sampling_params);
+                    optimization_runner.save_grid_search_optimization_results(&results)?;
+                },
+                farukon_core::settings::OptimizerType::LatinHypercube { sampling_params } => {
                     let results = optimization_runner
-                        .run_grid_search(total_combinations, combinations_to_grid_search);
+                        .run_sampling_search(farukon_core::optimization::SamplingMethod::LatinHypercube, sampling_params);
                     optimization_runner.save_grid_search_optimization_results(&results)?;
                 },
+                farukon_core::settings::OptimizerType::Bayesian { bayesian_params } => {
+                    let results = optimization_runner.run_bayesian_search(bayesian_params)?;
+                    optimization_runner.save_grid_search_optimization_results(&results)?;
+                },
+                farukon_core::settings::OptimizerType::Nsga2 { nsga2_params } => {
+                    let (results, pareto_front) = optimization_runner.run_nsga2_search(nsga2_params)?;
+                    optimization_runner.save_grid_search_optimization_results(&results)?;
+                    optimization_runner.save_pareto_front(&pareto_front, &nsga2_params.objectives)?;
+                },
                 farukon_core::settings::OptimizerType::Genetic { ga_params }=> {
                     optimization_runner.run_genetic_search(ga_params)?;
                 }
@@ -68,3 +261,135 @@ fn main() -> anyhow::Result<()>{
     println!("The main programm is finished in {:.3} seconds", start_time.elapsed().as_secs_f64());
     anyhow::Ok(())
 }
+
+/// Prints the strategies exported by a strategy library.
+/// # Arguments
+/// * `strategy_path` - Path to the library.
+/// * `configured_strategies` - (strategy id, strategy name) pairs of the config using this library.
+fn print_strategy_library(
+    strategy_path: &str,
+    configured_strategies: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    if script_strategy::is_script_path(strategy_path) {
+        return print_script_strategy(strategy_path, configured_strategies);
+    }
+
+    let library = strategy_loader::StrategyLibrary::open(strategy_path)?;
+    let strategy_names = library.strategy_names()?;
+
+    println!("{} (strategy ABI v{}, farukon_core {})", strategy_path, farukon_core::abi::ABI_VERSION, library.core_version()?);
+    for strategy_name in &strategy_names {
+        print_strategy(strategy_name, configured_strategies, library.param_schema(strategy_name)?.as_ref());
+    }
+    for (strategy_id, strategy_name) in configured_strategies {
+        if !strategy_names.contains(strategy_name) {
+            println!("  Warning: {} uses strategy '{}', which this library does not export", strategy_id, strategy_name);
+        }
+    }
+
+    anyhow::Ok(())
+}
+
+/// Prints a script strategy. A script holds one strategy, listed under the names the config gives it
+/// (or under the script's file name).
+/// # Arguments
+/// * `script_path` - Path to the `.rhai` script.
+/// * `configured_strategies` - (strategy id, strategy name) pairs of the config using this script.
+fn print_script_strategy(
+    script_path: &str,
+    configured_strategies: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    let param_schema = script_strategy::script_param_schema(script_path)?;
+
+    let mut strategy_names: Vec<&str> = configured_strategies.iter().map(|(_, strategy_name)| *strategy_name).collect();
+    strategy_names.sort();
+    strategy_names.dedup();
+    let file_name = std::path::Path::new(script_path)
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| script_path.to_string());
+    if strategy_names.is_empty() {
+        strategy_names.push(&file_name);
+    }
+
+    println!("{} (Rhai script strategy)", script_path);
+    for strategy_name in strategy_names {
+        print_strategy(strategy_name, configured_strategies, param_schema.as_ref());
+    }
+
+    anyhow::Ok(())
+}
+
+/// Prints the strategies compiled into the engine.
+/// # Arguments
+/// * `configured_strategies` - (strategy id, strategy name) pairs of the config without `strategy_path`.
+fn print_builtin_strategies(configured_strategies: &[(&str, &str)]) -> anyhow::Result<()> {
+    let static_strategies = farukon_core::registry::static_strategies();
+
+    println!("built-in (compiled into this binary, no strategy_path)");
+    for static_strategy in &static_strategies {
+        print_strategy(static_strategy.name, configured_strategies, (static_strategy.param_schema)().as_ref());
+    }
+    for (strategy_id, strategy_name) in configured_strategies {
+        if !static_strategies.iter().any(|static_strategy| static_strategy.name == *strategy_name) {
+            println!("  Warning: {} uses strategy '{}', which is not compiled into this binary", strategy_id, strategy_name);
+        }
+    }
+
+    anyhow::Ok(())
+}
+
+/// Prints one strategy of `list-strategies`: its name, the config strategies using it, and its parameter schema.
+fn print_strategy(
+    strategy_name: &str,
+    configured_strategies: &[(&str, &str)],
+    param_schema: Option<&farukon_core::param_schema::ParamSchema>,
+) {
+    let used_by: Vec<&str> = configured_strategies
+        .iter()
+        .filter(|(_, configured_name)| *configured_name == strategy_name)
+        .map(|(strategy_id, _)| *strategy_id)
+        .collect();
+    if used_by.is_empty() {
+        println!("  {}", strategy_name);
+    } else {
+        println!("  {} (used by {})", strategy_name, used_by.join(", "));
+    }
+
+    // Declared parameters and constraints
+    if let Some(param_schema) = param_schema {
+        for param in &param_schema.params {
+            let default = param.default.as_ref()
+                .map(|default| format!(", default {}", default))
+                .unwrap_or_default();
+            let description = param.description.as_ref()
+                .map(|description| format!(" - {}", description))
+                .unwrap_or_default();
+            println!("    {}: {}{}{}", param.name, format_param_kind(&param.kind), default, description);
+        }
+        for constraint in &param_schema.constraints {
+            println!("    constraint: {}", constraint);
+        }
+    }
+}
+
+/// Formats the type and bounds of a strategy parameter for `list-strategies`.
+fn format_param_kind(kind: &farukon_core::param_schema::ParamKind) -> String {
+    let format_bounds = |min: Option<String>, max: Option<String>| match (min, max) {
+        (Some(min), Some(max)) => format!(" in [{}, {}]", min, max),
+        (Some(min), None) => format!(" >= {}", min),
+        (None, Some(max)) => format!(" <= {}", max),
+        (None, None) => String::new(),
+    };
+
+    match kind {
+        farukon_core::param_schema::ParamKind::Int { min, max } => {
+            format!("int{}", format_bounds(min.map(|v| v.to_string()), max.map(|v| v.to_string())))
+        },
+        farukon_core::param_schema::ParamKind::Float { min, max } => {
+            format!("float{}", format_bounds(min.map(|v| v.to_string()), max.map(|v| v.to_string())))
+        },
+        farukon_core::param_schema::ParamKind::Bool => "bool".to_string(),
+        farukon_core::param_schema::ParamKind::Enum { values } => format!("enum {:?}", values),
+    }
+}
@@ -10,6 +10,10 @@ use crate::backtest;
 use crate::portfolio;
 use crate::execution;
 use crate::data_handler;
+use crate::event_journal;
+use crate::hot_reload;
+use crate::checkpoint;
+use crate::result_cache;
 use crate::strategy_loader; // Note: Typo in module name — should be "strategy_loader"
 
 #[derive(Debug, Clone)]
@@ -26,38 +30,114 @@ pub struct OptimizationRunner {
     strategy_settings: farukon_core::settings::StrategySettings,
     /// The Grid Search optimizer instance, configured based on strategy settings.
     grid_search_optimizer: farukon_core::optimization::GridSearchOptimizer,
+    /// Market data loaded once and shared by every backtest of this optimization.
+    market_data_store: std::sync::Arc<data_handler::MarketDataStore>,
+    /// Watches `strategy_path` for new builds when `hot_reload` is set.
+    library_watcher: Option<std::sync::Arc<std::sync::Mutex<hot_reload::LibraryWatcher>>>,
+    /// Checkpoint of the completed backtests when `checkpoint` is set.
+    checkpoint: Option<std::sync::Arc<checkpoint::Checkpoint>>,
+    /// Results of earlier runs when `result_cache` is set.
+    result_cache: Option<std::sync::Arc<result_cache::ResultCache>>,
 }
 
 impl OptimizationRunner {
     /// Creates a new `OptimizationRunner` instance.
-    /// Initializes the Grid Search optimizer based on the provided strategy settings.
+    /// Initializes the Grid Search optimizer based on the provided strategy settings
+    /// and loads the strategy's market data into a shared `MarketDataStore`.
     /// # Arguments
     /// * `mode` - The operational mode (affects verbosity and behavior).
     /// * `initial_capital_for_strategy` - The starting capital for backtests within this optimization run.
     /// * `strategy_settings` - The configuration for the strategy being optimized.
     /// * `strategy_instruments_info` - Metadata for all instruments traded by the strategy.
+    /// * `resume` - Continues the checkpoint of an interrupted run (`--resume`).
     /// # Returns
-    /// * `OptimizationRunner` - The newly created runner instance.
+    /// * `anyhow::Result<OptimizationRunner>` - The newly created runner instance, or an error if data loading fails.
     pub fn new(
         mode: &str,
         initial_capital_for_strategy: &f64,
         strategy_settings: &farukon_core::settings::StrategySettings,
-        strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>
-    ) -> Self {
+        strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+        resume: bool,
+    ) -> anyhow::Result<Self> {
         // Parse the strategy settings to extract the ranges for parameters to be optimized.
         let config = farukon_core::utils::parse_optimization_config(strategy_settings);
+        if !config.has_valid_combinations() {
+            anyhow::bail!(
+                "No combination of strategy_params satisfies the parameter constraints of {}",
+                strategy_settings.strategy_name,
+            );
+        }
 
         // Create the Grid Search optimizer and configure it with the extracted ranges.
         let grid_search_optimizer = farukon_core::optimization::GridSearchOptimizer::new()
             .with_optimization_config(config);
-        
-        Self {
+
+        // Load, resample and gap-fill the data once; every backtest only gets a cursor over it.
+        let market_data_store = data_handler::MarketDataStore::new_with_parallel_load(mode, strategy_settings)?;
+
+        // With hot reload the backtests load a private copy of the current build instead of `strategy_path`.
+        let library_watcher = match &strategy_settings.hot_reload {
+            Some(_) => {
+                let library_watcher = hot_reload::LibraryWatcher::new(strategy_settings)?;
+                println!(
+                    "Watching strategy library {} for new builds (build {})",
+                    strategy_settings.strategy_path.as_deref().unwrap_or_default(),
+                    library_watcher.current_build().hash,
+                );
+                Some(std::sync::Arc::new(std::sync::Mutex::new(library_watcher)))
+            },
+            None => None,
+        };
+
+        // With checkpoints every completed backtest is saved; a resumed run skips the backtests done before.
+        let checkpoint = match &strategy_settings.checkpoint {
+            Some(checkpoint_settings) => {
+                let checkpoint = checkpoint::Checkpoint::open(strategy_settings, checkpoint_settings, resume)?;
+                if resume {
+                    println!(
+                        "{}: resuming from checkpoint {} with {} completed backtests",
+                        strategy_settings.strategy_name,
+                        checkpoint.dir().display(),
+                        checkpoint.completed_count(),
+                    );
+                }
+                Some(std::sync::Arc::new(checkpoint))
+            },
+            None if resume => anyhow::bail!("--resume needs a checkpoint block in the settings of {}", strategy_settings.strategy_name),
+            None => None,
+        };
+
+        // With a result cache, backtests of earlier runs in the same context are reused.
+        let result_cache = match &strategy_settings.result_cache {
+            Some(cache_settings) => {
+                let result_cache = result_cache::ResultCache::open(
+                    strategy_settings,
+                    cache_settings,
+                    *initial_capital_for_strategy,
+                    strategy_instruments_info,
+                )?;
+                println!(
+                    "{}: result cache {} holds {} results of this strategy build, data and settings",
+                    strategy_settings.strategy_name,
+                    result_cache.path().display(),
+                    result_cache.context_len(),
+                );
+                Some(std::sync::Arc::new(result_cache))
+            },
+            None => None,
+        };
+
+        anyhow::Ok(Self {
             mode: mode.to_string(),
             initial_capital_for_strategy: *initial_capital_for_strategy,
             strategy_instruments_info: strategy_instruments_info.clone(),
             strategy_settings: strategy_settings.clone(),
             grid_search_optimizer,
-        }
+            market_data_store,
+            library_watcher,
+            checkpoint,
+            result_cache,
+        })
     }
 
     /// Provides access to the internal Grid Search optimizer.
@@ -67,9 +147,140 @@ impl OptimizationRunner {
         &self.grid_search_optimizer
     }
 
+    /// Ranks Grid Search combinations with the vectorized backtester and keeps the best `prefilter.keep` share of them.
+    /// Combinations with the same signal parameters share one vectorized backtest.
+    /// # Arguments
+    /// * `prefilter` - The pre-filter settings.
+    /// * `combinations` - The combinations of the grid.
+    /// # Returns
+    /// * `anyhow::Result<Vec<ParameterSet>>` - The kept combinations in grid order, or an error if a vectorized backtest fails.
+    pub fn prefilter_combinations(
+        &self,
+        prefilter: &farukon_core::settings::PrefilterSettings,
+        combinations: Vec<farukon_core::optimization::ParameterSet>,
+    ) -> anyhow::Result<Vec<farukon_core::optimization::ParameterSet>> {
+        use rayon::prelude::*;
+
+        let start_time = std::time::Instant::now();
+        let farukon_core::settings::PrefilterSignal::MaCross { indicator, fast_param, slow_param } = &prefilter.signal;
+        let indicator = indicator.as_deref().unwrap_or("sma");
+        let quantity = prefilter.quantity.unwrap_or(1.0);
+        let fitness_params = prefilter.fitness_params.clone().unwrap_or(farukon_core::settings::FitnessParams {
+            fitness_direction: "max".to_string(),
+            fitness_value: farukon_core::settings::FitnessValue::default(),
+        });
+
+        // Signal periods of each combination
+        let periods = combinations
+            .iter()
+            .map(|params| anyhow::Ok((Self::period_param(params, fast_param)?, Self::period_param(params, slow_param)?)))
+            .collect::<anyhow::Result<Vec<(usize, usize)>>>()?;
+        let mut signals = periods.clone();
+        signals.sort();
+        signals.dedup();
+
+        // --- Vectorized backtests, one per signal ---
+        let threads = self.strategy_settings.threads.unwrap_or(num_cpus::get());
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Failed to create thread pool");
+        let scores: std::collections::HashMap<(usize, usize), f64> = pool.install(|| {
+            signals
+                .par_iter()
+                .map(|&(fast, slow)| {
+                    let metrics = self.run_vectorized_backtest(indicator, fast, slow, quantity)?;
+                    let score = self.calculate_fitness_score(&metrics, &fitness_params.fitness_value, &fitness_params.fitness_direction);
+                    anyhow::Ok(((fast, slow), if score.is_nan() { f64::NEG_INFINITY } else { score }))
+                })
+                .collect::<anyhow::Result<_>>()
+        })?;
+
+        // --- Keep the best combinations, in grid order ---
+        let total_combinations = combinations.len();
+        let keep_count = ((total_combinations as f64 * prefilter.keep).ceil() as usize).clamp(1, total_combinations.max(1));
+        let mut ranking: Vec<usize> = (0..total_combinations).collect();
+        ranking.sort_by(|&a, &b| scores[&periods[b]].total_cmp(&scores[&periods[a]]));
+        let mut kept = vec![false; total_combinations];
+        for &index in ranking.iter().take(keep_count) {
+            kept[index] = true;
+        }
+        let survivors: Vec<farukon_core::optimization::ParameterSet> = combinations
+            .into_iter()
+            .zip(kept)
+            .filter_map(|(params, kept)| kept.then_some(params))
+            .collect();
+
+        println!(
+            "{}: prefilter kept {} of {} combinations after {} vectorized backtests in {:.3} seconds",
+            self.strategy_settings.strategy_name,
+            survivors.len(),
+            total_combinations,
+            signals.len(),
+            start_time.elapsed().as_secs_f64(),
+        );
+
+        anyhow::Ok(survivors)
+    }
+
+    /// Runs the vectorized backtest of a moving average crossover over every symbol of the strategy.
+    /// The averages come from the indicator cache of the market data store.
+    /// # Arguments
+    /// * `indicator` - Moving average name ("sma", "ema" or "wma").
+    /// * `fast` - Period of the fast average.
+    /// * `slow` - Period of the slow average.
+    /// * `quantity` - Contracts per position.
+    /// # Returns
+    /// * `anyhow::Result<PerformanceMetrics>` - The metrics of the vectorized backtest.
+    fn run_vectorized_backtest(
+        &self,
+        indicator: &str,
+        fast: usize,
+        slow: usize,
+        quantity: f64,
+    ) -> anyhow::Result<farukon_core::performance::PerformanceMetrics> {
+        let fast_spec = farukon_core::indicator_cache::IndicatorSpec::from_name(indicator, fast)
+            .ok_or_else(|| anyhow::anyhow!("Unknown prefilter indicator '{}'", indicator))?;
+        let slow_spec = farukon_core::indicator_cache::IndicatorSpec::from_name(indicator, slow)
+            .ok_or_else(|| anyhow::anyhow!("Unknown prefilter indicator '{}'", indicator))?;
+
+        let timeline_len = self.market_data_store.timeline_len();
+        let mut symbols = Vec::with_capacity(self.strategy_settings.symbols.len());
+        for symbol in &self.strategy_settings.symbols {
+            let bars = self.market_data_store.published_bars(symbol, timeline_len);
+            let (Some(fast_series), Some(slow_series)) = (
+                self.market_data_store.indicator_series(symbol, &fast_spec),
+                self.market_data_store.indicator_series(symbol, &slow_spec),
+            ) else {
+                anyhow::bail!("No market data for {}", symbol);
+            };
+            let instrument_info = self.strategy_instruments_info
+                .get(symbol)
+                .ok_or_else(|| anyhow::anyhow!("No instrument info for {}", symbol))?;
+            symbols.push(farukon_core::vectorized::crossover_targets(symbol, bars, &fast_series, &slow_series, quantity, instrument_info)?);
+        }
+
+        farukon_core::vectorized::VectorizedBacktester::new(
+            self.initial_capital_for_strategy,
+            &self.strategy_settings,
+            &self.strategy_instruments_info,
+        ).run(&symbols)
+    }
+
+    /// Reads a period (a positive integer) from the strategy parameters of a combination.
+    fn period_param(params: &farukon_core::optimization::ParameterSet, param_name: &str) -> anyhow::Result<usize> {
+        params.get_strategy_params()
+            .iter()
+            .find(|(name, _value)| name == param_name)
+            .and_then(|(_name, value)| value.as_u64().or_else(|| value.as_f64().map(|period| period as u64)))
+            .map(|period| period as usize)
+            .ok_or_else(|| anyhow::anyhow!("Prefilter parameter '{}' is missing or is not a period", param_name))
+    }
+
     /// Executes a Grid Search optimization.
     /// Evaluates all parameter combinations in parallel using Rayon.
     /// Each combination triggers a full backtest run.
+    /// With hot reload the combinations run in chunks, and `strategy_path` is checked for a new build before each chunk.
     /// # Arguments
     /// * `total_combinations` - The total number of parameter sets to evaluate.
     /// * `combinations_to_grid_search` - A vector of `ParameterSet` objects to test.
@@ -83,22 +294,284 @@ impl OptimizationRunner {
         // Determine the number of threads to use for parallel execution.
         // Defaults to the number of logical CPU cores if not specified in settings.
         let threads = self.strategy_settings.threads.unwrap_or(num_cpus::get());
-        let mode = self.mode.clone();
 
-        if mode == "Debug" {
+        if self.mode == "Debug" {
             println!("Starting grid search optimization:");
             println!("Strategy: {}", self.strategy_settings.strategy_name);
             println!("Configured threads: {:?}", threads);
             println!("Total combinations: {}", total_combinations);
         }
 
+        // Shared atomic counter to track the number of completed evaluations.
+        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let (Some(library_watcher), Some(hot_reload)) = (&self.library_watcher, &self.strategy_settings.hot_reload) else {
+            return self.run_grid_search_chunk(threads, &counter, total_combinations, combinations_to_grid_search, None);
+        };
+
+        // --- Hot reload: one build per chunk ---
+        let chunk_size = hot_reload.grid_chunk_size.unwrap_or(threads * 4);
+        let mut results = Vec::with_capacity(combinations_to_grid_search.len());
+        let mut pending = std::collections::VecDeque::from(combinations_to_grid_search);
+        let mut total_combinations = total_combinations;
+
+        while !pending.is_empty() {
+            let strategy_build = {
+                let mut library_watcher = library_watcher.lock().unwrap();
+                library_watcher.poll();
+                library_watcher.current_build().clone()
+            };
+            let chunk: Vec<farukon_core::optimization::ParameterSet> = pending.drain(..chunk_size.min(pending.len())).collect();
+            results.extend(self.run_grid_search_chunk(threads, &counter, total_combinations, chunk, Some(strategy_build)));
+
+            // Combinations evaluated by a replaced build run again with the newest one
+            if pending.is_empty() && hot_reload.rerun_stale {
+                let latest_build = {
+                    let mut library_watcher = library_watcher.lock().unwrap();
+                    library_watcher.poll();
+                    library_watcher.current_build().hash.clone()
+                };
+                let (stale_results, fresh_results): (Vec<_>, Vec<_>) = results
+                    .into_iter()
+                    .partition(|result: &farukon_core::optimization::OptimizationResult| result.get_strategy_build() != Some(latest_build.as_str()));
+                results = fresh_results;
+                if !stale_results.is_empty() {
+                    println!(
+                        "Re-running {} combinations evaluated by replaced builds with build {}",
+                        stale_results.len(), latest_build,
+                    );
+                    total_combinations += stale_results.len();
+                    pending.extend(stale_results.into_iter().map(|result| result.get_parameters().clone()));
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Executes a Random Search or Latin Hypercube optimization.
+    /// Draws `budget` parameter sets from the parameter ranges, without building the grid,
+    /// and evaluates them in parallel like a Grid Search (including hot reload).
+    /// # Arguments
+    /// * `method` - How the parameter sets are drawn.
+    /// * `sampling_params` - Budget and seed of the sampler.
+    /// # Returns
+    /// * A vector of `OptimizationResult` objects, one for each sampled parameter set.
+    pub fn run_sampling_search(
+        &self,
+        method: farukon_core::optimization::SamplingMethod,
+        sampling_params: &farukon_core::settings::SamplingParams,
+    ) -> Vec<farukon_core::optimization::OptimizationResult> {
+        let mut sampling_optimizer = farukon_core::optimization::SamplingOptimizer::new()
+            .with_optimization_config(self.grid_search_optimizer.get_config().clone())
+            .with_method(method)
+            .with_budget(sampling_params.budget);
+        if let Some(seed) = sampling_params.seed {
+            sampling_optimizer = sampling_optimizer.with_seed(seed);
+        }
+
+        let combinations = sampling_optimizer.generate_samples();
+        println!(
+            "{}: {:?} sampling drew {} of {} parameter sets (seed {})",
+            self.strategy_settings.strategy_name,
+            method,
+            combinations.len(),
+            sampling_params.budget,
+            sampling_optimizer.get_seed(),
+        );
+
+        self.run_grid_search(combinations.len(), combinations)
+    }
+
+    /// Executes a Bayesian optimization (tree-structured Parzen estimator).
+    /// Each proposed batch is evaluated in parallel like a Grid Search chunk; with hot reload a new build is picked up before each batch,
+    /// and the model is fitted again from the scores of the new build only. Every result is tagged with its build.
+    /// # Arguments
+    /// * `bayesian_params` - Budget, batching, convergence and fitness settings of the optimizer.
+    /// # Returns
+    /// * `anyhow::Result<Vec<OptimizationResult>>` - One result for each evaluated parameter set, in evaluation order.
+    pub fn run_bayesian_search(
+        &self,
+        bayesian_params: &farukon_core::settings::BayesianParams,
+    ) -> anyhow::Result<Vec<farukon_core::optimization::OptimizationResult>> {
+        let threads = self.strategy_settings.threads.unwrap_or(num_cpus::get());
+        let bayesian_config = farukon_core::optimization::BayesianConfig::from_settings(bayesian_params, threads);
+        println!(
+            "{}: Bayesian optimization of up to {} parameter sets in batches of {} (seed {})",
+            self.strategy_settings.strategy_name,
+            bayesian_config.get_budget(),
+            bayesian_config.get_batch_size(),
+            bayesian_config.get_seed(),
+        );
+
+        let bayesian_optimizer = farukon_core::optimization::BayesianOptimizer::new()
+            .with_bayesian_config(bayesian_config)
+            .with_optimization_config(self.grid_search_optimizer.get_config().clone());
+
+        // With hot reload a new build is picked up before each batch; the model forgets the scores of the replaced build.
+        let before_batch = |batch_idx: usize| {
+            let Some(library_watcher) = &self.library_watcher else {
+                return false;
+            };
+            let mut library_watcher = library_watcher.lock().unwrap();
+            let switched = batch_idx > 0 && library_watcher.poll().is_some();
+            if switched {
+                println!(
+                    "Batch {} uses strategy build {}; the scores of the replaced build are dropped from the model",
+                    batch_idx, library_watcher.current_build().hash,
+                );
+            }
+            switched
+        };
+
+        let fitness_params = &bayesian_params.fitness_params;
+        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut results = Vec::new();
+        bayesian_optimizer.run_with_batch_hook(|batch| {
+            let strategy_build = self.library_watcher
+                .as_ref()
+                .map(|library_watcher| library_watcher.lock().unwrap().current_build().clone());
+            let batch_results = self.run_grid_search_chunk(threads, &counter, bayesian_params.budget, batch.to_vec(), strategy_build);
+            let scores = batch_results
+                .iter()
+                .map(|result| self.calculate_fitness_score(result.get_results(), &fitness_params.fitness_value, &fitness_params.fitness_direction))
+                .collect();
+            results.extend(batch_results);
+            scores
+        }, before_batch)?;
+
+        anyhow::Ok(results)
+    }
+
+    /// Executes a multi-objective NSGA-II optimization.
+    /// Each generation's new parameter sets are evaluated in parallel like a Grid Search chunk; with hot reload a new build is picked up before each generation,
+    /// and the population is evaluated again with it.
+    /// # Arguments
+    /// * `nsga2_params` - Population, variation and objectives of the optimizer.
+    /// # Returns
+    /// * `anyhow::Result<(Vec<OptimizationResult>, Vec<ParetoPoint>)>` - One result for each evaluated parameter set, in evaluation order, and the Pareto front.
+    pub fn run_nsga2_search(
+        &self,
+        nsga2_params: &farukon_core::settings::Nsga2Params,
+    ) -> anyhow::Result<(Vec<farukon_core::optimization::OptimizationResult>, Vec<farukon_core::optimization::ParetoPoint>)> {
+        let threads = self.strategy_settings.threads.unwrap_or(num_cpus::get());
+        let nsga2_config = farukon_core::optimization::Nsga2Config::from_settings(nsga2_params);
+        println!(
+            "{}: NSGA-II optimization of {} objectives (seed {})",
+            self.strategy_settings.strategy_name,
+            nsga2_params.objectives.len(),
+            nsga2_config.get_seed(),
+        );
+        let max_evaluations = nsga2_config.get_population_size() * nsga2_config.get_max_generations();
+
+        let nsga2_optimizer = farukon_core::optimization::Nsga2Optimizer::new()
+            .with_nsga2_config(nsga2_config)
+            .with_optimization_config(self.grid_search_optimizer.get_config().clone());
+
+        // With hot reload a new build is picked up before each generation; the scores of the replaced build are dropped,
+        // so the Pareto front comes from one build.
+        let before_generation = |gen_idx: usize| {
+            let Some(library_watcher) = &self.library_watcher else {
+                return false;
+            };
+            let mut library_watcher = library_watcher.lock().unwrap();
+            let switched = gen_idx > 0 && library_watcher.poll().is_some();
+            if switched {
+                println!(
+                    "Generation {} uses strategy build {}; the population is evaluated again",
+                    gen_idx, library_watcher.current_build().hash,
+                );
+            }
+            switched
+        };
+
+        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut results = Vec::new();
+        let pareto_front = nsga2_optimizer.run_with_generation_hook(|batch| {
+            let strategy_build = self.library_watcher
+                .as_ref()
+                .map(|library_watcher| library_watcher.lock().unwrap().current_build().clone());
+            let batch_results = self.run_grid_search_chunk(threads, &counter, max_evaluations, batch.to_vec(), strategy_build);
+            let scores = batch_results
+                .iter()
+                .map(|result| {
+                    nsga2_params.objectives
+                        .iter()
+                        .map(|objective| self.calculate_fitness_score(result.get_results(), &objective.fitness_value, &objective.fitness_direction))
+                        .collect()
+                })
+                .collect();
+            results.extend(batch_results);
+            scores
+        }, before_generation)?;
+
+        anyhow::Ok((results, pareto_front))
+    }
+
+    /// Runs one chunk of Grid Search combinations in parallel.
+    /// Combinations completed before a resume (checkpoint) or by an earlier run (result cache) with the same build
+    /// are not backtested again; every new result is saved to the checkpoint and the result cache.
+    /// # Arguments
+    /// * `threads` - Number of threads.
+    /// * `counter` - Progress counter shared by the chunks.
+    /// * `total_combinations` - Number of combinations shown in the progress.
+    /// * `combinations` - The combinations of the chunk.
+    /// * `strategy_build` - Build to load instead of `strategy_path` (hot reload); every result is tagged with it.
+    /// # Returns
+    /// * A vector of `OptimizationResult` objects, one for each combination.
+    fn run_grid_search_chunk(
+        &self,
+        threads: usize,
+        counter: &std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        total_combinations: usize,
+        combinations: Vec<farukon_core::optimization::ParameterSet>,
+        strategy_build: Option<hot_reload::StrategyBuild>,
+    ) -> Vec<farukon_core::optimization::OptimizationResult> {
         // Capture necessary data for the parallel execution closure.
+        let mode = self.mode.clone();
         let initial_capital = self.initial_capital_for_strategy;
-        let strategy_settings = self.strategy_settings.clone();
+        let mut strategy_settings = self.strategy_settings.clone();
         let strategy_instruments_info = self.strategy_instruments_info.clone();
+        let market_data_store = self.market_data_store.clone();
+        let counter = counter.clone();
 
-        // Shared atomic counter to track the number of completed evaluations.
-        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let checkpoint = self.checkpoint.clone();
+        let result_cache = self.result_cache.clone();
+
+        let strategy_build_hash = strategy_build.as_ref().map(|strategy_build| strategy_build.hash.clone());
+        if let Some(strategy_build) = strategy_build {
+            strategy_settings.strategy_path = Some(strategy_build.path);
+        }
+
+        // Results restored from the checkpoint or found in the result cache, in the order of the combinations.
+        let mut restored_count = 0;
+        let mut cached_count = 0;
+        let reused: Vec<Option<farukon_core::optimization::OptimizationResult>> = combinations
+            .iter()
+            .map(|params| {
+                if let Some(result) = self.restored_result(params, strategy_build_hash.as_deref()) {
+                    restored_count += 1;
+                    Some(result)
+                } else if let Some(result) = self.cached_result(params, strategy_build_hash.as_deref()) {
+                    cached_count += 1;
+                    Some(result)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        counter.fetch_add(restored_count + cached_count, std::sync::atomic::Ordering::Relaxed);
+        if restored_count > 0 {
+            println!("{} of {} combinations restored from the checkpoint", restored_count, reused.len());
+        }
+        if cached_count > 0 {
+            println!("{} of {} combinations found in the result cache", cached_count, reused.len());
+        }
+        let combinations: Vec<farukon_core::optimization::ParameterSet> = combinations
+            .into_iter()
+            .zip(&reused)
+            .filter_map(|(params, reused)| reused.is_none().then_some(params))
+            .collect();
 
         // Create a Rayon thread pool with the specified number of threads.
         let pool = rayon::ThreadPoolBuilder::new()
@@ -142,22 +615,75 @@ impl OptimizationRunner {
                         &mode,
                         &initial_capital,
                         &test_settings,
-                        &strategy_instruments_info
+                        &strategy_instruments_info,
+                        &market_data_store,
                     );
                     
                     println!("# {} from {} is done in {:.3} seconds ", current_count, total_combinations, start_time.elapsed().as_secs_f64());
 
                     // Create an OptimizationResult object containing the parameters and the resulting performance metrics.
-                    farukon_core::optimization::OptimizationResult::new()
+                    let result = farukon_core::optimization::OptimizationResult::new()
                         .with_parameters(params.clone())
                         .with_results(results)
+                        .with_strategy_build(strategy_build_hash.clone());
+                    Self::record_result(&checkpoint, &result_cache, &result);
+                    result
                 },
                 threads, // Number of threads to use for the optimization.
-                combinations_to_grid_search // Vector of parameter sets to evaluate.
+                combinations // Vector of parameter sets to evaluate.
             )
         });
 
-        results
+        let mut results = results.into_iter();
+        reused
+            .into_iter()
+            .filter_map(|reused| reused.or_else(|| results.next()))
+            .collect()
+    }
+
+    /// Returns the result of a parameter set backtested by an earlier run with the build `strategy_build`, if the result cache has one.
+    /// The result is tagged with `strategy_build`, like the results of this run.
+    fn cached_result(
+        &self,
+        params: &farukon_core::optimization::ParameterSet,
+        strategy_build: Option<&str>,
+    ) -> Option<farukon_core::optimization::OptimizationResult> {
+        self.result_cache
+            .as_ref()?
+            .cached_result(params, strategy_build)
+            .map(|result| result.clone().with_strategy_build(strategy_build.map(str::to_string)))
+    }
+
+    /// Saves a new result to the checkpoint and the result cache, if set.
+    /// A failed write only loses the saved copy, so it is reported and the optimization goes on.
+    fn record_result(
+        checkpoint: &Option<std::sync::Arc<checkpoint::Checkpoint>>,
+        result_cache: &Option<std::sync::Arc<result_cache::ResultCache>>,
+        result: &farukon_core::optimization::OptimizationResult,
+    ) {
+        if let Some(checkpoint) = checkpoint
+            && let Err(error) = checkpoint.record_result(result)
+        {
+            println!("Warning: {:#}", error);
+        }
+        if let Some(result_cache) = result_cache
+            && let Err(error) = result_cache.record_result(result)
+        {
+            println!("Warning: {:#}", error);
+        }
+    }
+
+    /// Returns the result of a parameter set completed before a resume, if the checkpoint has one from the build `strategy_build`.
+    fn restored_result(
+        &self,
+        params: &farukon_core::optimization::ParameterSet,
+        strategy_build: Option<&str>,
+    ) -> Option<farukon_core::optimization::OptimizationResult> {
+        self.checkpoint
+            .as_ref()?
+            .completed_result(params)
+            .filter(|result| result.get_strategy_build() == strategy_build)
+            .cloned()
     }
 
     /// Executes a single backtest run with a given set of strategy parameters.
@@ -167,6 +693,7 @@ impl OptimizationRunner {
     /// * `initial_capital_for_strategy` - The starting capital for this backtest run.
     /// * `strategy_settings` - The strategy settings, potentially modified with new parameters.
     /// * `strategy_instruments_info` - Metadata for the instruments traded.
+    /// * `market_data_store` - The strategy's shared market data.
     /// # Returns
     /// * `farukon_core::performance::PerformanceMetrics` - The performance metrics from the completed backtest.
     fn run_backtest_with_settings(
@@ -174,38 +701,36 @@ impl OptimizationRunner {
         initial_capital_for_strategy: &f64,
         strategy_settings: &farukon_core::settings::StrategySettings,
         strategy_instruments_info:  &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+        market_data_store: &std::sync::Arc<data_handler::MarketDataStore>,
     ) -> farukon_core::performance::PerformanceMetrics {
         // Creates a full backtest environment for a single parameter set.
         // Used by Grid Search and Genetic Algorithm.
 
-        // Create the event channel used for communication between components (DataHandler, Strategy, Portfolio, Execution).
-        let (event_sender, event_receiver) = std::sync::mpsc::channel::<Box<dyn farukon_core::event::Event>>();
+        // Create the event queue used for communication between components (DataHandler, Strategy, Portfolio, Execution).
+        let event_queue = farukon_core::event::EventQueue::new();
 
-        // Initialize the data handler (uses zero-copy FlatBuffers).
+        // Initialize the data handler: a cursor over the shared market data.
         let data_handler: Box<dyn farukon_core::data_handler::DataHandler> = Box::new(
-            data_handler::HistoricFlatBuffersDataHandlerZC::new_with_sequential_load(
-                mode,
-                event_sender.clone(),
-                strategy_settings,
-            ).expect("Failed to create data handler")
+            data_handler::HistoricFlatBuffersDataHandlerZC::from_store(
+                market_data_store.clone(),
+                event_queue.clone(),
+            )
         );
 
-        // Load the dynamic strategy library (.so/.dylib) specified in settings.
-        let dynamic_strategy: Box<strategy_loader::DynamicStratagy> = Box::new(
-            strategy_loader::DynamicStratagy::load_from_path(
-                mode,
-                strategy_settings,
-                strategy_instruments_info,
-                &event_sender,
-            ).expect("Failed to load dynamic strategy")
-        );
+        // Create the strategy: from the library at `strategy_path`, or compiled into the engine.
+        let strategy = strategy_loader::create_strategy(
+            mode,
+            strategy_settings,
+            strategy_instruments_info,
+            &event_queue,
+        ).expect("Failed to create strategy");
 
         // Initialize the portfolio manager.
         let portfolio: Box<dyn farukon_core::portfolio::PortfolioHandler> = Box::new(
             portfolio::Portfolio::new(
                 mode,
                 initial_capital_for_strategy,
-                event_sender.clone(),
+                event_queue.clone(),
                 strategy_settings,
                 strategy_instruments_info,
             ).expect("Failed to create portfolio")
@@ -214,7 +739,7 @@ impl OptimizationRunner {
         // Initialize the simulated execution handler.
         let execution_handler: Box<dyn farukon_core::execution::ExecutionHandler> = Box::new(
             execution::SimulatedExecutionHandler::new(
-                event_sender.clone(),
+                event_queue.clone(),
             ).expect("Failed to create execution handler")
         );
 
@@ -224,12 +749,19 @@ impl OptimizationRunner {
             strategy_settings,
             strategy_instruments_info,
             data_handler,
-            event_receiver,
-            dynamic_strategy,
+            event_queue.clone(),
+            strategy,
             portfolio,
             execution_handler
         );
 
+        // Record the event journal if requested.
+        if let Some(journal_settings) = &strategy_settings.event_journal {
+            let event_journal = event_journal::EventJournal::create(strategy_settings, journal_settings)
+                .expect("Failed to create event journal");
+            backtest = backtest.with_event_journal(event_journal);
+        }
+
         // Run the backtest simulation and return the final performance metrics.
         backtest.simulate_trading().expect("Backtest failed").clone()
 
@@ -249,46 +781,106 @@ impl OptimizationRunner {
         let ga_config = farukon_core::optimization::GAConfig::from_settings(ga_params);
         // Get the optimization configuration (parameter ranges) for the strategy.
         let opt_config = farukon_core::utils::parse_optimization_config(&self.strategy_settings);
+        // A resumed run continues after the last generation saved in the checkpoint.
+        let ga_state = match &self.checkpoint {
+            Some(checkpoint) => checkpoint.load_ga_state()?,
+            None => None,
+        };
         // Create the Genetic Algorithm instance.
         let mut ga = farukon_core::optimization::GeneticAlgorythm::new()
             .with_ga_config(ga_config.clone())
-            .with_optimization_config(opt_config);
+            .with_optimization_config(opt_config)
+            .with_state(ga_state);
+
+        // With hot reload a new build is picked up before each generation; the builds are recorded per generation.
+        let library_watcher = self.library_watcher.clone();
+        let mut generation_builds: std::collections::HashMap<usize, String> = std::collections::HashMap::new();
+        let before_generation = |gen_idx: usize| {
+            let Some(library_watcher) = &library_watcher else {
+                return false;
+            };
+            let mut library_watcher = library_watcher.lock().unwrap();
+            // Fitness cached from the replaced build must not be reused.
+            let switched = gen_idx > 0 && library_watcher.poll().is_some();
+            println!("Generation # {} uses strategy build {}", gen_idx, library_watcher.current_build().hash);
+            generation_builds.insert(gen_idx, library_watcher.current_build().hash.clone());
+            switched
+        };
+        // The state after every generation is saved to the checkpoint.
+        let checkpoint = self.checkpoint.clone();
+        let after_generation = |ga_state: &farukon_core::optimization::GAState| match &checkpoint {
+            Some(checkpoint) => checkpoint.save_ga_state(ga_state),
+            None => anyhow::Ok(()),
+        };
         
         // Run the Genetic Algorithm, providing a fitness function that evaluates parameter sets.
-        let stats = ga.run(&self.strategy_settings.clone(), move |params| {
+        let stats = ga.run_with_hooks(&self.strategy_settings.clone(), move |params| {
+            let strategy_build = self.library_watcher
+                .as_ref()
+                .map(|library_watcher| library_watcher.lock().unwrap().current_build().clone());
+            let strategy_build_hash = strategy_build.as_ref().map(|strategy_build| strategy_build.hash.clone());
+            // A backtest completed before the resume or by an earlier run is not run again.
+            if let Some(result) = self.restored_result(params, strategy_build_hash.as_deref())
+                .or_else(|| self.cached_result(params, strategy_build_hash.as_deref()))
+            {
+                return self.calculate_fitness_score(result.get_results(), ga_config.get_fitness_metric(), ga_config.get_fitness_direction());
+            }
             // Create temporary strategy settings based on the current parameter set for this generation.
-            let test_settings = &farukon_core::utils::create_stratagy_settings_from_params(&self.strategy_settings, params);
+            let mut test_settings = farukon_core::utils::create_stratagy_settings_from_params(&self.strategy_settings, params);
+            if let Some(strategy_build) = strategy_build {
+                test_settings.strategy_path = Some(strategy_build.path);
+            }
+            let test_settings = &test_settings;
             // Run a backtest with these parameters.
             let backtest_result = Self::run_backtest_with_settings(
                 &self.mode,
                 &self.initial_capital_for_strategy,
                 test_settings, 
                 &self.strategy_instruments_info,
+                &self.market_data_store,
             );
             // Calculate the fitness score based on the backtest results.
-            self.calculate_fitness_score(&backtest_result, &ga_config)  
-        })?;
+            let fitness = self.calculate_fitness_score(&backtest_result, ga_config.get_fitness_metric(), ga_config.get_fitness_direction());
+            if self.checkpoint.is_some() || self.result_cache.is_some() {
+                let result = farukon_core::optimization::OptimizationResult::new()
+                    .with_parameters(params.clone())
+                    .with_results(backtest_result)
+                    .with_strategy_build(strategy_build_hash);
+                Self::record_result(&self.checkpoint, &self.result_cache, &result);
+            }
+            fitness
+        }, before_generation, after_generation)?;
+
+        let stats = stats
+            .into_iter()
+            .map(|stat| {
+                let strategy_build = generation_builds.get(&stat.get_generation()).cloned();
+                stat.with_strategy_build(strategy_build)
+            })
+            .collect();
 
         anyhow::Ok(stats)
     }
 
     /// Calculates a scalar fitness score from performance metrics.
-    /// The score is used by the Genetic Algorithm to rank parameter sets.
+    /// The score is used by the Genetic Algorithm and the Grid Search pre-filter to rank parameter sets.
     /// # Arguments
     /// * `metrics` - The performance metrics from a backtest run.
-    /// * `ga_config` - Configuration specifying the fitness metric and direction (maximize/minimize).
+    /// * `fitness_metric` - The metric to score.
+    /// * `fitness_direction` - "max" or "min".
     /// # Returns
     /// * `f64` - The calculated fitness score.
     fn calculate_fitness_score(
         &self,
         metrics: &farukon_core::performance::PerformanceMetrics,
-        ga_config: &farukon_core::optimization::GAConfig,
+        fitness_metric: &farukon_core::settings::FitnessValue,
+        fitness_direction: &str,
     ) -> f64 {
         // Converts performance metrics into a scalar fitness score.
         // Supports max/min direction and composite metrics.
 
         // Determine which metric to use for the raw fitness score.
-        let raw_fitness = match ga_config.get_fitness_metric() {
+        let raw_fitness = match fitness_metric {
             farukon_core::settings::FitnessValue::TotalReturn => metrics.get_total_return(),
             farukon_core::settings::FitnessValue::TotalReturnPercent => metrics.get_total_return_percent(),
             farukon_core::settings::FitnessValue::APR => metrics.get_apr(),
@@ -304,7 +896,7 @@ impl OptimizationRunner {
 
         // Apply the fitness direction (maximize or minimize).
         // If direction is "min", the score is negated.
-        let fitness = match ga_config.get_fitness_direction().as_str() {
+        let fitness = match fitness_direction {
             "max" => *raw_fitness,
             "min" => -raw_fitness,
             _ => *raw_fitness, // Default to "max" if direction is unknown.
@@ -351,6 +943,101 @@ impl OptimizationRunner {
         total_score
     }
 
+    /// Saves the Pareto front of an NSGA-II optimization to `pareto_front.csv`.
+    /// Each row holds a parameter set, the raw value of every objective and the crowding distance of the point.
+    /// With hot reload the front comes from the last build of the run, named in the `strategy_build` column.
+    /// # Arguments
+    /// * `pareto_front` - The non-dominated parameter sets.
+    /// * `objectives` - The objectives of the optimization, in the order of the scores of the points.
+    /// # Returns
+    /// * `anyhow::Result<()>` - `Ok(())` on success, or an `Err` if file creation or writing fails.
+    pub fn save_pareto_front(
+        &self,
+        pareto_front: &[farukon_core::optimization::ParetoPoint],
+        objectives: &[farukon_core::settings::FitnessParams],
+    ) -> anyhow::Result<()> {
+        let filename = format!("{}/pareto_front.csv", self.strategy_settings.exit_results_path);
+        let mut file = std::fs::File::create(&filename)?;
+
+        // --- Header ---
+        let mut strategy_params: Vec<&String> = self.strategy_settings.strategy_params.keys().collect();
+        strategy_params.sort();
+        let mut pos_sizer_additional_params: Vec<&String> = self.strategy_settings.pos_sizer_params.pos_sizer_params.keys().collect();
+        pos_sizer_additional_params.sort();
+
+        for name in &strategy_params {
+            write!(file, "{};", name)?;
+        }
+        write!(file, "pos_sizer_name;pos_sizer_value;")?;
+        for name in &pos_sizer_additional_params {
+            write!(file, "{};", name)?;
+        }
+        write!(file, "slippage;")?;
+        // Scores of replaced builds are dropped by `run_nsga2_search`: the front comes from the current build.
+        let strategy_build = self.library_watcher
+            .as_ref()
+            .map(|library_watcher| library_watcher.lock().unwrap().current_build().hash.clone());
+        if strategy_build.is_some() {
+            write!(file, "strategy_build;")?;
+        }
+        for objective in objectives {
+            write!(file, "{};", Self::objective_name(objective))?;
+        }
+        writeln!(file, "crowding_distance")?;
+
+        // --- Rows ---
+        for point in pareto_front {
+            let params = point.get_parameters();
+            let strategy_params_map: std::collections::HashMap<&String, &serde_json::Value> = params
+                .get_strategy_params()
+                .iter()
+                .map(|(name, value)| (name, value))
+                .collect();
+            for name in &strategy_params {
+                if let Some(value) = strategy_params_map.get(name) {
+                    write!(file, "{};", value)?;
+                }
+            }
+            write!(file, "{};{};", params.get_pos_sizer_name(), params.get_pos_sizer_value())?;
+            let pos_sizer_additional_params_map: std::collections::HashMap<&String, &serde_json::Value> = params
+                .get_pos_sizer_additional_params()
+                .iter()
+                .map(|(name, value)| (name, value))
+                .collect();
+            for name in &pos_sizer_additional_params {
+                if let Some(value) = pos_sizer_additional_params_map.get(name) {
+                    write!(file, "{};", value)?;
+                }
+            }
+            write!(file, "{};", params.get_slippage())?;
+            if let Some(strategy_build) = &strategy_build {
+                write!(file, "{};", strategy_build)?;
+            }
+
+            // Scores of objectives to minimize are negated; the file holds the raw metric values.
+            for (objective, score) in objectives.iter().zip(point.get_objectives()) {
+                let value = if objective.fitness_direction == "min" { -score } else { *score };
+                write!(file, "{:.5};", value)?;
+            }
+            writeln!(file, "{:.5}", point.get_crowding_distance())?;
+        }
+
+        println!("Pareto front of {} parameter sets saved to {}", pareto_front.len(), filename);
+        anyhow::Ok(())
+    }
+
+    /// Column name of an objective in `pareto_front.csv`, e.g. `APR (max)`.
+    fn objective_name(objective: &farukon_core::settings::FitnessParams) -> String {
+        let metric = match &objective.fitness_value {
+            farukon_core::settings::FitnessValue::Composite { metrics } => format!("Composite({})", metrics.join("+")),
+            fitness_value => serde_json::to_value(fitness_value)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_string))
+                .unwrap_or_default(),
+        };
+        format!("{} ({})", metric, objective.fitness_direction)
+    }
+
     /// Saves the results of a Grid Search optimization to a CSV file.
     /// The CSV file contains the tested parameter sets and their corresponding performance metrics.
     /// This allows for easy analysis and comparison of different hyperparameter combinations.
@@ -412,6 +1099,26 @@ impl OptimizationRunner {
         // Adds a column name for the slippage parameter used in the test.
         write!(file, "slippage;")?;
 
+        // --- 3.6.1: Write Strategy Build Column Names (hot reload) ---
+        // Every row names the build that produced it; `mixed_builds` flags an optimization run with several builds.
+        let hot_reload = self.strategy_settings.hot_reload.is_some();
+        let strategy_builds: std::collections::BTreeSet<&str> = results
+            .iter()
+            .filter_map(|result| result.get_strategy_build())
+            .collect();
+        let mixed_builds = strategy_builds.len() > 1;
+        if hot_reload {
+            write!(file, "strategy_build;mixed_builds;")?;
+            if mixed_builds {
+                println!(
+                    "Warning: the optimization results come from {} strategy builds ({}); see the strategy_build column of {}",
+                    strategy_builds.len(),
+                    strategy_builds.into_iter().collect::<Vec<_>>().join(", "),
+                    filename,
+                );
+            }
+        }
+
         // --- 3.7: Extract Performance Metric Names ---
         // Gets the names of the performance metrics from the *first* result.
         // Assumes all results have the same set of metrics.
@@ -489,6 +1196,11 @@ impl OptimizationRunner {
             // Writes the slippage value used for this result.
             write!(file, "{};", result.get_parameters().get_slippage())?;
 
+            // --- 4.6.1: Write Strategy Build (hot reload) ---
+            if hot_reload {
+                write!(file, "{};{};", result.get_strategy_build().unwrap_or_default(), mixed_builds)?;
+            }
+
             // --- 4.7: Extract Performance Metric Values ---
             // Gets the map of (metric_name, metric_value_string) for the current result's performance metrics.
             let performance_metrics_map: std::collections::HashMap<_, _> = result
@@ -13,7 +13,7 @@ pub struct Portfolio {
     mode: String,
     initial_capital_for_strategy: f64,
     /// Event sender for communicating with other components.
-    event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+    event_sender: farukon_core::event::EventQueue,
     /// Strategy settings for this portfolio.
     strategy_settings: farukon_core::settings::StrategySettings,
     /// Instrument metadata for all traded instruments.
@@ -43,7 +43,7 @@ impl Portfolio {
     pub fn new(
         mode: &String,
         initial_capital_for_strategy: &f64,
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        event_sender: farukon_core::event::EventQueue,
         strategy_settings: &farukon_core::settings::StrategySettings,
         strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
     ) -> anyhow::Result<Self> {
@@ -119,22 +119,22 @@ impl Portfolio {
             let mut quantity = signal_event.quantity.unwrap().abs();
             let mut direction = None;
 
-            if signal_name != "EXIT" && cur_quantity == 0.0 && cash > 0.0 {
+            if *signal_name != farukon_core::event::SignalName::Exit && cur_quantity == 0.0 && cash > 0.0 {
                 quantity = farukon_core::utils::calculate_max_available_quantity(
                     cash,
                     quantity,
                     self.strategy_instruments_info.get(symbol).unwrap());
                 
-                if signal_name == "LONG"  {
-                direction = Some("BUY".to_string());
-                } else if signal_name == "SHORT" {
-                direction = Some("SELL".to_string());
-            }
+                direction = match signal_name {
+                    farukon_core::event::SignalName::Long => Some(farukon_core::event::Direction::Buy),
+                    farukon_core::event::SignalName::Short => Some(farukon_core::event::Direction::Sell),
+                    farukon_core::event::SignalName::Exit => None,
+                };
             } else {
                 if cur_quantity > 0.0 {
-                direction = Some("SELL".to_string());
+                direction = Some(farukon_core::event::Direction::Sell);
                 } else if cur_quantity < 0.0 {
-                    direction = Some("BUY".to_string());
+                    direction = Some(farukon_core::event::Direction::Buy);
             }
             }
 
@@ -146,16 +146,26 @@ impl Portfolio {
                 let order = Some(farukon_core::event::OrderEvent::new(
                     current_datetime,
                     symbol.to_string(),
-                    order_type.to_string(),
+                    *order_type,
                     quantity,
                     direction,
-                    signal_name.to_string(),
+                    *signal_name,
                     limit_price,
                 ));
 
                 return order;
             }
 
+            // Let the strategy know its signal was dropped
+            if let Err(e) = self.event_sender.send(farukon_core::event::OrderRejectedEvent::new(
+                current_datetime,
+                signal_event.clone(),
+                quantity,
+                farukon_core::event::RejectionReason::InsufficientMargin,
+            )) {
+                eprintln!("Failed to send OrderRejectedEvent: {}", e);
+            }
+
             None
         }
 
@@ -179,28 +189,27 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
             println!("for Fill event, start Current_positions, {:?}, {:?}", timeindex, self.current_positions);
         }
 
-        let fill_dir = match fill_event.direction.as_deref() {
-            Some("BUY") => 1.0,
-            Some("SELL") => -1.0,
-            _ => {
-                eprintln!("Unknown fill direction: {}", fill_event.direction.clone().unwrap());
+        let fill_dir = match fill_event.direction {
+            Some(direction) => direction.sign(),
+            None => {
+                eprintln!("Unknown fill direction: {:?}", fill_event.direction);
                 0.0
             },
         };
         let symbol = &fill_event.symbol;
         let quantity = fill_event.quantity;
-        let signal_name = fill_event.signal_name.as_str();
+        let signal_name = fill_event.signal_name;
         let current_cash  = self.get_latest_holdings().unwrap().cash;
 
         if let Some(position_state) = self.current_positions.get_mut(symbol) {
             position_state.position += fill_dir * quantity;
 
             match signal_name {
-                "EXIT" => {
+                farukon_core::event::SignalName::Exit => {
                     position_state.entry_price = None;
                     position_state.entry_capital = 0.0;
                 },
-                _ => {
+                farukon_core::event::SignalName::Long | farukon_core::event::SignalName::Short => {
                     position_state.deal_number += 1;
                     position_state.entry_price = fill_event.execution_price;
                     position_state.entry_capital = current_cash;
@@ -233,8 +242,8 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
         let quantity = fill_event.quantity;
         let symbol = &fill_event.symbol;
         let commission = fill_event.commission.unwrap();
-        let signal_name = fill_event.signal_name.as_str();
-        let direction = fill_event.direction.clone().unwrap();
+        let signal_name = fill_event.signal_name;
+        let direction = fill_event.direction.unwrap();
         let execution_price = fill_event.execution_price.unwrap_or(0.0);
         let close = data_handler.get_latest_bar_value(symbol, "close").unwrap_or(0.0);
         let last_close = data_handler.get_latest_bars_values(symbol, "close", 2)[0];
@@ -244,21 +253,17 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
         let step = strategy_instrument_info_for_symbol.step;
         let cost_of_step_price = ((step_price / step) * 100_000.0).round() / 100_000.0;
       
-        self.current_holdings.get_mut(symbol).unwrap().signal_name = Some(fill_event.signal_name.clone());
+        self.current_holdings.get_mut(symbol).unwrap().signal_name = Some(fill_event.signal_name);
         
         match signal_name {
-            "EXIT" => {
-                match  direction.as_str() {
-                    "BUY" => {
+            farukon_core::event::SignalName::Exit => {
+                match direction {
+                    farukon_core::event::Direction::Buy => {
                         self.current_holdings.get_mut(symbol).unwrap().pnl = ((((last_close - execution_price) * cost_of_step_price) * quantity * 100.0).round() / 100.0) - commission;
                     },
-                    "SELL" => {
+                    farukon_core::event::Direction::Sell => {
                         self.current_holdings.get_mut(symbol).unwrap().pnl = ((((execution_price - last_close) * cost_of_step_price) * quantity * 100.0).round() / 100.0) - commission;
                     },
-                    _ => {
-                        eprintln!("Unknown fill direction: {:?}", fill_event.direction);
-                        return;
-                    }
                 }
 
                 match self.strategy_instruments_info.get(symbol).unwrap().instrument_type.as_str() {
@@ -270,18 +275,14 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
                     }
                 }
             },
-            _ => {
-                match direction.as_str() {
-                    "BUY" => {
+            farukon_core::event::SignalName::Long | farukon_core::event::SignalName::Short => {
+                match direction {
+                    farukon_core::event::Direction::Buy => {
                         self.current_holdings.get_mut(symbol).unwrap().pnl = ((((close - execution_price) * cost_of_step_price) * quantity * 100.0).round() / 100.0) - commission;
                     },
-                    "SELL" => {
+                    farukon_core::event::Direction::Sell => {
                         self.current_holdings.get_mut(symbol).unwrap().pnl = ((((execution_price - close) * cost_of_step_price) * quantity * 100.0).round() / 100.0) - commission;
                     },
-                    _ => {
-                        eprintln!("Unknown fill direction: {:?}", fill_event.direction);
-                        return;
-                    }
                 }
 
                 match self.strategy_instruments_info.get(symbol).unwrap().instrument_type.as_str() {
@@ -324,9 +325,13 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
         // Called on every MARKET event to update equity curve, snapshots, and metrics.
         // Also triggers margin call monitoring.
 
-        let current_bar_datetime = data_handler.get_latest_bar_datetime(
-            &self.strategy_settings.symbols[0]
-            ).unwrap();
+        // The timeline position is the most recent bar over all symbols:
+        // with the `skip` gap policy a single symbol's latest bar can be stale.
+        let current_bar_datetime = self.strategy_settings.symbols
+            .iter()
+            .filter_map(|symbol| data_handler.get_latest_bar_datetime(symbol))
+            .max()
+            .unwrap();
 
         if self.mode == "Debug".to_string() {
             println!(
@@ -357,16 +362,15 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
         // Update unrealized PnL for open positions
         {
             for symbol in &self.strategy_settings.symbols {
-                let close = match data_handler.get_latest_bar_value(symbol, "close") {
-                    Some(value) if value.is_nan() => 0.0,
-                    Some(value) => value,
-                    None => 0.0,
-                };
-
-                let last_close = match data_handler.get_latest_bars_values(symbol, "close", 2).first() {
-                    Some(value) if value.is_nan() => 0.0,
-                    Some(value) => *value,
-                    None => 0.0,
+                // PnL is only computed on a real bar of this timeline step with two valid closes.
+                // Filled (forward-filled / synthetic), stale (skipped) and NaN bars yield no PnL.
+                let is_new_real_bar = data_handler.get_latest_bar(symbol)
+                    .map(|bar| !bar.is_filled && bar.datetime == current_bar_datetime)
+                    .unwrap_or(false);
+                let latest_closes = data_handler.get_latest_bars_values(symbol, "close", 2);
+                let (last_close, close) = match latest_closes.as_slice() {
+                    [last_close, close] if is_new_real_bar && last_close.is_finite() && close.is_finite() => (*last_close, *close),
+                    _ => (0.0, 0.0),
                 };
 
                 let strategy_instrument_info_for_symbol = self.strategy_instruments_info.get(symbol).unwrap();
@@ -452,9 +456,7 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
         // Update metrics incrementally if in RealTime mode
         {
             let start_date = self.get_all_holdings().first().unwrap().datetime;
-            let end_date = data_handler.get_latest_bar_datetime(
-                self.strategy_settings.symbols.first().unwrap()
-            ).unwrap();
+            let end_date = current_bar_datetime;
 
             // Update deals counter
             let mut deals_count = 0 as usize;
@@ -487,17 +489,26 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
                 &self.strategy_instruments_info
             ).unwrap();
             if !margin_call_monitoring {
+                let _ = self.event_sender.send(farukon_core::event::MarginCallEvent::new(
+                    current_bar_datetime,
+                    self.get_latest_holdings().unwrap().capital,
+                    risks::min_margin_for_positions(
+                        self.get_current_positions(),
+                        &self.strategy_settings,
+                        &self.strategy_instruments_info,
+                    ),
+                ));
                 for symbol in &self.strategy_settings.symbols {
                     println!("{:?}", margin_call_monitoring);
                     let quantity = Some(self.get_current_positions().get(symbol).unwrap().position);
-                    let _ = self.event_sender.send(Box::new(farukon_core::event::SignalEvent::new(
+                    let _ = self.event_sender.send(farukon_core::event::SignalEvent::new(
                 current_bar_datetime,
                         symbol.clone(),
-                        "EXIT".to_string(),
-                        "MKT".to_string(),
+                        farukon_core::event::SignalName::Exit,
+                        farukon_core::event::OrderType::Market,
                         quantity,
                         None,
-                    )));
+                    ));
                 }
             }
         }
@@ -529,7 +540,7 @@ impl farukon_core::portfolio::PortfolioHandler for Portfolio {
         }
 
         if let Some(order) = self.generate_order(signal_event, data_handler) {
-            match self.event_sender.send(Box::new(order)) {
+            match self.event_sender.send(order) {
                 Ok(()) => {},
                 Err(e) => eprintln!("Failed to send OrderEvent: {}", e),
             }
@@ -0,0 +1,200 @@
+// Farukon_2_0/src/result_cache.rs
+
+//! Persistent cache of backtest results, shared by optimization runs.
+//! The cache is an append-only JSONL file; each line holds one `OptimizationResult` and the context of its backtest.
+//!
+//! The context is a hash of everything a backtest depends on besides its `ParameterSet`:
+//! the strategy build, the `.bin`/`.idx` data files, the instrument, commission and session settings,
+//! the initial capital and the remaining strategy settings (timeframe, margin, gap policy, ...).
+//! A re-run of an overlapping Grid Search or GA reuses the results with the same context and parameters.
+
+use anyhow::Context;
+use std::io::Write;
+
+use crate::checkpoint;
+use crate::hot_reload;
+
+/// Strategy settings left out of the context: the optimized parameters (part of each `ParameterSet`),
+/// the strategy build (hashed from the file) and settings that do not change a backtest.
+const IGNORED_SETTINGS: [&str; 13] = [
+    "threads",
+    "strategy_path",
+    "exit_results_path",
+    "strategy_weight",
+    "slippage",
+    "strategy_params",
+    "pos_sizer_params",
+    "optimizer_type",
+    "event_journal",
+    "hot_reload",
+    "prefilter",
+    "checkpoint",
+    "result_cache",
+];
+
+/// One line of the cache file.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    context: String,
+    result: farukon_core::optimization::OptimizationResult,
+}
+
+/// Result cache of one strategy.
+#[derive(Debug)]
+pub struct ResultCache {
+    path: std::path::PathBuf,
+    /// The cache file, shared by the threads of the optimization.
+    writer: std::sync::Mutex<std::fs::File>,
+    /// Hash of the backtest context without the strategy build.
+    base_context: String,
+    /// Build of `strategy_path` (or of the engine, for built-in strategies) when the cache was opened.
+    strategy_build: String,
+    /// Cached results, by `<context>|<ParameterSet::cache_key>`.
+    entries: std::collections::HashMap<String, farukon_core::optimization::OptimizationResult>,
+}
+
+impl ResultCache {
+    /// Opens (or creates) the result cache of a strategy and hashes the context of its backtests.
+    /// # Arguments
+    /// * `strategy_settings` - Settings of the optimized strategy.
+    /// * `cache_settings` - The `result_cache` block of the strategy.
+    /// * `initial_capital_for_strategy` - The starting capital of the backtests.
+    /// * `strategy_instruments_info` - Metadata for the instruments traded.
+    /// # Returns
+    /// * `anyhow::Result<ResultCache>` - The cache, or an error if a file cannot be read.
+    pub fn open(
+        strategy_settings: &farukon_core::settings::StrategySettings,
+        cache_settings: &farukon_core::settings::ResultCacheSettings,
+        initial_capital_for_strategy: f64,
+        strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+    ) -> anyhow::Result<Self> {
+        let path = match &cache_settings.path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => std::path::Path::new(&strategy_settings.exit_results_path).join("result_cache.jsonl"),
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create result cache directory {}", dir.display()))?;
+        }
+
+        let base_context = base_context(strategy_settings, initial_capital_for_strategy, strategy_instruments_info)?;
+        let strategy_build = strategy_build(strategy_settings)?;
+
+        let cache_entries: Vec<CacheEntry> = checkpoint::read_jsonl(&path)?;
+        let entries = cache_entries
+            .into_iter()
+            .map(|entry| (format!("{}|{}", entry.context, entry.result.get_parameters().cache_key()), entry.result))
+            .collect();
+
+        let writer = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open result cache {}", path.display()))?;
+
+        anyhow::Ok(ResultCache {
+            path,
+            writer: std::sync::Mutex::new(writer),
+            base_context,
+            strategy_build,
+            entries,
+        })
+    }
+
+    /// Returns the cache file.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Returns the number of cached results with the context of the current strategy build.
+    pub fn context_len(&self) -> usize {
+        let prefix = format!("{}|", self.context(None));
+        self.entries.keys().filter(|key| key.starts_with(&prefix)).count()
+    }
+
+    /// Returns the cached result of a parameter set backtested by the build `strategy_build`
+    /// (the build of `strategy_path` when `None`).
+    pub fn cached_result(
+        &self,
+        params: &farukon_core::optimization::ParameterSet,
+        strategy_build: Option<&str>,
+    ) -> Option<&farukon_core::optimization::OptimizationResult> {
+        self.entries.get(&format!("{}|{}", self.context(strategy_build), params.cache_key()))
+    }
+
+    /// Appends a new result to the cache file, with the context of the build that produced it.
+    /// Each result is written with a single append, so runs sharing the cache file do not mix their lines.
+    pub fn record_result(&self, result: &farukon_core::optimization::OptimizationResult) -> anyhow::Result<()> {
+        let entry = CacheEntry {
+            context: self.context(result.get_strategy_build()),
+            result: result.clone(),
+        };
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(line.as_bytes())
+            .and_then(|_| writer.flush())
+            .with_context(|| format!("Failed to write result cache {}", self.path.display()))
+    }
+
+    /// Context of the backtests run by the build `strategy_build` (the build of `strategy_path` when `None`).
+    fn context(&self, strategy_build: Option<&str>) -> String {
+        let strategy_build = strategy_build.unwrap_or(&self.strategy_build);
+        hot_reload::content_hash(format!("{}|{}", self.base_context, strategy_build).as_bytes())
+    }
+
+}
+
+/// Hashes the context of the backtests of a strategy, without the strategy build.
+fn base_context(
+    strategy_settings: &farukon_core::settings::StrategySettings,
+    initial_capital_for_strategy: f64,
+    strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+) -> anyhow::Result<String> {
+    let mut settings = serde_json::to_value(strategy_settings)?;
+    if let Some(settings) = settings.as_object_mut() {
+        for key in IGNORED_SETTINGS {
+            settings.remove(key);
+        }
+    }
+
+    let mut data_files = std::collections::BTreeMap::new();
+    for symbol in &strategy_settings.symbols {
+        for extension in ["bin", "idx"] {
+            let file_name = format!("{}.{}", symbol, extension);
+            let file_path = std::path::Path::new(&strategy_settings.data.data_path).join(&file_name);
+            data_files.insert(file_name, file_hash(&file_path)?);
+        }
+    }
+
+    // JSON objects keep their keys sorted, so the hash does not depend on the order of the settings files.
+    let context = serde_json::json!({
+        "engine_version": env!("CARGO_PKG_VERSION"),
+        "settings": settings,
+        "initial_capital": initial_capital_for_strategy,
+        "instruments_info": strategy_instruments_info,
+        "commission_plans": strategy_settings.commission_plans,
+        "session_calendar": strategy_settings.session_calendar,
+        "data_files": data_files,
+    });
+    anyhow::Ok(hot_reload::content_hash(context.to_string().as_bytes()))
+}
+
+/// Content hash of the current build of a strategy: of `strategy_path`, or of the engine for built-in strategies.
+pub fn strategy_build(strategy_settings: &farukon_core::settings::StrategySettings) -> anyhow::Result<String> {
+    match &strategy_settings.strategy_path {
+        Some(strategy_path) => file_hash(std::path::Path::new(strategy_path)),
+        // Built-in strategies are compiled into the engine.
+        None => file_hash(&std::env::current_exe().context("Failed to locate the engine binary")?),
+    }
+}
+
+/// Content hash of a file, as `hot_reload::content_hash` of its content, read in a stream.
+pub fn file_hash(path: &std::path::Path) -> anyhow::Result<String> {
+    use sha2::Digest;
+    let mut file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = sha2::Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    anyhow::Ok(hasher.finalize().iter().take(8).map(|byte| format!("{:02x}", byte)).collect())
+}
@@ -20,9 +20,9 @@ pub fn margin_call_control_for_signal(
     // Checks if sufficient capital exists to open a new position.
     // Used during SIGNAL → ORDER conversion.
 
-    let signal_name = &signal_event.signal_name;
+    let signal_name = signal_event.signal_name;
     
-    if signal_name != "EXIT" {
+    if signal_name != farukon_core::event::SignalName::Exit {
         let symbol = &signal_event.symbol;
         let instrument_type = &instrument_info.instrument_type;
         
@@ -65,20 +65,7 @@ pub fn margin_call_control_for_market(
 
     let cash = latest_holdings.cash;
     if cash < 0.0 {
-        let mut capital = 0.0;
-        
-        for symbol in &strategy_settings.symbols {
-            let instrument_info = strategy_instruments_info.get(symbol).unwrap();
-            if instrument_info.instrument_type == "futures" {
-                let current_position_for_symbol = current_positions.get(symbol).unwrap().position;
-                if current_position_for_symbol != 0.0 {
-                    let entry_capital_for_symbol = current_positions.get(symbol).unwrap().entry_capital;
-                    capital += entry_capital_for_symbol;
-                }
-            }
-        }
-        let min_margin_param = strategy_settings.margin_params.min_margin;
-        let min_margin_for_strategy = capital * min_margin_param;
+        let min_margin_for_strategy = min_margin_for_positions(current_positions, strategy_settings, strategy_instruments_info);
         let strategy_current_capital = latest_holdings.capital;
 
         if strategy_current_capital < min_margin_for_strategy {
@@ -91,3 +78,32 @@ pub fn margin_call_control_for_market(
 
     anyhow::Ok(true)
 }
+
+/// Minimal margin the strategy must keep for its open futures positions:
+/// `min_margin` × entry capital of every open position.
+/// # Arguments
+/// * `current_positions` - The current positions.
+/// * `strategy_settings` - The strategy settings.
+/// * `strategy_instruments_info` - The instrument metadata.
+/// # Returns
+/// * `f64` - The required minimal margin.
+pub fn min_margin_for_positions(
+    current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+    strategy_settings: &farukon_core::settings::StrategySettings,
+    strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+) -> f64 {
+    let mut capital = 0.0;
+
+    for symbol in &strategy_settings.symbols {
+        let instrument_info = strategy_instruments_info.get(symbol).unwrap();
+        if instrument_info.instrument_type == "futures" {
+            let current_position_for_symbol = current_positions.get(symbol).unwrap().position;
+            if current_position_for_symbol != 0.0 {
+                let entry_capital_for_symbol = current_positions.get(symbol).unwrap().entry_capital;
+                capital += entry_capital_for_symbol;
+            }
+        }
+    }
+
+    capital * strategy_settings.margin_params.min_margin
+}
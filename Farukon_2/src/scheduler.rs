@@ -0,0 +1,376 @@
+// Farukon_2_0/src/scheduler.rs
+
+//! Timer scheduler for strategy timers.
+//! Strategies register timers with `TimerCommand`s sent through the event queue;
+//! the backtest loop asks the scheduler once per bar which timers came due and delivers them to `Strategy::on_timer`.
+//!
+//! The scheduler only sees bar datetimes, so a timer fires on the first bar that reaches its due time.
+//! Due timers of one bar fire in order of due time, then registration order.
+
+/// A registered timer and its firing state.
+#[derive(Debug, Clone)]
+struct ScheduledTimer {
+    tag: String,
+    schedule: farukon_core::event::TimerSchedule,
+    /// Registration order, used to break ties between timers due at the same time.
+    seq: u64,
+    /// Next due moment for clock-based schedules (`At`, `DailyAt`, `Every`).
+    next_due: Option<chrono::DateTime<chrono::Utc>>,
+    /// Bar number at which an `AfterBars` timer fires.
+    due_step: usize,
+    /// Last (date, session name) seen by a session-based timer.
+    last_session: Option<(chrono::NaiveDate, String)>,
+}
+
+/// Keeps the timers of one backtest.
+#[derive(Debug, Clone)]
+pub struct TimerScheduler {
+    timers: Vec<ScheduledTimer>,
+    next_seq: u64,
+    /// Number of bars (timeline steps) seen so far.
+    steps: usize,
+    /// Datetime of the last bar passed to `advance`.
+    last_step_datetime: Option<chrono::DateTime<chrono::Utc>>,
+    /// True between `begin_bar` and `advance`: commands applied then were sent while processing a bar not counted yet.
+    bar_in_progress: bool,
+    /// Session calendar of the strategy, needed by `SessionOpen`/`SessionClose` timers.
+    session_calendar: Option<farukon_core::sessions::SessionCalendar>,
+}
+
+impl TimerScheduler {
+    /// Creates an empty scheduler for one strategy.
+    /// # Arguments
+    /// * `strategy_settings` - The strategy settings (the attached session calendar is used for session timers).
+    pub fn new(strategy_settings: &farukon_core::settings::StrategySettings) -> Self {
+        TimerScheduler {
+            timers: Vec::new(),
+            next_seq: 0,
+            steps: 0,
+            last_step_datetime: None,
+            bar_in_progress: false,
+            session_calendar: strategy_settings.session_calendar.clone(),
+        }
+    }
+
+    /// Marks the start of a new bar (its MARKET event is being processed).
+    /// Commands applied from now until `advance` come from the events of this bar (`on_fill`, `on_order_rejected`,
+    /// `on_margin_call`), so their `AfterBars` count starts from it; the commands queued before it were sent
+    /// on the previous bar.
+    pub fn begin_bar(&mut self) {
+        self.bar_in_progress = true;
+    }
+
+    /// Registers or cancels a timer.
+    /// # Arguments
+    /// * `command` - The command sent by the strategy.
+    /// * `now` - Datetime of the current bar (None before the first bar).
+    /// # Returns
+    /// * `anyhow::Result<()>` - Error if the schedule is invalid (e.g. session timer without a calendar).
+    pub fn apply(
+        &mut self,
+        command: &farukon_core::event::TimerCommand,
+        now: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> anyhow::Result<()> {
+        match command {
+            farukon_core::event::TimerCommand::Schedule { tag, schedule } => {
+                self.timers.retain(|timer| &timer.tag != tag);
+
+                let mut timer = ScheduledTimer {
+                    tag: tag.clone(),
+                    schedule: schedule.clone(),
+                    seq: self.next_seq,
+                    next_due: None,
+                    due_step: 0,
+                    last_session: None,
+                };
+                self.next_seq += 1;
+
+                match schedule {
+                    farukon_core::event::TimerSchedule::At(datetime) => {
+                        timer.next_due = Some(*datetime);
+                    },
+                    farukon_core::event::TimerSchedule::DailyAt(time) => {
+                        let now = now.unwrap_or_default();
+                        let today = now.date_naive().and_time(*time).and_utc();
+                        timer.next_due = Some(if today < now { today + chrono::Duration::days(1) } else { today });
+                    },
+                    farukon_core::event::TimerSchedule::Every { minutes } => {
+                        if *minutes <= 0 {
+                            anyhow::bail!("Timer '{}': interval must be positive, got {} minutes", tag, minutes);
+                        }
+                        // Before the first bar the interval is aligned on the first bar (see `advance`).
+                        timer.next_due = now.map(|now| next_interval_boundary(now, *minutes));
+                    },
+                    farukon_core::event::TimerSchedule::AfterBars(bars) => {
+                        // `steps` does not count the bar being processed until `advance`.
+                        let current_step = if self.bar_in_progress { self.steps + 1 } else { self.steps };
+                        timer.due_step = current_step + bars;
+                    },
+                    farukon_core::event::TimerSchedule::SessionOpen { symbol }
+                    | farukon_core::event::TimerSchedule::SessionClose { symbol, .. } => {
+                        let exchange_calendar = self.session_calendar
+                            .as_ref()
+                            .and_then(|calendar| calendar.for_symbol(symbol))
+                            .ok_or_else(|| anyhow::anyhow!("Timer '{}': no session calendar for symbol '{}'", tag, symbol))?;
+                        // Registering inside a session waits for the next one.
+                        if let farukon_core::event::TimerSchedule::SessionOpen { .. } = schedule {
+                            timer.last_session = now.and_then(|now| {
+                                exchange_calendar.session_at(now).map(|session| (now.date_naive(), session.name.clone()))
+                            });
+                        }
+                    },
+                }
+
+                self.timers.push(timer);
+            },
+            farukon_core::event::TimerCommand::Cancel { tag } => {
+                self.timers.retain(|timer| &timer.tag != tag);
+            },
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Moves the scheduler to the bar at `now` and returns the timers that came due, in firing order.
+    /// Calling it again with the same `now` (no new bar) fires nothing.
+    /// # Arguments
+    /// * `now` - Datetime of the current bar.
+    /// # Returns
+    /// * `Vec<TimerEvent>` - Timers to deliver to the strategy.
+    pub fn advance(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<farukon_core::event::TimerEvent> {
+        if self.last_step_datetime.is_some_and(|last| now <= last) {
+            return Vec::new();
+        }
+        self.last_step_datetime = Some(now);
+        self.steps += 1;
+        self.bar_in_progress = false;
+
+        // (due, seq, tag, one-shot)
+        let mut due_timers: Vec<(chrono::DateTime<chrono::Utc>, u64, String, bool)> = Vec::new();
+
+        for timer in self.timers.iter_mut() {
+            match &timer.schedule {
+                farukon_core::event::TimerSchedule::At(_) => {
+                    if let Some(due) = timer.next_due
+                        && due <= now
+                    {
+                        due_timers.push((due, timer.seq, timer.tag.clone(), true));
+                    }
+                },
+                farukon_core::event::TimerSchedule::DailyAt(time) => {
+                    if let Some(due) = timer.next_due
+                        && due <= now
+                    {
+                        // A day without a bar at or after `time` (weekend, holiday, short session) is skipped:
+                        // the timer is due at `time` of the current bar's day instead, never at the next open.
+                        let today = now.date_naive().and_time(*time).and_utc();
+                        let due = if due.date_naive() < now.date_naive() { today } else { due };
+                        if due <= now {
+                            due_timers.push((due, timer.seq, timer.tag.clone(), false));
+                            timer.next_due = Some(today + chrono::Duration::days(1));
+                        } else {
+                            timer.next_due = Some(due);
+                        }
+                    }
+                },
+                farukon_core::event::TimerSchedule::Every { minutes } => {
+                    match timer.next_due {
+                        Some(due) if due <= now => {
+                            due_timers.push((due, timer.seq, timer.tag.clone(), false));
+                            // Missed intervals are coalesced into one call.
+                            timer.next_due = Some(next_interval_boundary(now, *minutes));
+                        },
+                        Some(_) => {},
+                        // Scheduled before the first bar: the first boundary is the one after this bar.
+                        None => timer.next_due = Some(next_interval_boundary(now, *minutes)),
+                    }
+                },
+                farukon_core::event::TimerSchedule::AfterBars(_) => {
+                    if self.steps >= timer.due_step {
+                        due_timers.push((now, timer.seq, timer.tag.clone(), true));
+                    }
+                },
+                farukon_core::event::TimerSchedule::SessionOpen { symbol } => {
+                    let current_session = self.session_calendar
+                        .as_ref()
+                        .and_then(|calendar| calendar.for_symbol(symbol))
+                        .and_then(|exchange_calendar| exchange_calendar.session_at(now))
+                        .map(|session| (now.date_naive(), session.name.clone()));
+                    if current_session.is_some() && current_session != timer.last_session {
+                        due_timers.push((now, timer.seq, timer.tag.clone(), false));
+                        timer.last_session = current_session;
+                    }
+                },
+                farukon_core::event::TimerSchedule::SessionClose { symbol, minutes_before } => {
+                    let Some(exchange_calendar) = self.session_calendar
+                        .as_ref()
+                        .and_then(|calendar| calendar.for_symbol(symbol)) else {
+                        continue;
+                    };
+                    if let Some(session) = exchange_calendar.session_at(now)
+                        && let Some(minutes_to_close) = exchange_calendar.minutes_to_session_close(now)
+                    {
+                        let current_session = Some((now.date_naive(), session.name.clone()));
+                        if minutes_to_close <= *minutes_before && current_session != timer.last_session {
+                            due_timers.push((now, timer.seq, timer.tag.clone(), false));
+                            timer.last_session = current_session;
+                        }
+                    }
+                },
+            }
+        }
+
+        due_timers.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        let fired_once: Vec<u64> = due_timers.iter().filter(|timer| timer.3).map(|timer| timer.1).collect();
+        if !fired_once.is_empty() {
+            self.timers.retain(|timer| !fired_once.contains(&timer.seq));
+        }
+
+        due_timers
+            .into_iter()
+            .map(|(due, _, tag, _)| farukon_core::event::TimerEvent::new(now, due, tag))
+            .collect()
+    }
+
+}
+
+fn next_interval_boundary(
+    now: chrono::DateTime<chrono::Utc>,
+    minutes: i64,
+) -> chrono::DateTime<chrono::Utc> {
+    // First multiple of `minutes` (counted from midnight of the epoch) strictly after `now`.
+    let interval_sec = minutes * 60;
+    let next_timestamp = (now.timestamp().div_euclid(interval_sec) + 1) * interval_sec;
+    chrono::DateTime::from_timestamp(next_timestamp, 0).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(value: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap().and_utc()
+    }
+
+    fn scheduler() -> TimerScheduler {
+        TimerScheduler {
+            timers: Vec::new(),
+            next_seq: 0,
+            steps: 0,
+            last_step_datetime: None,
+            bar_in_progress: false,
+            session_calendar: None,
+        }
+    }
+
+    fn schedule(tag: &str, schedule: farukon_core::event::TimerSchedule) -> farukon_core::event::TimerCommand {
+        farukon_core::event::TimerCommand::Schedule { tag: tag.to_string(), schedule }
+    }
+
+    fn daily_at_scheduler(registered_at: &str) -> TimerScheduler {
+        let mut scheduler = scheduler();
+        let command = schedule(
+            "flatten",
+            farukon_core::event::TimerSchedule::DailyAt(chrono::NaiveTime::from_hms_opt(18, 40, 0).unwrap()),
+        );
+        scheduler.apply(&command, Some(datetime(registered_at))).unwrap();
+        scheduler
+    }
+
+    /// (bar datetime, scheduled_for) of every firing over the bars.
+    fn firings(scheduler: &mut TimerScheduler, bars: &[&str]) -> Vec<(String, String)> {
+        bars.iter()
+            .flat_map(|bar| scheduler.advance(datetime(bar)))
+            .map(|event| (
+                event.timeindex.format("%Y-%m-%d %H:%M").to_string(),
+                event.scheduled_for.format("%Y-%m-%d %H:%M").to_string(),
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn daily_at_skips_the_weekend_instead_of_firing_at_monday_open() {
+        // 2024-03-15 is a Friday.
+        let mut scheduler = daily_at_scheduler("2024-03-15 10:00");
+        let fired = firings(&mut scheduler, &[
+            "2024-03-15 18:35",
+            "2024-03-15 18:40",
+            "2024-03-15 18:45",
+            "2024-03-18 10:00",
+            "2024-03-18 18:35",
+            "2024-03-18 18:45",
+        ]);
+        assert_eq!(fired, vec![
+            ("2024-03-15 18:40".to_string(), "2024-03-15 18:40".to_string()),
+            ("2024-03-18 18:45".to_string(), "2024-03-18 18:40".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn daily_at_skips_a_short_session_ending_before_the_time() {
+        // Thursday's session ends at 14:00; Friday has bars after 18:40.
+        let mut scheduler = daily_at_scheduler("2024-03-14 10:00");
+        let fired = firings(&mut scheduler, &[
+            "2024-03-14 13:55",
+            "2024-03-15 10:00",
+            "2024-03-15 18:40",
+        ]);
+        assert_eq!(fired, vec![
+            ("2024-03-15 18:40".to_string(), "2024-03-15 18:40".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn daily_at_fires_late_on_the_same_day() {
+        // Monday's first bar after the weekend already is past 18:40: the timer of that day fires on it, once.
+        let mut scheduler = daily_at_scheduler("2024-03-15 19:00");
+        let fired = firings(&mut scheduler, &[
+            "2024-03-18 19:00",
+            "2024-03-18 19:05",
+        ]);
+        assert_eq!(fired, vec![
+            ("2024-03-18 19:00".to_string(), "2024-03-18 18:40".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn after_bars_counts_from_the_bar_that_scheduled_it() {
+        let mut scheduler = scheduler();
+        assert!(firings(&mut scheduler, &["2024-03-15 10:00"]).is_empty());
+
+        // Sent from `on_bar` of 10:00: registered while the queue is drained at 10:05, before its MARKET event.
+        scheduler.apply(&schedule("from_bar", farukon_core::event::TimerSchedule::AfterBars(1)), Some(datetime("2024-03-15 10:05"))).unwrap();
+        scheduler.begin_bar();
+        // Sent from `on_fill` of 10:05: registered after the MARKET event of 10:05.
+        scheduler.apply(&schedule("from_fill", farukon_core::event::TimerSchedule::AfterBars(1)), Some(datetime("2024-03-15 10:05"))).unwrap();
+
+        let fired: Vec<(String, String)> = ["2024-03-15 10:05", "2024-03-15 10:10"]
+            .iter()
+            .flat_map(|bar| scheduler.advance(datetime(bar)))
+            .map(|event| (event.timeindex.format("%H:%M").to_string(), event.tag))
+            .collect();
+        assert_eq!(fired, vec![
+            ("10:05".to_string(), "from_bar".to_string()),
+            ("10:10".to_string(), "from_fill".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn every_from_on_start_aligns_on_the_first_bar() {
+        let mut scheduler = scheduler();
+        scheduler.apply(&schedule("quarter", farukon_core::event::TimerSchedule::Every { minutes: 15 }), None).unwrap();
+        let fired = firings(&mut scheduler, &[
+            "2024-03-15 10:05",
+            "2024-03-15 10:10",
+            "2024-03-15 10:15",
+            "2024-03-15 10:20",
+            "2024-03-15 10:35",
+        ]);
+        assert_eq!(fired, vec![
+            ("2024-03-15 10:15".to_string(), "2024-03-15 10:15".to_string()),
+            ("2024-03-15 10:35".to_string(), "2024-03-15 10:30".to_string()),
+        ]);
+    }
+
+}
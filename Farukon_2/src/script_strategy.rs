@@ -0,0 +1,671 @@
+// Farukon_2_0/src/script_strategy.rs
+
+//! Script strategies: trading logic written in Rhai (https://rhai.rs) for rapid prototyping.
+//! Selected by a `strategy_path` ending in `.rhai`; the script is compiled when the backtest starts,
+//! so it can be edited between runs without building a plugin.
+//!
+//! The script defines `fn calculate_signals(symbols)` and optionally the lifecycle hooks
+//! (`on_start()`, `on_bar()`, `on_fill(fill)`, `on_order_rejected(rejected)`, `on_margin_call(margin_call)`,
+//! `on_timer(timer)`, `on_end()`) and `fn param_schema()`.
+//! `strategy_params` (the values of the current run), `mode` and `symbols` are script constants;
+//! state kept between calls lives in the `this` object map.
+//! Market data, positions, holdings, instruments, the `indicators` module, signals and timers
+//! are exposed through the functions registered in `build_engine`.
+
+use anyhow::Context;
+
+type ScriptResult<T> = Result<T, Box<rhai::EvalAltResult>>;
+
+/// File extension selecting a script strategy in `strategy_path`.
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Optional hooks of a script: (function name, number of parameters).
+const SCRIPT_HOOKS: [(&str, usize); 7] = [
+    ("on_start", 0),
+    ("on_bar", 0),
+    ("on_fill", 1),
+    ("on_order_rejected", 1),
+    ("on_margin_call", 1),
+    ("on_timer", 1),
+    ("on_end", 0),
+];
+
+/// Returns `true` if `strategy_path` names a script strategy rather than a strategy library.
+pub fn is_script_path(strategy_path: &str) -> bool {
+    std::path::Path::new(strategy_path)
+        .extension()
+        .is_some_and(|extension| extension == SCRIPT_EXTENSION)
+}
+
+/// Engine state the script functions read during one call into the script.
+/// Set by `ScriptCallGuard` for the duration of the call; empty between calls.
+#[derive(Default)]
+struct ScriptCallState {
+    /// `None` in `on_start`, which gets no market data.
+    data_handler: Option<*const (dyn farukon_core::data_handler::DataHandler + 'static)>,
+    current_positions: Option<*const std::collections::HashMap<String, farukon_core::portfolio::PositionState>>,
+    latest_holdings: Option<*const farukon_core::portfolio::HoldingSnapshot>,
+}
+
+impl ScriptCallState {
+    fn data_handler(&self) -> ScriptResult<&dyn farukon_core::data_handler::DataHandler> {
+        // Safety: the pointer is set only while the call that borrowed the data handler is in progress.
+        self.data_handler
+            .map(|data_handler| unsafe { &*data_handler })
+            .ok_or_else(|| "market data is not available in on_start".into())
+    }
+
+    fn current_positions(&self) -> ScriptResult<&std::collections::HashMap<String, farukon_core::portfolio::PositionState>> {
+        // Safety: as for `data_handler`.
+        self.current_positions
+            .map(|current_positions| unsafe { &*current_positions })
+            .ok_or_else(|| "positions are not available in on_start".into())
+    }
+
+    fn latest_holdings(&self) -> ScriptResult<&farukon_core::portfolio::HoldingSnapshot> {
+        // Safety: as for `data_handler`.
+        self.latest_holdings
+            .map(|latest_holdings| unsafe { &*latest_holdings })
+            .ok_or_else(|| "holdings are not available in on_start".into())
+    }
+
+}
+
+/// Exposes the engine state of one call to the script functions and hides it again when dropped.
+struct ScriptCallGuard<'a> {
+    call_state: &'a std::cell::RefCell<ScriptCallState>,
+}
+
+impl<'a> ScriptCallGuard<'a> {
+    fn enter<'b>(
+        call_state: &'a std::cell::RefCell<ScriptCallState>,
+        data_handler: Option<&'b dyn farukon_core::data_handler::DataHandler>,
+        portfolio_state: Option<(
+            &'b std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+            &'b farukon_core::portfolio::HoldingSnapshot,
+        )>,
+    ) -> Self {
+        let mut state = call_state.borrow_mut();
+        state.data_handler = data_handler.map(|data_handler| {
+            let data_handler: *const (dyn farukon_core::data_handler::DataHandler + 'b) = data_handler;
+            // Erases the borrow's lifetime: the guard clears the pointer before the borrow ends.
+            unsafe {
+                std::mem::transmute::<
+                    *const (dyn farukon_core::data_handler::DataHandler + 'b),
+                    *const (dyn farukon_core::data_handler::DataHandler + 'static),
+                >(data_handler)
+            }
+        });
+        state.current_positions = portfolio_state.map(|(current_positions, _)| current_positions as *const _);
+        state.latest_holdings = portfolio_state.map(|(_, latest_holdings)| latest_holdings as *const _);
+        drop(state);
+
+        ScriptCallGuard { call_state }
+    }
+
+}
+
+impl Drop for ScriptCallGuard<'_> {
+    fn drop(&mut self) {
+        *self.call_state.borrow_mut() = ScriptCallState::default();
+    }
+}
+
+/// A strategy implemented by a Rhai script.
+pub struct ScriptStrategy {
+    script_path: String,
+    engine: rhai::Engine,
+    ast: rhai::AST,
+    /// Script constants: `strategy_params`, `mode`, `symbols` and the script's own top-level `const`s.
+    scope: rhai::Scope<'static>,
+    /// `this` of every call: the script's state between calls.
+    state: rhai::Dynamic,
+    /// Optional hooks the script defines.
+    hooks: std::collections::HashSet<&'static str>,
+    call_state: std::rc::Rc<std::cell::RefCell<ScriptCallState>>,
+}
+
+impl ScriptStrategy {
+    /// Compiles the script at `script_path` and runs its top-level statements.
+    /// # Arguments
+    /// * `mode` - The operational mode ("Debug", "Optimize", "Visual").
+    /// * `script_path` - Path to the `.rhai` script.
+    /// * `strategy_settings` - Settings of the strategy.
+    /// * `strategy_instruments_info` - Instrument metadata for all symbols traded by this strategy.
+    /// * `event_sender` - The engine event queue.
+    /// # Returns
+    /// * `anyhow::Result<Self>` - Error if the script cannot be read or compiled, has no `calculate_signals(symbols)`,
+    ///   or its top-level statements fail.
+    pub fn load_from_path(
+        mode: &str,
+        script_path: &str,
+        strategy_settings: &farukon_core::settings::StrategySettings,
+        strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+        event_sender: &farukon_core::event::EventQueue,
+    ) -> anyhow::Result<Self> {
+        let call_state = std::rc::Rc::new(std::cell::RefCell::new(ScriptCallState::default()));
+        let engine = build_engine(mode, strategy_settings, strategy_instruments_info, event_sender, &call_state);
+        let ast = compile_script(&engine, script_path)?;
+
+        if !has_function(&ast, "calculate_signals", 1) {
+            anyhow::bail!("Script strategy {} does not define fn calculate_signals(symbols)", script_path);
+        }
+        let hooks = SCRIPT_HOOKS
+            .iter()
+            .filter(|(name, params_count)| has_function(&ast, name, *params_count))
+            .map(|(name, _)| *name)
+            .collect();
+
+        // --- Script constants ---
+        let mut scope = rhai::Scope::new();
+        let mut param_names: Vec<&String> = strategy_settings.strategy_params.keys().collect();
+        param_names.sort();
+        for name in param_names {
+            let Some(value) = strategy_settings.strategy_params[name].first() else {
+                continue;
+            };
+            let is_int = strategy_settings.param_schema
+                .as_ref()
+                .and_then(|param_schema| param_schema.get_param(name))
+                .is_some_and(|param| matches!(param.kind, farukon_core::param_schema::ParamKind::Int { .. }));
+            let value = match value.as_f64() {
+                // Ranges produce whole floats (10.0) for int parameters
+                Some(number) if is_int => rhai::Dynamic::from_int(number as rhai::INT),
+                _ => rhai::serde::to_dynamic(value)
+                    .map_err(|e| anyhow::anyhow!("Failed to pass strategy parameter '{}' to script {}: {}", name, script_path, e))?,
+            };
+            scope.push_constant(name.as_str(), value);
+        }
+        scope.push_constant("mode", mode.to_string());
+        scope.push_constant("symbols", symbols_array(&strategy_settings.symbols));
+
+        engine.run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| anyhow::anyhow!("Script strategy {} failed: {}", script_path, e))?;
+
+        anyhow::Ok(ScriptStrategy {
+            script_path: script_path.to_string(),
+            engine,
+            ast,
+            scope,
+            state: rhai::Dynamic::from_map(rhai::Map::new()),
+            hooks,
+            call_state,
+        })
+    }
+
+    /// Calls the script function `function_name` with the engine state of the call exposed to the script.
+    fn call_script(
+        &mut self,
+        function_name: &str,
+        args: impl rhai::FuncArgs,
+        data_handler: Option<&dyn farukon_core::data_handler::DataHandler>,
+        portfolio_state: Option<(
+            &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+            &farukon_core::portfolio::HoldingSnapshot,
+        )>,
+    ) -> anyhow::Result<()> {
+        let _guard = ScriptCallGuard::enter(&self.call_state, data_handler, portfolio_state);
+        let options = rhai::CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+
+        self.engine
+            .call_fn_with_options::<rhai::Dynamic>(options, &mut self.scope, &self.ast, function_name, args)
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("Script strategy {} failed in {}: {}", self.script_path, function_name, e))
+    }
+
+    /// Calls an optional hook with an engine event converted to an object map.
+    fn call_event_hook<E: serde::Serialize>(
+        &mut self,
+        hook: &str,
+        event: &E,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        if !self.hooks.contains(hook) {
+            return anyhow::Ok(());
+        }
+        let event = rhai::serde::to_dynamic(event)
+            .map_err(|e| anyhow::anyhow!("Failed to pass the {} event to script {}: {}", hook, self.script_path, e))?;
+        self.call_script(hook, (event,), Some(data_handler), Some((current_positions, latest_holdings)))
+    }
+
+}
+
+/// The engine calls a script strategy like a built-in one; every call runs a script function.
+impl farukon_core::strategy::Strategy for ScriptStrategy {
+    /// Calls `calculate_signals(symbols)` of the script.
+    fn calculate_signals(
+        &mut self,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+        symbol_list: &[String],
+    ) -> anyhow::Result<()> {
+        let symbols = symbols_array(symbol_list);
+        self.call_script("calculate_signals", (symbols,), Some(data_handler), Some((current_positions, latest_holdings)))
+    }
+
+    /// Calls `on_start()` of the script, if it defines one.
+    fn on_start(&mut self) -> anyhow::Result<()> {
+        if !self.hooks.contains("on_start") {
+            return anyhow::Ok(());
+        }
+        self.call_script("on_start", (), None, None)
+    }
+
+    /// Calls `on_bar()` of the script, if it defines one.
+    fn on_bar(
+        &mut self,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        if !self.hooks.contains("on_bar") {
+            return anyhow::Ok(());
+        }
+        self.call_script("on_bar", (), Some(data_handler), Some((current_positions, latest_holdings)))
+    }
+
+    /// Calls `on_fill(fill)` of the script, if it defines one.
+    fn on_fill(
+        &mut self,
+        fill_event: &farukon_core::event::FillEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        self.call_event_hook("on_fill", fill_event, data_handler, current_positions, latest_holdings)
+    }
+
+    /// Calls `on_order_rejected(rejected)` of the script, if it defines one.
+    fn on_order_rejected(
+        &mut self,
+        rejected_event: &farukon_core::event::OrderRejectedEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        self.call_event_hook("on_order_rejected", rejected_event, data_handler, current_positions, latest_holdings)
+    }
+
+    /// Calls `on_margin_call(margin_call)` of the script, if it defines one.
+    fn on_margin_call(
+        &mut self,
+        margin_call_event: &farukon_core::event::MarginCallEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        self.call_event_hook("on_margin_call", margin_call_event, data_handler, current_positions, latest_holdings)
+    }
+
+    /// Calls `on_timer(timer)` of the script, if it defines one.
+    fn on_timer(
+        &mut self,
+        timer_event: &farukon_core::event::TimerEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        self.call_event_hook("on_timer", timer_event, data_handler, current_positions, latest_holdings)
+    }
+
+    /// Calls `on_end()` of the script, if it defines one.
+    fn on_end(
+        &mut self,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        if !self.hooks.contains("on_end") {
+            return anyhow::Ok(());
+        }
+        self.call_script("on_end", (), Some(data_handler), Some((current_positions, latest_holdings)))
+    }
+
+}
+
+/// Parameter schema returned by `fn param_schema()` of the script, if it defines one.
+/// The function returns an object map shaped like the JSON of `ParamSchema`:
+/// `#{ params: [#{ name: "short_window", kind: #{ type: "int", min: 1 } }], constraints: [#{ left: "short_window", op: "<", right: "long_window" }] }`.
+/// # Arguments
+/// * `script_path` - Path to the `.rhai` script.
+/// # Returns
+/// * `anyhow::Result<Option<ParamSchema>>` - `None` if the script does not declare a schema.
+pub fn script_param_schema(script_path: &str) -> anyhow::Result<Option<farukon_core::param_schema::ParamSchema>> {
+    let engine = rhai::Engine::new();
+    let ast = compile_script(&engine, script_path)?;
+    if !has_function(&ast, "param_schema", 0) {
+        return anyhow::Ok(None);
+    }
+
+    let options = rhai::CallFnOptions::new().eval_ast(false);
+    let schema = engine
+        .call_fn_with_options::<rhai::Dynamic>(options, &mut rhai::Scope::new(), &ast, "param_schema", ())
+        .map_err(|e| anyhow::anyhow!("Script strategy {} failed in param_schema: {}", script_path, e))?;
+    let param_schema: farukon_core::param_schema::ParamSchema = rhai::serde::from_dynamic(&schema)
+        .map_err(|e| anyhow::anyhow!("Invalid parameter schema of script strategy {}: {}", script_path, e))?;
+    param_schema.validate()
+        .with_context(|| format!("Invalid parameter schema of script strategy {}", script_path))?;
+
+    anyhow::Ok(Some(param_schema))
+}
+
+/// Reads and compiles the script.
+fn compile_script(engine: &rhai::Engine, script_path: &str) -> anyhow::Result<rhai::AST> {
+    let source = std::fs::read_to_string(script_path)
+        .with_context(|| format!("Failed to read script strategy {}", script_path))?;
+    engine.compile(source)
+        .map_err(|e| anyhow::anyhow!("Failed to compile script strategy {}: {}", script_path, e))
+}
+
+/// Returns `true` if the script defines `name` with `params_count` parameters.
+fn has_function(ast: &rhai::AST, name: &str, params_count: usize) -> bool {
+    ast.iter_functions().any(|function| function.name == name && function.params.len() == params_count)
+}
+
+fn symbols_array(symbols: &[String]) -> rhai::Array {
+    symbols.iter().map(|symbol| rhai::Dynamic::from(symbol.clone())).collect()
+}
+
+/// Converts an optional script number (`()` for none) to `Option<f64>`.
+fn optional_number(value: &rhai::Dynamic, what: &str) -> ScriptResult<Option<f64>> {
+    if value.is_unit() {
+        return Ok(None);
+    }
+    value.as_float()
+        .or_else(|_| value.as_int().map(|number| number as f64))
+        .map(Some)
+        .map_err(|type_name| format!("{} must be a number or (), got {}", what, type_name).into())
+}
+
+/// Converts a bar count (int, or whole float from a parameter range) to `usize`.
+fn bar_count(value: &rhai::Dynamic, what: &str) -> ScriptResult<usize> {
+    let count = value.as_int()
+        .ok()
+        .or_else(|| value.as_float().ok().filter(|number| number.fract() == 0.0).map(|number| number as rhai::INT));
+    match count {
+        Some(count) if count >= 0 => Ok(count as usize),
+        _ => Err(format!("{} must be a non-negative integer, got {}", what, value).into()),
+    }
+}
+
+/// Builds the spec of a cached indicator; without `source` the indicator reads its default price field.
+fn indicator_spec(name: &str, period: &rhai::Dynamic, source: Option<&str>) -> ScriptResult<farukon_core::indicator_cache::IndicatorSpec> {
+    let period = bar_count(period, "period")?;
+    let spec = farukon_core::indicator_cache::IndicatorSpec::from_name(name, period)
+        .ok_or_else(|| format!("unknown indicator '{}'", name))?;
+    match source {
+        Some(source) => {
+            let price_source = farukon_core::indicators::streaming::PriceSource::from_name(source)
+                .ok_or_else(|| format!("unknown indicator source '{}'", source))?;
+            Ok(spec.with_source(price_source))
+        },
+        None => Ok(spec),
+    }
+}
+
+/// Converts an array of numbers to `f64` values.
+fn number_array(values: &rhai::Array) -> ScriptResult<Vec<f64>> {
+    values.iter().map(|value| {
+        optional_number(value, "value")?.ok_or_else(|| "indicator values must be numbers, got ()".into())
+    }).collect()
+}
+
+/// Converts an array of numbers, where `()` marks a missing value, to `Option<f64>` values.
+fn optional_number_array(values: &rhai::Array) -> ScriptResult<Vec<Option<f64>>> {
+    values.iter().map(|value| optional_number(value, "value")).collect()
+}
+
+fn optional_float(value: Option<f64>) -> rhai::Dynamic {
+    value.map_or(rhai::Dynamic::UNIT, rhai::Dynamic::from_float)
+}
+
+fn format_datetime(datetime: chrono::DateTime<chrono::Utc>) -> String {
+    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Parses the side of an opening signal ("LONG" or "SHORT").
+fn open_signal_name(side: &str) -> ScriptResult<farukon_core::event::SignalName> {
+    match side {
+        "LONG" => Ok(farukon_core::event::SignalName::Long),
+        "SHORT" => Ok(farukon_core::event::SignalName::Short),
+        _ => Err(format!("side must be \"LONG\" or \"SHORT\", got {:?}", side).into()),
+    }
+}
+
+/// Sends a signal dated with the latest bar of `symbol`.
+/// An exit without a quantity closes the whole position; an entry needs a quantity.
+fn send_signal(
+    call_state: &std::cell::RefCell<ScriptCallState>,
+    event_sender: &farukon_core::event::EventQueue,
+    symbol: &str,
+    signal_name: farukon_core::event::SignalName,
+    order_type: farukon_core::event::OrderType,
+    quantity: Option<f64>,
+    limit_price: Option<f64>,
+) -> ScriptResult<()> {
+    let call_state = call_state.borrow();
+    let current_bar_datetime = call_state.data_handler()?
+        .get_latest_bar_datetime(symbol)
+        .ok_or_else(|| format!("no bars of {} yet", symbol))?;
+    let quantity = match (quantity, signal_name) {
+        (Some(quantity), _) => quantity,
+        (None, farukon_core::event::SignalName::Exit) => call_state.current_positions()?
+            .get(symbol)
+            .map_or(0.0, |position_state| position_state.position),
+        (None, _) => return Err(format!("{} signal for {} needs a quantity, got ()", signal_name, symbol).into()),
+    };
+    event_sender.send(farukon_core::event::SignalEvent::new(
+        current_bar_datetime,
+        symbol.to_string(),
+        signal_name,
+        order_type,
+        Some(quantity),
+        limit_price,
+    )).map_err(|e| e.to_string().into())
+}
+
+/// Creates the script engine with the strategy API registered.
+fn build_engine(
+    mode: &str,
+    strategy_settings: &farukon_core::settings::StrategySettings,
+    strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+    event_sender: &farukon_core::event::EventQueue,
+    call_state: &std::rc::Rc<std::cell::RefCell<ScriptCallState>>,
+) -> rhai::Engine {
+    let mut engine = rhai::Engine::new();
+    let strategy_settings = std::rc::Rc::new(strategy_settings.clone());
+    let strategy_instruments_info = std::rc::Rc::new(strategy_instruments_info.clone());
+
+    // --- Market data ---
+
+    // latest_value(symbol, field) -> float or () without bars; field is "open", "high", "low", "close" or "volume"
+    let state = call_state.clone();
+    engine.register_fn("latest_value", move |symbol: &str, field: &str| -> ScriptResult<rhai::Dynamic> {
+        Ok(optional_float(state.borrow().data_handler()?.get_latest_bar_value(symbol, field)))
+    });
+
+    // latest_values(symbol, field, n) -> array of up to n floats, oldest first
+    let state = call_state.clone();
+    engine.register_fn("latest_values", move |symbol: &str, field: &str, n: rhai::Dynamic| -> ScriptResult<rhai::Array> {
+        let n = bar_count(&n, "n")?;
+        let values = state.borrow().data_handler()?.get_latest_bars_values(symbol, field, n);
+        Ok(values.into_iter().map(rhai::Dynamic::from_float).collect())
+    });
+
+    // latest_bar(symbol) -> #{ datetime, open, high, low, close, volume, is_filled } or () without bars
+    let state = call_state.clone();
+    engine.register_fn("latest_bar", move |symbol: &str| -> ScriptResult<rhai::Dynamic> {
+        let state = state.borrow();
+        let Some(bar) = state.data_handler()?.get_latest_bar(symbol) else {
+            return Ok(rhai::Dynamic::UNIT);
+        };
+        let mut map = rhai::Map::new();
+        map.insert("datetime".into(), format_datetime(bar.datetime).into());
+        map.insert("open".into(), bar.open.into());
+        map.insert("high".into(), bar.high.into());
+        map.insert("low".into(), bar.low.into());
+        map.insert("close".into(), bar.close.into());
+        map.insert("volume".into(), (bar.volume as rhai::INT).into());
+        map.insert("is_filled".into(), bar.is_filled.into());
+        Ok(map.into())
+    });
+
+    // bar_datetime(symbol) -> "YYYY-mm-dd HH:MM:SS" (the format of the instruments info, so strings compare) or ()
+    let state = call_state.clone();
+    engine.register_fn("bar_datetime", move |symbol: &str| -> ScriptResult<rhai::Dynamic> {
+        Ok(state.borrow().data_handler()?
+            .get_latest_bar_datetime(symbol)
+            .map_or(rhai::Dynamic::UNIT, |datetime| format_datetime(datetime).into()))
+    });
+
+    // indicator(symbol, name, period[, source[, shift]]) -> float or () while warming up.
+    // Read from the indicator cache shared by the backtests of an optimization; name is "sma", "ema", "wma", "rsi",
+    // "std", "highest", "lowest" or "atr", source a field or "median"/"typical", shift the number of bars back.
+    let state = call_state.clone();
+    engine.register_fn("indicator", move |symbol: &str, name: &str, period: rhai::Dynamic| -> ScriptResult<rhai::Dynamic> {
+        let spec = indicator_spec(name, &period, None)?;
+        Ok(optional_float(state.borrow().data_handler()?.get_indicator_value(symbol, &spec, 0)))
+    });
+    let state = call_state.clone();
+    engine.register_fn("indicator", move |symbol: &str, name: &str, period: rhai::Dynamic, source: &str| -> ScriptResult<rhai::Dynamic> {
+        let spec = indicator_spec(name, &period, Some(source))?;
+        Ok(optional_float(state.borrow().data_handler()?.get_indicator_value(symbol, &spec, 0)))
+    });
+    let state = call_state.clone();
+    engine.register_fn("indicator", move |symbol: &str, name: &str, period: rhai::Dynamic, source: &str, shift: rhai::Dynamic| -> ScriptResult<rhai::Dynamic> {
+        let spec = indicator_spec(name, &period, Some(source))?;
+        let shift = bar_count(&shift, "shift")?;
+        Ok(optional_float(state.borrow().data_handler()?.get_indicator_value(symbol, &spec, shift)))
+    });
+
+    // --- Portfolio ---
+
+    // position(symbol) -> signed quantity (0.0 when flat)
+    let state = call_state.clone();
+    engine.register_fn("position", move |symbol: &str| -> ScriptResult<f64> {
+        Ok(state.borrow().current_positions()?
+            .get(symbol)
+            .map_or(0.0, |position_state| position_state.position))
+    });
+
+    // entry_price(symbol) -> float or () when flat
+    let state = call_state.clone();
+    engine.register_fn("entry_price", move |symbol: &str| -> ScriptResult<rhai::Dynamic> {
+        Ok(optional_float(state.borrow().current_positions()?
+            .get(symbol)
+            .and_then(|position_state| position_state.entry_price)))
+    });
+
+    let state = call_state.clone();
+    engine.register_fn("capital", move || -> ScriptResult<f64> {
+        Ok(state.borrow().latest_holdings()?.capital)
+    });
+    let state = call_state.clone();
+    engine.register_fn("cash", move || -> ScriptResult<f64> {
+        Ok(state.borrow().latest_holdings()?.cash)
+    });
+    let state = call_state.clone();
+    engine.register_fn("blocked", move || -> ScriptResult<f64> {
+        Ok(state.borrow().latest_holdings()?.blocked)
+    });
+
+    // --- Instruments and position sizing ---
+
+    // instrument(symbol) -> object map of the instrument info (margin, step, expiration_date, ...)
+    let instruments_info = strategy_instruments_info.clone();
+    engine.register_fn("instrument", move |symbol: &str| -> ScriptResult<rhai::Dynamic> {
+        let instrument_info = instruments_info.get(symbol)
+            .ok_or_else(|| format!("no instrument info for {}", symbol))?;
+        rhai::serde::to_dynamic(instrument_info)
+    });
+
+    // pos_size(symbol, entry_price, exit_price) -> quantity from the configured pos sizer, or ()
+    let state = call_state.clone();
+    let mode = mode.to_string();
+    let settings = strategy_settings.clone();
+    let instruments_info = strategy_instruments_info.clone();
+    engine.register_fn("pos_size", move |symbol: &str, entry_price: rhai::Dynamic, exit_price: rhai::Dynamic| -> ScriptResult<rhai::Dynamic> {
+        let instrument_info = instruments_info.get(symbol)
+            .ok_or_else(|| format!("no instrument info for {}", symbol))?;
+        let capital = state.borrow().latest_holdings()?.capital;
+        Ok(optional_float(farukon_core::pos_sizers::get_pos_sizer_from_settings(
+            &mode,
+            Some(capital),
+            optional_number(&entry_price, "entry_price")?,
+            optional_number(&exit_price, "exit_price")?,
+            &settings,
+            instrument_info,
+        )))
+    });
+
+    // --- Indicators (indicators::sma, indicators::highest, indicators::lowest) ---
+
+    let mut indicators = rhai::Module::new();
+    indicators.set_native_fn("sma", |values: rhai::Array, n: rhai::Dynamic| -> ScriptResult<rhai::Dynamic> {
+        let values = number_array(&values)?;
+        Ok(optional_float(farukon_core::indicators::sma(&values, bar_count(&n, "n")?)))
+    });
+    indicators.set_native_fn("highest", |values: rhai::Array, n: rhai::Dynamic, shift: rhai::Dynamic| -> ScriptResult<rhai::Dynamic> {
+        let values = optional_number_array(&values)?;
+        Ok(optional_float(farukon_core::indicators::highest(&values, bar_count(&n, "n")?, bar_count(&shift, "shift")?)))
+    });
+    indicators.set_native_fn("lowest", |values: rhai::Array, n: rhai::Dynamic, shift: rhai::Dynamic| -> ScriptResult<rhai::Dynamic> {
+        let values = optional_number_array(&values)?;
+        Ok(optional_float(farukon_core::indicators::lowest(&values, bar_count(&n, "n")?, bar_count(&shift, "shift")?)))
+    });
+    engine.register_static_module("indicators", indicators.into());
+
+    // --- Signals (dated with the latest bar of the symbol; closing without a quantity closes the whole position) ---
+
+    let (state, sender) = (call_state.clone(), event_sender.clone());
+    engine.register_fn("open_by_market", move |symbol: &str, side: &str, quantity: rhai::Dynamic| -> ScriptResult<()> {
+        let quantity = optional_number(&quantity, "quantity")?;
+        send_signal(&state, &sender, symbol, open_signal_name(side)?, farukon_core::event::OrderType::Market, quantity, None)
+    });
+    let (state, sender) = (call_state.clone(), event_sender.clone());
+    engine.register_fn("open_by_limit", move |symbol: &str, side: &str, quantity: rhai::Dynamic, limit_price: rhai::Dynamic| -> ScriptResult<()> {
+        let quantity = optional_number(&quantity, "quantity")?;
+        let limit_price = optional_number(&limit_price, "limit_price")?;
+        send_signal(&state, &sender, symbol, open_signal_name(side)?, farukon_core::event::OrderType::Limit, quantity, limit_price)
+    });
+    let (state, sender) = (call_state.clone(), event_sender.clone());
+    engine.register_fn("close_by_market", move |symbol: &str| -> ScriptResult<()> {
+        send_signal(&state, &sender, symbol, farukon_core::event::SignalName::Exit, farukon_core::event::OrderType::Market, None, None)
+    });
+    let (state, sender) = (call_state.clone(), event_sender.clone());
+    engine.register_fn("close_by_market", move |symbol: &str, quantity: rhai::Dynamic| -> ScriptResult<()> {
+        let quantity = optional_number(&quantity, "quantity")?;
+        send_signal(&state, &sender, symbol, farukon_core::event::SignalName::Exit, farukon_core::event::OrderType::Market, quantity, None)
+    });
+    let (state, sender) = (call_state.clone(), event_sender.clone());
+    engine.register_fn("close_by_limit", move |symbol: &str, quantity: rhai::Dynamic, limit_price: rhai::Dynamic| -> ScriptResult<()> {
+        let quantity = optional_number(&quantity, "quantity")?;
+        let limit_price = optional_number(&limit_price, "limit_price")?;
+        send_signal(&state, &sender, symbol, farukon_core::event::SignalName::Exit, farukon_core::event::OrderType::Limit, quantity, limit_price)
+    });
+
+    // --- Timers ---
+
+    // schedule_timer(tag, schedule): schedule is a TimerSchedule as JSON, e.g. #{ Every: #{ minutes: 15 } }, #{ AfterBars: 3 }
+    let sender = event_sender.clone();
+    engine.register_fn("schedule_timer", move |tag: &str, schedule: rhai::Dynamic| -> ScriptResult<()> {
+        let schedule: farukon_core::event::TimerSchedule = rhai::serde::from_dynamic(&schedule)?;
+        sender.send(farukon_core::event::TimerCommand::Schedule {
+            tag: tag.to_string(),
+            schedule,
+        }).map_err(|e| e.to_string().into())
+    });
+    let sender = event_sender.clone();
+    engine.register_fn("cancel_timer", move |tag: &str| -> ScriptResult<()> {
+        sender.send(farukon_core::event::TimerCommand::Cancel {
+            tag: tag.to_string(),
+        }).map_err(|e| e.to_string().into())
+    });
+
+    engine
+}
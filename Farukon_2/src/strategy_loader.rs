@@ -2,111 +2,717 @@
 
 //! Dynamic strategy loader: loads compiled Rust libraries (.dylib/.so/.dll) at runtime.
 //! Enables hot-swapping of trading logic without recompiling the core engine.
-//! Uses `libloading` to load symbols: create_strategy, destroy_strategy, calculate_signals.
+//! Settings without `strategy_path` use a strategy compiled into the engine (`farukon_core::registry`) instead,
+//! and a `strategy_path` ending in `.rhai` selects a script strategy (`script_strategy`).
+//!
+//! Plugins speak the versioned C ABI of `farukon_core::abi`: the loader reads the `farukon_plugin_manifest` symbol,
+//! refuses plugins built for another ABI version, and then calls the strategy only through the manifest's vtable.
+//! Market data, signals and timers cross the boundary through the `HostApi` callback table implemented here.
+
+use anyhow::Context;
+
+use crate::script_strategy;
+
+/// State behind `AbiCallContext::host_ctx` during one call into the plugin.
+struct HostCallState<'a> {
+    /// `None` in `on_start`, which gets no market data.
+    data_handler: Option<&'a dyn farukon_core::data_handler::DataHandler>,
+    event_sender: &'a farukon_core::event::EventQueue,
+    /// Error reported by the plugin (`set_error`) or by a failed host callback.
+    error: std::cell::RefCell<Option<String>>,
+}
+
+impl HostCallState<'_> {
+    /// # Safety
+    /// `ctx` must be the `host_ctx` of a call in progress.
+    unsafe fn from_ctx<'a>(ctx: *mut std::ffi::c_void) -> &'a HostCallState<'a> {
+        unsafe { &*(ctx as *const HostCallState<'a>) }
+    }
+
+    fn record_error(&self, message: String) {
+        let mut error = self.error.borrow_mut();
+        *error = Some(match error.take() {
+            Some(previous) => format!("{}: {}", message, previous),
+            None => message,
+        });
+    }
+
+}
+
+/// Callback table handed to every plugin call.
+static HOST_API: farukon_core::abi::HostApi = farukon_core::abi::HostApi {
+    latest_bar: host_latest_bar,
+    latest_bars: host_latest_bars,
+    latest_value: host_latest_value,
+    latest_values: host_latest_values,
+    indicator_value: host_indicator_value,
+    send_signal: host_send_signal,
+    schedule_timer: host_schedule_timer,
+    cancel_timer: host_cancel_timer,
+    set_error: host_set_error,
+};
+
+unsafe extern "C" fn host_latest_bar(
+    ctx: *mut std::ffi::c_void,
+    symbol: farukon_core::abi::AbiStr,
+    out: *mut farukon_core::abi::AbiBar,
+) -> u8 {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+    let Ok(symbol) = (unsafe { symbol.as_str() }) else {
+        return 0;
+    };
+
+    let Some(data_handler) = state.data_handler else {
+        return 0;
+    };
+
+    match data_handler.get_latest_bar(symbol) {
+        Some(bar) => {
+            unsafe { *out = farukon_core::abi::AbiBar::from(bar) };
+            1
+        },
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn host_latest_bars(
+    ctx: *mut std::ffi::c_void,
+    symbol: farukon_core::abi::AbiStr,
+    n: usize,
+    out: *mut farukon_core::abi::AbiBar,
+) -> usize {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+    let Ok(symbol) = (unsafe { symbol.as_str() }) else {
+        return 0;
+    };
+
+    let Some(data_handler) = state.data_handler else {
+        return 0;
+    };
+
+    let bars = data_handler.get_latest_bars(symbol, n);
+    let written = bars.len().min(n);
+    for (i, bar) in bars.iter().take(written).enumerate() {
+        unsafe { *out.add(i) = farukon_core::abi::AbiBar::from(*bar) };
+    }
+    written
+}
+
+unsafe extern "C" fn host_latest_value(
+    ctx: *mut std::ffi::c_void,
+    symbol: farukon_core::abi::AbiStr,
+    field: farukon_core::abi::AbiStr,
+    out: *mut f64,
+) -> u8 {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+    let (Ok(symbol), Ok(field)) = (unsafe { symbol.as_str() }, unsafe { field.as_str() }) else {
+        return 0;
+    };
+
+    let Some(data_handler) = state.data_handler else {
+        return 0;
+    };
+
+    match data_handler.get_latest_bar_value(symbol, field) {
+        Some(value) => {
+            unsafe { *out = value };
+            1
+        },
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn host_latest_values(
+    ctx: *mut std::ffi::c_void,
+    symbol: farukon_core::abi::AbiStr,
+    field: farukon_core::abi::AbiStr,
+    n: usize,
+    out: *mut f64,
+) -> usize {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+    let (Ok(symbol), Ok(field)) = (unsafe { symbol.as_str() }, unsafe { field.as_str() }) else {
+        return 0;
+    };
+
+    let Some(data_handler) = state.data_handler else {
+        return 0;
+    };
+
+    let values = data_handler.get_latest_bars_values(symbol, field, n);
+    let written = values.len().min(n);
+    unsafe { std::ptr::copy_nonoverlapping(values.as_ptr(), out, written) };
+    written
+}
+
+unsafe extern "C" fn host_indicator_value(
+    ctx: *mut std::ffi::c_void,
+    symbol: farukon_core::abi::AbiStr,
+    spec: *const farukon_core::abi::AbiIndicatorSpec,
+    shift: usize,
+    out: *mut f64,
+) -> u8 {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+    if spec.is_null() {
+        return 0;
+    }
+    let (Ok(symbol), Ok(spec)) = (unsafe { symbol.as_str() }, unsafe { (*spec).to_indicator_spec() }) else {
+        return 0;
+    };
+
+    let Some(data_handler) = state.data_handler else {
+        return 0;
+    };
+
+    match data_handler.get_indicator_value(symbol, &spec, shift) {
+        Some(value) => {
+            unsafe { *out = value };
+            1
+        },
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn host_send_signal(
+    ctx: *mut std::ffi::c_void,
+    signal: *const farukon_core::abi::AbiSignal,
+) -> i32 {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+
+    let result = unsafe { signal.as_ref() }
+        .ok_or_else(|| anyhow::anyhow!("Null signal"))
+        .and_then(|signal| unsafe { signal.to_signal_event() })
+        .and_then(|signal_event| state.event_sender.send(signal_event));
+
+    match result {
+        Ok(()) => farukon_core::abi::STATUS_OK,
+        Err(e) => {
+            state.record_error(format!("Invalid signal: {}", e));
+            farukon_core::abi::STATUS_ERROR
+        },
+    }
+}
+
+unsafe extern "C" fn host_schedule_timer(
+    ctx: *mut std::ffi::c_void,
+    tag: farukon_core::abi::AbiStr,
+    schedule_json: farukon_core::abi::AbiStr,
+) -> i32 {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+
+    let result = (|| -> anyhow::Result<()> {
+        let tag = unsafe { tag.as_str() }?.to_string();
+        let schedule: farukon_core::event::TimerSchedule = serde_json::from_str(unsafe { schedule_json.as_str() }?)
+            .map_err(|e| anyhow::anyhow!("Invalid schedule of timer '{}': {}", tag, e))?;
+        state.event_sender.send(farukon_core::event::TimerCommand::Schedule { tag, schedule })
+    })();
+
+    match result {
+        Ok(()) => farukon_core::abi::STATUS_OK,
+        Err(e) => {
+            state.record_error(e.to_string());
+            farukon_core::abi::STATUS_ERROR
+        },
+    }
+}
+
+unsafe extern "C" fn host_cancel_timer(
+    ctx: *mut std::ffi::c_void,
+    tag: farukon_core::abi::AbiStr,
+) -> i32 {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+
+    let result = unsafe { tag.as_str() }
+        .and_then(|tag| state.event_sender.send(farukon_core::event::TimerCommand::Cancel { tag: tag.to_string() }));
+
+    match result {
+        Ok(()) => farukon_core::abi::STATUS_OK,
+        Err(e) => {
+            state.record_error(e.to_string());
+            farukon_core::abi::STATUS_ERROR
+        },
+    }
+}
+
+unsafe extern "C" fn host_set_error(
+    ctx: *mut std::ffi::c_void,
+    message: farukon_core::abi::AbiStr,
+) {
+    let state = unsafe { HostCallState::from_ctx(ctx) };
+    let message = unsafe { message.as_str() }.unwrap_or("<error message is not UTF-8>").to_string();
+    state.record_error(message);
+}
+
+/// A loaded strategy library whose manifest passed the ABI check.
+pub struct StrategyLibrary {
+    _lib: libloading::Library,  // Keeps the manifest and the strategy functions mapped
+    manifest: &'static farukon_core::abi::PluginManifest,  // Static data of the library, valid while `_lib` is loaded
+    path: String,
+}
+
+impl StrategyLibrary {
+    /// Loads a strategy library and checks its manifest.
+    /// # Arguments
+    /// * `lib_path` - Path to the .so/.dylib/.dll.
+    /// # Returns
+    /// * `anyhow::Result<Self>` - Error if the library cannot be loaded, has no manifest, was built for another ABI version,
+    ///   or exports an invalid strategy registry.
+    pub fn open(lib_path: &str) -> anyhow::Result<Self> {
+        let lib = unsafe { libloading::Library::new(lib_path) }
+            .map_err(|e| anyhow::anyhow!("Failed to load strategy library '{}': {}", lib_path, e))?;
+
+        let manifest_fn: libloading::Symbol<extern "C" fn() -> *const farukon_core::abi::PluginManifest> =
+            unsafe { lib.get(farukon_core::abi::MANIFEST_SYMBOL) }
+                .map_err(|_| anyhow::anyhow!(
+                    "'{}' is not a compatible strategy plugin: it does not export `farukon_plugin_manifest`. \
+                    Rebuild it against farukon_core {} (strategy ABI v{}).",
+                    lib_path, farukon_core::abi::CORE_VERSION, farukon_core::abi::ABI_VERSION,
+                ))?;
+
+        let manifest_ptr = manifest_fn();
+        if manifest_ptr.is_null() {
+            anyhow::bail!("Strategy plugin '{}' returned a null manifest", lib_path);
+        }
+
+        // Only the leading `abi_version` field has the same layout in every ABI version.
+        let abi_version = unsafe { std::ptr::read(manifest_ptr as *const u32) };
+        if abi_version != farukon_core::abi::ABI_VERSION {
+            anyhow::bail!(
+                "Strategy plugin '{}' was built for strategy ABI v{}, but this engine supports v{}. \
+                Rebuild it against farukon_core {}.",
+                lib_path, abi_version, farukon_core::abi::ABI_VERSION, farukon_core::abi::CORE_VERSION,
+            );
+        }
+
+        let library = StrategyLibrary {
+            _lib: lib,
+            manifest: unsafe { &*manifest_ptr },
+            path: lib_path.to_string(),
+        };
+
+        // Names must be readable and unique, or `strategy_name` would be ambiguous.
+        let strategy_names = library.strategy_names()?;
+        if strategy_names.is_empty() {
+            anyhow::bail!("Strategy plugin '{}' does not export any strategy", lib_path);
+        }
+        let mut seen_names = std::collections::HashSet::new();
+        for strategy_name in &strategy_names {
+            if !seen_names.insert(*strategy_name) {
+                anyhow::bail!("Strategy plugin '{}' exports strategy '{}' more than once", lib_path, strategy_name);
+            }
+        }
+
+        anyhow::Ok(library)
+    }
+
+    /// `farukon_core` version the library was built with.
+    pub fn core_version(&self) -> anyhow::Result<&str> {
+        unsafe { self.manifest.core_version.as_str() }
+            .with_context(|| format!("Invalid manifest in strategy plugin '{}'", self.path))
+    }
+
+    /// Names of the strategies exported by the library, in registry order.
+    pub fn strategy_names(&self) -> anyhow::Result<Vec<&str>> {
+        unsafe { self.manifest.strategies() }
+            .iter()
+            .map(|entry| unsafe { entry.name.as_str() })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .with_context(|| format!("Invalid strategy registry in strategy plugin '{}'", self.path))
+    }
+
+    /// Parameter schema declared by the strategy named `strategy_name`.
+    /// # Arguments
+    /// * `strategy_name` - Name of the strategy in the registry.
+    /// # Returns
+    /// * `anyhow::Result<Option<ParamSchema>>` - `None` if the strategy does not declare a schema;
+    ///   error if the strategy is unknown or its schema is invalid.
+    pub fn param_schema(&self, strategy_name: &str) -> anyhow::Result<Option<farukon_core::param_schema::ParamSchema>> {
+        let entry = self.find_strategy(strategy_name)?;
+        let Some(param_schema_fn) = entry.vtable.param_schema else {
+            return anyhow::Ok(None);
+        };
+
+        // The plugin hands the JSON to `receive_schema`, which copies it before the call returns.
+        unsafe extern "C" fn receive_schema(receiver: *mut std::ffi::c_void, schema_json: farukon_core::abi::AbiStr) {
+            let receiver = unsafe { &mut *(receiver as *mut Option<String>) };
+            *receiver = unsafe { schema_json.as_str() }.ok().map(str::to_string);
+        }
+
+        let mut schema_json: Option<String> = None;
+        let status = unsafe { param_schema_fn(&mut schema_json as *mut Option<String> as *mut std::ffi::c_void, receive_schema) };
+        if status != farukon_core::abi::STATUS_OK {
+            anyhow::bail!("Strategy {} of '{}' failed to export its parameter schema (status {})", strategy_name, self.path, status);
+        }
+
+        let Some(schema_json) = schema_json else {
+            return anyhow::Ok(None);
+        };
+        let param_schema: farukon_core::param_schema::ParamSchema = serde_json::from_str(&schema_json)
+            .with_context(|| format!("Invalid parameter schema of strategy {} in '{}'", strategy_name, self.path))?;
+        param_schema.validate()
+            .with_context(|| format!("Invalid parameter schema of strategy {} in '{}'", strategy_name, self.path))?;
+
+        anyhow::Ok(Some(param_schema))
+    }
+
+    /// Registry entry of the strategy named `strategy_name`.
+    fn find_strategy(&self, strategy_name: &str) -> anyhow::Result<&'static farukon_core::abi::StrategyEntry> {
+        let manifest = self.manifest;
+        unsafe { manifest.strategies() }
+            .iter()
+            .find(|entry| unsafe { entry.name.as_str() }.is_ok_and(|name| name == strategy_name))
+            .ok_or_else(|| anyhow::anyhow!(
+                "Strategy plugin '{}' does not export strategy '{}'. Available strategies: {}",
+                self.path,
+                strategy_name,
+                self.strategy_names().unwrap_or_default().join(", "),
+            ))
+    }
+
+}
+
+/// Creates the strategy of the settings: from the library at `strategy_path` (a Rhai script if it ends in `.rhai`),
+/// or, without `strategy_path`, the strategy compiled into the engine under `strategy_name`.
+/// # Arguments
+/// * `mode` - The operational mode ("Debug", "Optimize", "Visual").
+/// * `strategy_settings` - Settings of the strategy.
+/// * `strategy_instruments_info` - Instrument metadata for all symbols traded by this strategy.
+/// * `event_sender` - The engine event queue.
+/// # Returns
+/// * `anyhow::Result<Box<dyn Strategy>>` - The strategy, or an error if it cannot be found or fails to initialize.
+pub fn create_strategy(
+    mode: &str,
+    strategy_settings: &farukon_core::settings::StrategySettings,
+    strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+    event_sender: &farukon_core::event::EventQueue,
+) -> anyhow::Result<Box<dyn farukon_core::strategy::Strategy>> {
+    match &strategy_settings.strategy_path {
+        Some(script_path) if script_strategy::is_script_path(script_path) => {
+            let script_strategy = script_strategy::ScriptStrategy::load_from_path(mode, script_path, strategy_settings, strategy_instruments_info, event_sender)?;
+            anyhow::Ok(Box::new(script_strategy))
+        },
+        Some(lib_path) => {
+            let dynamic_strategy = DynamicStratagy::load_from_path(mode, lib_path, strategy_settings, strategy_instruments_info, event_sender)?;
+            anyhow::Ok(Box::new(dynamic_strategy))
+        },
+        None => {
+            let static_strategy = farukon_core::registry::find_static_strategy(&strategy_settings.strategy_name)?;
+            (static_strategy.create)(
+                mode.to_string(),
+                strategy_settings.clone(),
+                strategy_instruments_info.clone(),
+                event_sender.clone(),
+            ).with_context(|| format!("Failed to create built-in strategy '{}'", strategy_settings.strategy_name))
+        },
+    }
+}
+
+/// Parameter schema of the strategy of the settings (from its library or script, or from the built-in registry).
+/// # Arguments
+/// * `strategy_settings` - Settings of the strategy.
+/// # Returns
+/// * `anyhow::Result<Option<ParamSchema>>` - `None` if the strategy does not declare a schema.
+pub fn strategy_param_schema(
+    strategy_settings: &farukon_core::settings::StrategySettings,
+) -> anyhow::Result<Option<farukon_core::param_schema::ParamSchema>> {
+    match &strategy_settings.strategy_path {
+        Some(script_path) if script_strategy::is_script_path(script_path) => script_strategy::script_param_schema(script_path),
+        Some(lib_path) => StrategyLibrary::open(lib_path)?.param_schema(&strategy_settings.strategy_name),
+        None => {
+            let static_strategy = farukon_core::registry::find_static_strategy(&strategy_settings.strategy_name)?;
+            let param_schema = (static_strategy.param_schema)();
+            if let Some(param_schema) = &param_schema {
+                param_schema.validate()
+                    .with_context(|| format!("Invalid parameter schema of built-in strategy {}", strategy_settings.strategy_name))?;
+            }
+            anyhow::Ok(param_schema)
+        },
+    }
+}
+
+/// Reads the parameter schema of every strategy, checks the configured `strategy_params`
+/// against it, fills in defaults and stores the schema in the strategy settings for the optimizers.
+/// # Arguments
+/// * `all_settings` - The loaded settings.
+/// # Returns
+/// * `anyhow::Result<()>` - Error naming the strategy whose parameters do not fit its schema.
+pub fn load_param_schemas(all_settings: &mut farukon_core::settings::Settings) -> anyhow::Result<()> {
+    for (strategy_id, strategy_settings) in all_settings.portfolio.iter_mut() {
+        let Some(param_schema) = strategy_param_schema(strategy_settings)? else {
+            continue;
+        };
+
+        param_schema.apply_to_strategy_params(&mut strategy_settings.strategy_params)
+            .with_context(|| format!("Invalid strategy_params of {} ({})", strategy_id, strategy_settings.strategy_name))?;
+        strategy_settings.param_schema = Some(param_schema);
+    }
+
+    anyhow::Ok(())
+}
 
 pub struct DynamicStratagy {
-    _lib: libloading::Library,  // Holds reference to loaded library
+    _library: StrategyLibrary,  // Holds reference to loaded library
+    vtable: &'static farukon_core::abi::StrategyVTable,  // Functions of the selected strategy, valid while `_library` is loaded
     strategy_ptr: *mut std::ffi::c_void,    // Pointer to strategy instance
-    destroy_fn: libloading::Symbol<'static, extern "C" fn(*mut std::ffi::c_void)>,  // Destructor
+    event_sender: farukon_core::event::EventQueue,  // Receives the signals and timer commands of the strategy
+    symbols: Vec<String>,   // Symbols passed to the lifecycle hooks
 }
 
 impl DynamicStratagy {
+    /// Loads the strategy library and creates an instance of the strategy named `strategy_settings.strategy_name`.
+    /// # Arguments
+    /// * `mode` - The operational mode ("Debug", "Optimize", "Visual").
+    /// * `lib_path` - Path to the strategy library.
+    /// * `strategy_settings` - Settings of the strategy.
+    /// * `strategy_instruments_info` - Instrument metadata for all symbols traded by this strategy.
+    /// * `event_sender` - The engine event queue.
+    /// # Returns
+    /// * `anyhow::Result<Self>` - Error if the library cannot be loaded, was built for another ABI version,
+    ///   does not export the strategy, or the strategy fails to initialize.
     pub fn load_from_path(
         mode: &str,
+        lib_path: &str,
         strategy_settings: &farukon_core::settings::StrategySettings,
         strategy_instruments_info: &std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
-        event_sender: &std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        event_sender: &farukon_core::event::EventQueue,
     ) -> anyhow::Result<Self> {
-        // Loads dynamic strategy library and creates strategy instance.
-        // Expects 3 exported C functions: create_strategy, destroy_strategy, calculate_signals.
-
-        let lib_path = &strategy_settings.strategy_path;
-        let lib = unsafe { libloading::Library::new(lib_path)? };
-
-        let create_strategy: libloading::Symbol<extern "C" fn(
-            *const std::os::raw::c_char,
-            *const farukon_core::settings::StrategySettings,
-            *const std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
-            *const std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
-        ) -> *mut std::ffi::c_void> = unsafe { lib.get(b"create_strategy")? };
- 
-        let destroy_strategy: libloading::Symbol<extern "C" fn(*mut std::ffi::c_void)> =
-            unsafe { lib.get(b"destroy_strategy")? };
-        let mode_c = std::ffi::CString::new(mode)?;
-            
-        let strategy_ptr = create_strategy(
-            mode_c.as_ptr(),
-            strategy_settings as *const _,
-            strategy_instruments_info as *const _,
-            event_sender as *const _,
-        );
+        let strategy_name = &strategy_settings.strategy_name;
+        let library = StrategyLibrary::open(lib_path)?;
+        let vtable = &library.find_strategy(strategy_name)?.vtable;
+
+        // --- Create the strategy ---
+        let settings_json = farukon_core::abi::AbiStrategySettings::to_json(strategy_settings)?;
+        let instruments_json = serde_json::to_string(strategy_instruments_info)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize instruments info: {}", e))?;
+        let mut error_buf = [0u8; 1024];
+
+        let strategy_ptr = unsafe {
+            (vtable.create)(
+                farukon_core::abi::AbiStr::borrowed(mode),
+                farukon_core::abi::AbiStr::borrowed(&settings_json),
+                farukon_core::abi::AbiStr::borrowed(&instruments_json),
+                error_buf.as_mut_ptr(),
+                error_buf.len(),
+            )
+        };
 
         if strategy_ptr.is_null() {
-            return Err(anyhow::anyhow!("Failed to create strategy"));
+            let error_len = error_buf.iter().position(|&byte| byte == 0).unwrap_or(error_buf.len());
+            anyhow::bail!(
+                "Failed to create strategy '{}' from '{}': {}",
+                strategy_name, lib_path, String::from_utf8_lossy(&error_buf[..error_len]),
+            );
         }
 
-        let destroy_fn: libloading::Symbol<'static, extern "C" fn(*mut std::ffi::c_void)> = 
-            unsafe { std::mem::transmute(destroy_strategy) };
-
         anyhow::Ok(DynamicStratagy {
-            _lib: lib,
+            _library: library,
+            vtable,
             strategy_ptr,
-            destroy_fn,
+            event_sender: event_sender.clone(),
+            symbols: strategy_settings.symbols.clone(),
         })
     }
 
-    pub fn calculate_signals(
+    /// Builds the `AbiCallContext` of one call, runs `call` and turns its status code into a result.
+    fn call_strategy(
         &self,
+        callback_name: &str,
+        data_handler: Option<&dyn farukon_core::data_handler::DataHandler>,
+        portfolio_state: Option<(
+            &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+            &farukon_core::portfolio::HoldingSnapshot,
+        )>,
+        symbol_list: &[String],
+        call: impl FnOnce(*mut std::ffi::c_void, *const farukon_core::abi::AbiCallContext) -> i32,
+    ) -> anyhow::Result<()> {
+        let abi_symbols: Vec<farukon_core::abi::AbiStr> = symbol_list
+            .iter()
+            .map(|symbol| farukon_core::abi::AbiStr::borrowed(symbol))
+            .collect();
+
+        let mut abi_positions: Vec<farukon_core::abi::AbiPosition> = Vec::new();
+        let mut abi_symbol_holdings: Vec<farukon_core::abi::AbiSymbolHoldings> = Vec::new();
+        let mut abi_holdings: Option<farukon_core::abi::AbiHoldings> = None;
+
+        if let Some((current_positions, latest_holdings)) = portfolio_state {
+            abi_positions.extend(current_positions.iter().map(|(symbol, position_state)| farukon_core::abi::AbiPosition {
+                symbol: farukon_core::abi::AbiStr::borrowed(symbol),
+                deal_number: position_state.deal_number as u64,
+                position: position_state.position,
+                entry_capital: position_state.entry_capital,
+                entry_price: position_state.entry_price.unwrap_or(f64::NAN),
+            }));
+            abi_symbol_holdings.extend(latest_holdings.holdings.iter().map(|(symbol, holdings_state)| farukon_core::abi::AbiSymbolHoldings {
+                symbol: farukon_core::abi::AbiStr::borrowed(symbol),
+                pnl: holdings_state.pnl,
+                blocked: holdings_state.blocked,
+                signal_name: match holdings_state.signal_name {
+                    None => farukon_core::abi::SIGNAL_NONE,
+                    Some(farukon_core::event::SignalName::Long) => farukon_core::abi::SIGNAL_LONG,
+                    Some(farukon_core::event::SignalName::Short) => farukon_core::abi::SIGNAL_SHORT,
+                    Some(farukon_core::event::SignalName::Exit) => farukon_core::abi::SIGNAL_EXIT,
+                },
+            }));
+            abi_holdings = Some(farukon_core::abi::AbiHoldings {
+                timestamp_ms: latest_holdings.datetime.timestamp_millis(),
+                capital: latest_holdings.capital,
+                cash: latest_holdings.cash,
+                blocked: latest_holdings.blocked,
+                symbols: abi_symbol_holdings.as_ptr(),
+                symbols_len: abi_symbol_holdings.len(),
+            });
+        }
+
+        let state = HostCallState {
+            data_handler,
+            event_sender: &self.event_sender,
+            error: std::cell::RefCell::new(None),
+        };
+
+        let ctx = farukon_core::abi::AbiCallContext {
+            host: &HOST_API,
+            host_ctx: &state as *const HostCallState as *mut std::ffi::c_void,
+            symbols: abi_symbols.as_ptr(),
+            symbols_len: abi_symbols.len(),
+            positions: abi_positions.as_ptr(),
+            positions_len: abi_positions.len(),
+            holdings: abi_holdings.as_ref().map_or(std::ptr::null(), |holdings| holdings as *const _),
+        };
+
+        match call(self.strategy_ptr, &ctx) {
+            farukon_core::abi::STATUS_OK => anyhow::Ok(()),
+            farukon_core::abi::STATUS_PANIC => Err(anyhow::anyhow!("Strategy {} panicked", callback_name)),
+            result => {
+                let message = state.error.borrow_mut().take()
+                    .unwrap_or_else(|| format!("failed with code: {}", result));
+                Err(anyhow::anyhow!("Strategy {} failed: {}", callback_name, message))
+            },
+        }
+    }
+
+}
+
+/// The engine calls a plugin strategy like a built-in one; every call goes through the plugin's vtable.
+impl farukon_core::strategy::Strategy for DynamicStratagy {
+    /// Calls `calculate_signals()` of the strategy.
+    fn calculate_signals(
+        &mut self,
         data_handler: &dyn farukon_core::data_handler::DataHandler,
         current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
         latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
         symbol_list: &[String],
     ) -> anyhow::Result<()> {
-        // Calls calculate_signals() from the loaded library.
-        // Transforms Rust types into C-compatible pointers.
-        // Returns 0 on success, -1 on error.
-
-        let calculate_signals_fn: libloading::Symbol<extern "C" fn (
-            *mut std::ffi::c_void,
-            *const farukon_core::DataHandlerVTable,
-            *const (),
-            *const std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
-            *const farukon_core::portfolio::HoldingSnapshot,
-            *const *const std::os::raw::c_char,
-            usize,
-        ) -> i32> = unsafe {
-            self._lib.get(b"calculate_signals")?
+        let calculate_signals_fn = self.vtable.calculate_signals;
+        self.call_strategy("calculate_signals", Some(data_handler), Some((current_positions, latest_holdings)), symbol_list, |strategy_ptr, ctx| unsafe {
+            calculate_signals_fn(strategy_ptr, ctx)
+        })
+    }
+
+    /// Calls `on_start()` of the strategy, if the plugin provides it.
+    fn on_start(&mut self) -> anyhow::Result<()> {
+        let Some(on_start_fn) = self.vtable.on_start else {
+            return anyhow::Ok(());
         };
+        self.call_strategy("on_start", None, None, &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_start_fn(strategy_ptr, ctx)
+        })
+    }
 
-        let (data_handler_ptr, data_handler_vtable) = unsafe {
-            std::mem::transmute::<_, (*const (), *const farukon_core::DataHandlerVTable)>(data_handler)
+    /// Calls `on_bar()` of the strategy, if the plugin provides it.
+    fn on_bar(
+        &mut self,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        let Some(on_bar_fn) = self.vtable.on_bar else {
+            return anyhow::Ok(());
         };
+        self.call_strategy("on_bar", Some(data_handler), Some((current_positions, latest_holdings)), &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_bar_fn(strategy_ptr, ctx)
+        })
+    }
 
-        let c_strings: Vec<std::ffi::CString> = symbol_list
-            .iter()
-            .map(|s| std::ffi::CString::new(s.as_str()))
-            .collect::<anyhow::Result<Vec<_>, _>>()?;
+    /// Calls `on_fill()` of the strategy, if the plugin provides it.
+    fn on_fill(
+        &mut self,
+        fill_event: &farukon_core::event::FillEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        let Some(on_fill_fn) = self.vtable.on_fill else {
+            return anyhow::Ok(());
+        };
+        let abi_fill = farukon_core::abi::AbiFill::borrowed(fill_event);
+        self.call_strategy("on_fill", Some(data_handler), Some((current_positions, latest_holdings)), &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_fill_fn(strategy_ptr, ctx, &abi_fill)
+        })
+    }
 
-        let c_str_ptrs: Vec<*const std::os::raw::c_char> = c_strings
-            .iter()
-            .map(|s| s.as_ptr())
-            .collect();
+    /// Calls `on_order_rejected()` of the strategy, if the plugin provides it.
+    fn on_order_rejected(
+        &mut self,
+        rejected_event: &farukon_core::event::OrderRejectedEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        let Some(on_order_rejected_fn) = self.vtable.on_order_rejected else {
+            return anyhow::Ok(());
+        };
+        let abi_rejected = farukon_core::abi::AbiOrderRejected::borrowed(rejected_event);
+        self.call_strategy("on_order_rejected", Some(data_handler), Some((current_positions, latest_holdings)), &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_order_rejected_fn(strategy_ptr, ctx, &abi_rejected)
+        })
+    }
 
-        let result = calculate_signals_fn(
-            self.strategy_ptr,
-            data_handler_vtable,
-            data_handler_ptr,
-            current_positions as *const _,
-            latest_holdings as *const _,
-            c_str_ptrs.as_ptr(),
-            symbol_list.len(),
-        );
-
-        if result == 0{
-            anyhow::Ok(())
-        } else {
-            Err(anyhow::anyhow!("Strategy calculate_signals failed with code: {}", result))
-        }
+    /// Calls `on_margin_call()` of the strategy, if the plugin provides it.
+    fn on_margin_call(
+        &mut self,
+        margin_call_event: &farukon_core::event::MarginCallEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        let Some(on_margin_call_fn) = self.vtable.on_margin_call else {
+            return anyhow::Ok(());
+        };
+        let abi_margin_call = farukon_core::abi::AbiMarginCall::borrowed(margin_call_event);
+        self.call_strategy("on_margin_call", Some(data_handler), Some((current_positions, latest_holdings)), &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_margin_call_fn(strategy_ptr, ctx, &abi_margin_call)
+        })
+    }
+
+    /// Calls `on_timer()` of the strategy, if the plugin provides it.
+    fn on_timer(
+        &mut self,
+        timer_event: &farukon_core::event::TimerEvent,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        let Some(on_timer_fn) = self.vtable.on_timer else {
+            return anyhow::Ok(());
+        };
+        let abi_timer_event = farukon_core::abi::AbiTimerEvent::borrowed(timer_event);
+        self.call_strategy("on_timer", Some(data_handler), Some((current_positions, latest_holdings)), &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_timer_fn(strategy_ptr, ctx, &abi_timer_event)
+        })
+    }
+
+    /// Calls `on_end()` of the strategy, if the plugin provides it.
+    fn on_end(
+        &mut self,
+        data_handler: &dyn farukon_core::data_handler::DataHandler,
+        current_positions: &std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
+        latest_holdings: &farukon_core::portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        let Some(on_end_fn) = self.vtable.on_end else {
+            return anyhow::Ok(());
+        };
+        self.call_strategy("on_end", Some(data_handler), Some((current_positions, latest_holdings)), &self.symbols, |strategy_ptr, ctx| unsafe {
+            on_end_fn(strategy_ptr, ctx)
+        })
     }
 
 }
@@ -117,7 +723,7 @@ impl Drop for DynamicStratagy {
         // Prevents memory leaks.
 
         if !self.strategy_ptr.is_null() {
-                (self.destroy_fn)(self.strategy_ptr);
+            unsafe { (self.vtable.destroy)(self.strategy_ptr) };
         }
     }
 }
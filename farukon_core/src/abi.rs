@@ -0,0 +1,1119 @@
+// farukon_core/src/abi.rs
+
+//! Versioned C ABI between the engine and strategy plugins (.dylib/.so/.dll).
+//!
+//! A plugin exports exactly one symbol, `farukon_plugin_manifest`, returning a pointer to a static `PluginManifest`.
+//! The manifest starts with the ABI version, so the engine can refuse an incompatible plugin before it touches anything else,
+//! and lists the strategies of the plugin by name; the engine picks one by `strategy_name`.
+//! Everything that crosses the boundary is `#[repr(C)]`: borrowed strings (`AbiStr`), bars, positions, holdings and events.
+//! Settings and instrument info are passed once, as JSON, when the strategy is created.
+//! The parameter schema of a strategy (`param_schema::ParamSchema`) is read as JSON, before any instance exists.
+//!
+//! The engine calls the strategy through the manifest's `StrategyVTable`.
+//! The strategy calls back into the engine through the `HostApi` table of the `AbiCallContext` it receives on every call:
+//! market data, sending signals, scheduling timers and reporting errors.
+//!
+//! Plugin authors do not write any of this by hand: implement `strategy::Strategy` and `PluginStrategy`
+//! and export the types with `farukon_core::export_strategies!`. The adapters below turn the C calls back into
+//! the `Strategy` trait and forward the events the strategy sends to its `EventQueue` to the engine.
+//!
+//! Bump `ABI_VERSION` on every change to a `#[repr(C)]` type, a callback signature or the calling conventions.
+
+use crate::event;
+use crate::settings;
+use crate::strategy;
+use crate::portfolio;
+use crate::data_handler;
+use crate::indicator_cache;
+use crate::instruments_info;
+use crate::commission_plans;
+use crate::sessions;
+use crate::param_schema;
+
+/// Version of the plugin ABI described in this module.
+pub const ABI_VERSION: u32 = 4;
+
+/// Version of `farukon_core` (informational, stored in the manifest of every plugin).
+pub const CORE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name of the symbol every plugin exports: `extern "C" fn() -> *const PluginManifest`.
+pub const MANIFEST_SYMBOL: &[u8] = b"farukon_plugin_manifest";
+
+// --- Codes ---
+
+/// No signal (e.g. no fill on the current bar).
+pub const SIGNAL_NONE: u32 = 0;
+pub const SIGNAL_LONG: u32 = 1;
+pub const SIGNAL_SHORT: u32 = 2;
+pub const SIGNAL_EXIT: u32 = 3;
+
+pub const ORDER_MARKET: u32 = 1;
+pub const ORDER_LIMIT: u32 = 2;
+
+/// Direction of a fill: `DIRECTION_NONE`, `DIRECTION_BUY` or `DIRECTION_SELL`.
+pub const DIRECTION_NONE: i32 = 0;
+pub const DIRECTION_BUY: i32 = 1;
+pub const DIRECTION_SELL: i32 = -1;
+
+pub const REJECTION_INSUFFICIENT_MARGIN: u32 = 1;
+
+/// Return code of a successful call.
+pub const STATUS_OK: i32 = 0;
+/// Return code of a call that failed with an error (the message is passed to `HostApi::set_error`).
+pub const STATUS_ERROR: i32 = -1;
+/// Return code of a call that panicked inside the plugin.
+pub const STATUS_PANIC: i32 = -2;
+
+fn signal_name_to_abi(signal_name: Option<event::SignalName>) -> u32 {
+    match signal_name {
+        None => SIGNAL_NONE,
+        Some(event::SignalName::Long) => SIGNAL_LONG,
+        Some(event::SignalName::Short) => SIGNAL_SHORT,
+        Some(event::SignalName::Exit) => SIGNAL_EXIT,
+    }
+}
+
+fn signal_name_from_abi(code: u32) -> Option<event::SignalName> {
+    match code {
+        SIGNAL_LONG => Some(event::SignalName::Long),
+        SIGNAL_SHORT => Some(event::SignalName::Short),
+        SIGNAL_EXIT => Some(event::SignalName::Exit),
+        _ => None,
+    }
+}
+
+fn order_type_to_abi(order_type: event::OrderType) -> u32 {
+    match order_type {
+        event::OrderType::Market => ORDER_MARKET,
+        event::OrderType::Limit => ORDER_LIMIT,
+    }
+}
+
+fn order_type_from_abi(code: u32) -> Option<event::OrderType> {
+    match code {
+        ORDER_MARKET => Some(event::OrderType::Market),
+        ORDER_LIMIT => Some(event::OrderType::Limit),
+        _ => None,
+    }
+}
+
+fn direction_to_abi(direction: Option<event::Direction>) -> i32 {
+    match direction {
+        None => DIRECTION_NONE,
+        Some(event::Direction::Buy) => DIRECTION_BUY,
+        Some(event::Direction::Sell) => DIRECTION_SELL,
+    }
+}
+
+fn direction_from_abi(code: i32) -> Option<event::Direction> {
+    match code {
+        DIRECTION_BUY => Some(event::Direction::Buy),
+        DIRECTION_SELL => Some(event::Direction::Sell),
+        _ => None,
+    }
+}
+
+/// `None` ↔ NaN for optional prices and quantities.
+fn option_to_abi(value: Option<f64>) -> f64 {
+    value.unwrap_or(f64::NAN)
+}
+
+fn option_from_abi(value: f64) -> Option<f64> {
+    if value.is_nan() { None } else { Some(value) }
+}
+
+fn datetime_to_abi(datetime: chrono::DateTime<chrono::Utc>) -> i64 {
+    datetime.timestamp_millis()
+}
+
+fn datetime_from_abi(timestamp_ms: i64) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .ok_or_else(|| anyhow::anyhow!("Timestamp out of range: {} ms", timestamp_ms))
+}
+
+// --- C-compatible types ---
+
+/// Borrowed UTF-8 string (not NUL-terminated). Valid only for the duration of the call it is passed to,
+/// except in the manifest, where it points to static data.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiStr {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+impl AbiStr {
+    /// Wraps a static string (for the manifest).
+    pub const fn from_static(value: &'static str) -> Self {
+        AbiStr { ptr: value.as_ptr(), len: value.len() }
+    }
+
+    /// Borrows `value` for the duration of a call.
+    pub fn borrowed(value: &str) -> Self {
+        AbiStr { ptr: value.as_ptr(), len: value.len() }
+    }
+
+    /// Reads the string back.
+    /// # Safety
+    /// `ptr` must point to `len` readable bytes that stay alive for `'a`.
+    /// # Returns
+    /// * `anyhow::Result<&str>` - Error if the bytes are not UTF-8.
+    pub unsafe fn as_str<'a>(&self) -> anyhow::Result<&'a str> {
+        if self.len == 0 {
+            return anyhow::Ok("");
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(self.ptr, self.len) };
+        std::str::from_utf8(bytes).map_err(|e| anyhow::anyhow!("String passed across the plugin ABI is not UTF-8: {}", e))
+    }
+
+}
+
+/// One OHLCV bar.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiBar {
+    /// Bar open time, milliseconds since the Unix epoch (exchange-local clock, like `MarketBar::datetime`).
+    pub timestamp_ms: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+    /// 1 for bars produced by the gap policy, 0 otherwise.
+    pub is_filled: u8,
+}
+
+impl AbiBar {
+    /// An all-zero bar, used to size output buffers.
+    pub const EMPTY: AbiBar = AbiBar { timestamp_ms: 0, open: 0.0, high: 0.0, low: 0.0, close: 0.0, volume: 0, is_filled: 0 };
+
+}
+
+impl From<&data_handler::MarketBar> for AbiBar {
+    fn from(bar: &data_handler::MarketBar) -> Self {
+        AbiBar {
+            timestamp_ms: datetime_to_abi(bar.datetime),
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            is_filled: bar.is_filled as u8,
+        }
+    }
+}
+
+impl AbiBar {
+    /// Converts the bar back to a `MarketBar`.
+    pub fn to_market_bar(&self) -> anyhow::Result<data_handler::MarketBar> {
+        anyhow::Ok(data_handler::MarketBar {
+            datetime: datetime_from_abi(self.timestamp_ms)?,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            is_filled: self.is_filled != 0,
+        })
+    }
+
+}
+
+/// An indicator and its parameters (`indicator_cache::IndicatorSpec`), by name.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiIndicatorSpec {
+    /// "sma", "ema", "wma", "rsi", "std", "highest", "lowest" or "atr".
+    pub indicator: AbiStr,
+    pub period: usize,
+    /// Price field: "open", "high", "low", "close", "volume", "median" or "typical"; ignored by "atr".
+    pub source: AbiStr,
+}
+
+impl From<&indicator_cache::IndicatorSpec> for AbiIndicatorSpec {
+    fn from(spec: &indicator_cache::IndicatorSpec) -> Self {
+        AbiIndicatorSpec {
+            indicator: AbiStr::from_static(spec.name()),
+            period: spec.period(),
+            source: AbiStr::from_static(spec.source().name()),
+        }
+    }
+}
+
+impl AbiIndicatorSpec {
+    /// Converts the spec back to an `IndicatorSpec`.
+    /// # Safety
+    /// The strings must be valid for the duration of the call.
+    pub unsafe fn to_indicator_spec(&self) -> anyhow::Result<indicator_cache::IndicatorSpec> {
+        let indicator = unsafe { self.indicator.as_str() }?;
+        let source_name = unsafe { self.source.as_str() }?;
+        let source = crate::indicators::streaming::PriceSource::from_name(source_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown indicator price source: {}", source_name))?;
+        indicator_cache::IndicatorSpec::from_parts(indicator, self.period, source)
+            .ok_or_else(|| anyhow::anyhow!("Unknown indicator: {}", indicator))
+    }
+
+}
+
+/// Position of one symbol (`portfolio::PositionState`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiPosition {
+    pub symbol: AbiStr,
+    pub deal_number: u64,
+    pub position: f64,
+    pub entry_capital: f64,
+    /// NaN if there is no entry price.
+    pub entry_price: f64,
+}
+
+/// Holdings of one symbol (`portfolio::HoldingsState`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiSymbolHoldings {
+    pub symbol: AbiStr,
+    pub pnl: f64,
+    pub blocked: f64,
+    /// `SIGNAL_*` code of the fill processed on the current bar, `SIGNAL_NONE` if none.
+    pub signal_name: u32,
+}
+
+/// Latest holdings snapshot (`portfolio::HoldingSnapshot`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiHoldings {
+    pub timestamp_ms: i64,
+    pub capital: f64,
+    pub cash: f64,
+    pub blocked: f64,
+    pub symbols: *const AbiSymbolHoldings,
+    pub symbols_len: usize,
+}
+
+/// A strategy signal (`event::SignalEvent`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiSignal {
+    pub timestamp_ms: i64,
+    pub symbol: AbiStr,
+    /// `SIGNAL_LONG`, `SIGNAL_SHORT` or `SIGNAL_EXIT`.
+    pub signal_name: u32,
+    /// `ORDER_MARKET` or `ORDER_LIMIT`.
+    pub order_type: u32,
+    /// NaN lets the portfolio size the order.
+    pub quantity: f64,
+    /// NaN for market orders.
+    pub limit_price: f64,
+}
+
+impl AbiSignal {
+    /// Borrows a `SignalEvent` for the duration of a call.
+    pub fn borrowed(signal: &event::SignalEvent) -> Self {
+        AbiSignal {
+            timestamp_ms: datetime_to_abi(signal.timeindex),
+            symbol: AbiStr::borrowed(&signal.symbol),
+            signal_name: signal_name_to_abi(Some(signal.signal_name)),
+            order_type: order_type_to_abi(signal.order_type),
+            quantity: option_to_abi(signal.quantity),
+            limit_price: option_to_abi(signal.limit_price),
+        }
+    }
+
+    /// Converts the signal back to a `SignalEvent`.
+    /// # Safety
+    /// `symbol` must be valid for the duration of the call.
+    pub unsafe fn to_signal_event(&self) -> anyhow::Result<event::SignalEvent> {
+        anyhow::Ok(event::SignalEvent {
+            timeindex: datetime_from_abi(self.timestamp_ms)?,
+            symbol: unsafe { self.symbol.as_str() }?.to_string(),
+            signal_name: signal_name_from_abi(self.signal_name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown signal code: {}", self.signal_name))?,
+            order_type: order_type_from_abi(self.order_type)
+                .ok_or_else(|| anyhow::anyhow!("Unknown order type code: {}", self.order_type))?,
+            quantity: option_from_abi(self.quantity),
+            limit_price: option_from_abi(self.limit_price),
+        })
+    }
+
+}
+
+/// An executed order (`event::FillEvent`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiFill {
+    pub timestamp_ms: i64,
+    pub symbol: AbiStr,
+    pub exchange: AbiStr,
+    pub quantity: f64,
+    /// `DIRECTION_*` code.
+    pub direction: i32,
+    /// NaN if unknown.
+    pub execution_price: f64,
+    /// NaN if unknown.
+    pub commission: f64,
+    pub signal_name: u32,
+}
+
+impl AbiFill {
+    /// Borrows a `FillEvent` for the duration of a call.
+    pub fn borrowed(fill: &event::FillEvent) -> Self {
+        AbiFill {
+            timestamp_ms: datetime_to_abi(fill.timeindex),
+            symbol: AbiStr::borrowed(&fill.symbol),
+            exchange: AbiStr::borrowed(&fill.exchange),
+            quantity: fill.quantity,
+            direction: direction_to_abi(fill.direction),
+            execution_price: option_to_abi(fill.execution_price),
+            commission: option_to_abi(fill.commission),
+            signal_name: signal_name_to_abi(Some(fill.signal_name)),
+        }
+    }
+
+    /// Converts the fill back to a `FillEvent`.
+    /// # Safety
+    /// `symbol` and `exchange` must be valid for the duration of the call.
+    pub unsafe fn to_fill_event(&self) -> anyhow::Result<event::FillEvent> {
+        anyhow::Ok(event::FillEvent {
+            timeindex: datetime_from_abi(self.timestamp_ms)?,
+            symbol: unsafe { self.symbol.as_str() }?.to_string(),
+            exchange: unsafe { self.exchange.as_str() }?.to_string(),
+            quantity: self.quantity,
+            direction: direction_from_abi(self.direction),
+            execution_price: option_from_abi(self.execution_price),
+            commission: option_from_abi(self.commission),
+            signal_name: signal_name_from_abi(self.signal_name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown signal code: {}", self.signal_name))?,
+        })
+    }
+
+}
+
+/// A signal the portfolio refused to turn into an order (`event::OrderRejectedEvent`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiOrderRejected {
+    pub timestamp_ms: i64,
+    pub signal: AbiSignal,
+    pub quantity: f64,
+    /// `REJECTION_*` code.
+    pub reason: u32,
+}
+
+impl AbiOrderRejected {
+    /// Borrows an `OrderRejectedEvent` for the duration of a call.
+    pub fn borrowed(rejected: &event::OrderRejectedEvent) -> Self {
+        AbiOrderRejected {
+            timestamp_ms: datetime_to_abi(rejected.timeindex),
+            signal: AbiSignal::borrowed(&rejected.signal),
+            quantity: rejected.quantity,
+            reason: match rejected.reason {
+                event::RejectionReason::InsufficientMargin => REJECTION_INSUFFICIENT_MARGIN,
+            },
+        }
+    }
+
+    /// Converts the event back to an `OrderRejectedEvent`.
+    /// # Safety
+    /// The strings of `signal` must be valid for the duration of the call.
+    pub unsafe fn to_order_rejected_event(&self) -> anyhow::Result<event::OrderRejectedEvent> {
+        anyhow::Ok(event::OrderRejectedEvent {
+            timeindex: datetime_from_abi(self.timestamp_ms)?,
+            signal: unsafe { self.signal.to_signal_event() }?,
+            quantity: self.quantity,
+            reason: match self.reason {
+                REJECTION_INSUFFICIENT_MARGIN => event::RejectionReason::InsufficientMargin,
+                code => anyhow::bail!("Unknown rejection reason code: {}", code),
+            },
+        })
+    }
+
+}
+
+/// Capital fell below the margin required by the open positions (`event::MarginCallEvent`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiMarginCall {
+    pub timestamp_ms: i64,
+    pub capital: f64,
+    pub min_margin: f64,
+}
+
+impl AbiMarginCall {
+    /// Converts a `MarginCallEvent`.
+    pub fn borrowed(margin_call: &event::MarginCallEvent) -> Self {
+        AbiMarginCall {
+            timestamp_ms: datetime_to_abi(margin_call.timeindex),
+            capital: margin_call.capital,
+            min_margin: margin_call.min_margin,
+        }
+    }
+
+    /// Converts the event back to a `MarginCallEvent`.
+    pub fn to_margin_call_event(&self) -> anyhow::Result<event::MarginCallEvent> {
+        anyhow::Ok(event::MarginCallEvent {
+            timeindex: datetime_from_abi(self.timestamp_ms)?,
+            capital: self.capital,
+            min_margin: self.min_margin,
+        })
+    }
+
+}
+
+/// A strategy timer that came due (`event::TimerEvent`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AbiTimerEvent {
+    pub timestamp_ms: i64,
+    pub scheduled_for_ms: i64,
+    pub tag: AbiStr,
+}
+
+impl AbiTimerEvent {
+    /// Borrows a `TimerEvent` for the duration of a call.
+    pub fn borrowed(timer_event: &event::TimerEvent) -> Self {
+        AbiTimerEvent {
+            timestamp_ms: datetime_to_abi(timer_event.timeindex),
+            scheduled_for_ms: datetime_to_abi(timer_event.scheduled_for),
+            tag: AbiStr::borrowed(&timer_event.tag),
+        }
+    }
+
+    /// Converts the event back to a `TimerEvent`.
+    /// # Safety
+    /// `tag` must be valid for the duration of the call.
+    pub unsafe fn to_timer_event(&self) -> anyhow::Result<event::TimerEvent> {
+        anyhow::Ok(event::TimerEvent::new(
+            datetime_from_abi(self.timestamp_ms)?,
+            datetime_from_abi(self.scheduled_for_ms)?,
+            unsafe { self.tag.as_str() }?.to_string(),
+        ))
+    }
+
+}
+
+// --- Callback tables ---
+
+/// Engine functions a strategy may call while it is being called. `ctx` is `AbiCallContext::host_ctx`.
+/// Functions returning `i32` return `STATUS_OK` or `STATUS_ERROR`.
+#[repr(C)]
+pub struct HostApi {
+    /// Writes the latest bar of `symbol` to `out`. Returns 1, or 0 if the symbol has no bar yet.
+    pub latest_bar: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, symbol: AbiStr, out: *mut AbiBar) -> u8,
+    /// Writes up to `n` latest bars of `symbol` to `out` (room for `n`), oldest first. Returns the number written.
+    pub latest_bars: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, symbol: AbiStr, n: usize, out: *mut AbiBar) -> usize,
+    /// Writes the `field` ("open", "high", "low", "close", "volume") of the latest bar to `out`. Returns 1, or 0 if there is no bar.
+    pub latest_value: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, symbol: AbiStr, field: AbiStr, out: *mut f64) -> u8,
+    /// Writes `field` of up to `n` latest bars to `out` (room for `n`), oldest first. Returns the number written.
+    pub latest_values: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, symbol: AbiStr, field: AbiStr, n: usize, out: *mut f64) -> usize,
+    /// Writes the value of an indicator `shift` bars before the latest bar to `out`, from the engine's shared indicator cache.
+    /// Returns 1, or 0 while the indicator is warming up (or for an unknown indicator).
+    pub indicator_value: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, symbol: AbiStr, spec: *const AbiIndicatorSpec, shift: usize, out: *mut f64) -> u8,
+    /// Sends a signal to the portfolio.
+    pub send_signal: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, signal: *const AbiSignal) -> i32,
+    /// Registers a timer; `schedule_json` is an `event::TimerSchedule` serialized as JSON.
+    pub schedule_timer: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, tag: AbiStr, schedule_json: AbiStr) -> i32,
+    /// Removes the timer with `tag`.
+    pub cancel_timer: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, tag: AbiStr) -> i32,
+    /// Records the error message of a call that is about to return `STATUS_ERROR`.
+    pub set_error: unsafe extern "C" fn(ctx: *mut std::ffi::c_void, message: AbiStr),
+}
+
+/// Everything a strategy call receives from the engine. Valid only for the duration of the call.
+#[repr(C)]
+pub struct AbiCallContext {
+    pub host: *const HostApi,
+    pub host_ctx: *mut std::ffi::c_void,
+    /// Symbols traded by the strategy.
+    pub symbols: *const AbiStr,
+    pub symbols_len: usize,
+    pub positions: *const AbiPosition,
+    pub positions_len: usize,
+    /// Null before the portfolio has a holdings snapshot (`on_start`).
+    pub holdings: *const AbiHoldings,
+}
+
+/// `create`: (mode, strategy settings JSON, instruments info JSON, error buffer, error buffer size) -> strategy or null.
+/// On failure the UTF-8 error message is written to the buffer (truncated, not NUL-terminated; unused bytes are zero).
+pub type CreateFn = unsafe extern "C" fn(AbiStr, AbiStr, AbiStr, *mut u8, usize) -> *mut std::ffi::c_void;
+/// `destroy`: (strategy).
+pub type DestroyFn = unsafe extern "C" fn(*mut std::ffi::c_void);
+/// `calculate_signals`, `on_start`, `on_bar`, `on_end`: (strategy, context) -> status code.
+pub type CallFn = unsafe extern "C" fn(*mut std::ffi::c_void, *const AbiCallContext) -> i32;
+/// `on_fill`, `on_order_rejected`, `on_margin_call`, `on_timer`: (strategy, context, event) -> status code.
+pub type EventCallFn<E> = unsafe extern "C" fn(*mut std::ffi::c_void, *const AbiCallContext, *const E) -> i32;
+/// Receives the parameter schema JSON: (receiver context, schema JSON). The string is valid only during the call.
+pub type SchemaWriterFn = unsafe extern "C" fn(*mut std::ffi::c_void, AbiStr);
+/// `param_schema`: (receiver context, writer) -> status code. Calls the writer once if the strategy declares a schema.
+pub type ParamSchemaFn = unsafe extern "C" fn(*mut std::ffi::c_void, SchemaWriterFn) -> i32;
+
+/// Strategy functions called by the engine. Optional hooks may be null.
+#[repr(C)]
+pub struct StrategyVTable {
+    pub create: CreateFn,
+    pub destroy: DestroyFn,
+    pub calculate_signals: CallFn,
+    pub on_start: Option<CallFn>,
+    pub on_bar: Option<CallFn>,
+    pub on_fill: Option<EventCallFn<AbiFill>>,
+    pub on_order_rejected: Option<EventCallFn<AbiOrderRejected>>,
+    pub on_margin_call: Option<EventCallFn<AbiMarginCall>>,
+    pub on_timer: Option<EventCallFn<AbiTimerEvent>>,
+    pub on_end: Option<CallFn>,
+    pub param_schema: Option<ParamSchemaFn>,
+}
+
+impl StrategyVTable {
+    /// Table of adapters calling the `Strategy` implementation of `S`.
+    pub const fn of<S: PluginStrategy>() -> Self {
+        StrategyVTable {
+            create: plugin_create::<S>,
+            destroy: plugin_destroy::<S>,
+            calculate_signals: plugin_calculate_signals::<S>,
+            on_start: Some(plugin_on_start::<S>),
+            on_bar: Some(plugin_on_bar::<S>),
+            on_fill: Some(plugin_on_fill::<S>),
+            on_order_rejected: Some(plugin_on_order_rejected::<S>),
+            on_margin_call: Some(plugin_on_margin_call::<S>),
+            on_timer: Some(plugin_on_timer::<S>),
+            on_end: Some(plugin_on_end::<S>),
+            param_schema: Some(plugin_param_schema::<S>),
+        }
+    }
+
+}
+
+/// One strategy of a plugin: its name and the functions of its instances (`vtable.create` is the factory).
+#[repr(C)]
+pub struct StrategyEntry {
+    pub name: AbiStr,
+    pub vtable: StrategyVTable,
+}
+
+// Entries only point to static data.
+unsafe impl Sync for StrategyEntry {}
+
+impl StrategyEntry {
+    /// Registry entry exporting strategy `S` under `name`.
+    pub const fn new<S: PluginStrategy>(name: &'static str) -> Self {
+        StrategyEntry {
+            name: AbiStr::from_static(name),
+            vtable: StrategyVTable::of::<S>(),
+        }
+    }
+
+}
+
+/// What a plugin exports through `farukon_plugin_manifest`.
+/// `abi_version` must stay the first field in every ABI version.
+#[repr(C)]
+pub struct PluginManifest {
+    pub abi_version: u32,
+    /// `farukon_core` version the plugin was built with.
+    pub core_version: AbiStr,
+    /// Strategies exported by the plugin, with unique names.
+    pub strategies: *const StrategyEntry,
+    pub strategies_len: usize,
+}
+
+// The manifest only points to static data.
+unsafe impl Sync for PluginManifest {}
+
+impl PluginManifest {
+    /// Manifest of a plugin exporting `strategies`.
+    pub const fn new(strategies: &'static [StrategyEntry]) -> Self {
+        PluginManifest {
+            abi_version: ABI_VERSION,
+            core_version: AbiStr::from_static(CORE_VERSION),
+            strategies: strategies.as_ptr(),
+            strategies_len: strategies.len(),
+        }
+    }
+
+    /// The strategy registry of the manifest.
+    /// # Safety
+    /// The manifest must come from a loaded plugin built for this `ABI_VERSION`.
+    pub unsafe fn strategies(&self) -> &[StrategyEntry] {
+        unsafe { raw_slice(self.strategies, self.strategies_len) }
+    }
+
+}
+
+/// Exports strategies from a plugin, each under its name (the `strategy_name` of the settings).
+/// Every type must implement `PluginStrategy`.
+/// ```ignore
+/// farukon_core::export_strategies! {
+///     "MA_cross" => MovingAverageCrossStrategy,
+///     "MA_cross_long_only" => LongOnlyStrategy,
+/// }
+/// ```
+#[macro_export]
+macro_rules! export_strategies {
+    ($($name:expr => $strategy:ty),+ $(,)?) => {
+        static FARUKON_STRATEGIES: &[$crate::abi::StrategyEntry] = &[
+            $($crate::abi::StrategyEntry::new::<$strategy>($name)),+
+        ];
+
+        static FARUKON_PLUGIN_MANIFEST: $crate::abi::PluginManifest = $crate::abi::PluginManifest::new(FARUKON_STRATEGIES);
+
+        /// Entry point of the plugin: returns the manifest checked by the engine's loader.
+        #[unsafe(no_mangle)]
+        pub extern "C" fn farukon_plugin_manifest() -> *const $crate::abi::PluginManifest {
+            &FARUKON_PLUGIN_MANIFEST
+        }
+    };
+}
+
+// --- Plugin side ---
+
+/// Constructor of a strategy exported from a plugin (`export_strategies!`) or compiled into the engine (`register_strategies!`).
+pub trait PluginStrategy: strategy::Strategy + Sized + 'static {
+    /// Creates the strategy.
+    /// # Arguments
+    /// * `mode` - The operational mode ("Debug", "Optimize", "Visual").
+    /// * `strategy_settings` - The settings of this strategy (including commission plans and session calendar).
+    /// * `strategy_instruments_info` - Instrument metadata for all symbols traded by this strategy.
+    /// * `event_sender` - Queue for signals and timer commands; forwarded to the engine after every call.
+    /// # Returns
+    /// * `anyhow::Result<Self>` - The strategy, or an error reported to the engine.
+    fn create(
+        mode: String,
+        strategy_settings: settings::StrategySettings,
+        strategy_instruments_info: std::collections::HashMap<String, instruments_info::InstrumentInfo>,
+        event_sender: event::EventQueue,
+    ) -> anyhow::Result<Self>;
+
+    /// Declares the parameters of the strategy, checked by the engine before a run.
+    /// # Returns
+    /// * `Option<param_schema::ParamSchema>` - The schema, or `None` to accept any `strategy_params` (the default).
+    fn param_schema() -> Option<param_schema::ParamSchema> {
+        None
+    }
+}
+
+/// Strategy settings as passed to `create`: the fields skipped by the settings deserializer travel next to them.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct AbiStrategySettings {
+    pub strategy_settings: settings::StrategySettings,
+    pub commission_plans: Option<commission_plans::CommissionPlans>,
+    pub session_calendar: Option<sessions::SessionCalendar>,
+}
+
+impl AbiStrategySettings {
+    /// Serializes strategy settings for `create`.
+    pub fn to_json(strategy_settings: &settings::StrategySettings) -> anyhow::Result<String> {
+        serde_json::to_string(&AbiStrategySettings {
+            strategy_settings: strategy_settings.clone(),
+            commission_plans: strategy_settings.commission_plans.clone(),
+            session_calendar: strategy_settings.session_calendar.clone(),
+        })
+        .map_err(|e| anyhow::anyhow!("Failed to serialize strategy settings: {}", e))
+    }
+
+    /// Parses strategy settings passed to `create`.
+    pub fn from_json(json: &str) -> anyhow::Result<settings::StrategySettings> {
+        let abi_settings: AbiStrategySettings = serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("Failed to parse strategy settings: {}", e))?;
+        let mut strategy_settings = abi_settings.strategy_settings;
+        strategy_settings.commission_plans = abi_settings.commission_plans;
+        strategy_settings.session_calendar = abi_settings.session_calendar;
+        anyhow::Ok(strategy_settings)
+    }
+
+}
+
+/// A strategy living in a plugin, together with the state rebuilt from the `AbiCallContext` of every call.
+struct PluginInstance<S> {
+    strategy: S,
+    /// Queue given to the strategy; drained into the `HostApi` after every call.
+    event_queue: event::EventQueue,
+    symbols: Vec<String>,
+    positions: std::collections::HashMap<String, portfolio::PositionState>,
+    holdings: portfolio::HoldingSnapshot,
+}
+
+impl<S: PluginStrategy> PluginInstance<S> {
+    /// Refreshes symbols, positions and holdings from the context, reusing the allocated maps.
+    unsafe fn sync_context(&mut self, ctx: &AbiCallContext) -> anyhow::Result<()> {
+        let symbols = unsafe { raw_slice(ctx.symbols, ctx.symbols_len) };
+        let same_symbols = symbols.len() == self.symbols.len()
+            && symbols.iter().zip(&self.symbols).all(|(abi_symbol, symbol)| unsafe { abi_symbol.as_str() }.is_ok_and(|s| s == symbol));
+        if !same_symbols {
+            self.symbols = symbols
+                .iter()
+                .map(|symbol| unsafe { symbol.as_str() }.map(str::to_string))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+        }
+
+        let positions = unsafe { raw_slice(ctx.positions, ctx.positions_len) };
+        if positions.len() != self.positions.len() {
+            self.positions.clear();
+        }
+        for abi_position in positions {
+            let symbol = unsafe { abi_position.symbol.as_str() }?;
+            let position_state = match self.positions.get_mut(symbol) {
+                Some(position_state) => position_state,
+                None => self.positions.entry(symbol.to_string()).or_insert_with(portfolio::PositionState::new),
+            };
+            position_state.deal_number = abi_position.deal_number as usize;
+            position_state.position = abi_position.position;
+            position_state.entry_capital = abi_position.entry_capital;
+            position_state.entry_price = option_from_abi(abi_position.entry_price);
+        }
+
+        if let Some(abi_holdings) = unsafe { ctx.holdings.as_ref() } {
+            self.holdings.datetime = datetime_from_abi(abi_holdings.timestamp_ms)?;
+            self.holdings.capital = abi_holdings.capital;
+            self.holdings.cash = abi_holdings.cash;
+            self.holdings.blocked = abi_holdings.blocked;
+
+            let symbol_holdings = unsafe { raw_slice(abi_holdings.symbols, abi_holdings.symbols_len) };
+            if symbol_holdings.len() != self.holdings.holdings.len() {
+                self.holdings.holdings.clear();
+            }
+            for abi_symbol_holdings in symbol_holdings {
+                let symbol = unsafe { abi_symbol_holdings.symbol.as_str() }?;
+                let holdings_state = match self.holdings.holdings.get_mut(symbol) {
+                    Some(holdings_state) => holdings_state,
+                    None => self.holdings.holdings.entry(symbol.to_string()).or_insert_with(portfolio::HoldingsState::new),
+                };
+                holdings_state.pnl = abi_symbol_holdings.pnl;
+                holdings_state.blocked = abi_symbol_holdings.blocked;
+                holdings_state.signal_name = signal_name_from_abi(abi_symbol_holdings.signal_name);
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Forwards the signals and timer commands the strategy sent during the call to the engine, in order.
+    unsafe fn forward_events(&self, ctx: &AbiCallContext) -> anyhow::Result<()> {
+        let host = unsafe { &*ctx.host };
+
+        while let Some(engine_event) = self.event_queue.try_recv() {
+            let status = match &engine_event {
+                event::EngineEvent::Signal(signal) => {
+                    let abi_signal = AbiSignal::borrowed(signal);
+                    unsafe { (host.send_signal)(ctx.host_ctx, &abi_signal) }
+                },
+                event::EngineEvent::Timer(event::TimerCommand::Schedule { tag, schedule }) => {
+                    let schedule_json = serde_json::to_string(schedule)
+                        .map_err(|e| anyhow::anyhow!("Failed to serialize timer '{}': {}", tag, e))?;
+                    unsafe { (host.schedule_timer)(ctx.host_ctx, AbiStr::borrowed(tag), AbiStr::borrowed(&schedule_json)) }
+                },
+                event::EngineEvent::Timer(event::TimerCommand::Cancel { tag }) => {
+                    unsafe { (host.cancel_timer)(ctx.host_ctx, AbiStr::borrowed(tag)) }
+                },
+                other => anyhow::bail!("Strategies can only send SIGNAL and TIMER events, got {}", other.event_type()),
+            };
+            if status != STATUS_OK {
+                anyhow::bail!("Engine rejected {} event sent by the strategy", engine_event.event_type());
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+}
+
+/// `DataHandler` seen by a plugin strategy: every call goes through the `HostApi`.
+/// The engine drives the data feed, so `update_bars` and `set_continue_backtest` do nothing here.
+struct HostDataHandler<'a> {
+    ctx: &'a AbiCallContext,
+    host: &'a HostApi,
+    /// Bars handed out by reference; kept until the end of the call.
+    bars: std::cell::RefCell<Vec<Box<[data_handler::MarketBar]>>>,
+}
+
+impl<'a> HostDataHandler<'a> {
+    /// # Safety
+    /// `ctx.host` must point to a valid `HostApi`.
+    unsafe fn new(ctx: &'a AbiCallContext) -> Self {
+        HostDataHandler {
+            ctx,
+            host: unsafe { &*ctx.host },
+            bars: std::cell::RefCell::new(Vec::new()),
+        }
+    }
+
+    fn latest_abi_bars(&self, symbol: &str, n: usize) -> Vec<AbiBar> {
+        let mut abi_bars = vec![AbiBar::EMPTY; n];
+        let written = unsafe { (self.host.latest_bars)(self.ctx.host_ctx, AbiStr::borrowed(symbol), n, abi_bars.as_mut_ptr()) };
+        abi_bars.truncate(written.min(n));
+        abi_bars
+    }
+
+    /// Stores bars for the rest of the call and returns references to them.
+    fn keep_bars(&self, market_bars: Vec<data_handler::MarketBar>) -> &[data_handler::MarketBar] {
+        let boxed_bars = market_bars.into_boxed_slice();
+        let bars_ptr: *const [data_handler::MarketBar] = &*boxed_bars;
+        self.bars.borrow_mut().push(boxed_bars);
+        // The boxed slice never moves and is only dropped with `self`.
+        unsafe { &*bars_ptr }
+    }
+
+}
+
+impl data_handler::DataHandler for HostDataHandler<'_> {
+    fn get_latest_bar(&self, symbol: &str) -> Option<&data_handler::MarketBar> {
+        let mut abi_bar = AbiBar::EMPTY;
+        let found = unsafe { (self.host.latest_bar)(self.ctx.host_ctx, AbiStr::borrowed(symbol), &mut abi_bar) };
+        if found == 0 {
+            return None;
+        }
+        let market_bar = abi_bar.to_market_bar().ok()?;
+        self.keep_bars(vec![market_bar]).first()
+    }
+
+    fn get_latest_bars(&self, symbol: &str, n: usize) -> Vec<&data_handler::MarketBar> {
+        let market_bars: Vec<data_handler::MarketBar> = self.latest_abi_bars(symbol, n)
+            .iter()
+            .filter_map(|abi_bar| abi_bar.to_market_bar().ok())
+            .collect();
+        self.keep_bars(market_bars).iter().collect()
+    }
+
+    fn get_latest_bar_datetime(&self, symbol: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        let mut abi_bar = AbiBar::EMPTY;
+        let found = unsafe { (self.host.latest_bar)(self.ctx.host_ctx, AbiStr::borrowed(symbol), &mut abi_bar) };
+        if found == 0 {
+            return None;
+        }
+        datetime_from_abi(abi_bar.timestamp_ms).ok()
+    }
+
+    fn get_latest_bar_value(&self, symbol: &str, val_type: &str) -> Option<f64> {
+        let mut value = 0.0;
+        let found = unsafe {
+            (self.host.latest_value)(self.ctx.host_ctx, AbiStr::borrowed(symbol), AbiStr::borrowed(val_type), &mut value)
+        };
+        if found == 0 { None } else { Some(value) }
+    }
+
+    fn get_latest_bars_values(&self, symbol: &str, val_type: &str, n: usize) -> Vec<f64> {
+        let mut values = vec![0.0; n];
+        let written = unsafe {
+            (self.host.latest_values)(self.ctx.host_ctx, AbiStr::borrowed(symbol), AbiStr::borrowed(val_type), n, values.as_mut_ptr())
+        };
+        values.truncate(written.min(n));
+        values
+    }
+
+    fn get_indicator_value(&self, symbol: &str, spec: &indicator_cache::IndicatorSpec, shift: usize) -> Option<f64> {
+        let abi_spec = AbiIndicatorSpec::from(spec);
+        let mut value = 0.0;
+        let found = unsafe {
+            (self.host.indicator_value)(self.ctx.host_ctx, AbiStr::borrowed(symbol), &abi_spec, shift, &mut value)
+        };
+        if found == 0 { None } else { Some(value) }
+    }
+
+    fn update_bars(&mut self) {}
+
+    fn get_continue_backtest(&self) -> bool {
+        true
+    }
+
+    fn set_continue_backtest(&mut self, _value: bool) {}
+
+}
+
+/// Reads a C array; null or empty arrays give an empty slice.
+unsafe fn raw_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ptr, len) }
+    }
+}
+
+/// Writes `message` into a caller-provided error buffer, truncated to the buffer size.
+unsafe fn write_error(error_buf: *mut u8, error_buf_len: usize, message: &str) {
+    if error_buf.is_null() || error_buf_len == 0 {
+        return;
+    }
+    let bytes = message.as_bytes();
+    let len = bytes.len().min(error_buf_len);
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), error_buf, len) };
+}
+
+/// Runs one strategy call: syncs the context, calls `f`, forwards the sent events and turns errors and panics into status codes.
+unsafe fn plugin_call<S: PluginStrategy>(
+    strategy_ptr: *mut std::ffi::c_void,
+    ctx: *const AbiCallContext,
+    f: impl FnOnce(&mut PluginInstance<S>, &HostDataHandler) -> anyhow::Result<()>,
+) -> i32 {
+    if strategy_ptr.is_null() || ctx.is_null() {
+        return STATUS_ERROR;
+    }
+    let instance = unsafe { &mut *(strategy_ptr as *mut PluginInstance<S>) };
+    let ctx = unsafe { &*ctx };
+    let host = unsafe { &*ctx.host };
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        unsafe { instance.sync_context(ctx) }?;
+        let data_handler = unsafe { HostDataHandler::new(ctx) };
+        let call_result = f(instance, &data_handler);
+        // Signals sent before an error still reach the engine, as they would without the ABI boundary.
+        let forward_result = unsafe { instance.forward_events(ctx) };
+        call_result.and(forward_result)
+    }));
+
+    match result {
+        Ok(Ok(())) => STATUS_OK,
+        Ok(Err(e)) => {
+            let message = format!("{:#}", e);
+            unsafe { (host.set_error)(ctx.host_ctx, AbiStr::borrowed(&message)) };
+            STATUS_ERROR
+        },
+        Err(_) => STATUS_PANIC,
+    }
+}
+
+unsafe extern "C" fn plugin_create<S: PluginStrategy>(
+    mode: AbiStr,
+    settings_json: AbiStr,
+    instruments_json: AbiStr,
+    error_buf: *mut u8,
+    error_buf_len: usize,
+) -> *mut std::ffi::c_void {
+    let result = std::panic::catch_unwind(|| -> anyhow::Result<PluginInstance<S>> {
+        let mode = unsafe { mode.as_str() }?.to_string();
+        let strategy_settings = AbiStrategySettings::from_json(unsafe { settings_json.as_str() }?)?;
+        let strategy_instruments_info: std::collections::HashMap<String, instruments_info::InstrumentInfo> =
+            serde_json::from_str(unsafe { instruments_json.as_str() }?)
+                .map_err(|e| anyhow::anyhow!("Failed to parse instruments info: {}", e))?;
+
+        let event_queue = event::EventQueue::new();
+        let strategy = S::create(mode, strategy_settings, strategy_instruments_info, event_queue.clone())?;
+
+        anyhow::Ok(PluginInstance {
+            strategy,
+            event_queue,
+            symbols: Vec::new(),
+            positions: std::collections::HashMap::new(),
+            holdings: portfolio::HoldingSnapshot::new(chrono::DateTime::default(), 0.0, 0.0, 0.0, std::collections::HashMap::new()),
+        })
+    });
+
+    match result {
+        Ok(Ok(instance)) => Box::into_raw(Box::new(instance)) as *mut std::ffi::c_void,
+        Ok(Err(e)) => {
+            unsafe { write_error(error_buf, error_buf_len, &format!("{:#}", e)) };
+            std::ptr::null_mut()
+        },
+        Err(_) => {
+            unsafe { write_error(error_buf, error_buf_len, "strategy constructor panicked") };
+            std::ptr::null_mut()
+        },
+    }
+}
+
+unsafe extern "C" fn plugin_destroy<S: PluginStrategy>(strategy_ptr: *mut std::ffi::c_void) {
+    if !strategy_ptr.is_null() {
+        let _ = std::panic::catch_unwind(|| unsafe {
+            drop(Box::from_raw(strategy_ptr as *mut PluginInstance<S>));
+        });
+    }
+}
+
+unsafe extern "C" fn plugin_param_schema<S: PluginStrategy>(receiver: *mut std::ffi::c_void, write: SchemaWriterFn) -> i32 {
+    let result = std::panic::catch_unwind(|| {
+        S::param_schema()
+            .map(|schema| serde_json::to_string(&schema))
+            .transpose()
+    });
+
+    match result {
+        Ok(Ok(Some(schema_json))) => {
+            unsafe { write(receiver, AbiStr::borrowed(&schema_json)) };
+            STATUS_OK
+        },
+        Ok(Ok(None)) => STATUS_OK,
+        Ok(Err(_)) => STATUS_ERROR,
+        Err(_) => STATUS_PANIC,
+    }
+}
+
+unsafe extern "C" fn plugin_calculate_signals<S: PluginStrategy>(strategy_ptr: *mut std::ffi::c_void, ctx: *const AbiCallContext) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            instance.strategy.calculate_signals(data_handler, &instance.positions, &instance.holdings, &instance.symbols)
+        })
+    }
+}
+
+unsafe extern "C" fn plugin_on_start<S: PluginStrategy>(strategy_ptr: *mut std::ffi::c_void, ctx: *const AbiCallContext) -> i32 {
+    unsafe { plugin_call::<S>(strategy_ptr, ctx, |instance, _| instance.strategy.on_start()) }
+}
+
+unsafe extern "C" fn plugin_on_bar<S: PluginStrategy>(strategy_ptr: *mut std::ffi::c_void, ctx: *const AbiCallContext) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            instance.strategy.on_bar(data_handler, &instance.positions, &instance.holdings)
+        })
+    }
+}
+
+unsafe extern "C" fn plugin_on_end<S: PluginStrategy>(strategy_ptr: *mut std::ffi::c_void, ctx: *const AbiCallContext) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            instance.strategy.on_end(data_handler, &instance.positions, &instance.holdings)
+        })
+    }
+}
+
+unsafe extern "C" fn plugin_on_fill<S: PluginStrategy>(
+    strategy_ptr: *mut std::ffi::c_void,
+    ctx: *const AbiCallContext,
+    abi_fill: *const AbiFill,
+) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            let abi_fill = abi_fill.as_ref().ok_or_else(|| anyhow::anyhow!("Null fill event"))?;
+            let fill_event = abi_fill.to_fill_event()?;
+            instance.strategy.on_fill(&fill_event, data_handler, &instance.positions, &instance.holdings)
+        })
+    }
+}
+
+unsafe extern "C" fn plugin_on_order_rejected<S: PluginStrategy>(
+    strategy_ptr: *mut std::ffi::c_void,
+    ctx: *const AbiCallContext,
+    abi_rejected: *const AbiOrderRejected,
+) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            let abi_rejected = abi_rejected.as_ref().ok_or_else(|| anyhow::anyhow!("Null order rejected event"))?;
+            let rejected_event = abi_rejected.to_order_rejected_event()?;
+            instance.strategy.on_order_rejected(&rejected_event, data_handler, &instance.positions, &instance.holdings)
+        })
+    }
+}
+
+unsafe extern "C" fn plugin_on_margin_call<S: PluginStrategy>(
+    strategy_ptr: *mut std::ffi::c_void,
+    ctx: *const AbiCallContext,
+    abi_margin_call: *const AbiMarginCall,
+) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            let abi_margin_call = abi_margin_call.as_ref().ok_or_else(|| anyhow::anyhow!("Null margin call event"))?;
+            let margin_call_event = abi_margin_call.to_margin_call_event()?;
+            instance.strategy.on_margin_call(&margin_call_event, data_handler, &instance.positions, &instance.holdings)
+        })
+    }
+}
+
+unsafe extern "C" fn plugin_on_timer<S: PluginStrategy>(
+    strategy_ptr: *mut std::ffi::c_void,
+    ctx: *const AbiCallContext,
+    abi_timer_event: *const AbiTimerEvent,
+) -> i32 {
+    unsafe {
+        plugin_call::<S>(strategy_ptr, ctx, |instance, data_handler| {
+            let abi_timer_event = abi_timer_event.as_ref().ok_or_else(|| anyhow::anyhow!("Null timer event"))?;
+            let timer_event = abi_timer_event.to_timer_event()?;
+            instance.strategy.on_timer(&timer_event, data_handler, &instance.positions, &instance.holdings)
+        })
+    }
+}
@@ -7,6 +7,8 @@
 //! All data handlers must implement this trait to be used by the backtesting engine.
 //! The trait defines methods for accessing OHLCV data, advancing to the next bar, and checking if the backtest should continue.
 
+use crate::indicator_cache;
+
 /// Represents a single bar of market data (OHLCV).
 #[derive(Debug, Clone)]
 pub struct MarketBar {
@@ -16,6 +18,9 @@ pub struct MarketBar {
     pub low: f64,
     pub close: f64,
     pub volume: u64,
+    /// `true` for bars produced by the gap policy (forward-filled or synthetic) rather than by the exchange.
+    /// No PnL is computed on such bars.
+    pub is_filled: bool,
 }
 
 /// Defines the interface for a data handler.
@@ -59,6 +64,25 @@ pub trait DataHandler {
     /// # Returns
     /// * A vector of `f64` values.
     fn get_latest_bars_values(&self, symbol: &str, val_type: &str, n: usize) -> Vec<f64>;
+
+    /// Returns the value of an indicator `shift` bars before the latest bar of the symbol.
+    /// Handlers backed by a shared market data store must override it with a lookup in the store's `IndicatorCache`,
+    /// computed once per optimization. This default is a slow fallback for handlers without a cache:
+    /// it recomputes the value from the published bars on every call, reading the last `period + shift` bars
+    /// of a window indicator but the whole history of a recursive one (EMA, RSI, ATR), which makes a backtest
+    /// calling it on every bar quadratic in the number of bars.
+    /// # Arguments
+    /// * `symbol` - The symbol to retrieve data for.
+    /// * `spec` - The indicator and its parameters.
+    /// * `shift` - Number of bars back from the latest one (0 = latest bar).
+    /// # Returns
+    /// * An optional `f64`, or `None` while the indicator is warming up.
+    fn get_indicator_value(&self, symbol: &str, spec: &indicator_cache::IndicatorSpec, shift: usize) -> Option<f64> {
+        let needed = spec.lookback().map_or(usize::MAX, |lookback| lookback.saturating_add(shift));
+        let bars = self.get_latest_bars(symbol, needed);
+        let end = bars.len().checked_sub(shift)?;
+        spec.last_value(&bars[..end])
+    }
     
     /// Advances the data handler to the next bar.
     /// This method is called by the backtesting engine to simulate time passing.
@@ -1,53 +1,117 @@
 // farukon_core/src/event.rs
 
 //! Event system: defines messages passed between components.
-//! Uses trait-based polymorphism to handle MARKET, SIGNAL, ORDER, FILL uniformly.
+//! `EngineEvent` is a closed enum of MARKET, SIGNAL, ORDER, FILL, ORDER_REJECTED, MARGIN_CALL and TIMER events,
+//! so every consumer matches it exhaustively instead of dispatching on strings.
 //!
 //! Components (DataHandler, Strategy, Portfolio, Execution) communicate via events.
-//! Events are sent through channels and processed in FIFO order.
+//! Events are pushed into a shared, preallocated `EventQueue` and processed in FIFO order.
 //! This decouples components and enables modularity and speed.
 
-pub trait Event: std::fmt::Debug + Send + Sync {
-    /// Returns the type of the event as a static string.
-    fn event_type(&self) -> &'static str;
-    /// Returns a reference to the SignalEvent parameters, if this is a SignalEvent.
-    fn get_signal_event_params(&self) -> Option<&SignalEvent>;
-    /// Returns a reference to the OrderEvent parameters, if this is an OrderEvent.
-    fn get_order_event_params(&self) -> Option<&OrderEvent>;
-        /// Returns a reference to the FillEvent parameters, if this is a FillEvent.
-    fn get_fill_event_params(&self) -> Option<&FillEvent>;
+// --- SIGNAL / ORDER FIELDS ---
+
+/// The intent of a strategy signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SignalName {
+    /// Open a long position.
+    Long,
+    /// Open a short position.
+    Short,
+    /// Close the current position.
+    Exit,
 }
 
-// --- MARKET EVENT ---
+impl SignalName {
+    /// Returns the canonical upper-case name ("LONG", "SHORT", "EXIT").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalName::Long => "LONG",
+            SignalName::Short => "SHORT",
+            SignalName::Exit => "EXIT",
+        }
+    }
 
-/// Represents a new market bar arriving.
-/// Triggered by the DataHandler when new data is available.
-#[derive(Debug)]
-pub struct MarketEvent;
+}
 
-impl MarketEvent {
-    /// Creates a new MarketEvent.
-    pub fn new() -> Self {
-        Self
+impl std::fmt::Display for SignalName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How an order is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum OrderType {
+    /// Market order ("MKT"): executed at the bar extreme with slippage.
+    #[serde(rename = "MKT")]
+    Market,
+    /// Limit order ("LMT"): executed only if the bar reaches the limit price.
+    #[serde(rename = "LMT")]
+    Limit,
+}
+
+impl OrderType {
+    /// Returns the canonical short name ("MKT", "LMT").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderType::Market => "MKT",
+            OrderType::Limit => "LMT",
+        }
+    }
+
+}
+
+impl std::fmt::Display for OrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
-    
 }
 
-impl Event for MarketEvent {
-    fn event_type (&self) -> &'static str {
-        "MARKET"
+/// Side of an order or a fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Direction {
+    Buy,
+    Sell,
+}
+
+impl Direction {
+    /// Returns the canonical upper-case name ("BUY", "SELL").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Buy => "BUY",
+            Direction::Sell => "SELL",
+        }
     }
 
-    fn get_signal_event_params(&self) -> Option<&SignalEvent> {
-        None
+    /// Returns +1.0 for `Buy` and -1.0 for `Sell`.
+    pub fn sign(&self) -> f64 {
+        match self {
+            Direction::Buy => 1.0,
+            Direction::Sell => -1.0,
+        }
     }
 
-    fn get_order_event_params(&self) -> Option<&OrderEvent> {
-        None
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
+}
+
+// --- MARKET EVENT ---
 
-    fn get_fill_event_params(&self) -> Option<&FillEvent> {
-        None
+/// Represents a new market bar arriving.
+/// Triggered by the DataHandler when new data is available.
+#[derive(Debug, Clone)]
+pub struct MarketEvent;
+
+impl MarketEvent {
+    /// Creates a new MarketEvent.
+    pub fn new() -> Self {
+        Self
     }
 
 }
@@ -55,17 +119,17 @@ impl Event for MarketEvent {
 // --- SIGNAL EVENT ---
 
 /// Represents a signal generated by a strategy.
-/// Signals indicate an intent to trade (e.g., "LONG", "SHORT", "EXIT").
-#[derive(Debug)]
+/// Signals indicate an intent to trade (LONG, SHORT, EXIT).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SignalEvent {
     /// The timestamp of the order.
     pub timeindex: chrono::DateTime<chrono::Utc>,
-    /// The name of the signal that triggered the order.
+    /// The symbol to trade.
     pub symbol: String,
     /// The name of the signal that triggered the order.
-    pub signal_name: String,
-    /// The type of order to place ("MKT" for market, "LMT" for limit).
-    pub order_type: String,
+    pub signal_name: SignalName,
+    /// The type of order to place.
+    pub order_type: OrderType,
     /// The quantity to trade.
     pub quantity: Option<f64>,
     /// The limit price (optional, only for limit orders).
@@ -73,11 +137,19 @@ pub struct SignalEvent {
 }
 
 impl SignalEvent {
+    /// Creates a new SignalEvent.
+    /// # Arguments
+    /// * `timeindex` - The timestamp of the signal.
+    /// * `symbol` - The symbol to trade.
+    /// * `signal_name` - The name of the signal.
+    /// * `order_type` - The type of order to place.
+    /// * `quantity` - The quantity to trade.
+    /// * `limit_price` - The limit price.
     pub fn new(
         timeindex: chrono::DateTime<chrono::Utc>,
         symbol: String,
-        signal_name: String,
-        order_type: String,
+        signal_name: SignalName,
+        order_type: OrderType,
         quantity: Option<f64>,
         limit_price: Option<f64>,
     ) -> Self {
@@ -93,63 +165,45 @@ impl SignalEvent {
 
 }
 
-impl Event for SignalEvent {
-    /// Creates a new SignalEvent.
-    /// # Arguments
-    /// * `timeindex` - The timestamp of the signal.
-    /// * `symbol` - The symbol to trade.
-    /// * `signal_name` - The name of the signal.
-    /// * `order_type` - The type of order to place.
-    /// * `quantity` - The quantity to trade.
-    /// * `limit_price` - The limit price.
-    fn event_type (&self) -> &'static str {
-        "SIGNAL"
-    }
-
-    fn get_signal_event_params(&self) -> Option<&SignalEvent> {
-        Some(self)
-    }
-
-    fn get_order_event_params(&self) -> Option<&OrderEvent> {
-        None
-    }
-
-    fn get_fill_event_params(&self) -> Option<&FillEvent> {
-        None
-    }
-
-}
-
 // --- ORDER EVENT ---
 
 /// Represents an order to be executed.
 /// Created by the Portfolio based on a SignalEvent.
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OrderEvent {
     /// The timestamp of the order.
     pub timeindex: chrono::DateTime<chrono::Utc>,
-    /// The name of the signal that triggered the order.
+    /// The symbol to trade.
     pub symbol: String,
     /// The name of the signal that triggered the order.
-    pub signal_name: String,
-    /// The type of order ("MKT" for market, "LMT" for limit).
-    pub order_type: String,
+    pub signal_name: SignalName,
+    /// The type of order.
+    pub order_type: OrderType,
     /// The quantity to trade.
     pub quantity: f64,
-    /// The direction of the order (optional, "BUY" or "SELL").       
-    pub direction: Option<String>,
+    /// The direction of the order (None if the portfolio could not determine it).
+    pub direction: Option<Direction>,
     /// The limit price (optional, only for limit orders).
     pub limit_price: Option<f64>,
 }
 
 impl OrderEvent {
+    /// Creates a new OrderEvent.
+    /// # Arguments
+    /// * `timeindex` - The timestamp of the order.
+    /// * `symbol` - The symbol to trade.
+    /// * `order_type` - The type of order.
+    /// * `quantity` - The quantity to trade.
+    /// * `direction` - The direction of the order.
+    /// * `signal_name` - The name of the signal.
+    /// * `limit_price` - The limit price.
     pub fn new(
         timeindex: chrono::DateTime<chrono::Utc>,
         symbol: String,
-        order_type: String,
+        order_type: OrderType,
         quantity: f64,
-        direction: Option<String>,
-        signal_name: String,
+        direction: Option<Direction>,
+        signal_name: SignalName,
         limit_price: Option<f64>,
     ) -> Self {
         Self {
@@ -165,39 +219,11 @@ impl OrderEvent {
 
 }
 
-impl Event for OrderEvent {
-    /// Creates a new OrderEvent.
-    /// # Arguments
-    /// * `timeindex` - The timestamp of the order.
-    /// * `symbol` - The symbol to trade.
-    /// * `order_type` - The type of order.
-    /// * `quantity` - The quantity to trade.
-    /// * `direction` - The direction of the order.
-    /// * `signal_name` - The name of the signal.
-    /// * `limit_price` - The limit price.
-    fn event_type (&self) -> &'static str {
-        "ORDER"
-    }
-
-    fn get_signal_event_params(&self) -> Option<&SignalEvent> {
-        None
-    }
-
-    fn get_order_event_params(&self) -> Option<&OrderEvent> {
-        Some(self)
-    }
-
-    fn get_fill_event_params(&self) -> Option<&FillEvent> {
-        None
-    }
-
-}
-
 // --- FILL EVENT ---
 
 /// Represents a filled order.
 /// Created by the ExecutionHandler after successfully executing an OrderEvent.
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FillEvent {
     /// The timestamp of the fill.
     pub timeindex: chrono::DateTime<chrono::offset::Utc>,
@@ -207,14 +233,14 @@ pub struct FillEvent {
     pub exchange: String,
     /// The quantity that was traded.
     pub quantity: f64,
-    /// The direction of the trade (optional, "BUY" or "SELL").
-    pub direction: Option<String>,
+    /// The direction of the trade.
+    pub direction: Option<Direction>,
      /// The execution price of the trade.
     pub execution_price: Option<f64>,
     /// The commission charged for the trade.
     pub commission: Option<f64>,
     /// The name of the signal that triggered the order.
-    pub signal_name: String,
+    pub signal_name: SignalName,
 }
 
 impl FillEvent {
@@ -233,10 +259,10 @@ impl FillEvent {
         symbol: String,
         exchange: String,
         quantity: f64,
-        direction: Option<String>,
+        direction: Option<Direction>,
         execution_price: Option<f64>,
         commission: Option<f64>,
-        signal_name: String,
+        signal_name: SignalName,
     ) -> Self {
         Self {
             timeindex,
@@ -249,24 +275,281 @@ impl FillEvent {
             signal_name,
         }
     }
-    
+
+}
+
+// --- ORDER REJECTED EVENT ---
+
+/// Why the portfolio refused to turn a signal into an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RejectionReason {
+    /// `margin_call_control_for_signal`: capital does not cover the initial margin of the order.
+    InsufficientMargin,
+}
+
+/// A signal the portfolio did not convert into an order.
+/// Created by the Portfolio, delivered to `Strategy::on_order_rejected`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct OrderRejectedEvent {
+    /// The timestamp of the rejection.
+    pub timeindex: chrono::DateTime<chrono::Utc>,
+    /// The signal that was rejected.
+    pub signal: SignalEvent,
+    /// The order quantity after position sizing.
+    pub quantity: f64,
+    /// Why the order was rejected.
+    pub reason: RejectionReason,
+}
+
+impl OrderRejectedEvent {
+    /// Creates a new OrderRejectedEvent.
+    /// # Arguments
+    /// * `timeindex` - The timestamp of the rejection.
+    /// * `signal` - The rejected signal.
+    /// * `quantity` - The order quantity after position sizing.
+    /// * `reason` - Why the order was rejected.
+    pub fn new(
+        timeindex: chrono::DateTime<chrono::Utc>,
+        signal: SignalEvent,
+        quantity: f64,
+        reason: RejectionReason,
+    ) -> Self {
+        Self {
+            timeindex,
+            signal,
+            quantity,
+            reason,
+        }
+    }
+
+}
+
+// --- MARGIN CALL EVENT ---
+
+/// Strategy capital fell below the minimal margin; the portfolio is closing every position.
+/// Created by the Portfolio, delivered to `Strategy::on_margin_call` before the forced EXIT orders are processed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MarginCallEvent {
+    /// The timestamp of the margin call.
+    pub timeindex: chrono::DateTime<chrono::Utc>,
+    /// Strategy capital at the margin call.
+    pub capital: f64,
+    /// Minimal margin required for the open positions (`min_margin` × entry capital).
+    pub min_margin: f64,
 }
 
-impl Event for FillEvent {  
-    fn event_type (&self) -> &'static str {
-        "FILL"
+impl MarginCallEvent {
+    /// Creates a new MarginCallEvent.
+    /// # Arguments
+    /// * `timeindex` - The timestamp of the margin call.
+    /// * `capital` - Strategy capital.
+    /// * `min_margin` - Required minimal margin.
+    pub fn new(
+        timeindex: chrono::DateTime<chrono::Utc>,
+        capital: f64,
+        min_margin: f64,
+    ) -> Self {
+        Self {
+            timeindex,
+            capital,
+            min_margin,
+        }
     }
 
-    fn get_signal_event_params(&self) -> Option<&SignalEvent> {
-        None
+}
+
+// --- TIMER EVENTS ---
+
+/// When a strategy timer fires. Times are exchange-local, the same clock as bar timestamps.
+/// A timer fires on the first bar whose datetime reaches its due time, after `calculate_signals` of that bar.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TimerSchedule {
+    /// Once, at the given moment.
+    At(chrono::DateTime<chrono::Utc>),
+    /// Every trading day at the given time (e.g. 18:40 to flatten before the close).
+    /// A day without a bar at or after the time (weekend, holiday, short session) is skipped.
+    DailyAt(chrono::NaiveTime),
+    /// Every `minutes`, aligned to the clock (every 15 minutes → :00, :15, :30, :45).
+    /// Scheduled before the first bar, it first fires at the boundary after the first bar.
+    Every { minutes: i64 },
+    /// Once, `bars` bars after the bar on which the strategy scheduled it.
+    AfterBars(usize),
+    /// On the first bar of every session of `symbol`'s exchange (needs a session calendar).
+    SessionOpen { symbol: String },
+    /// Once per session, on the first bar with at most `minutes_before` minutes left to the close of `symbol`'s session.
+    SessionClose { symbol: String, minutes_before: i64 },
+}
+
+/// Request from a strategy to the engine's timer scheduler.
+/// Sent through the event queue; the engine registers it when the queue is next drained.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TimerCommand {
+    /// Registers a timer. A timer with the same tag is replaced.
+    Schedule { tag: String, schedule: TimerSchedule },
+    /// Removes the timer with this tag.
+    Cancel { tag: String },
+}
+
+/// A strategy timer that came due. Delivered to `Strategy::on_timer`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TimerEvent {
+    /// Datetime of the bar on which the timer fired.
+    pub timeindex: chrono::DateTime<chrono::Utc>,
+    /// The moment the timer was due (equal to `timeindex` for bar- and session-based timers).
+    pub scheduled_for: chrono::DateTime<chrono::Utc>,
+    /// Tag given when the timer was scheduled.
+    pub tag: String,
+}
+
+impl TimerEvent {
+    /// Creates a new TimerEvent.
+    /// # Arguments
+    /// * `timeindex` - Datetime of the bar on which the timer fired.
+    /// * `scheduled_for` - The moment the timer was due.
+    /// * `tag` - Tag of the timer.
+    pub fn new(
+        timeindex: chrono::DateTime<chrono::Utc>,
+        scheduled_for: chrono::DateTime<chrono::Utc>,
+        tag: String,
+    ) -> Self {
+        Self {
+            timeindex,
+            scheduled_for,
+            tag,
+        }
     }
 
-    fn get_order_event_params(&self) -> Option<&OrderEvent> {
-        None
+}
+
+// --- ENGINE EVENT ---
+
+/// Every message exchanged by the engine components.
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    Market(MarketEvent),
+    Signal(SignalEvent),
+    Order(OrderEvent),
+    Fill(FillEvent),
+    OrderRejected(OrderRejectedEvent),
+    MarginCall(MarginCallEvent),
+    Timer(TimerCommand),
+}
+
+impl EngineEvent {
+    /// Returns the type of the event as a static string (for logs).
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            EngineEvent::Market(_) => "MARKET",
+            EngineEvent::Signal(_) => "SIGNAL",
+            EngineEvent::Order(_) => "ORDER",
+            EngineEvent::Fill(_) => "FILL",
+            EngineEvent::OrderRejected(_) => "ORDER_REJECTED",
+            EngineEvent::MarginCall(_) => "MARGIN_CALL",
+            EngineEvent::Timer(_) => "TIMER",
+        }
+    }
+
+}
+
+impl From<MarketEvent> for EngineEvent {
+    fn from(event: MarketEvent) -> Self {
+        EngineEvent::Market(event)
+    }
+}
+
+impl From<SignalEvent> for EngineEvent {
+    fn from(event: SignalEvent) -> Self {
+        EngineEvent::Signal(event)
+    }
+}
+
+impl From<OrderEvent> for EngineEvent {
+    fn from(event: OrderEvent) -> Self {
+        EngineEvent::Order(event)
+    }
+}
+
+impl From<FillEvent> for EngineEvent {
+    fn from(event: FillEvent) -> Self {
+        EngineEvent::Fill(event)
+    }
+}
+
+impl From<OrderRejectedEvent> for EngineEvent {
+    fn from(event: OrderRejectedEvent) -> Self {
+        EngineEvent::OrderRejected(event)
     }
+}
 
-    fn get_fill_event_params(&self) -> Option<&FillEvent> {
-        Some(self)
+impl From<MarginCallEvent> for EngineEvent {
+    fn from(event: MarginCallEvent) -> Self {
+        EngineEvent::MarginCall(event)
     }
+}
 
+impl From<TimerCommand> for EngineEvent {
+    fn from(command: TimerCommand) -> Self {
+        EngineEvent::Timer(command)
+    }
+}
+
+// --- EVENT QUEUE ---
+
+/// FIFO queue shared by all components of one backtest.
+/// Cloning is cheap (one `Arc`); every clone pushes into and pops from the same buffer.
+/// The buffer is allocated once, so sending an event does not allocate.
+#[derive(Debug, Clone)]
+pub struct EventQueue {
+    events: std::sync::Arc<std::sync::Mutex<std::collections::VecDeque<EngineEvent>>>,
+}
+
+impl EventQueue {
+    /// Initial capacity used by `EventQueue::new`. A bar rarely produces more than a few events per symbol.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Creates a queue with `DEFAULT_CAPACITY` preallocated slots.
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Creates a queue with `capacity` preallocated slots.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: std::sync::Arc::new(std::sync::Mutex::new(std::collections::VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Pushes an event to the back of the queue.
+    /// # Returns
+    /// * `anyhow::Result<()>` - Error only if the queue lock is poisoned.
+    pub fn send(&self, event: impl Into<EngineEvent>) -> anyhow::Result<()> {
+        self.events
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Event queue lock is poisoned"))?
+            .push_back(event.into());
+        anyhow::Ok(())
+    }
+
+    /// Pops the oldest event, or `None` if the queue is empty.
+    pub fn try_recv(&self) -> Option<EngineEvent> {
+        self.events.lock().ok()?.pop_front()
+    }
+
+    /// Number of pending events.
+    pub fn len(&self) -> usize {
+        self.events.lock().map(|events| events.len()).unwrap_or(0)
+    }
+
+    /// Returns true if no events are pending.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
 }
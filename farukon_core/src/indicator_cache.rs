@@ -0,0 +1,427 @@
+// farukon_core/src/indicator_cache.rs
+
+//! Indicator series shared by all backtests of an optimization.
+//!
+//! Every backtest of a grid or a GA population runs on the same bars, so an indicator with the same
+//! parameters has the same values in all of them. `IndicatorCache` computes each series once, on first use,
+//! over the whole history of the symbol, and keeps it for the rest of the optimization. Strategies then look
+//! the value of the current bar up by index (`DataHandler::get_indicator_value`) instead of recomputing it.
+//! Series are keyed by (symbol, timeframe, indicator, parameters); one cache holds the bars of one timeframe.
+//!
+//! Every indicator here is causal: its value at bar `i` depends only on bars `0..=i`,
+//! so computing the series over the full history does not let future bars into a backtest.
+//!
+//! Window indicators (SMA, WMA, standard deviation, highest, lowest) are computed four bars at a time
+//! with `wide::f64x4`; the SMA sums each window in the same order as `indicators::sma`, so both give identical values.
+//! Recursive indicators (EMA, RSI, ATR) are computed bar by bar with the `streaming` types.
+
+use crate::data_handler;
+use crate::indicators::streaming;
+use crate::indicators::streaming::StreamingIndicator;
+
+/// An indicator and its parameters. Single-series indicators read the `source` field of each bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicatorSpec {
+    /// Simple Moving Average.
+    Sma { period: usize, source: streaming::PriceSource },
+    /// Exponential Moving Average, seeded with the SMA of the first `period` values.
+    Ema { period: usize, source: streaming::PriceSource },
+    /// Linearly Weighted Moving Average.
+    Wma { period: usize, source: streaming::PriceSource },
+    /// Relative Strength Index with Wilder's smoothing.
+    Rsi { period: usize, source: streaming::PriceSource },
+    /// Population standard deviation.
+    Std { period: usize, source: streaming::PriceSource },
+    /// Highest value over `period` bars (including the current one).
+    Highest { period: usize, source: streaming::PriceSource },
+    /// Lowest value over `period` bars (including the current one).
+    Lowest { period: usize, source: streaming::PriceSource },
+    /// Average True Range with Wilder's smoothing (reads high, low and close).
+    Atr { period: usize },
+}
+
+impl IndicatorSpec {
+    /// SMA of the close over `period` bars.
+    pub fn sma(period: usize) -> Self {
+        IndicatorSpec::Sma { period, source: streaming::PriceSource::Close }
+    }
+
+    /// EMA of the close over `period` bars.
+    pub fn ema(period: usize) -> Self {
+        IndicatorSpec::Ema { period, source: streaming::PriceSource::Close }
+    }
+
+    /// WMA of the close over `period` bars.
+    pub fn wma(period: usize) -> Self {
+        IndicatorSpec::Wma { period, source: streaming::PriceSource::Close }
+    }
+
+    /// RSI of the close over `period` bars.
+    pub fn rsi(period: usize) -> Self {
+        IndicatorSpec::Rsi { period, source: streaming::PriceSource::Close }
+    }
+
+    /// Standard deviation of the close over `period` bars.
+    pub fn std(period: usize) -> Self {
+        IndicatorSpec::Std { period, source: streaming::PriceSource::Close }
+    }
+
+    /// Highest high over `period` bars.
+    pub fn highest(period: usize) -> Self {
+        IndicatorSpec::Highest { period, source: streaming::PriceSource::High }
+    }
+
+    /// Lowest low over `period` bars.
+    pub fn lowest(period: usize) -> Self {
+        IndicatorSpec::Lowest { period, source: streaming::PriceSource::Low }
+    }
+
+    /// ATR over `period` bars.
+    pub fn atr(period: usize) -> Self {
+        IndicatorSpec::Atr { period }
+    }
+
+    /// Sets the price field of a single-series indicator; ATR keeps reading high, low and close.
+    pub fn with_source(self, new_source: streaming::PriceSource) -> Self {
+        match self {
+            IndicatorSpec::Sma { period, .. } => IndicatorSpec::Sma { period, source: new_source },
+            IndicatorSpec::Ema { period, .. } => IndicatorSpec::Ema { period, source: new_source },
+            IndicatorSpec::Wma { period, .. } => IndicatorSpec::Wma { period, source: new_source },
+            IndicatorSpec::Rsi { period, .. } => IndicatorSpec::Rsi { period, source: new_source },
+            IndicatorSpec::Std { period, .. } => IndicatorSpec::Std { period, source: new_source },
+            IndicatorSpec::Highest { period, .. } => IndicatorSpec::Highest { period, source: new_source },
+            IndicatorSpec::Lowest { period, .. } => IndicatorSpec::Lowest { period, source: new_source },
+            IndicatorSpec::Atr { period } => IndicatorSpec::Atr { period },
+        }
+    }
+
+    /// Builds a spec from the indicator name, with its default price source (see the constructors above).
+    /// # Arguments
+    /// * `name` - "sma", "ema", "wma", "rsi", "std", "highest", "lowest" or "atr".
+    /// * `period` - The lookback period.
+    /// # Returns
+    /// * `Option<Self>` - `None` for an unknown indicator name.
+    pub fn from_name(name: &str, period: usize) -> Option<Self> {
+        match name {
+            "sma" => Some(IndicatorSpec::sma(period)),
+            "ema" => Some(IndicatorSpec::ema(period)),
+            "wma" => Some(IndicatorSpec::wma(period)),
+            "rsi" => Some(IndicatorSpec::rsi(period)),
+            "std" => Some(IndicatorSpec::std(period)),
+            "highest" => Some(IndicatorSpec::highest(period)),
+            "lowest" => Some(IndicatorSpec::lowest(period)),
+            "atr" => Some(IndicatorSpec::atr(period)),
+            _ => None,
+        }
+    }
+
+    /// Builds a spec from its parts, as passed by plugins: `from_name` with the price field `source` (ignored by ATR).
+    pub fn from_parts(name: &str, period: usize, source: streaming::PriceSource) -> Option<Self> {
+        Self::from_name(name, period).map(|spec| spec.with_source(source))
+    }
+
+    /// Name of the indicator (see `from_name`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndicatorSpec::Sma { .. } => "sma",
+            IndicatorSpec::Ema { .. } => "ema",
+            IndicatorSpec::Wma { .. } => "wma",
+            IndicatorSpec::Rsi { .. } => "rsi",
+            IndicatorSpec::Std { .. } => "std",
+            IndicatorSpec::Highest { .. } => "highest",
+            IndicatorSpec::Lowest { .. } => "lowest",
+            IndicatorSpec::Atr { .. } => "atr",
+        }
+    }
+
+    /// The lookback period.
+    pub fn period(&self) -> usize {
+        match *self {
+            IndicatorSpec::Sma { period, .. }
+            | IndicatorSpec::Ema { period, .. }
+            | IndicatorSpec::Wma { period, .. }
+            | IndicatorSpec::Rsi { period, .. }
+            | IndicatorSpec::Std { period, .. }
+            | IndicatorSpec::Highest { period, .. }
+            | IndicatorSpec::Lowest { period, .. }
+            | IndicatorSpec::Atr { period } => period,
+        }
+    }
+
+    /// Number of latest bars the value at a bar depends on: the period for window indicators,
+    /// `None` for recursive ones (EMA, RSI, ATR), which depend on the whole history.
+    pub fn lookback(&self) -> Option<usize> {
+        match self {
+            IndicatorSpec::Sma { .. }
+            | IndicatorSpec::Wma { .. }
+            | IndicatorSpec::Std { .. }
+            | IndicatorSpec::Highest { .. }
+            | IndicatorSpec::Lowest { .. } => Some(self.period()),
+            IndicatorSpec::Ema { .. } | IndicatorSpec::Rsi { .. } | IndicatorSpec::Atr { .. } => None,
+        }
+    }
+
+    /// The price field read from each bar (close for ATR, which reads high, low and close).
+    pub fn source(&self) -> streaming::PriceSource {
+        match *self {
+            IndicatorSpec::Sma { source, .. }
+            | IndicatorSpec::Ema { source, .. }
+            | IndicatorSpec::Wma { source, .. }
+            | IndicatorSpec::Rsi { source, .. }
+            | IndicatorSpec::Std { source, .. }
+            | IndicatorSpec::Highest { source, .. }
+            | IndicatorSpec::Lowest { source, .. } => source,
+            IndicatorSpec::Atr { .. } => streaming::PriceSource::Close,
+        }
+    }
+
+    /// Computes the indicator for every bar.
+    /// # Arguments
+    /// * `bars` - The bars, oldest first.
+    /// # Returns
+    /// * `Vec<f64>` - One value per bar; `NaN` while the indicator is warming up.
+    pub fn compute<B: std::borrow::Borrow<data_handler::MarketBar>>(&self, bars: &[B]) -> Vec<f64> {
+        let source = self.source();
+        let values: Vec<f64> = bars.iter().map(|bar| source.value(bar.borrow())).collect();
+
+        match *self {
+            IndicatorSpec::Sma { period, .. } => rolling_sma(&values, period),
+            IndicatorSpec::Wma { period, .. } => rolling_wma(&values, period),
+            IndicatorSpec::Std { period, .. } => rolling_std(&values, period),
+            IndicatorSpec::Highest { period, .. } => rolling_extreme(&values, period, true),
+            IndicatorSpec::Lowest { period, .. } => rolling_extreme(&values, period, false),
+            IndicatorSpec::Ema { period, .. } => {
+                let mut ema = streaming::Ema::new(period);
+                values.iter().map(|&value| ema.update_value(value).unwrap_or(f64::NAN)).collect()
+            },
+            IndicatorSpec::Rsi { period, .. } => {
+                let mut rsi = streaming::Rsi::new(period);
+                values.iter().map(|&value| rsi.update_value(value).unwrap_or(f64::NAN)).collect()
+            },
+            IndicatorSpec::Atr { period } => {
+                let mut atr = streaming::Atr::new(period);
+                bars.iter().map(|bar| atr.update(bar.borrow()).unwrap_or(f64::NAN)).collect()
+            },
+        }
+    }
+
+    /// Computes the value at the last bar only, reading no more bars than the indicator needs.
+    /// # Returns
+    /// * `Option<f64>` - The value, or `None` while the indicator is warming up.
+    pub fn last_value<B: std::borrow::Borrow<data_handler::MarketBar>>(&self, bars: &[B]) -> Option<f64> {
+        let bars = match self.lookback() {
+            Some(lookback) => &bars[bars.len().saturating_sub(lookback)..],
+            None => bars,
+        };
+        self.compute(bars).last().copied().filter(|value| !value.is_nan())
+    }
+
+}
+
+// --- SIMD window computations ---
+
+/// Computes one value per full window of `period` values: `simd` four consecutive windows at a time
+/// (the windows starting at `start..start + 4`), `scalar` for the remaining windows.
+fn rolling<F, S>(values: &[f64], period: usize, simd: F, scalar: S) -> Vec<f64>
+where
+    F: Fn(usize) -> wide::f64x4,
+    S: Fn(&[f64]) -> f64,
+{
+    let mut series = vec![f64::NAN; values.len()];
+    if period == 0 || values.len() < period {
+        return series;
+    }
+
+    // The window starting at `start` ends at bar `start + period - 1`.
+    let windows = values.len() - period + 1;
+    let mut start = 0;
+    while start + 4 <= windows {
+        let lanes: [f64; 4] = simd(start).into();
+        series[start + period - 1..start + period + 3].copy_from_slice(&lanes);
+        start += 4;
+    }
+
+    // Handle remainder
+    for start in start..windows {
+        series[start + period - 1] = scalar(&values[start..start + period]);
+    }
+    series
+}
+
+/// The `offset`-th value of the four windows starting at `start..start + 4`.
+#[inline]
+fn lanes(values: &[f64], start: usize, offset: usize) -> wide::f64x4 {
+    let idx = start + offset;
+    wide::f64x4::from([values[idx], values[idx + 1], values[idx + 2], values[idx + 3]])
+}
+
+fn rolling_sma(values: &[f64], period: usize) -> Vec<f64> {
+    let divisor = period as f64;
+    rolling(
+        values,
+        period,
+        |start| {
+            // Each lane adds its window left to right, like `indicators::sma`.
+            let mut sum = wide::f64x4::splat(0.0);
+            for offset in 0..period {
+                sum += lanes(values, start, offset);
+            }
+            sum / wide::f64x4::splat(divisor)
+        },
+        |window| window.iter().sum::<f64>() / divisor,
+    )
+}
+
+fn rolling_wma(values: &[f64], period: usize) -> Vec<f64> {
+    let weights = (period * (period + 1)) as f64 / 2.0;
+    rolling(
+        values,
+        period,
+        |start| {
+            let mut weighted_sum = wide::f64x4::splat(0.0);
+            for offset in 0..period {
+                weighted_sum += lanes(values, start, offset) * wide::f64x4::splat((offset + 1) as f64);
+            }
+            weighted_sum / wide::f64x4::splat(weights)
+        },
+        |window| {
+            window.iter()
+                .enumerate()
+                .map(|(idx, value)| (idx + 1) as f64 * value)
+                .sum::<f64>() / weights
+        },
+    )
+}
+
+fn rolling_std(values: &[f64], period: usize) -> Vec<f64> {
+    let divisor = period as f64;
+    rolling(
+        values,
+        period,
+        |start| {
+            // Two passes: the mean, then the squared deviations from it.
+            let mut sum = wide::f64x4::splat(0.0);
+            for offset in 0..period {
+                sum += lanes(values, start, offset);
+            }
+            let mean = sum / wide::f64x4::splat(divisor);
+            let mut squares = wide::f64x4::splat(0.0);
+            for offset in 0..period {
+                let deviation = lanes(values, start, offset) - mean;
+                squares += deviation * deviation;
+            }
+            (squares / wide::f64x4::splat(divisor)).sqrt()
+        },
+        |window| {
+            let mean = window.iter().sum::<f64>() / divisor;
+            let squares: f64 = window.iter().map(|value| (value - mean) * (value - mean)).sum();
+            (squares / divisor).sqrt()
+        },
+    )
+}
+
+fn rolling_extreme(values: &[f64], period: usize, highest: bool) -> Vec<f64> {
+    rolling(
+        values,
+        period,
+        |start| {
+            let mut extreme = lanes(values, start, 0);
+            for offset in 1..period {
+                let value = lanes(values, start, offset);
+                extreme = if highest { extreme.max(value) } else { extreme.min(value) };
+            }
+            extreme
+        },
+        |window| {
+            if highest {
+                window.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+            } else {
+                window.iter().copied().fold(f64::INFINITY, f64::min)
+            }
+        },
+    )
+}
+
+// --- Cache ---
+
+/// A computed series: one value per bar of the symbol, `NaN` during warm-up.
+pub type Series = std::sync::Arc<[f64]>;
+
+/// Series of one symbol, by indicator. A slot is inserted before its series is computed,
+/// so that concurrent backtests asking for the same series wait for one computation.
+type SymbolSeries = std::collections::HashMap<IndicatorSpec, std::sync::Arc<std::sync::OnceLock<Series>>>;
+
+/// Memoized indicator series of the symbols of one timeframe, shared by every backtest of an optimization.
+#[derive(Debug, Default)]
+pub struct IndicatorCache {
+    timeframe: String,
+    series: std::sync::RwLock<std::collections::HashMap<String, SymbolSeries>>,
+}
+
+impl IndicatorCache {
+    /// Creates an empty cache for the bars of `timeframe`.
+    pub fn new(timeframe: &str) -> Self {
+        Self {
+            timeframe: timeframe.to_string(),
+            series: std::sync::RwLock::new(std::collections::HashMap::new()),
+        }
+    }
+
+    /// The timeframe of the bars the series are computed on.
+    pub fn timeframe(&self) -> &str {
+        &self.timeframe
+    }
+
+    /// Returns the series of an indicator, computing it on first use.
+    /// # Arguments
+    /// * `symbol` - The symbol.
+    /// * `spec` - The indicator and its parameters.
+    /// * `bars` - All bars of `symbol` in the cache's timeframe; must be the same on every call for this symbol.
+    /// # Returns
+    /// * `Series` - One value per bar, `NaN` during warm-up.
+    pub fn series(&self, symbol: &str, spec: &IndicatorSpec, bars: &[data_handler::MarketBar]) -> Series {
+        let cached = self.series.read().unwrap()
+            .get(symbol)
+            .and_then(|symbol_series| symbol_series.get(spec))
+            .cloned();
+        let slot = match cached {
+            Some(slot) => slot,
+            None => self.series.write().unwrap()
+                .entry(symbol.to_string())
+                .or_default()
+                .entry(*spec)
+                .or_default()
+                .clone(),
+        };
+        // Computed outside the map lock: lookups of other series are not blocked meanwhile.
+        slot.get_or_init(|| spec.compute(bars).into()).clone()
+    }
+
+    /// Returns the value of an indicator at bar `index`.
+    /// # Arguments
+    /// * `symbol` - The symbol.
+    /// * `spec` - The indicator and its parameters.
+    /// * `bars` - All bars of `symbol` (see `series`).
+    /// * `index` - Index of the bar in `bars`.
+    /// # Returns
+    /// * `Option<f64>` - The value, or `None` during warm-up or past the last bar.
+    pub fn value_at(&self, symbol: &str, spec: &IndicatorSpec, bars: &[data_handler::MarketBar], index: usize) -> Option<f64> {
+        let value = *self.series(symbol, spec, bars).get(index)?;
+        if value.is_nan() { None } else { Some(value) }
+    }
+
+    /// Number of series computed so far.
+    pub fn len(&self) -> usize {
+        self.series.read().unwrap()
+            .values()
+            .flat_map(|symbol_series| symbol_series.values())
+            .filter(|slot| slot.get().is_some())
+            .count()
+    }
+
+    /// `true` if no series has been computed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+}
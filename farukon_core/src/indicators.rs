@@ -11,9 +11,24 @@
 //! - Simple Moving Average (`sma`)
 //! - Highest High over N periods (`highest`)
 //! - Lowest Low over N periods (`lowest`)
+//! - Exponential and Weighted Moving Average (`ema`, `wma`)
+//! - Relative Strength Index (`rsi`) and Average True Range (`atr`)
+//! - Bollinger Bands, Donchian and Keltner channels (`bollinger`, `donchian`, `keltner`)
+//! - MACD (`macd`), Stochastic oscillator (`stochastic`), ADX/DMI (`adx`)
+//! - Parabolic SAR (`parabolic_sar`) and VWAP (`vwap`)
 //!
 //! All functions are pure, stateless, and return `None` when insufficient historical
-//! data is available for the requested lookback window.
+//! data is available for the requested lookback window. They return the value at the
+//! last data point. Indicators with recursive smoothing (EMA, RSI, ATR, MACD, ADX, SAR, VWAP)
+//! depend on the whole history passed in, not only on the last `n` values: pass a long enough
+//! history (several times the period) for a value that matches a streaming indicator run from the start.
+//!
+//! Stateful versions updated once per bar, at O(1) cost instead of a full window scan,
+//! live in `streaming`.
+
+pub mod streaming;
+
+use crate::data_handler;
 
 /// Computes the **Simple Moving Average (SMA)** over the last `n` data points.
 ///
@@ -42,18 +57,16 @@ where
     // Returns average of last n bars.
     if n == 0 { return None; }
 
-    // Convert to Vec
-    let data: Vec<f64> = dh.into_iter().copied().collect();
-    let total_count = data.len();
+    // Count without collecting: the iterator is cloneable.
+    let data = dh.into_iter();
+    let total_count = data.clone().count();
 
     // If insufficient data is available, return None.
     if total_count < n { return None; }
 
-    // Calculate the sum of the last `n` bars, starting from `shift` bars ago.
-    let start = total_count - n;
-    let end = start + n;
-    let sum: f64 = data[start..end]
-        .iter()
+    // Calculate the sum of the last `n` bars.
+    let sum: f64 = data
+        .skip(total_count - n)
         .sum();
     // Return the average.
     Some(sum / (n as f64))
@@ -172,3 +185,279 @@ where
         .flat_map(|&x| x)
         .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
 }
+
+// --- Multi-value outputs ---
+
+/// Upper, middle and lower line of a band or channel indicator (Bollinger, Donchian, Keltner).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// MACD line, its signal line and the histogram (MACD - signal).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdOutput {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// Stochastic %K and its average %D, in 0..=100.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticOutput {
+    pub k: f64,
+    pub d: f64,
+}
+
+/// Average Directional Index with the directional indicators, in 0..=100.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxOutput {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+// --- Single-series indicators ---
+
+/// Computes the **Exponential Moving Average (EMA)** with smoothing factor `2 / (n + 1)`,
+/// seeded with the SMA of the first `n` values.
+///
+/// # Arguments
+/// * `dh` — An iterator over historical data points, oldest first.
+/// * `n` — The period. Must be ≥ 1.
+///
+/// # Returns
+/// * `Some(f64)` — The EMA at the last data point.
+/// * `None` — If `n == 0` or fewer than `n` data points are available.
+pub fn ema<'a, I>(dh: I, n: usize) -> Option<f64>
+where
+    I: IntoIterator<Item = &'a f64>,
+{
+    let mut indicator = streaming::Ema::new(n);
+    dh.into_iter().fold(None, |_, value| indicator.update_value(*value))
+}
+
+/// Computes the **Weighted Moving Average (WMA)** of the last `n` values, with linear weights `1..=n`
+/// (the newest value weighs `n`).
+///
+/// # Arguments
+/// * `dh` — An iterator over historical data points, oldest first.
+/// * `n` — The lookback period. Must be ≥ 1.
+///
+/// # Returns
+/// * `Some(f64)` — The WMA.
+/// * `None` — If `n == 0` or fewer than `n` data points are available.
+pub fn wma<'a, I>(dh: I, n: usize) -> Option<f64>
+where
+    I: IntoIterator<Item = &'a f64>,
+    I::IntoIter: Clone,
+{
+    if n == 0 { return None; }
+
+    let data = dh.into_iter();
+    let total_count = data.clone().count();
+    if total_count < n { return None; }
+
+    let weighted_sum: f64 = data
+        .skip(total_count - n)
+        .enumerate()
+        .map(|(idx, value)| (idx + 1) as f64 * value)
+        .sum();
+    Some(weighted_sum / ((n * (n + 1)) as f64 / 2.0))
+}
+
+/// Computes the **Relative Strength Index (RSI)** with Wilder's smoothing, in 0..=100.
+///
+/// # Arguments
+/// * `dh` — An iterator over historical data points, oldest first.
+/// * `n` — The number of changes averaged (commonly 14). Must be ≥ 1.
+///
+/// # Returns
+/// * `Some(f64)` — The RSI at the last data point.
+/// * `None` — If `n == 0` or fewer than `n + 1` data points are available.
+pub fn rsi<'a, I>(dh: I, n: usize) -> Option<f64>
+where
+    I: IntoIterator<Item = &'a f64>,
+{
+    let mut indicator = streaming::Rsi::new(n);
+    dh.into_iter().fold(None, |_, value| indicator.update_value(*value))
+}
+
+/// Computes the **Bollinger Bands**: SMA of the last `n` values ± `multiplier` population standard deviations.
+///
+/// # Arguments
+/// * `dh` — An iterator over historical data points, oldest first.
+/// * `n` — The lookback period (commonly 20). Must be ≥ 1.
+/// * `multiplier` — Width of the bands in standard deviations (commonly 2.0).
+///
+/// # Returns
+/// * `Some(Bands)` — The bands at the last data point.
+/// * `None` — If `n == 0` or fewer than `n` data points are available.
+pub fn bollinger<'a, I>(dh: I, n: usize, multiplier: f64) -> Option<Bands>
+where
+    I: IntoIterator<Item = &'a f64>,
+    I::IntoIter: Clone,
+{
+    if n == 0 { return None; }
+
+    let data = dh.into_iter();
+    let total_count = data.clone().count();
+    if total_count < n { return None; }
+
+    // Only the window matters: feed just the last `n` values.
+    let mut indicator = streaming::Bollinger::new(n, multiplier);
+    data.skip(total_count - n).fold(None, |_, value| indicator.update_value(*value))
+}
+
+/// Computes the **MACD**: EMA(`fast`) - EMA(`slow`), its EMA(`signal`) and the histogram.
+///
+/// # Arguments
+/// * `dh` — An iterator over historical data points, oldest first.
+/// * `fast`, `slow`, `signal` — The EMA periods (commonly 12, 26, 9).
+///
+/// # Returns
+/// * `Some(MacdOutput)` — MACD line, signal line and histogram at the last data point.
+/// * `None` — If fewer than `slow + signal - 1` data points are available.
+pub fn macd<'a, I>(dh: I, fast: usize, slow: usize, signal: usize) -> Option<MacdOutput>
+where
+    I: IntoIterator<Item = &'a f64>,
+{
+    let mut indicator = streaming::Macd::new(fast, slow, signal);
+    dh.into_iter().fold(None, |_, value| indicator.update_value(*value))
+}
+
+// --- Bar indicators ---
+
+/// Feeds every bar to `indicator` and returns its value at the last bar.
+fn run_on_bars<I, B, T>(bars: I, mut indicator: T) -> Option<T::Output>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+    T: streaming::StreamingIndicator,
+{
+    bars.into_iter().fold(None, |_, bar| indicator.update(bar.borrow()))
+}
+
+/// Computes the **Average True Range (ATR)** with Wilder's smoothing.
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first (e.g. the result of `DataHandler::get_latest_bars`).
+/// * `n` — The period (commonly 14). Must be ≥ 1.
+///
+/// # Returns
+/// * `Some(f64)` — The ATR at the last bar.
+/// * `None` — If `n == 0` or fewer than `n + 1` bars are available.
+pub fn atr<I, B>(bars: I, n: usize) -> Option<f64>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::Atr::new(n))
+}
+
+/// Computes the **Stochastic oscillator**: %K over `k_period` bars and its SMA %D over `d_period` values.
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first.
+/// * `k_period` — Lookback of the high/low range (commonly 14).
+/// * `d_period` — Period of %D (commonly 3).
+///
+/// # Returns
+/// * `Some(StochasticOutput)` — %K and %D at the last bar.
+/// * `None` — If fewer than `k_period + d_period - 1` bars are available.
+pub fn stochastic<I, B>(bars: I, k_period: usize, d_period: usize) -> Option<StochasticOutput>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::Stochastic::new(k_period, d_period))
+}
+
+/// Computes the **ADX with +DI / -DI** (Wilder's DMI).
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first.
+/// * `n` — The period (commonly 14). Must be ≥ 1.
+///
+/// # Returns
+/// * `Some(AdxOutput)` — ADX, +DI and -DI at the last bar.
+/// * `None` — If `n == 0` or fewer than `2 * n` bars are available.
+pub fn adx<I, B>(bars: I, n: usize) -> Option<AdxOutput>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::Adx::new(n))
+}
+
+/// Computes the **Donchian channel**: highest high and lowest low of the last `n` bars.
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first.
+/// * `n` — The lookback period (commonly 20). Must be ≥ 1.
+///
+/// # Returns
+/// * `Some(Bands)` — The channel at the last bar; the middle line is halfway between high and low.
+/// * `None` — If `n == 0` or fewer than `n` bars are available.
+pub fn donchian<I, B>(bars: I, n: usize) -> Option<Bands>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::Donchian::new(n))
+}
+
+/// Computes the **Keltner channel**: EMA of the close ± `multiplier` ATRs.
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first.
+/// * `ema_period` — Period of the middle EMA (commonly 20).
+/// * `atr_period` — Period of the ATR (commonly 10).
+/// * `multiplier` — Width of the channel in ATRs (commonly 2.0).
+///
+/// # Returns
+/// * `Some(Bands)` — The channel at the last bar.
+/// * `None` — While the EMA or the ATR lacks data.
+pub fn keltner<I, B>(bars: I, ema_period: usize, atr_period: usize, multiplier: f64) -> Option<Bands>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::Keltner::new(ema_period, atr_period, multiplier))
+}
+
+/// Computes the **Parabolic SAR** (see `streaming::ParabolicSar` for the rules).
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first.
+/// * `step` — Acceleration step (commonly 0.02).
+/// * `max_step` — Maximum acceleration (commonly 0.2).
+///
+/// # Returns
+/// * `Some(f64)` — The SAR at the last bar.
+/// * `None` — If fewer than 2 bars are available.
+pub fn parabolic_sar<I, B>(bars: I, step: f64, max_step: f64) -> Option<f64>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::ParabolicSar::new(step, max_step))
+}
+
+/// Computes the **VWAP** of the typical price over all the given bars.
+///
+/// # Arguments
+/// * `bars` — Historical bars, oldest first; pass the bars of the session only for a session VWAP.
+///
+/// # Returns
+/// * `Some(f64)` — The VWAP at the last bar.
+/// * `None` — If the bars have no volume.
+pub fn vwap<I, B>(bars: I) -> Option<f64>
+where
+    I: IntoIterator<Item = B>,
+    B: std::borrow::Borrow<data_handler::MarketBar>,
+{
+    run_on_bars(bars, streaming::Vwap::new())
+}
@@ -0,0 +1,1321 @@
+// farukon_core/src/indicators/streaming.rs
+
+//! Streaming (incremental) indicators.
+//!
+//! The batch functions of `indicators` recompute the whole lookback window on every bar.
+//! The indicators here keep their state between bars instead: a strategy creates each one once
+//! per symbol (see `SymbolIndicators`) and feeds it the new bar, at O(1) cost per bar
+//! (amortized O(1) for `RollingMax` / `RollingMin`).
+//!
+//! Currently implemented indicators:
+//! - Simple / Exponential / Weighted Moving Average (`Sma`, `Ema`, `Wma`)
+//! - Highest / lowest value over N bars (`RollingMax`, `RollingMin`)
+//! - Rolling standard deviation (`RollingStd`)
+//! - Relative Strength Index (`Rsi`) and Average True Range (`Atr`), with Wilder's smoothing
+//! - Bollinger Bands, Donchian and Keltner channels (`Bollinger`, `Donchian`, `Keltner`)
+//! - MACD (`Macd`), Stochastic oscillator (`Stochastic`), ADX/DMI (`Adx`)
+//! - Parabolic SAR (`ParabolicSar`) and VWAP (`Vwap`)
+//!
+//! Every indicator returns `None` until it has seen enough bars for its period.
+//! The batch functions of `indicators` feed their input through these types, so both forms give the same values.
+
+use crate::data_handler;
+use crate::indicators;
+
+/// Price field of a bar fed to a single-series indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PriceSource {
+    Open,
+    High,
+    Low,
+    #[default]
+    Close,
+    Volume,
+    /// (high + low) / 2
+    Median,
+    /// (high + low + close) / 3
+    Typical,
+}
+
+impl PriceSource {
+    /// Returns the value of this field of `bar`.
+    pub fn value(&self, bar: &data_handler::MarketBar) -> f64 {
+        match self {
+            PriceSource::Open => bar.open,
+            PriceSource::High => bar.high,
+            PriceSource::Low => bar.low,
+            PriceSource::Close => bar.close,
+            PriceSource::Volume => bar.volume as f64,
+            PriceSource::Median => (bar.high + bar.low) / 2.0,
+            PriceSource::Typical => (bar.high + bar.low + bar.close) / 3.0,
+        }
+    }
+
+    /// Name of the field: "open", "high", "low", "close", "volume", "median" or "typical".
+    pub fn name(&self) -> &'static str {
+        match self {
+            PriceSource::Open => "open",
+            PriceSource::High => "high",
+            PriceSource::Low => "low",
+            PriceSource::Close => "close",
+            PriceSource::Volume => "volume",
+            PriceSource::Median => "median",
+            PriceSource::Typical => "typical",
+        }
+    }
+
+    /// Parses a field name (see `name`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "open" => Some(PriceSource::Open),
+            "high" => Some(PriceSource::High),
+            "low" => Some(PriceSource::Low),
+            "close" => Some(PriceSource::Close),
+            "volume" => Some(PriceSource::Volume),
+            "median" => Some(PriceSource::Median),
+            "typical" => Some(PriceSource::Typical),
+            _ => None,
+        }
+    }
+}
+
+/// Common interface of the streaming indicators.
+pub trait StreamingIndicator {
+    /// Value produced for each bar.
+    type Output;
+
+    /// Feeds the next bar.
+    /// # Arguments
+    /// * `bar` - The new bar; every bar must be fed exactly once, in time order.
+    /// # Returns
+    /// * `Option<Self::Output>` - The indicator value after this bar, or `None` while it is still warming up.
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<Self::Output>;
+
+    /// The value after the last bar fed, without updating.
+    fn value(&self) -> Option<Self::Output>;
+
+    /// Forgets every bar fed so far.
+    fn reset(&mut self);
+}
+
+// --- Window ---
+
+/// Fixed-size ring buffer of the last `capacity` values.
+#[derive(Debug, Clone)]
+struct Window {
+    values: std::collections::VecDeque<f64>,
+    capacity: usize,
+}
+
+impl Window {
+    fn new(capacity: usize) -> Self {
+        Self {
+            values: std::collections::VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Pushes `value` and returns the value dropped out of the window, if it was full.
+    fn push(&mut self, value: f64) -> Option<f64> {
+        let dropped = if self.values.len() == self.capacity { self.values.pop_front() } else { None };
+        self.values.push_back(value);
+        dropped
+    }
+
+    fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+}
+
+// --- SMA ---
+
+/// Streaming **Simple Moving Average** over the last `period` values.
+///
+/// Keeps a running sum; the sum is recomputed from the window once per `period` updates,
+/// so rounding errors of the running sum do not accumulate over long backtests.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    source: PriceSource,
+    window: Window,
+    sum: f64,
+    /// Updates since the sum was last recomputed.
+    updates_since_resum: usize,
+}
+
+impl Sma {
+    /// Creates an SMA of the close over `period` bars.
+    /// # Arguments
+    /// * `period` - The lookback period; must be ≥ 1 (an SMA of period 0 never produces a value).
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            source: PriceSource::Close,
+            window: Window::new(period),
+            sum: 0.0,
+            updates_since_resum: 0,
+        }
+    }
+
+    /// Sets the price field the SMA is computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The SMA, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 { return None; }
+
+        let dropped = self.window.push(value);
+        self.updates_since_resum += 1;
+        if self.updates_since_resum >= self.period {
+            // Same summation order as the batch `indicators::sma`.
+            self.sum = self.window.values.iter().sum();
+            self.updates_since_resum = 0;
+        } else {
+            self.sum += value - dropped.unwrap_or(0.0);
+        }
+        self.current()
+    }
+
+    fn current(&self) -> Option<f64> {
+        if self.period == 0 || !self.window.is_full() { return None; }
+        Some(self.sum / (self.period as f64))
+    }
+}
+
+impl StreamingIndicator for Sma {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period).with_source(self.source);
+    }
+}
+
+// --- EMA ---
+
+/// Streaming **Exponential Moving Average** with smoothing factor `2 / (period + 1)`.
+///
+/// The first value is the SMA of the first `period` values; the EMA is `None` before that.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    source: PriceSource,
+    alpha: f64,
+    /// Sum of the first values while warming up.
+    seed_sum: f64,
+    count: usize,
+    ema: Option<f64>,
+}
+
+impl Ema {
+    /// Creates an EMA of the close over `period` bars.
+    /// # Arguments
+    /// * `period` - The lookback period; must be ≥ 1.
+    pub fn new(period: usize) -> Self {
+        Self::with_alpha(period, 2.0 / (period as f64 + 1.0))
+    }
+
+    /// Creates an EMA with an explicit smoothing factor, e.g. `1 / period` for Wilder's smoothing.
+    /// # Arguments
+    /// * `period` - Number of values averaged into the first EMA value.
+    /// * `alpha` - Weight of the newest value.
+    pub fn with_alpha(period: usize, alpha: f64) -> Self {
+        Self {
+            period,
+            source: PriceSource::Close,
+            alpha,
+            seed_sum: 0.0,
+            count: 0,
+            ema: None,
+        }
+    }
+
+    /// Sets the price field the EMA is computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The EMA, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 { return None; }
+
+        self.ema = match self.ema {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => {
+                self.seed_sum += value;
+                self.count += 1;
+                if self.count == self.period { Some(self.seed_sum / self.period as f64) } else { None }
+            },
+        };
+        self.ema
+    }
+}
+
+impl StreamingIndicator for Ema {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.ema
+    }
+
+    fn reset(&mut self) {
+        *self = Self::with_alpha(self.period, self.alpha).with_source(self.source);
+    }
+}
+
+// --- Rolling max / min ---
+
+/// Monotonic deque of (bar number, value): the front is the extremum of the window.
+#[derive(Debug, Clone)]
+struct MonotonicWindow {
+    period: usize,
+    /// `true` keeps the maximum at the front, `false` the minimum.
+    keep_max: bool,
+    deque: std::collections::VecDeque<(usize, f64)>,
+    count: usize,
+}
+
+impl MonotonicWindow {
+    fn new(period: usize, keep_max: bool) -> Self {
+        Self {
+            period,
+            keep_max,
+            deque: std::collections::VecDeque::new(),
+            count: 0,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 { return None; }
+
+        // Values dominated by the new one can never be the extremum again.
+        while let Some(&(_, back)) = self.deque.back() {
+            let dominated = if self.keep_max { back <= value } else { back >= value };
+            if !dominated { break; }
+            self.deque.pop_back();
+        }
+        self.deque.push_back((self.count, value));
+        self.count += 1;
+
+        // Drop the front once it left the window.
+        while let Some(&(idx, _)) = self.deque.front() {
+            if idx + self.period > self.count - 1 { break; }
+            self.deque.pop_front();
+        }
+        self.current()
+    }
+
+    fn current(&self) -> Option<f64> {
+        if self.period == 0 || self.count < self.period { return None; }
+        self.deque.front().map(|&(_, value)| value)
+    }
+}
+
+/// Streaming **highest value** over the last `period` bars (the high by default).
+#[derive(Debug, Clone)]
+pub struct RollingMax {
+    source: PriceSource,
+    window: MonotonicWindow,
+}
+
+impl RollingMax {
+    /// Creates the highest high over `period` bars.
+    pub fn new(period: usize) -> Self {
+        Self {
+            source: PriceSource::High,
+            window: MonotonicWindow::new(period, true),
+        }
+    }
+
+    /// Sets the price field the maximum is taken of.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The maximum of the last `period` values, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        self.window.update(value)
+    }
+}
+
+impl StreamingIndicator for RollingMax {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.window.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.window.period).with_source(self.source);
+    }
+}
+
+/// Streaming **lowest value** over the last `period` bars (the low by default).
+#[derive(Debug, Clone)]
+pub struct RollingMin {
+    source: PriceSource,
+    window: MonotonicWindow,
+}
+
+impl RollingMin {
+    /// Creates the lowest low over `period` bars.
+    pub fn new(period: usize) -> Self {
+        Self {
+            source: PriceSource::Low,
+            window: MonotonicWindow::new(period, false),
+        }
+    }
+
+    /// Sets the price field the minimum is taken of.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The minimum of the last `period` values, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        self.window.update(value)
+    }
+}
+
+impl StreamingIndicator for RollingMin {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.window.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.window.period).with_source(self.source);
+    }
+}
+
+// --- Rolling standard deviation ---
+
+/// Streaming **population standard deviation** over the last `period` values.
+///
+/// Mean and sum of squared deviations are updated with Welford's method for a sliding window,
+/// which avoids the cancellation of the naive `E[x²] - E[x]²`.
+#[derive(Debug, Clone)]
+pub struct RollingStd {
+    period: usize,
+    source: PriceSource,
+    window: Window,
+    mean: f64,
+    /// Sum of squared deviations from the mean.
+    m2: f64,
+}
+
+impl RollingStd {
+    /// Creates the standard deviation of the close over `period` bars.
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            source: PriceSource::Close,
+            window: Window::new(period),
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// Sets the price field the deviation is computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The standard deviation, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 { return None; }
+
+        match self.window.push(value) {
+            Some(dropped) => {
+                let old_mean = self.mean;
+                self.mean += (value - dropped) / self.period as f64;
+                self.m2 += (value - dropped) * (value - self.mean + dropped - old_mean);
+            },
+            None => {
+                let count = self.window.values.len() as f64;
+                let delta = value - self.mean;
+                self.mean += delta / count;
+                self.m2 += delta * (value - self.mean);
+            },
+        }
+        self.current()
+    }
+
+    /// The mean of the window, available once it is full.
+    pub fn mean(&self) -> Option<f64> {
+        if self.period == 0 || !self.window.is_full() { return None; }
+        Some(self.mean)
+    }
+
+    fn current(&self) -> Option<f64> {
+        if self.period == 0 || !self.window.is_full() { return None; }
+        // Rounding may leave a tiny negative sum for a constant series.
+        Some((self.m2.max(0.0) / self.period as f64).sqrt())
+    }
+}
+
+impl StreamingIndicator for RollingStd {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period).with_source(self.source);
+    }
+}
+
+// --- WMA ---
+
+/// Streaming **Weighted Moving Average**: weights `1..=period`, the newest value weighs `period`.
+///
+/// Keeps the plain and the weighted sum of the window; both are recomputed once per `period` updates.
+#[derive(Debug, Clone)]
+pub struct Wma {
+    period: usize,
+    source: PriceSource,
+    window: Window,
+    sum: f64,
+    weighted_sum: f64,
+    updates_since_resum: usize,
+}
+
+impl Wma {
+    /// Creates a WMA of the close over `period` bars.
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            source: PriceSource::Close,
+            window: Window::new(period),
+            sum: 0.0,
+            weighted_sum: 0.0,
+            updates_since_resum: 0,
+        }
+    }
+
+    /// Sets the price field the WMA is computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The WMA, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 { return None; }
+
+        let was_full = self.window.is_full();
+        let dropped = self.window.push(value);
+        self.updates_since_resum += 1;
+        if !self.window.is_full() {
+            return None;
+        }
+
+        if !was_full || self.updates_since_resum >= self.period {
+            // Full recomputation: on the first full window and periodically against rounding drift.
+            self.sum = self.window.values.iter().sum();
+            self.weighted_sum = self.window.values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| (idx + 1) as f64 * value)
+                .sum();
+            self.updates_since_resum = 0;
+        } else {
+            // Every value moves one weight down: the weighted sum loses the old plain sum.
+            self.weighted_sum += self.period as f64 * value - self.sum;
+            self.sum += value - dropped.unwrap_or(0.0);
+        }
+        self.current()
+    }
+
+    fn current(&self) -> Option<f64> {
+        if self.period == 0 || !self.window.is_full() { return None; }
+        let weights = (self.period * (self.period + 1)) as f64 / 2.0;
+        Some(self.weighted_sum / weights)
+    }
+}
+
+impl StreamingIndicator for Wma {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period).with_source(self.source);
+    }
+}
+
+/// Wilder's smoothing: an EMA with smoothing factor `1 / period`, seeded with the SMA of the first `period` values.
+fn wilder_average(period: usize) -> Ema {
+    Ema::with_alpha(period, 1.0 / period as f64)
+}
+
+/// True range of a bar: the largest of high - low, |high - previous close| and |low - previous close|.
+fn true_range(bar: &data_handler::MarketBar, prev_close: f64) -> f64 {
+    (bar.high - bar.low)
+        .max((bar.high - prev_close).abs())
+        .max((bar.low - prev_close).abs())
+}
+
+// --- RSI ---
+
+/// Streaming **Relative Strength Index** (Wilder), in 0..=100.
+///
+/// Average gain and average loss of the close-to-close changes use Wilder's smoothing;
+/// the first value needs `period + 1` values. A window without losses gives 100, without any change 50.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    source: PriceSource,
+    prev_value: Option<f64>,
+    avg_gain: Ema,
+    avg_loss: Ema,
+}
+
+impl Rsi {
+    /// Creates an RSI of the close over `period` changes.
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            source: PriceSource::Close,
+            prev_value: None,
+            avg_gain: wilder_average(period),
+            avg_loss: wilder_average(period),
+        }
+    }
+
+    /// Sets the price field the RSI is computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<f64>` - The RSI, or `None` until `period + 1` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        let prev_value = self.prev_value.replace(value)?;
+        let change = value - prev_value;
+        self.avg_gain.update_value(change.max(0.0));
+        self.avg_loss.update_value((-change).max(0.0));
+        self.current()
+    }
+
+    fn current(&self) -> Option<f64> {
+        let (avg_gain, avg_loss) = (self.avg_gain.value()?, self.avg_loss.value()?);
+        if avg_loss == 0.0 {
+            return Some(if avg_gain == 0.0 { 50.0 } else { 100.0 });
+        }
+        Some(100.0 - 100.0 / (1.0 + avg_gain / avg_loss))
+    }
+}
+
+impl StreamingIndicator for Rsi {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period).with_source(self.source);
+    }
+}
+
+// --- ATR ---
+
+/// Streaming **Average True Range** (Wilder).
+///
+/// The true range needs the previous close, so the first value needs `period + 1` bars.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    prev_close: Option<f64>,
+    average: Ema,
+}
+
+impl Atr {
+    /// Creates an ATR over `period` bars.
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_close: None,
+            average: wilder_average(period),
+        }
+    }
+}
+
+impl StreamingIndicator for Atr {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        let prev_close = self.prev_close.replace(bar.close)?;
+        self.average.update_value(true_range(bar, prev_close))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.average.value()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+// --- Bollinger Bands ---
+
+/// Streaming **Bollinger Bands**: SMA ± `multiplier` population standard deviations.
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    multiplier: f64,
+    std: RollingStd,
+}
+
+impl Bollinger {
+    /// Creates Bollinger Bands of the close.
+    /// # Arguments
+    /// * `period` - The lookback period of the SMA and the deviation (commonly 20).
+    /// * `multiplier` - Width of the bands in standard deviations (commonly 2.0).
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        Self {
+            multiplier,
+            std: RollingStd::new(period),
+        }
+    }
+
+    /// Sets the price field the bands are computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.std = self.std.with_source(source);
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<indicators::Bands>` - The bands, or `None` until `period` values have been fed.
+    pub fn update_value(&mut self, value: f64) -> Option<indicators::Bands> {
+        self.std.update_value(value);
+        self.current()
+    }
+
+    fn current(&self) -> Option<indicators::Bands> {
+        let (middle, std) = (self.std.mean()?, self.std.value()?);
+        Some(indicators::Bands {
+            upper: middle + self.multiplier * std,
+            middle,
+            lower: middle - self.multiplier * std,
+        })
+    }
+}
+
+impl StreamingIndicator for Bollinger {
+    type Output = indicators::Bands;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<indicators::Bands> {
+        self.std.update(bar);
+        self.current()
+    }
+
+    fn value(&self) -> Option<indicators::Bands> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        self.std.reset();
+    }
+}
+
+// --- MACD ---
+
+/// Streaming **MACD**: EMA(fast) - EMA(slow), its EMA(signal) and the histogram.
+///
+/// The first value needs `slow + signal - 1` values.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    source: PriceSource,
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    current: Option<indicators::MacdOutput>,
+}
+
+impl Macd {
+    /// Creates a MACD of the close.
+    /// # Arguments
+    /// * `fast` - Period of the fast EMA (commonly 12).
+    /// * `slow` - Period of the slow EMA (commonly 26).
+    /// * `signal` - Period of the signal EMA of the MACD line (commonly 9).
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            source: PriceSource::Close,
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+            current: None,
+        }
+    }
+
+    /// Sets the price field the MACD is computed on.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Feeds the next value of the series.
+    /// # Returns
+    /// * `Option<indicators::MacdOutput>` - MACD line, signal and histogram, or `None` while warming up.
+    pub fn update_value(&mut self, value: f64) -> Option<indicators::MacdOutput> {
+        let fast = self.fast.update_value(value);
+        let slow = self.slow.update_value(value);
+        let (Some(fast), Some(slow)) = (fast, slow) else {
+            return None;
+        };
+
+        let macd = fast - slow;
+        self.current = self.signal.update_value(macd).map(|signal| indicators::MacdOutput {
+            macd,
+            signal,
+            histogram: macd - signal,
+        });
+        self.current
+    }
+}
+
+impl StreamingIndicator for Macd {
+    type Output = indicators::MacdOutput;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<indicators::MacdOutput> {
+        self.update_value(self.source.value(bar))
+    }
+
+    fn value(&self) -> Option<indicators::MacdOutput> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+        self.current = None;
+    }
+}
+
+// --- Stochastic ---
+
+/// Streaming **Stochastic oscillator**: %K = 100 * (close - lowest low) / (highest high - lowest low)
+/// over `k_period` bars, %D = SMA(`d_period`) of %K. A flat range gives %K = 50.
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    highest: RollingMax,
+    lowest: RollingMin,
+    d: Sma,
+    current: Option<indicators::StochasticOutput>,
+}
+
+impl Stochastic {
+    /// Creates a Stochastic oscillator.
+    /// # Arguments
+    /// * `k_period` - Lookback of the high/low range (commonly 14).
+    /// * `d_period` - Period of the %D average (commonly 3).
+    pub fn new(k_period: usize, d_period: usize) -> Self {
+        Self {
+            highest: RollingMax::new(k_period),
+            lowest: RollingMin::new(k_period),
+            d: Sma::new(d_period),
+            current: None,
+        }
+    }
+}
+
+impl StreamingIndicator for Stochastic {
+    type Output = indicators::StochasticOutput;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<indicators::StochasticOutput> {
+        let highest = self.highest.update(bar);
+        let lowest = self.lowest.update(bar);
+        let (Some(highest), Some(lowest)) = (highest, lowest) else {
+            return None;
+        };
+
+        let k = if highest > lowest { 100.0 * (bar.close - lowest) / (highest - lowest) } else { 50.0 };
+        self.current = self.d.update_value(k).map(|d| indicators::StochasticOutput { k, d });
+        self.current
+    }
+
+    fn value(&self) -> Option<indicators::StochasticOutput> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+        self.d.reset();
+        self.current = None;
+    }
+}
+
+// --- ADX / DMI ---
+
+/// Streaming **ADX with the directional indicators +DI / -DI** (Wilder).
+///
+/// True range and directional movements are Wilder-smoothed over `period`; the ADX is the
+/// Wilder average of DX, so the first value needs `2 * period` bars.
+#[derive(Debug, Clone)]
+pub struct Adx {
+    period: usize,
+    prev_bar: Option<(f64, f64, f64)>,
+    true_range: Ema,
+    plus_dm: Ema,
+    minus_dm: Ema,
+    adx: Ema,
+    current: Option<indicators::AdxOutput>,
+}
+
+impl Adx {
+    /// Creates an ADX over `period` bars (commonly 14).
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_bar: None,
+            true_range: wilder_average(period),
+            plus_dm: wilder_average(period),
+            minus_dm: wilder_average(period),
+            adx: wilder_average(period),
+            current: None,
+        }
+    }
+}
+
+impl StreamingIndicator for Adx {
+    type Output = indicators::AdxOutput;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<indicators::AdxOutput> {
+        let (prev_high, prev_low, prev_close) = self.prev_bar.replace((bar.high, bar.low, bar.close))?;
+
+        // Only the larger of the two moves counts, and only if it is positive.
+        let up_move = bar.high - prev_high;
+        let down_move = prev_low - bar.low;
+        let plus_dm = if up_move > down_move && up_move > 0.0 { up_move } else { 0.0 };
+        let minus_dm = if down_move > up_move && down_move > 0.0 { down_move } else { 0.0 };
+
+        let true_range = self.true_range.update_value(true_range(bar, prev_close));
+        let plus_dm = self.plus_dm.update_value(plus_dm);
+        let minus_dm = self.minus_dm.update_value(minus_dm);
+        let (Some(true_range), Some(plus_dm), Some(minus_dm)) = (true_range, plus_dm, minus_dm) else {
+            return None;
+        };
+
+        let (plus_di, minus_di) = if true_range > 0.0 {
+            (100.0 * plus_dm / true_range, 100.0 * minus_dm / true_range)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 { 100.0 * (plus_di - minus_di).abs() / di_sum } else { 0.0 };
+
+        self.current = self.adx.update_value(dx).map(|adx| indicators::AdxOutput { adx, plus_di, minus_di });
+        self.current
+    }
+
+    fn value(&self) -> Option<indicators::AdxOutput> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+// --- Donchian channel ---
+
+/// Streaming **Donchian channel**: highest high and lowest low over `period` bars, middle halfway.
+#[derive(Debug, Clone)]
+pub struct Donchian {
+    highest: RollingMax,
+    lowest: RollingMin,
+}
+
+impl Donchian {
+    /// Creates a Donchian channel over `period` bars (commonly 20).
+    pub fn new(period: usize) -> Self {
+        Self {
+            highest: RollingMax::new(period),
+            lowest: RollingMin::new(period),
+        }
+    }
+
+    fn current(&self) -> Option<indicators::Bands> {
+        let (upper, lower) = (self.highest.value()?, self.lowest.value()?);
+        Some(indicators::Bands {
+            upper,
+            middle: (upper + lower) / 2.0,
+            lower,
+        })
+    }
+}
+
+impl StreamingIndicator for Donchian {
+    type Output = indicators::Bands;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<indicators::Bands> {
+        self.highest.update(bar);
+        self.lowest.update(bar);
+        self.current()
+    }
+
+    fn value(&self) -> Option<indicators::Bands> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+    }
+}
+
+// --- Keltner channel ---
+
+/// Streaming **Keltner channel**: EMA of the close ± `multiplier` ATRs.
+#[derive(Debug, Clone)]
+pub struct Keltner {
+    multiplier: f64,
+    ema: Ema,
+    atr: Atr,
+}
+
+impl Keltner {
+    /// Creates a Keltner channel.
+    /// # Arguments
+    /// * `ema_period` - Period of the middle EMA (commonly 20).
+    /// * `atr_period` - Period of the ATR (commonly 10).
+    /// * `multiplier` - Width of the channel in ATRs (commonly 2.0).
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: f64) -> Self {
+        Self {
+            multiplier,
+            ema: Ema::new(ema_period),
+            atr: Atr::new(atr_period),
+        }
+    }
+
+    fn current(&self) -> Option<indicators::Bands> {
+        let (middle, atr) = (self.ema.value()?, self.atr.value()?);
+        Some(indicators::Bands {
+            upper: middle + self.multiplier * atr,
+            middle,
+            lower: middle - self.multiplier * atr,
+        })
+    }
+}
+
+impl StreamingIndicator for Keltner {
+    type Output = indicators::Bands;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<indicators::Bands> {
+        self.ema.update(bar);
+        self.atr.update(bar);
+        self.current()
+    }
+
+    fn value(&self) -> Option<indicators::Bands> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        self.ema.reset();
+        self.atr.reset();
+    }
+}
+
+// --- Parabolic SAR ---
+
+/// Streaming **Parabolic SAR** (Wilder).
+///
+/// The second bar sets the initial trend: long if its high rose at least as much as its low fell
+/// (SAR at the first low), short otherwise (SAR at the first high). Afterwards the SAR moves by
+/// `acceleration * (extreme point - SAR)`, never into the range of the two previous bars;
+/// the acceleration grows by `step` with every new extreme point, up to `max_step`.
+/// When the low (long) or high (short) crosses the SAR, the trend reverses and the SAR jumps to the extreme point.
+#[derive(Debug, Clone)]
+pub struct ParabolicSar {
+    step: f64,
+    max_step: f64,
+    is_long: bool,
+    sar: Option<f64>,
+    /// Extreme point: highest high of a long trend, lowest low of a short one.
+    extreme_point: f64,
+    acceleration: f64,
+    /// (high, low) of the previous bar and of the one before it.
+    prev_bar: Option<(f64, f64)>,
+    prev2_bar: Option<(f64, f64)>,
+}
+
+impl ParabolicSar {
+    /// Creates a Parabolic SAR.
+    /// # Arguments
+    /// * `step` - Acceleration step and initial acceleration (commonly 0.02).
+    /// * `max_step` - Maximum acceleration (commonly 0.2).
+    pub fn new(step: f64, max_step: f64) -> Self {
+        Self {
+            step,
+            max_step,
+            is_long: true,
+            sar: None,
+            extreme_point: 0.0,
+            acceleration: step,
+            prev_bar: None,
+            prev2_bar: None,
+        }
+    }
+
+    /// `true` while the SAR is below the price (long trend).
+    pub fn is_long(&self) -> Option<bool> {
+        self.sar.map(|_| self.is_long)
+    }
+}
+
+impl StreamingIndicator for ParabolicSar {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        let (high, low) = (bar.high, bar.low);
+        let prev_bar = self.prev_bar.replace((high, low));
+        let prev2_bar = std::mem::replace(&mut self.prev2_bar, prev_bar);
+        let (prev_high, prev_low) = prev_bar?;
+
+        let Some(sar) = self.sar else {
+            // Second bar: initial trend from the directional movement.
+            self.is_long = high - prev_high >= prev_low - low;
+            let (sar, extreme_point) = if self.is_long { (prev_low, high) } else { (prev_high, low) };
+            self.extreme_point = extreme_point;
+            self.acceleration = self.step;
+            self.sar = Some(sar);
+            return self.sar;
+        };
+
+        let mut next_sar = sar + self.acceleration * (self.extreme_point - sar);
+        if self.is_long {
+            next_sar = next_sar.min(prev_low).min(prev2_bar.map_or(prev_low, |(_, low)| low));
+            if low < next_sar {
+                // Reversal to short: the SAR starts at the extreme point, above the last two bars.
+                self.is_long = false;
+                next_sar = self.extreme_point.max(high).max(prev_high);
+                self.extreme_point = low;
+                self.acceleration = self.step;
+            } else if high > self.extreme_point {
+                self.extreme_point = high;
+                self.acceleration = (self.acceleration + self.step).min(self.max_step);
+            }
+        } else {
+            next_sar = next_sar.max(prev_high).max(prev2_bar.map_or(prev_high, |(high, _)| high));
+            if high > next_sar {
+                // Reversal to long: the SAR starts at the extreme point, below the last two bars.
+                self.is_long = true;
+                next_sar = self.extreme_point.min(low).min(prev_low);
+                self.extreme_point = high;
+                self.acceleration = self.step;
+            } else if low < self.extreme_point {
+                self.extreme_point = low;
+                self.acceleration = (self.acceleration + self.step).min(self.max_step);
+            }
+        }
+
+        self.sar = Some(next_sar);
+        self.sar
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.sar
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.step, self.max_step);
+    }
+}
+
+// --- VWAP ---
+
+/// Streaming **Volume Weighted Average Price** of the typical price (high + low + close) / 3.
+///
+/// Cumulative from the first bar, or from the first bar of each UTC day with `with_daily_reset`.
+/// `None` while no volume was traded.
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    daily_reset: bool,
+    day: Option<chrono::NaiveDate>,
+    price_volume: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    /// Creates a VWAP accumulated over the whole backtest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restarts the accumulation with the first bar of every UTC day (session VWAP).
+    pub fn with_daily_reset(mut self) -> Self {
+        self.daily_reset = true;
+        self
+    }
+
+    fn current(&self) -> Option<f64> {
+        if self.volume <= 0.0 { return None; }
+        Some(self.price_volume / self.volume)
+    }
+}
+
+impl StreamingIndicator for Vwap {
+    type Output = f64;
+
+    fn update(&mut self, bar: &data_handler::MarketBar) -> Option<f64> {
+        let day = bar.datetime.date_naive();
+        if self.daily_reset && self.day != Some(day) {
+            self.price_volume = 0.0;
+            self.volume = 0.0;
+        }
+        self.day = Some(day);
+
+        let volume = bar.volume as f64;
+        self.price_volume += PriceSource::Typical.value(bar) * volume;
+        self.volume += volume;
+        self.current()
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.current()
+    }
+
+    fn reset(&mut self) {
+        *self = Self { daily_reset: self.daily_reset, ..Self::default() };
+    }
+}
+
+// --- Per-symbol registry ---
+
+/// One indicator per symbol, each fed once per new bar of its symbol.
+///
+/// `calculate_signals` runs on every step of the backtest, also for symbols without a new bar
+/// (e.g. with the "skip" gap policy); `update` feeds a bar only if its datetime differs from the
+/// last bar fed, so the indicators never count a bar twice.
+#[derive(Debug, Clone)]
+pub struct SymbolIndicators<T: StreamingIndicator> {
+    indicators: std::collections::HashMap<String, (T, Option<chrono::DateTime<chrono::Utc>>)>,
+}
+
+impl<T: StreamingIndicator> Default for SymbolIndicators<T> {
+    fn default() -> Self {
+        Self { indicators: std::collections::HashMap::new() }
+    }
+}
+
+impl<T: StreamingIndicator> SymbolIndicators<T> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an indicator for each symbol with `make_indicator`.
+    /// # Arguments
+    /// * `symbols` - The symbols of the strategy.
+    /// * `make_indicator` - Creates the indicator of one symbol.
+    pub fn for_symbols<F>(symbols: &[String], make_indicator: F) -> Self
+    where
+        F: Fn() -> T,
+    {
+        let mut symbol_indicators = Self::new();
+        for symbol in symbols {
+            symbol_indicators.register(symbol, make_indicator());
+        }
+        symbol_indicators
+    }
+
+    /// Registers (or replaces) the indicator of `symbol`.
+    pub fn register(&mut self, symbol: &str, indicator: T) {
+        self.indicators.insert(symbol.to_string(), (indicator, None));
+    }
+
+    /// Feeds the latest bar of `symbol` to its indicator, unless that bar was already fed.
+    /// # Arguments
+    /// * `symbol` - The symbol of the bar.
+    /// * `bar` - The latest bar of the symbol.
+    /// # Returns
+    /// * `Option<T::Output>` - The indicator value, or `None` while warming up or if no indicator is registered for `symbol`.
+    pub fn update(&mut self, symbol: &str, bar: &data_handler::MarketBar) -> Option<T::Output> {
+        let (indicator, last_datetime) = self.indicators.get_mut(symbol)?;
+        if *last_datetime == Some(bar.datetime) {
+            return indicator.value();
+        }
+        *last_datetime = Some(bar.datetime);
+        indicator.update(bar)
+    }
+
+    /// The current value of the indicator of `symbol`.
+    pub fn value(&self, symbol: &str) -> Option<T::Output> {
+        self.indicators.get(symbol).and_then(|(indicator, _)| indicator.value())
+    }
+
+    /// The indicator of `symbol`.
+    pub fn get(&self, symbol: &str) -> Option<&T> {
+        self.indicators.get(symbol).map(|(indicator, _)| indicator)
+    }
+
+    /// Resets every indicator.
+    pub fn reset(&mut self) {
+        for (indicator, last_datetime) in self.indicators.values_mut() {
+            indicator.reset();
+            *last_datetime = None;
+        }
+    }
+}
@@ -160,8 +160,16 @@ impl InstrumentsInfoRegistry {
     /// # Returns
     /// * `anyhow::Result<InstrumentsInfoRegistry>` containing the loaded metadata.
     pub fn load(settings: &settings::Settings,) -> anyhow::Result<Self> {
+        Self::load_file(std::path::Path::new(&settings.common.instrument_info_path))
+    }
+
+    /// Loads the instrument metadata from an `instruments_info.json` file given by path.
+    /// # Arguments
+    /// * `file_path` - The instruments file.
+    /// # Returns
+    /// * `anyhow::Result<InstrumentsInfoRegistry>` containing the loaded metadata.
+    pub fn load_file(file_path: &std::path::Path) -> anyhow::Result<Self> {
         // Validate all instruments
-        let file_path = settings.common.instrument_info_path.clone();
         let contents = std::fs::read_to_string(file_path)?;
         let registry: InstrumentsInfoRegistry = serde_json::from_str(&contents)?;
 
@@ -3,91 +3,30 @@
 //! Core library entry point.
 //! Re-exports all public modules for use by Farukon_2_0 and strategy_lib.
 //!
-//! This file also defines the VTable for dynamic strategy loading.
-//! The VTable allows C-compatible interfaces to Rust DataHandler, enabling dynamic linking.
+//! The C ABI used by dynamically loaded strategies lives in `abi`;
+//! strategies compiled into the engine binary register themselves in `registry`.
 
+pub mod abi;
 pub mod event;
 pub mod index;
 pub mod utils;
 pub mod strategy;
 pub mod settings;
+pub mod sessions;
 pub mod portfolio;
 pub mod execution;
 pub mod indicators;
+pub mod indicator_cache;
+pub mod vectorized;
 pub mod pos_sizers;
 pub mod performance;
 pub mod data_handler;
 pub mod optimization;
+pub mod registry;
+pub mod param_schema;
 pub mod instruments_info;
 pub mod commission_plans;
 
-// VTable for dynamic strategy loading.
-// Allows C-compatible interface to Rust DataHandler.
-#[repr(C)]
-pub struct DataHandlerVTable {
-    get_latest_bar: unsafe fn(*const (), &str) -> Option<&'static data_handler::MarketBar>,
-    get_latest_bars: unsafe fn(*const (), &str, usize) -> Vec<&'static data_handler::MarketBar>,
-    get_latest_bar_datetime: unsafe fn(*const (), &str) -> Option<chrono::DateTime<chrono::Utc>>,
-    get_latest_bar_value: unsafe fn(*const (), &str, &str) -> Option<f64>,
-    get_latest_bar_values: unsafe fn(*const (), &str, &str, usize) -> Vec<f64>,
-    update_bars: unsafe fn(*const ()) -> (),
-    get_continue_backtest: unsafe fn(*const ()) -> bool,
-    set_continue_backtest: unsafe fn(*const (), bool) -> (),
-}
-
-impl data_handler::DataHandler for DataHandlerVTable {
-    // Implements DataHandler trait by calling C function pointers.
-    // Used by dynamic strategy to access data without Rust type dependencies.
-
-    fn get_latest_bar(&self, symbol: &str) -> Option<&data_handler::MarketBar> {
-        unsafe {
-            (self.get_latest_bar)(self as *const _ as *const (), symbol)
-        }
-    }
-    fn get_latest_bars(&self, symbol: &str, size: usize) -> Vec<&data_handler::MarketBar> {
-        unsafe {
-            (self.get_latest_bars)(self as *const _ as *const (), symbol, size)
-        }
-    }
-    fn get_latest_bar_datetime(&self, symbol: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-        unsafe {
-            (self.get_latest_bar_datetime)(self as *const _ as *const (), symbol)
-        }
-    }
-    fn get_latest_bar_value(&self, symbol: &str, val_type: &str) -> Option<f64> {
-        unsafe {
-            (self.get_latest_bar_value)(self as *const _ as *const (), symbol, val_type)
-        }
-    }
-    fn get_latest_bars_values(&self, symbol: &str, val_type: &str, n: usize) -> Vec<f64> {
-        unsafe {
-            (self.get_latest_bar_values)(self as *const _ as *const (), symbol, val_type, n)
-        }
-    }
-    fn update_bars(&mut self) {
-        unsafe {
-            (self.update_bars)(self as *const _ as *const ())
-        }
-    }
-    fn get_continue_backtest(&self) -> bool {
-        unsafe {
-            (self.get_continue_backtest)(self as *const _ as *const ())
-        }
-    }
-    fn set_continue_backtest(&mut self, value: bool) {
-        unsafe {
-            (self.set_continue_backtest)(self as *const _ as *const (), value)
-        }
-    }
-
-}
-
-impl DataHandlerVTable {
-    pub unsafe fn from_raw_parts<'a>(
-        vtable: *const Self,
-        _data: *const ()
-    ) -> &'a Self {
-        unsafe { &*vtable }
-    }
-
-}
+// Used by `register_strategies!` in the crates registering strategies.
+#[doc(hidden)]
+pub use inventory;
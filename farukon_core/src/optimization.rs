@@ -1,7 +1,9 @@
 // farukon_core/src/optimization.rs
 
 //! Optimization engine for hyperparameter tuning.
-//! Supports Grid Search (exhaustive) and Genetic Algorithm (evolutionary).
+//! Supports Grid Search (exhaustive), Random Search and Latin Hypercube sampling (fixed budget),
+//! Bayesian optimization (tree-structured Parzen estimator), Genetic Algorithm (evolutionary)
+//! and NSGA-II (multi-objective, Pareto front).
 //! Uses Rayon for parallel evaluation of thousands of parameter combinations.
 
 use std::hash::Hash;
@@ -12,13 +14,16 @@ use itertools::Itertools;
 
 use crate::settings;
 use crate::performance;
+use crate::param_schema;
 
 /// Represents the result of evaluating a single parameter set.
 /// Contains the parameters used and the resulting performance metrics.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OptimizationResult {
     parameters: ParameterSet,
     results:  performance::PerformanceMetrics,
+    /// Content hash of the strategy build that produced the result (set with hot reload).
+    strategy_build: Option<String>,
 }
 
 impl OptimizationResult {
@@ -27,6 +32,7 @@ impl OptimizationResult {
         Self {
             parameters: ParameterSet::new(),
             results: performance::PerformanceMetrics::default(),
+            strategy_build: None,
         }
     }
 
@@ -42,6 +48,12 @@ impl OptimizationResult {
         self
     }
 
+    /// Sets the strategy build that produced this result.
+    pub fn with_strategy_build(mut self, strategy_build: Option<String>) -> Self {
+        self.strategy_build = strategy_build;
+        self
+    }
+
     /// --- Getters ---
     pub fn get_parameters(&self) -> &ParameterSet {
         &self.parameters
@@ -50,12 +62,16 @@ impl OptimizationResult {
     pub fn get_results(&self) -> &performance::PerformanceMetrics {
         &self.results
     }
+
+    pub fn get_strategy_build(&self) -> Option<&str> {
+        self.strategy_build.as_deref()
+    }
     
 }
 
 /// Represents a single set of strategy, position sizing, and slippage parameters.
 /// Used as input to the backtest engine during optimization.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ParameterSet {
     /// Strategy-specific parameters (e.g., short_window, long_window).
     strategy_params: Vec<(String, serde_json::Value)>,
@@ -175,6 +191,26 @@ impl ParameterSet {
         format!("{} {} {}", strategy_str, pos_sizer_str, slippage_str)
     }
 
+    /// Builds a key identifying the parameter set across runs: parameters are sorted by name,
+    /// so the key does not depend on the order in which the combinations were generated.
+    pub fn cache_key(&self) -> String {
+        let sorted = |params: &[(String, serde_json::Value)]| {
+            params
+                .iter()
+                .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(k, v)| format!("{}={}", k, v))
+                .join(",")
+        };
+        format!(
+            "{}|{}:{}:{}|{}",
+            sorted(&self.strategy_params),
+            self.pos_sizer_name,
+            self.pos_sizer_value,
+            sorted(&self.pos_sizer_additional_params),
+            self.slippage,
+        )
+    }
+
 }
 
 /// Configuration for the optimization process.
@@ -191,6 +227,8 @@ pub struct OptimizationConfig {
     pos_sizer_name: String,
     /// Maps position sizer additional params
     pos_sizer_additional_params: std::collections::HashMap<String, Vec<serde_json::Value>>,
+    /// Parameter schema of the strategy; combinations breaking its constraints are skipped.
+    param_schema: Option<param_schema::ParamSchema>,
 }
 
 impl OptimizationConfig {
@@ -202,6 +240,7 @@ impl OptimizationConfig {
             slippage_range: Vec::new(),
             pos_sizer_name: String::new(),
             pos_sizer_additional_params: std::collections::HashMap::new(),
+            param_schema: None,
         }
     }
 
@@ -235,12 +274,79 @@ impl OptimizationConfig {
         self
     }
 
+    /// Sets the parameter schema used to skip invalid combinations.
+    pub fn with_param_schema(mut self, param_schema: Option<param_schema::ParamSchema>) -> Self {
+        self.param_schema = param_schema;
+        self
+    }
+
+    /// Checks a parameter set against the constraints of the parameter schema.
+    /// Without a schema every parameter set is valid.
+    pub fn is_valid_combination(&self, params: &ParameterSet) -> bool {
+        self.param_schema
+            .as_ref()
+            .is_none_or(|param_schema| param_schema.is_valid_combination(&params.strategy_params))
+    }
+
+    /// Returns `true` when at least one combination satisfies the parameter schema.
+    pub fn has_valid_combinations(&self) -> bool {
+        self.generate_all_combinations_iter().next().is_some()
+    }
+
     /// Generates all possible combinations of parameters.
+    /// Combinations breaking the constraints of the parameter schema are left out.
     /// Returns a vector of ParameterSet objects.
     pub fn generate_all_combinations_vec(&self) -> Vec<ParameterSet> {
         self.generate_all_combinations_iter().collect()
     }
 
+    /// Number of values of each sampling axis: the strategy parameters sorted by name, then `pos_sizer_value` and `slippage`.
+    fn sampling_axes(&self) -> (Vec<&String>, Vec<usize>) {
+        let mut strategy_params_names: Vec<&String> = self.strategy_params_ranges.keys().collect();
+        strategy_params_names.sort();
+        let axes = strategy_params_names
+            .iter()
+            .map(|name| self.strategy_params_ranges[*name].len())
+            .chain([self.pos_sizer_value_range.len(), self.slippage_range.len()])
+            .collect();
+        (strategy_params_names, axes)
+    }
+
+    /// Builds the parameter set with the values at `indices`, one index per sampling axis.
+    fn parameter_set_at(&self, strategy_params_names: &[&String], indices: &[usize]) -> ParameterSet {
+        let strategy_params = strategy_params_names
+            .iter()
+            .zip(indices)
+            .map(|(name, &index)| ((*name).clone(), self.strategy_params_ranges[*name][index].clone()))
+            .collect();
+        let pos_sizer_additional_params = self.pos_sizer_additional_params
+            .iter()
+            .flat_map(|(key, values)| {
+                values.iter().map(|value| (key.clone(), value.clone()))
+            })
+            .collect();
+
+        ParameterSet::new()
+            .with_strategy_params(strategy_params)
+            .with_pos_sizer_name(self.pos_sizer_name.clone())
+            .with_pos_sizer_additional_params(pos_sizer_additional_params)
+            .with_pos_sizer_value(self.pos_sizer_value_range[indices[strategy_params_names.len()]])
+            .with_slippage(self.slippage_range[indices[strategy_params_names.len() + 1]])
+    }
+
+    /// Finds the index of every value of `params` on its sampling axis (the inverse of `parameter_set_at`).
+    /// Returns `None` if a value is not in its range.
+    fn value_indices(&self, strategy_params_names: &[&String], params: &ParameterSet) -> Option<Vec<usize>> {
+        let mut indices = Vec::with_capacity(strategy_params_names.len() + 2);
+        for name in strategy_params_names {
+            let (_, value) = params.strategy_params.iter().find(|(param_name, _)| param_name == *name)?;
+            indices.push(self.strategy_params_ranges[*name].iter().position(|range_value| range_value == value)?);
+        }
+        indices.push(self.pos_sizer_value_range.iter().position(|value| *value == params.pos_sizer_value)?);
+        indices.push(self.slippage_range.iter().position(|value| *value == params.slippage)?);
+        Some(indices)
+    }
+
     /// Generates an iterator over all possible combinations of parameters.
     fn generate_all_combinations_iter(&self) -> impl Iterator<Item = ParameterSet> + '_ {
         // println!("DEBUG {:#?}", self);
@@ -289,6 +395,7 @@ impl OptimizationConfig {
                                                 .with_slippage(slippage)  
                                         }
                                     })
+                                    .filter(move |params| self.is_valid_combination(params))
                             }
                         })
                 }
@@ -324,7 +431,7 @@ impl GridSearchOptimizer {
         &self.config
     }
 
-    /// Calculates the total number of parameter combinations to test.
+    /// Calculates the total number of parameter combinations, including those the parameter schema skips.
     pub fn calculate_total_combinations(&self) -> usize {
         let strategy_combinations = self.config.strategy_params_ranges
             .values()
@@ -376,16 +483,888 @@ impl GridSearchOptimizer {
 
 }
 
+// --- SAMPLING OPTIMIZERS ---
+
+/// Maximum number of batches drawn by `SamplingOptimizer` to replace duplicate or invalid parameter sets.
+const MAX_SAMPLING_ROUNDS: usize = 100;
+
+/// How `SamplingOptimizer` draws parameter sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingMethod {
+    /// Every parameter is drawn uniformly and independently from its values.
+    Random,
+    /// Latin hypercube: the values of every parameter are split into `budget` equal strata
+    /// and each stratum is drawn once, so every parameter is covered evenly whatever the budget.
+    LatinHypercube,
+}
+
+/// An optimizer that evaluates a fixed budget of parameter sets drawn from the parameter ranges.
+/// Parameter sets are built from per-parameter indices, so the full grid is never materialized:
+/// the cost depends on the budget, not on the size of the parameter space.
+#[derive(Debug, Clone)]
+pub struct SamplingOptimizer {
+    config: OptimizationConfig,
+    method: SamplingMethod,
+    budget: usize,
+    seed: u64,
+}
+
+impl SamplingOptimizer {
+    /// Creates a new SamplingOptimizer (random sampling, empty budget) with a random seed.
+    pub fn new() -> Self {
+        Self {
+            config: OptimizationConfig::new(),
+            method: SamplingMethod::Random,
+            budget: 0,
+            seed: rand::random(),
+        }
+    }
+
+    /// Sets the optimization configuration.
+    pub fn with_optimization_config(mut self, config: OptimizationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the sampling method.
+    pub fn with_method(mut self, method: SamplingMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sets the number of parameter sets to draw.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Sets the seed of the sampler; the same seed and configuration give the same samples.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the seed of the sampler.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Draws up to `budget` distinct parameter sets satisfying the parameter schema.
+    /// A parameter space no larger than the budget is returned whole, in index order.
+    /// Fewer than `budget` sets are returned only when the schema leaves fewer valid combinations.
+    /// # Returns
+    /// * A vector of ParameterSet objects.
+    pub fn generate_samples(&self) -> Vec<ParameterSet> {
+        let (strategy_params_names, axes) = self.config.sampling_axes();
+        if axes.contains(&0) || self.budget == 0 {
+            return Vec::new();
+        }
+
+        let space_size = axes.iter().try_fold(1usize, |size, &axis| size.checked_mul(axis));
+        if space_size.is_some_and(|space_size| space_size <= self.budget) {
+            return axes
+                .iter()
+                .map(|&axis| 0..axis)
+                .multi_cartesian_product()
+                .map(|indices| self.config.parameter_set_at(&strategy_params_names, &indices))
+                .filter(|params| self.config.is_valid_combination(params))
+                .collect();
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut samples = Vec::with_capacity(self.budget);
+        let mut seen: std::collections::HashSet<u64> = std::collections::HashSet::new();
+
+        // Duplicates and parameter sets breaking the constraints are replaced by the next batch.
+        for _ in 0..MAX_SAMPLING_ROUNDS {
+            let missing = self.budget - samples.len();
+            if missing == 0 {
+                break;
+            }
+            let batch = match self.method {
+                SamplingMethod::Random => random_indices(&axes, missing, &mut rng),
+                SamplingMethod::LatinHypercube => latin_hypercube_indices(&axes, missing, &mut rng),
+            };
+            for indices in batch {
+                let params = self.config.parameter_set_at(&strategy_params_names, &indices);
+                if samples.len() < self.budget
+                    && self.config.is_valid_combination(&params)
+                    && seen.insert(hash_parameter_set(&params))
+                {
+                    samples.push(params);
+                }
+            }
+        }
+
+        samples
+    }
+
+}
+
+impl Default for SamplingOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws `count` index vectors, each index uniform over its axis.
+fn random_indices(axes: &[usize], count: usize, rng: &mut StdRng) -> Vec<Vec<usize>> {
+    (0..count)
+        .map(|_| axes.iter().map(|&axis| rng.gen_range(0..axis)).collect())
+        .collect()
+}
+
+/// Draws `count` index vectors as a Latin hypercube: on every axis, the i-th stratum of
+/// `[0, 1)` (of width `1 / count`) holds exactly one point, mapped onto the values of the axis.
+fn latin_hypercube_indices(axes: &[usize], count: usize, rng: &mut StdRng) -> Vec<Vec<usize>> {
+    let columns: Vec<Vec<usize>> = axes
+        .iter()
+        .map(|&axis| {
+            let mut column: Vec<usize> = (0..count)
+                .map(|stratum| {
+                    let position = (stratum as f64 + rng.gen_range(0.0..1.0)) / count as f64;
+                    ((position * axis as f64) as usize).min(axis - 1)
+                })
+                .collect();
+            column.shuffle(rng);
+            column
+        })
+        .collect();
+
+    (0..count)
+        .map(|sample| columns.iter().map(|column| column[sample]).collect())
+        .collect()
+}
+
+// --- BAYESIAN OPTIMIZER ---
+
+/// Configuration for the Bayesian optimizer.
+#[derive(Debug, Clone)]
+pub struct BayesianConfig {
+    budget: usize,
+    batch_size: usize,
+    initial_samples: usize,
+    gamma: f64,
+    candidates: usize,
+    patience: Option<usize>,
+    min_improvement: f64,
+    seed: u64,
+}
+
+impl BayesianConfig {
+    /// Creates a BayesianConfig from the provided BayesianParams.
+    /// # Arguments
+    /// * `bayesian_params` - The settings of the optimizer.
+    /// * `default_batch_size` - The batch size used when `batch_size` is not set (usually the number of threads).
+    pub fn from_settings(bayesian_params: &settings::BayesianParams, default_batch_size: usize) -> Self {
+        let batch_size = bayesian_params.batch_size.unwrap_or(default_batch_size).max(1);
+        Self {
+            budget: bayesian_params.budget,
+            batch_size,
+            initial_samples: bayesian_params.initial_samples
+                .unwrap_or((bayesian_params.budget / 5).max(batch_size))
+                .min(bayesian_params.budget),
+            gamma: bayesian_params.gamma.unwrap_or(0.25),
+            candidates: bayesian_params.candidates.unwrap_or(64),
+            patience: bayesian_params.patience,
+            min_improvement: bayesian_params.min_improvement.unwrap_or(0.0),
+            seed: bayesian_params.seed.unwrap_or_else(rand::random),
+        }
+    }
+
+    /// Returns the maximum number of evaluations.
+    pub fn get_budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Returns the number of parameter sets proposed per batch.
+    pub fn get_batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Returns the seed of the optimizer.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+}
+
+/// Bayesian optimizer based on a tree-structured Parzen estimator (TPE).
+/// Every parameter is an axis of value indices, as in `SamplingOptimizer`. After a Latin hypercube
+/// initial design, the evaluated parameter sets are split into the best `gamma` share ("good") and the rest;
+/// on every axis a Parzen density is fitted to the good indices (`l`) and to the others (`g`).
+/// Each proposed parameter set is the candidate drawn from `l` with the highest `l / g`.
+pub struct BayesianOptimizer {
+    config: BayesianConfig,
+    optimization_config: OptimizationConfig,
+}
+
+impl BayesianOptimizer {
+    /// Creates a new BayesianOptimizer (empty budget, batches of one) with a random seed.
+    pub fn new() -> Self {
+        Self {
+            config: BayesianConfig {
+                budget: 0,
+                batch_size: 1,
+                initial_samples: 0,
+                gamma: 0.25,
+                candidates: 64,
+                patience: None,
+                min_improvement: 0.0,
+                seed: rand::random(),
+            },
+            optimization_config: OptimizationConfig::new(),
+        }
+    }
+
+    /// Sets the Bayesian optimizer configuration.
+    pub fn with_bayesian_config(mut self, config: BayesianConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the optimization configuration.
+    pub fn with_optimization_config(mut self, opt_config: OptimizationConfig) -> Self {
+        self.optimization_config = opt_config;
+        self
+    }
+
+    /// Runs the Bayesian optimization.
+    /// Stops when the budget is spent, when no parameter set is left to evaluate, or after `patience` batches
+    /// in a row that do not improve the best fitness by more than `min_improvement`.
+    /// The same seed and fitness scores give the same proposals.
+    /// # Arguments
+    /// * `evaluate_batch` - Evaluates a batch of parameter sets (in parallel) and returns their fitness scores in the same order, higher being better.
+    /// # Returns
+    /// * The evaluated parameter sets with their fitness scores, in evaluation order.
+    pub fn run<E>(&self, evaluate_batch: E) -> anyhow::Result<Vec<(ParameterSet, f64)>>
+    where
+        E: FnMut(&[ParameterSet]) -> Vec<f64>,
+    {
+        self.run_with_batch_hook(evaluate_batch, |_| false)
+    }
+
+    /// Runs the Bayesian optimization, calling `before_batch` before every batch is evaluated.
+    /// The hook returns `true` when the fitness function changed since the previous batch
+    /// (e.g. a new build of the strategy was loaded): the observations are then dropped, so the model is fitted
+    /// only to scores of the new function, and parameter sets evaluated earlier may be proposed again.
+    /// # Arguments
+    /// * `evaluate_batch` - Evaluates a batch of parameter sets (in parallel) and returns their fitness scores in the same order, higher being better.
+    /// * `before_batch` - Called with the batch index; returns `true` if the fitness function changed.
+    /// # Returns
+    /// * The evaluated parameter sets with their fitness scores, in evaluation order.
+    pub fn run_with_batch_hook<E, H>(&self, mut evaluate_batch: E, mut before_batch: H) -> anyhow::Result<Vec<(ParameterSet, f64)>>
+    where
+        E: FnMut(&[ParameterSet]) -> Vec<f64>,
+        H: FnMut(usize) -> bool,
+    {
+        let (strategy_params_names, axes) = self.optimization_config.sampling_axes();
+        let mut history: Vec<(ParameterSet, f64)> = Vec::new();
+        if axes.contains(&0) || self.config.budget == 0 {
+            return anyhow::Ok(history);
+        }
+
+        // --- Initial design ---
+        // A parameter space no larger than the budget is evaluated whole.
+        let space_size = axes.iter().try_fold(1usize, |size, &axis| size.checked_mul(axis));
+        let initial_budget = match space_size {
+            Some(space_size) if space_size <= self.config.budget => self.config.budget,
+            _ => self.config.initial_samples,
+        };
+        let initial_samples = SamplingOptimizer::new()
+            .with_optimization_config(self.optimization_config.clone())
+            .with_method(SamplingMethod::LatinHypercube)
+            .with_budget(initial_budget)
+            .with_seed(self.config.seed)
+            .generate_samples();
+
+        let mut observations: Vec<(Vec<usize>, f64)> = Vec::new();
+        let mut seen: std::collections::HashSet<Vec<usize>> = std::collections::HashSet::new();
+        let mut best_fitness = f64::NEG_INFINITY;
+        let mut batch: Vec<(Vec<usize>, ParameterSet)> = initial_samples
+            .into_iter()
+            .filter_map(|params| {
+                self.optimization_config
+                    .value_indices(&strategy_params_names, &params)
+                    .map(|indices| (indices, params))
+            })
+            .collect();
+
+        let mut rng = StdRng::seed_from_u64(self.config.seed);
+        let mut batch_idx = 0;
+        let mut stale_batches = 0;
+
+        while !batch.is_empty() {
+            if before_batch(batch_idx) && !observations.is_empty() {
+                observations.clear();
+                seen = batch.iter().map(|(indices, _)| indices.clone()).collect();
+                best_fitness = f64::NEG_INFINITY;
+                stale_batches = 0;
+            }
+            let batch_params: Vec<ParameterSet> = batch.iter().map(|(_, params)| params.clone()).collect();
+            let scores = evaluate_batch(&batch_params);
+            anyhow::ensure!(
+                scores.len() == batch_params.len(),
+                "Bayesian optimizer got {} fitness scores for a batch of {} parameter sets",
+                scores.len(),
+                batch_params.len(),
+            );
+
+            // NaN scores rank last.
+            let mut batch_best = f64::NEG_INFINITY;
+            for ((indices, params), fitness) in batch.drain(..).zip(scores) {
+                let fitness = if fitness.is_nan() { f64::NEG_INFINITY } else { fitness };
+                batch_best = batch_best.max(fitness);
+                seen.insert(indices.clone());
+                observations.push((indices, fitness));
+                history.push((params, fitness));
+            }
+
+            if batch_best > best_fitness + self.config.min_improvement {
+                stale_batches = 0;
+            } else {
+                stale_batches += 1;
+            }
+            best_fitness = best_fitness.max(batch_best);
+
+            println!(
+                "Batch {}: {} of {} evaluations, Batch Best Fitness= {:.3}, Best Fitness= {:.3}",
+                batch_idx, history.len(), self.config.budget, batch_best, best_fitness
+            );
+            batch_idx += 1;
+
+            if history.len() >= self.config.budget {
+                break;
+            }
+            // The initial design does not count towards convergence.
+            if batch_idx > 1 && self.config.patience.is_some_and(|patience| stale_batches >= patience) {
+                println!("Bayesian optimization converged: no improvement in {} batches", stale_batches);
+                break;
+            }
+
+            let count = self.config.batch_size.min(self.config.budget - history.len());
+            batch = self.propose_batch(&strategy_params_names, &axes, &observations, &mut seen, count, &mut rng);
+            if batch.is_empty() {
+                println!("Bayesian optimization stopped: every parameter set has been evaluated");
+            }
+        }
+
+        anyhow::Ok(history)
+    }
+
+    /// Proposes up to `count` parameter sets not evaluated yet, each the best of `candidates` draws from the good densities.
+    /// Candidates breaking the parameter constraints are skipped; fewer sets are returned when none are left.
+    fn propose_batch(
+        &self,
+        strategy_params_names: &[&String],
+        axes: &[usize],
+        observations: &[(Vec<usize>, f64)],
+        seen: &mut std::collections::HashSet<Vec<usize>>,
+        count: usize,
+        rng: &mut StdRng,
+    ) -> Vec<(Vec<usize>, ParameterSet)> {
+        // --- Split the observations into good and bad ---
+        let mut ranked: Vec<&(Vec<usize>, f64)> = observations.iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let good_count = ((ranked.len() as f64 * self.config.gamma).ceil() as usize).clamp(1, ranked.len().max(1));
+        let (good, bad) = ranked.split_at(good_count.min(ranked.len()));
+
+        // --- Parzen densities per axis ---
+        let densities: Vec<(Vec<f64>, Vec<f64>)> = axes
+            .iter()
+            .enumerate()
+            .map(|(axis_idx, &axis)| {
+                let good_indices: Vec<usize> = good.iter().map(|(indices, _)| indices[axis_idx]).collect();
+                let bad_indices: Vec<usize> = bad.iter().map(|(indices, _)| indices[axis_idx]).collect();
+                (parzen_density(axis, &good_indices), parzen_density(axis, &bad_indices))
+            })
+            .collect();
+        let samplers: Vec<rand::distributions::WeightedIndex<f64>> = densities
+            .iter()
+            .map(|(good_density, _)| rand::distributions::WeightedIndex::new(good_density).expect("Parzen densities are positive"))
+            .collect();
+
+        // --- Best candidate per proposal ---
+        let mut batch = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut proposal = None;
+            for _ in 0..MAX_SAMPLING_ROUNDS {
+                let mut candidates: Vec<(f64, Vec<usize>)> = (0..self.config.candidates)
+                    .map(|_| {
+                        let indices: Vec<usize> = samplers.iter().map(|sampler| sampler.sample(rng)).collect();
+                        let score = indices
+                            .iter()
+                            .zip(&densities)
+                            .map(|(&index, (good_density, bad_density))| good_density[index].ln() - bad_density[index].ln())
+                            .sum();
+                        (score, indices)
+                    })
+                    .collect();
+                candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+                proposal = candidates.into_iter().find_map(|(_, indices)| {
+                    if seen.contains(&indices) {
+                        return None;
+                    }
+                    let params = self.optimization_config.parameter_set_at(strategy_params_names, &indices);
+                    self.optimization_config.is_valid_combination(&params).then_some((indices, params))
+                });
+                if proposal.is_some() {
+                    break;
+                }
+            }
+
+            let Some((indices, params)) = proposal else {
+                break;
+            };
+            seen.insert(indices.clone());
+            batch.push((indices, params));
+        }
+
+        batch
+    }
+
+}
+
+impl Default for BayesianOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parzen density over the `axis` value indices of one axis: a uniform prior (weighing as one observation)
+/// plus a Gaussian kernel at every observed index. The bandwidth shrinks as observations accumulate.
+fn parzen_density(axis: usize, observed: &[usize]) -> Vec<f64> {
+    let bandwidth = (axis as f64 / 5.0 * (observed.len().max(1) as f64).powf(-0.2)).max(0.5);
+    let mut density = vec![1.0 / axis as f64; axis];
+    for &center in observed {
+        let kernel: Vec<f64> = (0..axis)
+            .map(|index| (-0.5 * ((index as f64 - center as f64) / bandwidth).powi(2)).exp())
+            .collect();
+        let kernel_sum: f64 = kernel.iter().sum();
+        for (value, weight) in density.iter_mut().zip(kernel) {
+            *value += weight / kernel_sum;
+        }
+    }
+    let total: f64 = density.iter().sum();
+    density.iter_mut().for_each(|value| *value /= total);
+    density
+}
+
+// --- NSGA-II MULTI-OBJECTIVE OPTIMIZER ---
+
+/// Configuration for the NSGA-II optimizer.
+#[derive(Debug, Clone)]
+pub struct Nsga2Config {
+    population_size: usize,
+    max_generations: usize,
+    p_crossover: f64,
+    p_mutation: f64,
+    objectives_count: usize,
+    seed: u64,
+}
+
+impl Nsga2Config {
+    /// Creates a Nsga2Config from the provided Nsga2Params.
+    pub fn from_settings(nsga2_params: &settings::Nsga2Params) -> Self {
+        Self {
+            population_size: nsga2_params.population_size,
+            max_generations: nsga2_params.max_generations,
+            p_crossover: nsga2_params.p_crossover,
+            p_mutation: nsga2_params.p_mutation,
+            objectives_count: nsga2_params.objectives.len(),
+            seed: nsga2_params.seed.unwrap_or_else(rand::random),
+        }
+    }
+
+    /// Returns the number of parameter sets per generation.
+    pub fn get_population_size(&self) -> usize {
+        self.population_size
+    }
+
+    /// Returns the number of generations.
+    pub fn get_max_generations(&self) -> usize {
+        self.max_generations
+    }
+
+    /// Returns the seed of the optimizer.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+}
+
+/// A parameter set of the Pareto front found by NSGA-II.
+#[derive(Debug, Clone)]
+pub struct ParetoPoint {
+    parameters: ParameterSet,
+    /// Objective scores, higher being better.
+    objectives: Vec<f64>,
+    /// Crowding distance within the front; infinite for the extreme points of an objective.
+    crowding_distance: f64,
+}
+
+impl ParetoPoint {
+    /// Returns the parameter set.
+    pub fn get_parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    /// Returns the objective scores, in the order of the objectives, higher being better.
+    pub fn get_objectives(&self) -> &[f64] {
+        &self.objectives
+    }
+
+    /// Returns the crowding distance of the point within the Pareto front.
+    pub fn get_crowding_distance(&self) -> f64 {
+        self.crowding_distance
+    }
+
+}
+
+/// NSGA-II multi-objective optimizer.
+/// Instead of blending the objectives into one score, parameter sets are ranked by Pareto dominance
+/// (non-dominated sorting) and, within a front, by crowding distance so the population spreads along the front.
+/// Parameter sets are handled as value indices, as in `SamplingOptimizer`; each one is evaluated once.
+pub struct Nsga2Optimizer {
+    config: Nsga2Config,
+    optimization_config: OptimizationConfig,
+}
+
+impl Nsga2Optimizer {
+    /// Creates a new Nsga2Optimizer (empty population, one objective) with a random seed.
+    pub fn new() -> Self {
+        Self {
+            config: Nsga2Config {
+                population_size: 0,
+                max_generations: 0,
+                p_crossover: 0.0,
+                p_mutation: 0.0,
+                objectives_count: 1,
+                seed: rand::random(),
+            },
+            optimization_config: OptimizationConfig::new(),
+        }
+    }
+
+    /// Sets the NSGA-II configuration.
+    pub fn with_nsga2_config(mut self, config: Nsga2Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the optimization configuration.
+    pub fn with_optimization_config(mut self, opt_config: OptimizationConfig) -> Self {
+        self.optimization_config = opt_config;
+        self
+    }
+
+    /// Runs NSGA-II.
+    /// The first generation is a Latin hypercube sample; every next one is selected from the previous generation
+    /// and its offspring (binary tournament, uniform crossover, random value mutation) by front, then crowding distance.
+    /// The same seed and objective scores give the same generations.
+    /// # Arguments
+    /// * `evaluate_batch` - Evaluates a batch of parameter sets (in parallel) and returns one vector of objective scores per set, in the same order, higher being better.
+    /// # Returns
+    /// * The Pareto front of every evaluated parameter set, with crowding distances.
+    pub fn run<E>(&self, evaluate_batch: E) -> anyhow::Result<Vec<ParetoPoint>>
+    where
+        E: FnMut(&[ParameterSet]) -> Vec<Vec<f64>>,
+    {
+        self.run_with_generation_hook(evaluate_batch, |_| false)
+    }
+
+    /// Runs NSGA-II, calling `before_generation` before every generation is evaluated.
+    /// The hook returns `true` when the objective functions changed since the previous generation
+    /// (e.g. a new build of the strategy was loaded): every archived score is then dropped and the population
+    /// is evaluated again, so the selection and the Pareto front never mix scores of the old and new functions.
+    /// # Arguments
+    /// * `evaluate_batch` - Evaluates a batch of parameter sets (in parallel) and returns one vector of objective scores per set, in the same order, higher being better.
+    /// * `before_generation` - Called with the generation index; returns `true` if the objective functions changed.
+    /// # Returns
+    /// * The Pareto front of every parameter set evaluated since the last change, with crowding distances.
+    pub fn run_with_generation_hook<E, H>(&self, mut evaluate_batch: E, mut before_generation: H) -> anyhow::Result<Vec<ParetoPoint>>
+    where
+        E: FnMut(&[ParameterSet]) -> Vec<Vec<f64>>,
+        H: FnMut(usize) -> bool,
+    {
+        let (strategy_params_names, axes) = self.optimization_config.sampling_axes();
+        if axes.contains(&0) || self.config.population_size == 0 {
+            return anyhow::Ok(Vec::new());
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.config.seed);
+        // Objective scores of every evaluated parameter set, by value indices
+        let mut archive: std::collections::HashMap<Vec<usize>, (ParameterSet, Vec<f64>)> = std::collections::HashMap::new();
+        let mut evaluation_order: Vec<Vec<usize>> = Vec::new();
+
+        let mut population: Vec<Vec<usize>> = SamplingOptimizer::new()
+            .with_optimization_config(self.optimization_config.clone())
+            .with_method(SamplingMethod::LatinHypercube)
+            .with_budget(self.config.population_size)
+            .with_seed(self.config.seed)
+            .generate_samples()
+            .iter()
+            .filter_map(|params| self.optimization_config.value_indices(&strategy_params_names, params))
+            .collect();
+
+        for gen_idx in 0..self.config.max_generations {
+            if population.is_empty() {
+                break;
+            }
+            if before_generation(gen_idx) && !archive.is_empty() {
+                archive.clear();
+                evaluation_order.clear();
+                self.evaluate_new(&strategy_params_names, &population, &mut archive, &mut evaluation_order, &mut evaluate_batch)?;
+            }
+            if gen_idx > 0 {
+                let offspring = self.make_offspring(&strategy_params_names, &axes, &population, &archive, &mut rng);
+                population.extend(offspring);
+            }
+            self.evaluate_new(&strategy_params_names, &population, &mut archive, &mut evaluation_order, &mut evaluate_batch)?;
+
+            // --- Environmental selection: whole fronts, the last one cut by crowding distance ---
+            let objectives = objectives_of(&population, &archive);
+            let mut survivors = Vec::with_capacity(self.config.population_size);
+            for front in non_dominated_fronts(&objectives) {
+                let room = self.config.population_size - survivors.len();
+                if front.len() <= room {
+                    survivors.extend(front);
+                } else {
+                    let distances = crowding_distances(&objectives, &front);
+                    let mut order: Vec<usize> = (0..front.len()).collect();
+                    order.sort_by(|&a, &b| distances[b].total_cmp(&distances[a]));
+                    survivors.extend(order.into_iter().take(room).map(|position| front[position]));
+                }
+                if survivors.len() == self.config.population_size {
+                    break;
+                }
+            }
+            survivors.sort_unstable();
+            population = survivors.into_iter().map(|index| population[index].clone()).collect();
+
+            let first_front_size = non_dominated_fronts(&objectives_of(&population, &archive))
+                .first()
+                .map_or(0, |front| front.len());
+            println!(
+                "Generation {}: First Front= {} of {}, Evaluations= {}",
+                gen_idx, first_front_size, population.len(), archive.len()
+            );
+        }
+
+        // --- Pareto front of every evaluated parameter set ---
+        let objectives = objectives_of(&evaluation_order, &archive);
+        let Some(front) = non_dominated_fronts(&objectives).into_iter().next() else {
+            return anyhow::Ok(Vec::new());
+        };
+        let distances = crowding_distances(&objectives, &front);
+        let mut pareto_front: Vec<ParetoPoint> = front
+            .iter()
+            .zip(distances)
+            .map(|(&index, crowding_distance)| ParetoPoint {
+                parameters: archive[&evaluation_order[index]].0.clone(),
+                objectives: objectives[index].clone(),
+                crowding_distance,
+            })
+            .collect();
+        pareto_front.sort_by(|a, b| b.objectives[0].total_cmp(&a.objectives[0]));
+
+        println!("NSGA-II Pareto front: {} of {} evaluated parameter sets", pareto_front.len(), archive.len());
+        anyhow::Ok(pareto_front)
+    }
+
+    /// Evaluates the parameter sets of `population` not evaluated yet and stores their objective scores in `archive`.
+    fn evaluate_new<E>(
+        &self,
+        strategy_params_names: &[&String],
+        population: &[Vec<usize>],
+        archive: &mut std::collections::HashMap<Vec<usize>, (ParameterSet, Vec<f64>)>,
+        evaluation_order: &mut Vec<Vec<usize>>,
+        evaluate_batch: &mut E,
+    ) -> anyhow::Result<()>
+    where
+        E: FnMut(&[ParameterSet]) -> Vec<Vec<f64>>,
+    {
+        let new_indices: Vec<&Vec<usize>> = population.iter().filter(|indices| !archive.contains_key(*indices)).collect();
+        if new_indices.is_empty() {
+            return anyhow::Ok(());
+        }
+        let batch: Vec<ParameterSet> = new_indices
+            .iter()
+            .map(|indices| self.optimization_config.parameter_set_at(strategy_params_names, indices))
+            .collect();
+        let scores = evaluate_batch(&batch);
+        anyhow::ensure!(
+            scores.len() == batch.len() && scores.iter().all(|objectives| objectives.len() == self.config.objectives_count),
+            "NSGA-II expects {} objective scores for each of the {} parameter sets of a batch",
+            self.config.objectives_count,
+            batch.len(),
+        );
+
+        // NaN scores rank last.
+        for ((indices, params), objectives) in new_indices.into_iter().zip(batch).zip(scores) {
+            let objectives = objectives
+                .into_iter()
+                .map(|score| if score.is_nan() { f64::NEG_INFINITY } else { score })
+                .collect();
+            evaluation_order.push(indices.clone());
+            archive.insert(indices.clone(), (params, objectives));
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Creates up to `population_size` children of the population that are not in it, and satisfy the parameter schema.
+    /// Children evaluated in earlier generations are allowed and reuse their objective scores.
+    fn make_offspring(
+        &self,
+        strategy_params_names: &[&String],
+        axes: &[usize],
+        population: &[Vec<usize>],
+        archive: &std::collections::HashMap<Vec<usize>, (ParameterSet, Vec<f64>)>,
+        rng: &mut StdRng,
+    ) -> Vec<Vec<usize>> {
+        // Front rank and crowding distance of every parameter set, for the tournaments
+        let objectives = objectives_of(population, archive);
+        let mut ranks = vec![0; population.len()];
+        let mut distances = vec![0.0; population.len()];
+        for (rank, front) in non_dominated_fronts(&objectives).into_iter().enumerate() {
+            for (&index, distance) in front.iter().zip(crowding_distances(&objectives, &front)) {
+                ranks[index] = rank;
+                distances[index] = distance;
+            }
+        }
+        let tournament = |rng: &mut StdRng| {
+            let a = rng.gen_range(0..population.len());
+            let b = rng.gen_range(0..population.len());
+            if ranks[a] < ranks[b] || (ranks[a] == ranks[b] && distances[a] >= distances[b]) { a } else { b }
+        };
+
+        let mut offspring: Vec<Vec<usize>> = Vec::with_capacity(self.config.population_size);
+        let mut attempts = 0;
+        while offspring.len() < self.config.population_size && attempts < self.config.population_size * MAX_SAMPLING_ROUNDS {
+            attempts += 1;
+            let parent_a = &population[tournament(rng)];
+            let parent_b = &population[tournament(rng)];
+
+            let mut child: Vec<usize> = if rng.gen_bool(self.config.p_crossover) {
+                parent_a.iter().zip(parent_b).map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b }).collect()
+            } else {
+                parent_a.clone()
+            };
+            for (index, &axis) in child.iter_mut().zip(axes) {
+                if rng.gen_bool(self.config.p_mutation) {
+                    *index = rng.gen_range(0..axis);
+                }
+            }
+
+            if population.contains(&child) || offspring.contains(&child) {
+                continue;
+            }
+            // A child breaking the parameter constraints is not worth a backtest.
+            if !self.optimization_config.is_valid_combination(&self.optimization_config.parameter_set_at(strategy_params_names, &child)) {
+                continue;
+            }
+            offspring.push(child);
+        }
+
+        offspring
+    }
+
+}
+
+impl Default for Nsga2Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Objective scores of the evaluated parameter sets `population`.
+fn objectives_of(
+    population: &[Vec<usize>],
+    archive: &std::collections::HashMap<Vec<usize>, (ParameterSet, Vec<f64>)>,
+) -> Vec<Vec<f64>> {
+    population.iter().map(|indices| archive[indices].1.clone()).collect()
+}
+
+/// Returns `true` if `a` Pareto-dominates `b`: no objective is worse and at least one is better (higher being better).
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+/// Fast non-dominated sorting: splits the points into fronts (lists of indices), the first one being the Pareto front.
+pub fn non_dominated_fronts(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); objectives.len()];
+    let mut domination_count = vec![0usize; objectives.len()];
+    for (i, a) in objectives.iter().enumerate() {
+        for (j, b) in objectives.iter().enumerate() {
+            if dominates(a, b) {
+                dominated[i].push(j);
+            } else if dominates(b, a) {
+                domination_count[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut current: Vec<usize> = (0..objectives.len()).filter(|&i| domination_count[i] == 0).collect();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &i in &current {
+            for &j in &dominated[i] {
+                domination_count[j] -= 1;
+                if domination_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        fronts.push(current);
+        current = next;
+    }
+    fronts
+}
+
+/// Crowding distance of every point of `front`: the sum over the objectives of the normalized distance
+/// between its two neighbours. The extreme points of each objective get an infinite distance.
+pub fn crowding_distances(objectives: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    if front.len() <= 2 {
+        return vec![f64::INFINITY; front.len()];
+    }
+
+    let mut distances = vec![0.0; front.len()];
+    let columns = (0..objectives[front[0]].len())
+        .map(|objective| front.iter().map(|&index| objectives[index][objective]).collect::<Vec<f64>>());
+    for column in columns {
+        let value = |position: usize| column[position];
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+        let span = value(last) - value(first);
+        if span <= 0.0 || !span.is_finite() {
+            continue;
+        }
+        for window in order.windows(3) {
+            distances[window[1]] += (value(window[2]) - value(window[0])) / span;
+        }
+    }
+    distances
+}
+
 // --- GENETIC ALGORITHM OPTIMIZER ---
 
 /// Statistics for a single generation of the Genetic Algorithm.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GAStatsPerGeneration {
     best_fitness: f64,
     worst_fitness: f64,
     mean_fitness: f64,
     best_chromosome_id: Vec<String>,
     generation: usize, 
+    /// Content hash of the strategy build that evaluated the generation (set with hot reload).
+    strategy_build: Option<String>,
 }
 
 impl GAStatsPerGeneration {
@@ -397,6 +1376,7 @@ impl GAStatsPerGeneration {
             mean_fitness: 0.0,
             best_chromosome_id: Vec::new(),
             generation: 0,
+            strategy_build: None,
         }
     }
 
@@ -430,6 +1410,22 @@ impl GAStatsPerGeneration {
         self
     }
 
+    /// Sets the strategy build that evaluated this generation.
+    pub fn with_strategy_build(mut self, strategy_build: Option<String>) -> Self {
+        self.strategy_build = strategy_build;
+        self
+    }
+
+    /// Returns the generation number.
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the best fitness score of the generation.
+    pub fn get_best_fitness(&self) -> f64 {
+        self.best_fitness
+    }
+
 }
 
 /// Configuration for the Genetic Algorithm.
@@ -441,6 +1437,12 @@ pub struct GAConfig {
     p_mutation: f64,
     fitness_metric: settings::FitnessValue,
     fitness_direction: String,
+    elite_count: usize,
+    mutation_sigma: f64,
+    adaptive_mutation: bool,
+    patience: Option<usize>,
+    min_improvement: f64,
+    seed: u64,
 }
 
 impl GAConfig {
@@ -453,6 +1455,12 @@ impl GAConfig {
             p_mutation: 0.0,
             fitness_metric: settings::FitnessValue::default(),
             fitness_direction: String::new(),
+            elite_count: 1,
+            mutation_sigma: 1.0,
+            adaptive_mutation: false,
+            patience: None,
+            min_improvement: 0.0,
+            seed: rand::random(),
         }
     }
 
@@ -465,6 +1473,12 @@ impl GAConfig {
             p_mutation: ga_params.p_mutation,
             fitness_metric: ga_params.fitness_params.fitness_value.clone(),
             fitness_direction: ga_params.fitness_params.fitness_direction.clone(),
+            elite_count: ga_params.elite_count.unwrap_or(1),
+            mutation_sigma: ga_params.mutation_sigma.unwrap_or(1.0),
+            adaptive_mutation: ga_params.adaptive_mutation.unwrap_or(false),
+            patience: ga_params.patience,
+            min_improvement: ga_params.min_improvement.unwrap_or(0.0),
+            seed: ga_params.seed.unwrap_or_else(rand::random),
         }
     }
 
@@ -478,6 +1492,48 @@ impl GAConfig {
         &self.fitness_direction
     }
 
+    /// Returns the seed of the GA.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+}
+
+/// State of a Genetic Algorithm run between two generations.
+/// Saved by checkpoints: a run resumed from it continues exactly as the interrupted run would have.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GAState {
+    /// Index of the next generation to evaluate.
+    next_generation: usize,
+    /// `true` when the run has ended (last generation or early stopping).
+    finished: bool,
+    /// Population of the next generation.
+    population: Vec<ParameterSet>,
+    /// Cached fitness scores, by parameter set hash.
+    chromosome_bank: Vec<(u64, Option<f64>)>,
+    /// Random generator of the run.
+    rng: rand_chacha::ChaCha12Rng,
+    /// Current mutation rate (adaptive mutation).
+    p_mutation: f64,
+    /// Best fitness so far (early stopping).
+    best_fitness: Option<f64>,
+    /// Generations without improvement (early stopping).
+    stale_generations: usize,
+    /// Statistics of the generations done.
+    stats: Vec<GAStatsPerGeneration>,
+}
+
+impl GAState {
+    /// Returns the index of the next generation to evaluate.
+    pub fn get_next_generation(&self) -> usize {
+        self.next_generation
+    }
+
+    /// Returns `true` when the run has ended.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
 }
 
 /// The main Genetic Algorithm optimizer.
@@ -486,6 +1542,8 @@ pub struct GeneticAlgorythm {
     optimization_config: OptimizationConfig,
     chromosome_bank: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<u64, Option<f64>>>>,
     populations: Vec<Vec<ParameterSet>>,
+    /// State to resume the run from (see `with_state`).
+    resume_state: Option<GAState>,
 }
 
 impl GeneticAlgorythm {
@@ -496,6 +1554,7 @@ impl GeneticAlgorythm {
             optimization_config: OptimizationConfig::new(),
             chromosome_bank: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             populations: Vec::new(),
+            resume_state: None,
         }
     }
 
@@ -511,10 +1570,16 @@ impl GeneticAlgorythm {
         self
     }
 
+    /// Sets the state of an interrupted run (from a checkpoint) to resume from.
+    pub fn with_state(mut self, state: Option<GAState>) -> Self {
+        self.resume_state = state;
+        self
+    }
+
     /// Runs the genetic algorithm optimization.
     /// # Arguments
     /// * `initial_strategy_settings` - The initial strategy settings.
-    /// * `evaluate` - A function that takes a ParameterSet and returns a fitness score.
+    /// * `evaluate` - A function that takes a ParameterSet and returns a fitness score (higher is better: "min" metrics are negated).
     /// # Returns
     /// * A vector of GAStatsPerGeneration objects.
     pub fn run<F>(
@@ -524,17 +1589,86 @@ impl GeneticAlgorythm {
     ) -> anyhow::Result<Vec<GAStatsPerGeneration>>
     where
         F: Fn(&ParameterSet) -> f64 + Send + Sync + Clone + 'static,
+    {
+        self.run_with_generation_hook(initial_strategy_settings, evaluate, |_| false)
+    }
+
+    /// Runs the genetic algorithm, calling `before_generation` before every generation is evaluated.
+    /// The hook returns `true` when the fitness function changed since the previous generation
+    /// (e.g. a new build of the strategy was loaded): the cached fitness scores are then dropped,
+    /// so chromosomes seen earlier are evaluated again instead of reusing scores of the old fitness function.
+    /// # Arguments
+    /// * `initial_strategy_settings` - The initial strategy settings.
+    /// * `evaluate` - A function that takes a ParameterSet and returns a fitness score (higher is better: "min" metrics are negated).
+    /// * `before_generation` - Called with the generation index; returns `true` if the fitness function changed.
+    /// # Returns
+    /// * A vector of GAStatsPerGeneration objects.
+    pub fn run_with_generation_hook<F, H>(
+        &mut self,
+        initial_strategy_settings: &settings::StrategySettings,
+        evaluate: F,
+        before_generation: H,
+    ) -> anyhow::Result<Vec<GAStatsPerGeneration>>
+    where
+        F: Fn(&ParameterSet) -> f64 + Send + Sync + Clone + 'static,
+        H: FnMut(usize) -> bool,
+    {
+        self.run_with_hooks(initial_strategy_settings, evaluate, before_generation, |_| anyhow::Ok(()))
+    }
+
+    /// Runs the genetic algorithm like `run_with_generation_hook`, and calls `after_generation` with the state
+    /// of the run once every generation is done (e.g. to checkpoint it). The run resumes from the state set
+    /// with `with_state`, if any.
+    /// # Arguments
+    /// * `initial_strategy_settings` - The initial strategy settings.
+    /// * `evaluate` - A function that takes a ParameterSet and returns a fitness score (higher is better: "min" metrics are negated).
+    /// * `before_generation` - Called with the generation index; returns `true` if the fitness function changed.
+    /// * `after_generation` - Called with the state of the run after each generation; an error stops the run.
+    /// # Returns
+    /// * `anyhow::Result<Vec<GAStatsPerGeneration>>` - The statistics of every generation, including resumed ones.
+    pub fn run_with_hooks<F, H, A>(
+        &mut self,
+        initial_strategy_settings: &settings::StrategySettings,
+        evaluate: F,
+        mut before_generation: H,
+        mut after_generation: A,
+    ) -> anyhow::Result<Vec<GAStatsPerGeneration>>
+    where
+        F: Fn(&ParameterSet) -> f64 + Send + Sync + Clone + 'static,
+        H: FnMut(usize) -> bool,
+        A: FnMut(&GAState) -> anyhow::Result<()>,
     {
         let threads = initial_strategy_settings.threads.unwrap_or(num_cpus::get());
-        let mut stats = Vec::new();
         let total_population = self.ga_config.population_size;
-        self.create_initial_population(total_population);
 
-        for gen_idx in 0..self.ga_config.max_generations {
+        let (mut rng, mut population, first_generation, mut p_mutation, mut best_fitness, mut stale_generations, mut stats) =
+            match self.resume_state.take() {
+                Some(state) => {
+                    if state.finished {
+                        println!("Genetic Algorithm already finished after generation # {}", state.next_generation - 1);
+                        return anyhow::Ok(state.stats);
+                    }
+                    println!("Genetic Algorithm resumed at generation # {}", state.next_generation);
+                    self.chromosome_bank.lock().unwrap().extend(state.chromosome_bank);
+                    (state.rng, state.population, state.next_generation, state.p_mutation, state.best_fitness, state.stale_generations, state.stats)
+                },
+                None => {
+                    // Every random choice of the run comes from this generator, so a seed reproduces the run.
+                    let mut rng = rand_chacha::ChaCha12Rng::seed_from_u64(self.ga_config.seed);
+                    println!("Genetic Algorithm seed: {}", self.ga_config.seed);
+                    let population = self.create_initial_population(total_population, &mut rng);
+                    (rng, population, 0, self.ga_config.p_mutation, None, 0, Vec::new())
+                },
+            };
+
+        for gen_idx in first_generation..self.ga_config.max_generations {
             println!("Generation: # {}", gen_idx);
+            if before_generation(gen_idx) {
+                self.chromosome_bank.lock().unwrap().clear();
+            }
             // Beginer population
-            let current_population = self.populations[gen_idx].clone();
-            let results = self.evaluate_population(threads, &current_population, evaluate.clone())?;
+            self.populations.push(population.clone());
+            let results = self.evaluate_population(threads, &population, evaluate.clone())?;
 
             let stat = self.calculate_generation_stats(&results, gen_idx);
             stats.push(stat.clone());
@@ -544,10 +1678,49 @@ impl GeneticAlgorythm {
                 gen_idx, stat.best_fitness, stat.mean_fitness, stat.worst_fitness
             );
 
+            // --- Stagnation: early stopping and adaptive mutation ---
+            let improved = best_fitness.is_none_or(|best| stat.best_fitness > best + self.ga_config.min_improvement);
+            if improved {
+                best_fitness = Some(stat.best_fitness);
+                stale_generations = 0;
+            } else {
+                stale_generations += 1;
+            }
+            let stopped_early = self.ga_config.patience.is_some_and(|patience| stale_generations >= patience);
+            if stopped_early {
+                println!("Genetic Algorithm stopped early: no improvement in {} generations", stale_generations);
+            } else if self.ga_config.adaptive_mutation && gen_idx > 0 {
+                p_mutation = adapt_mutation_rate(p_mutation, self.ga_config.p_mutation, improved);
+                println!("Generation {}: Mutation Rate= {:.3}", gen_idx, p_mutation);
+            }
+
             // Next Populations 
-            if gen_idx + 1 < self.ga_config.max_generations {
-                let next_population = self.tournament_selection(&results);
-                self.populations.push(next_population);
+            let finished = stopped_early || gen_idx + 1 >= self.ga_config.max_generations;
+            if !finished {
+                population = self.tournament_selection(&results, p_mutation, &mut rng);
+            }
+
+            let mut chromosome_bank: Vec<(u64, Option<f64>)> = self.chromosome_bank
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(hash, fitness)| (*hash, *fitness))
+                .collect();
+            chromosome_bank.sort_by_key(|(hash, _)| *hash);
+            after_generation(&GAState {
+                next_generation: gen_idx + 1,
+                finished,
+                population: population.clone(),
+                chromosome_bank,
+                rng: rng.clone(),
+                p_mutation,
+                best_fitness,
+                stale_generations,
+                stats: stats.clone(),
+            })?;
+
+            if finished {
+                break;
             }
         }
 
@@ -616,26 +1789,15 @@ impl GeneticAlgorythm {
         anyhow::Ok(results)
     }
 
-    /// Creates the initial population by sampling from all possible parameter combinations.
-    fn create_initial_population(&mut self, target_size: usize) {
-        let all_combinations = self.optimization_config.generate_all_combinations_vec();
-        let mut unique_pool: std::collections::HashSet<u64> = std::collections::HashSet::new();
-        let mut population = Vec::with_capacity(target_size.min(all_combinations.len()));
-        let mut rng = rand::thread_rng();
-
-        let sample_size = target_size.min(all_combinations.len());
-        let choices: Vec<_> = all_combinations.choose_multiple(&mut rng, sample_size).collect();
-
-        for param_set in choices {
-            let hash = hash_parameter_set(&param_set);
-            // println!("{}", hash);
-            if !unique_pool.contains(&hash) {
-                population.push(param_set.clone());
-                unique_pool.insert(hash);
-            }
-        }
-        
-        self.populations.push(population);
+    /// Creates the initial population by random sampling from the parameter ranges,
+    /// without materializing all possible combinations.
+    fn create_initial_population(&self, target_size: usize, rng: &mut impl rand::Rng) -> Vec<ParameterSet> {
+        SamplingOptimizer::new()
+            .with_optimization_config(self.optimization_config.clone())
+            .with_method(SamplingMethod::Random)
+            .with_budget(target_size)
+            .with_seed(rng.next_u64())
+            .generate_samples()
     }
 
     /// Creates a human-readable ID for a chromosome (parameter set) for display.
@@ -688,13 +1850,7 @@ impl GeneticAlgorythm {
     fn calculate_generation_stats(&self, results: &[(ParameterSet, f64)], gen_idx: usize) -> GAStatsPerGeneration {
         let fitness_values: Vec<f64> = results.iter().map(|(_, f)| *f).collect();
         
-        let (mean, best_fitness, worst_fitness) = self.calculate_stats(&fitness_values);
-
-        let (best, worst, _) = match self.ga_config.fitness_direction.as_str() {
-            "max" => (best_fitness, worst_fitness, std::cmp::Ordering::Greater),
-            "min" => (worst_fitness, best_fitness, std::cmp::Ordering::Less),
-            _ => (best_fitness, worst_fitness, std::cmp::Ordering::Greater),
-        };
+        let (mean, best, worst) = self.calculate_stats(&fitness_values);
 
         let best_params = results.iter()
             .find(|(_, f)| (*f - best).abs() < 1e-8)
@@ -713,42 +1869,34 @@ impl GeneticAlgorythm {
     }
 
     /// Performs crossover and mutation on two parent chromosomes to create a child.
-    fn crossover_mutation(&self, a: &ParameterSet, b: &ParameterSet) -> ParameterSet {
-        let mut new_params = Vec::new();
-
-        for ((name_a, val_a), (_, val_b)) in a.strategy_params.iter().zip(b.strategy_params.iter()) {
-            if rand::random::<f64>() < self.ga_config.p_crossover {
-                new_params.push((name_a.clone(), val_a.clone()));
-            } else {
-                new_params.push((name_a.clone(), val_b.clone()));
-            }
-        }
-
-        let pos_sizer_value = if rand::random::<f64>() < self.ga_config.p_mutation {
-            self.optimization_config.pos_sizer_value_range
-                .choose(&mut rand::thread_rng())
-                .copied()
-                .unwrap_or(a.pos_sizer_value)
-        } else {
-            a.pos_sizer_value
+    /// Each value comes from parent `a` with probability `p_crossover`, else from `b`. Each value then mutates
+    /// with probability `p_mutation`: it moves along its range by a Gaussian number of steps (at least one,
+    /// `mutation_sigma` steps of standard deviation), stopping at the ends of the range.
+    fn crossover_mutation(&self, a: &ParameterSet, b: &ParameterSet, p_mutation: f64, rng: &mut impl rand::Rng) -> ParameterSet {
+        let (strategy_params_names, axes) = self.optimization_config.sampling_axes();
+        let (Some(indices_a), Some(indices_b)) = (
+            self.optimization_config.value_indices(&strategy_params_names, a),
+            self.optimization_config.value_indices(&strategy_params_names, b),
+        ) else {
+            return a.clone();
         };
 
-        let slippage = if rand::random::<f64>() < self.ga_config.p_mutation {
-            self.optimization_config.slippage_range
-                .choose(&mut rand::thread_rng())
-                .copied()
-                .unwrap_or(a.slippage)
-        } else {
-            a.slippage
-        };
-
-        ParameterSet::new()
-            .with_strategy_params(new_params)
-            .with_pos_sizer_name(a.pos_sizer_name.clone())
-            .with_pos_sizer_value(pos_sizer_value)
-            .with_pos_sizer_additional_params(a.pos_sizer_additional_params.clone())
-            .with_slippage(slippage)
+        let child: Vec<usize> = indices_a
+            .iter()
+            .zip(&indices_b)
+            .zip(&axes)
+            .map(|((&index_a, &index_b), &axis)| {
+                let index = if rng.gen_bool(self.ga_config.p_crossover) { index_a } else { index_b };
+                if !rng.gen_bool(p_mutation) {
+                    return index;
+                }
+                let steps = (gaussian(rng) * self.ga_config.mutation_sigma).round() as i64;
+                let steps = if steps != 0 { steps } else if rng.gen_bool(0.5) { 1 } else { -1 };
+                (index as i64 + steps).clamp(0, axis as i64 - 1) as usize
+            })
+            .collect();
 
+        self.optimization_config.parameter_set_at(&strategy_params_names, &child)
     }
 
     /// Selects a parent chromosome using tournament selection.
@@ -757,49 +1905,44 @@ impl GeneticAlgorythm {
         let idx_b = rng.gen_range(0..results.len());
         let (params_a, fitness_a) = &results[idx_a];
         let (params_b, fitness_b) = &results[idx_b];
-    
-        match self.ga_config.fitness_direction.as_str() {
-            "max" => {
-                if fitness_a >= fitness_b {
-                    params_a.clone()
-                } else {
-                    params_b.clone()
-                }
-            },
-            "min" => {
-                if fitness_a <= fitness_b {
-                    params_a.clone()
-                } else {
-                    params_b.clone()
-                }
-            }
-            _ => params_a.clone(),
+
+        if fitness_a >= fitness_b {
+            params_a.clone()
+        } else {
+            params_b.clone()
         }
     }
 
     /// Performs tournament selection to create the next generation.
-    fn tournament_selection(&self, results: &[(ParameterSet, f64)]) -> Vec<ParameterSet> {
+    /// The `elite_count` best distinct chromosomes are carried over unchanged; the rest are children of tournament winners.
+    fn tournament_selection(&self, results: &[(ParameterSet, f64)], p_mutation: f64, rng: &mut impl rand::Rng) -> Vec<ParameterSet> {
         let mut next_gen = Vec::with_capacity(self.ga_config.population_size);
-        let mut rng = rand::thread_rng();
 
-        // Save bset individ
-        let best_individ = match self.ga_config.fitness_direction.as_str() {
-            "max" => results.iter().max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)),
-            "min" => results.iter().min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)),
-            _ => results.iter().max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)),
-        };
-
-        if let Some((best_params, _)) = best_individ {
-            next_gen.push(best_params.clone());
+        // Save best individs (elitism)
+        let mut ranking: Vec<&(ParameterSet, f64)> = results.iter().collect();
+        ranking.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let mut elite_hashes = std::collections::HashSet::new();
+        for (params, _) in ranking {
+            if next_gen.len() >= self.ga_config.elite_count {
+                break;
+            }
+            if elite_hashes.insert(hash_parameter_set(params)) {
+                next_gen.push(params.clone());
+            }
         }
 
         // tournament selection
         while next_gen.len() < self.ga_config.population_size {
-            let parent_a = self.choose_parent(results, &mut rng);
-            let parent_b = self.choose_parent(results, &mut rng);
+            let parent_a = self.choose_parent(results, rng);
+            let parent_b = self.choose_parent(results, rng);
 
-            let child = self.crossover_mutation(&parent_a, &parent_b);
-            next_gen.push(child);
+            let child = self.crossover_mutation(&parent_a, &parent_b, p_mutation, rng);
+            // A child breaking the parameter constraints is not worth a backtest: its parent takes its place.
+            if self.optimization_config.is_valid_combination(&child) {
+                next_gen.push(child);
+            } else {
+                next_gen.push(parent_a);
+            }
         }
 
         next_gen
@@ -807,6 +1950,20 @@ impl GeneticAlgorythm {
 
 }
 
+/// Adapts the GA mutation rate after a generation: lower (x0.8) when the best fitness improved, higher (x1.25) when it
+/// stagnated, within a quarter and four times the configured rate (at most 1.0).
+fn adapt_mutation_rate(p_mutation: f64, configured_p_mutation: f64, improved: bool) -> f64 {
+    let p_mutation = if improved { p_mutation * 0.8 } else { p_mutation * 1.25 };
+    p_mutation.clamp(configured_p_mutation / 4.0, (configured_p_mutation * 4.0).min(1.0))
+}
+
+/// Draws a standard normal value (Box-Muller transform).
+fn gaussian(rng: &mut impl rand::Rng) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen_range(0.0..1.0);
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
 /// Hashes a ParameterSet for caching fitness scores.
 fn hash_parameter_set(params: &ParameterSet) -> u64 {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
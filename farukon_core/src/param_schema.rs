@@ -0,0 +1,343 @@
+// farukon_core/src/param_schema.rs
+
+//! Parameter schema of a strategy.
+//! A strategy declares the parameters it accepts (type, bounds, default value)
+//! and the constraints between them (e.g. `short_window < long_window`).
+//! The engine checks the configured `strategy_params` against the schema before a run
+//! and the optimizers skip the combinations breaking a constraint instead of backtesting them.
+
+use anyhow::Context;
+
+/// Type of a strategy parameter, with its bounds.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ParamKind {
+    /// Integer value. Whole floats (e.g. `10.0` from a `start/end/step` range) are accepted.
+    Int {
+        #[serde(default)]
+        min: Option<i64>,
+        #[serde(default)]
+        max: Option<i64>,
+    },
+    /// Floating-point value.
+    Float {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    /// `true` or `false`.
+    Bool,
+    /// One of the listed strings.
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+impl ParamKind {
+    /// Returns `true` for `Int` and `Float`, the only kinds that can be ordered in a constraint.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, ParamKind::Int { .. } | ParamKind::Float { .. })
+    }
+
+    /// Checks one value against the type and the bounds.
+    /// # Arguments
+    /// * `value` - The value from the config.
+    /// # Returns
+    /// * `anyhow::Result<()>` - An error describing why the value does not fit.
+    pub fn check_value(&self, value: &serde_json::Value) -> anyhow::Result<()> {
+        match self {
+            ParamKind::Int { min, max } => {
+                let number = value.as_i64()
+                    .or_else(|| value.as_f64().filter(|num| num.fract() == 0.0).map(|num| num as i64))
+                    .ok_or_else(|| anyhow::anyhow!("expected an integer, got {}", value))?;
+                if let Some(min) = min && number < *min {
+                    anyhow::bail!("{} is below the minimum {}", number, min);
+                }
+                if let Some(max) = max && number > *max {
+                    anyhow::bail!("{} is above the maximum {}", number, max);
+                }
+            },
+            ParamKind::Float { min, max } => {
+                let number = value.as_f64()
+                    .ok_or_else(|| anyhow::anyhow!("expected a number, got {}", value))?;
+                if let Some(min) = min && number < *min {
+                    anyhow::bail!("{} is below the minimum {}", number, min);
+                }
+                if let Some(max) = max && number > *max {
+                    anyhow::bail!("{} is above the maximum {}", number, max);
+                }
+            },
+            ParamKind::Bool => {
+                if !value.is_boolean() {
+                    anyhow::bail!("expected true or false, got {}", value);
+                }
+            },
+            ParamKind::Enum { values } => {
+                let text = value.as_str()
+                    .ok_or_else(|| anyhow::anyhow!("expected one of {:?}, got {}", values, value))?;
+                if !values.iter().any(|allowed| allowed == text) {
+                    anyhow::bail!("expected one of {:?}, got {:?}", values, text);
+                }
+            },
+        }
+        anyhow::Ok(())
+    }
+
+}
+
+/// One parameter of a strategy.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamSpec {
+    /// Name of the parameter in `strategy_params`.
+    pub name: String,
+    pub kind: ParamKind,
+    /// Value used when the config does not set the parameter. Without it the parameter is required.
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl ParamSpec {
+    /// Creates a required parameter.
+    pub fn new(name: &str, kind: ParamKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            default: None,
+            description: None,
+        }
+    }
+
+    /// Sets the default value, making the parameter optional.
+    pub fn with_default(mut self, default: impl Into<serde_json::Value>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Sets the description shown by `list-strategies`.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+}
+
+/// Comparison of a constraint.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CompareOp {
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = "==")]
+    Equal,
+    #[serde(rename = "!=")]
+    NotEqual,
+}
+
+impl CompareOp {
+    /// The operator as written in the schema.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompareOp::Less => "<",
+            CompareOp::LessOrEqual => "<=",
+            CompareOp::Greater => ">",
+            CompareOp::GreaterOrEqual => ">=",
+            CompareOp::Equal => "==",
+            CompareOp::NotEqual => "!=",
+        }
+    }
+
+    /// Returns `true` for the operators needing numeric operands.
+    pub fn is_ordering(&self) -> bool {
+        !matches!(self, CompareOp::Equal | CompareOp::NotEqual)
+    }
+
+}
+
+/// Constraint between two parameters: `left op right`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamConstraint {
+    pub left: String,
+    pub op: CompareOp,
+    pub right: String,
+}
+
+impl ParamConstraint {
+    /// Creates the constraint `left op right`.
+    pub fn new(left: &str, op: CompareOp, right: &str) -> Self {
+        Self {
+            left: left.to_string(),
+            op,
+            right: right.to_string(),
+        }
+    }
+
+    /// Evaluates the constraint for two values.
+    /// Numbers are compared as `f64`, other values only for (in)equality.
+    fn holds(&self, left: &serde_json::Value, right: &serde_json::Value) -> bool {
+        if let (Some(left), Some(right)) = (left.as_f64(), right.as_f64()) {
+            return match self.op {
+                CompareOp::Less => left < right,
+                CompareOp::LessOrEqual => left <= right,
+                CompareOp::Greater => left > right,
+                CompareOp::GreaterOrEqual => left >= right,
+                CompareOp::Equal => left == right,
+                CompareOp::NotEqual => left != right,
+            };
+        }
+        match self.op {
+            CompareOp::Equal => left == right,
+            CompareOp::NotEqual => left != right,
+            _ => false,
+        }
+    }
+
+}
+
+impl std::fmt::Display for ParamConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.left, self.op.as_str(), self.right)
+    }
+}
+
+/// Parameters and constraints declared by a strategy.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamSchema {
+    #[serde(default)]
+    pub params: Vec<ParamSpec>,
+    #[serde(default)]
+    pub constraints: Vec<ParamConstraint>,
+}
+
+impl ParamSchema {
+    /// Creates an empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a parameter.
+    pub fn with_param(mut self, param: ParamSpec) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Adds a constraint between two parameters.
+    pub fn with_constraint(mut self, constraint: ParamConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Returns the parameter called `name`.
+    pub fn get_param(&self, name: &str) -> Option<&ParamSpec> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    /// Checks the schema itself: unique names, valid defaults and constraints between declared parameters.
+    /// # Returns
+    /// * `anyhow::Result<()>` - An error describing the first problem found.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for param in &self.params {
+            if param.name.is_empty() {
+                anyhow::bail!("Parameter schema has a parameter without a name");
+            }
+            if !names.insert(param.name.as_str()) {
+                anyhow::bail!("Parameter schema declares '{}' more than once", param.name);
+            }
+            if let ParamKind::Enum { values } = &param.kind && values.is_empty() {
+                anyhow::bail!("Parameter schema: enum '{}' has no values", param.name);
+            }
+            if let Some(default) = &param.default {
+                param.kind.check_value(default)
+                    .with_context(|| format!("Parameter schema: invalid default of '{}'", param.name))?;
+            }
+        }
+
+        for constraint in &self.constraints {
+            for name in [&constraint.left, &constraint.right] {
+                let param = self.get_param(name)
+                    .ok_or_else(|| anyhow::anyhow!("Parameter schema: constraint '{}' uses undeclared parameter '{}'", constraint, name))?;
+                if constraint.op.is_ordering() && !param.kind.is_numeric() {
+                    anyhow::bail!("Parameter schema: constraint '{}' orders non-numeric parameter '{}'", constraint, name);
+                }
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Checks the configured parameter ranges against the schema and fills in defaults.
+    /// Every configured value must fit its parameter; parameters missing from the config get their default
+    /// as a single-value range.
+    /// # Arguments
+    /// * `strategy_params` - The `strategy_params` ranges of the strategy settings.
+    /// # Returns
+    /// * `anyhow::Result<()>` - An error for unknown parameters, invalid values or missing required parameters.
+    pub fn apply_to_strategy_params(
+        &self,
+        strategy_params: &mut std::collections::HashMap<String, Vec<serde_json::Value>>,
+    ) -> anyhow::Result<()> {
+        let mut configured_names: Vec<&String> = strategy_params.keys().collect();
+        configured_names.sort();
+        for name in configured_names {
+            if self.get_param(name).is_none() {
+                let known_names: Vec<&str> = self.params.iter().map(|param| param.name.as_str()).collect();
+                anyhow::bail!("Unknown strategy parameter '{}'. Declared parameters: {}", name, known_names.join(", "));
+            }
+        }
+
+        for param in &self.params {
+            match strategy_params.get(&param.name) {
+                Some(values) => {
+                    for value in values {
+                        param.kind.check_value(value)
+                            .with_context(|| format!("Invalid value of strategy parameter '{}'", param.name))?;
+                    }
+                },
+                None => {
+                    let default = param.default.clone()
+                        .ok_or_else(|| anyhow::anyhow!("Missing strategy parameter '{}' (it has no default)", param.name))?;
+                    strategy_params.insert(param.name.clone(), vec![default]);
+                },
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Checks the constraints for one parameter combination.
+    /// Constraints on parameters missing from the combination are not checked.
+    /// # Arguments
+    /// * `params` - (name, value) pairs of the combination.
+    /// # Returns
+    /// * `anyhow::Result<()>` - An error naming the first broken constraint.
+    pub fn check_combination(&self, params: &[(String, serde_json::Value)]) -> anyhow::Result<()> {
+        let get_value = |name: &str| params.iter().find(|(param_name, _)| param_name == name).map(|(_, value)| value);
+
+        for constraint in &self.constraints {
+            if let (Some(left), Some(right)) = (get_value(&constraint.left), get_value(&constraint.right))
+                && !constraint.holds(left, right)
+            {
+                anyhow::bail!("Constraint '{}' does not hold for {} = {}, {} = {}", constraint, constraint.left, left, constraint.right, right);
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Returns `true` when the combination satisfies every constraint.
+    pub fn is_valid_combination(&self, params: &[(String, serde_json::Value)]) -> bool {
+        self.check_combination(params).is_ok()
+    }
+
+}
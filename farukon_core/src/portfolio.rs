@@ -64,7 +64,8 @@ pub struct HoldingsState {
     pub pnl: f64,
     /// Margin blocked by open positions for this symbol.
     pub blocked: f64,
-    pub signal_name: Option<String>,
+    /// Signal of the fill processed on the current bar, if any.
+    pub signal_name: Option<event::SignalName>,
 }
 
 impl HoldingsState {
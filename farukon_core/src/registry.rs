@@ -0,0 +1,104 @@
+// farukon_core/src/registry.rs
+
+//! In-process strategy registry.
+//! Strategies compiled into the engine binary register themselves with `farukon_core::register_strategies!`
+//! and are selected by `strategy_name` when the settings have no `strategy_path`.
+//! The engine calls them through the `Strategy` trait directly: no library loading and no C ABI,
+//! so they can be stepped through in a debugger and used from unit tests.
+//!
+//! A strategy implementing `abi::PluginStrategy` can be both registered here and exported from a plugin.
+
+use crate::abi;
+use crate::event;
+use crate::settings;
+use crate::strategy;
+use crate::param_schema;
+use crate::instruments_info;
+
+/// Creates a registered strategy: (mode, strategy settings, instruments info, event sender).
+pub type CreateStrategyFn = fn(
+    String,
+    settings::StrategySettings,
+    std::collections::HashMap<String, instruments_info::InstrumentInfo>,
+    event::EventQueue,
+) -> anyhow::Result<Box<dyn strategy::Strategy>>;
+
+/// A strategy compiled into the binary, registered under `name`.
+pub struct StaticStrategy {
+    pub name: &'static str,
+    pub create: CreateStrategyFn,
+    pub param_schema: fn() -> Option<param_schema::ParamSchema>,
+}
+
+impl StaticStrategy {
+    /// Registry entry creating strategy `S` under `name`.
+    pub const fn new<S: abi::PluginStrategy>(name: &'static str) -> Self {
+        StaticStrategy {
+            name,
+            create: create_boxed::<S>,
+            param_schema: S::param_schema,
+        }
+    }
+
+}
+
+inventory::collect!(StaticStrategy);
+
+fn create_boxed<S: abi::PluginStrategy>(
+    mode: String,
+    strategy_settings: settings::StrategySettings,
+    strategy_instruments_info: std::collections::HashMap<String, instruments_info::InstrumentInfo>,
+    event_sender: event::EventQueue,
+) -> anyhow::Result<Box<dyn strategy::Strategy>> {
+    let strategy = S::create(mode, strategy_settings, strategy_instruments_info, event_sender)?;
+    anyhow::Ok(Box::new(strategy))
+}
+
+/// Strategies registered in this binary, sorted by name.
+pub fn static_strategies() -> Vec<&'static StaticStrategy> {
+    let mut strategies: Vec<&'static StaticStrategy> = inventory::iter::<StaticStrategy>.into_iter().collect();
+    strategies.sort_by_key(|entry| entry.name);
+    strategies
+}
+
+/// Finds the registered strategy called `name`.
+/// # Arguments
+/// * `name` - The `strategy_name` from the settings.
+/// # Returns
+/// * `anyhow::Result<&'static StaticStrategy>` - Error listing the registered strategies if there is none with this name,
+///   or if several strategies were registered under it.
+pub fn find_static_strategy(name: &str) -> anyhow::Result<&'static StaticStrategy> {
+    let strategies = static_strategies();
+    let mut matching = strategies.iter().filter(|entry| entry.name == name);
+
+    match (matching.next(), matching.next()) {
+        (Some(entry), None) => anyhow::Ok(*entry),
+        (Some(_), Some(_)) => anyhow::bail!("Strategy '{}' is registered in this binary more than once", name),
+        (None, _) => {
+            let names: Vec<&str> = strategies.iter().map(|entry| entry.name).collect();
+            anyhow::bail!(
+                "Strategy '{}' is not compiled into this binary and the settings have no strategy_path. Built-in strategies: {}",
+                name,
+                if names.is_empty() { "none".to_string() } else { names.join(", ") },
+            )
+        },
+    }
+}
+
+/// Registers strategies compiled into the binary, each under its name (the `strategy_name` of the settings).
+/// Every type must implement `abi::PluginStrategy`.
+/// ```ignore
+/// farukon_core::register_strategies! {
+///     "MA_cross" => MovingAverageCrossStrategy,
+/// }
+/// ```
+#[macro_export]
+macro_rules! register_strategies {
+    ($($name:expr => $strategy:ty),+ $(,)?) => {
+        $(
+            $crate::inventory::submit! {
+                $crate::registry::StaticStrategy::new::<$strategy>($name)
+            }
+        )+
+    };
+}
@@ -0,0 +1,393 @@
+// farukon_core/src/sessions.rs
+
+//! Trading session calendar per exchange.
+//! Loads sessions.json (next to instruments_info.json by default) and answers
+//! "is this bar inside a session?" and "how many minutes until the session closes?".
+//!
+//! The calendar is defined in a JSON file with the following format:
+//! {
+//!   "exchanges": {
+//!     "FORTS": {
+//!       "trading_weekdays": ["Mon", "Tue", "Wed", "Thu", "Fri"],
+//!       "sessions": [
+//!         { "name": "main", "start": "10:00", "end": "18:50", "breaks": [{ "start": "14:00", "end": "14:05" }] },
+//!         { "name": "evening", "start": "19:05", "end": "23:50" }
+//!       ],
+//!       "holidays": ["2024-01-01"],
+//!       "shortened_days": [{ "date": "2023-12-29", "close": "18:50" }]
+//!     }
+//!   }
+//! }
+//!
+//! All times are exchange-local, the same clock the bar timestamps are stored in.
+//! A bar belongs to a session when its open time is in `[start, end)` and outside every break.
+
+use anyhow::Context;
+
+use crate::settings;
+use crate::instruments_info;
+
+/// Half-open time interval `[start, end)` within a day.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TimeRange {
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start: chrono::NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end: chrono::NaiveTime,
+}
+
+impl TimeRange {
+    fn contains(&self, time: chrono::NaiveTime) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+/// A named trading session (e.g. "main", "evening") with optional intra-session breaks (e.g. clearing).
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SessionDefinition {
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start: chrono::NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end: chrono::NaiveTime,
+    #[serde(default)]
+    pub breaks: Vec<TimeRange>,
+}
+
+/// A day on which the exchange closes early. Sessions are cut at `close`; sessions starting later are cancelled.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ShortenedDay {
+    pub date: chrono::NaiveDate,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub close: chrono::NaiveTime,
+}
+
+/// Calendar of one exchange: regular sessions, breaks, holidays and shortened days.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeCalendar {
+    #[serde(default = "default_trading_weekdays")]
+    pub trading_weekdays: Vec<chrono::Weekday>,
+    pub sessions: Vec<SessionDefinition>,
+    #[serde(default)]
+    pub holidays: Vec<chrono::NaiveDate>,
+    #[serde(default)]
+    pub shortened_days: Vec<ShortenedDay>,
+}
+
+impl ExchangeCalendar {
+    /// Validates session bounds, breaks and ordering.
+    /// # Arguments
+    /// * `exchange` - The exchange name, used in error messages.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    pub fn validate(&self, exchange: &str) -> anyhow::Result<()> {
+        if self.sessions.is_empty() {
+            anyhow::bail!("Session calendar for '{}' has no sessions", exchange);
+        }
+
+        for session in &self.sessions {
+            if session.start >= session.end {
+                anyhow::bail!(
+                    "Session '{}' of '{}': start {} must be before end {} (overnight sessions are not supported)",
+                    session.name, exchange, session.start, session.end,
+                );
+            }
+            for session_break in &session.breaks {
+                if session_break.start >= session_break.end
+                    || session_break.start < session.start
+                    || session_break.end > session.end
+                {
+                    anyhow::bail!(
+                        "Break {}-{} of session '{}' of '{}' must lie inside the session",
+                        session_break.start, session_break.end, session.name, exchange,
+                    );
+                }
+            }
+        }
+
+        for pair in self.sessions.windows(2) {
+            if pair[0].end > pair[1].start {
+                anyhow::bail!(
+                    "Sessions '{}' and '{}' of '{}' overlap or are not sorted by start time",
+                    pair[0].name, pair[1].name, exchange,
+                );
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Returns `true` if the exchange trades on `date` (trading weekday and not a holiday).
+    pub fn is_trading_day(&self, date: chrono::NaiveDate) -> bool {
+        use chrono::Datelike;
+        self.trading_weekdays.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Effective close of `session` on `date`, taking shortened days into account.
+    /// Returns `None` if the session does not take place on that date.
+    pub fn session_close(&self, session: &SessionDefinition, date: chrono::NaiveDate) -> Option<chrono::NaiveTime> {
+        if !self.is_trading_day(date) {
+            return None;
+        }
+        match self.shortened_days.iter().find(|day| day.date == date) {
+            Some(day) if day.close <= session.start => None,
+            Some(day) => Some(session.end.min(day.close)),
+            None => Some(session.end),
+        }
+    }
+
+    /// Returns the session that is trading at `datetime`, or `None` outside sessions, in breaks and on holidays.
+    pub fn session_at(&self, datetime: chrono::DateTime<chrono::Utc>) -> Option<&SessionDefinition> {
+        let naive_datetime = datetime.naive_utc();
+        let date = naive_datetime.date();
+        let time = naive_datetime.time();
+
+        self.sessions.iter().find(|session| {
+            self.session_close(session, date)
+                .map(|close| session.start <= time && time < close)
+                .unwrap_or(false)
+                && !session.breaks.iter().any(|session_break| session_break.contains(time))
+        })
+    }
+
+    /// Returns `true` if `datetime` is inside a trading session.
+    pub fn is_trading_time(&self, datetime: chrono::DateTime<chrono::Utc>) -> bool {
+        self.session_at(datetime).is_some()
+    }
+
+    /// Minutes from `datetime` until the close of the session it belongs to.
+    /// Breaks do not close the session. Returns `None` outside sessions.
+    pub fn minutes_to_session_close(&self, datetime: chrono::DateTime<chrono::Utc>) -> Option<i64> {
+        let session = self.session_at(datetime)?;
+        let naive_datetime = datetime.naive_utc();
+        let close = self.session_close(session, naive_datetime.date())?;
+
+        Some((close - naive_datetime.time()).num_minutes())
+    }
+
+    /// Number of trading minutes in `[from, to)` according to the calendar.
+    pub fn trading_minutes_between(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> i64 {
+        let mut minutes = 0;
+        let mut current = from;
+        while current < to {
+            if self.is_trading_time(current) {
+                minutes += 1;
+            }
+            current += chrono::Duration::minutes(1);
+        }
+        minutes
+    }
+
+}
+
+/// Session calendars for all exchanges (or, once attached to a strategy, only for the exchanges it trades).
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SessionCalendar {
+    /// A map of exchange names to their calendars.
+    pub exchanges: std::collections::HashMap<String, ExchangeCalendar>,
+    /// Symbol → exchange lookup, filled when the calendar is attached to a strategy.
+    #[serde(default)]
+    pub symbol_exchanges: std::collections::HashMap<String, String>,
+}
+
+impl SessionCalendar {
+    /// Loads the session calendar and attaches the relevant part to every strategy.
+    /// The file is `common.sessions_path` if set, otherwise `sessions.json` next to `instruments_info.json`.
+    /// A missing default file is not an error unless some strategy uses `data.session_filter`.
+    /// # Returns
+    /// * `anyhow::Result<Option<SessionCalendar>>` containing the loaded calendar, if any.
+    pub fn load(
+        settings: &mut settings::Settings,
+        instruments_info: &instruments_info::InstrumentsInfoRegistry,
+    ) -> anyhow::Result<Option<Self>> {
+        let file_path = match &settings.common.sessions_path {
+            Some(sessions_path) => std::path::PathBuf::from(sessions_path),
+            None => std::path::Path::new(&settings.common.instrument_info_path).with_file_name("sessions.json"),
+        };
+
+        if settings.common.sessions_path.is_none() && !file_path.exists() {
+            for (strategy_id, strategy_settings) in &settings.portfolio {
+                if strategy_settings.data.session_filter.is_some() {
+                    anyhow::bail!(
+                        "Strategy '{}' uses data.session_filter, but no session calendar found at {}",
+                        strategy_id, file_path.display(),
+                    );
+                }
+            }
+            return anyhow::Ok(None);
+        }
+
+        let calendar = Self::read_file(&file_path)?;
+        calendar.add_session_calendar_to_settings(settings, instruments_info)?;
+
+        anyhow::Ok(Some(calendar))
+    }
+
+    /// Loads a session calendar file for a list of symbols, outside of any strategy (e.g. for `check-data`).
+    /// Symbols without instrument info, or whose exchange has no calendar, are left out of `symbol_exchanges`.
+    /// # Arguments
+    /// * `file_path` - The session calendar file.
+    /// * `instruments_info` - Metadata mapping the symbols to their exchanges.
+    /// * `symbols` - The symbols to look up.
+    /// # Returns
+    /// * `anyhow::Result<SessionCalendar>` containing the calendar with `symbol_exchanges` filled.
+    pub fn load_for_symbols(
+        file_path: &std::path::Path,
+        instruments_info: &instruments_info::InstrumentsInfoRegistry,
+        symbols: &[String],
+    ) -> anyhow::Result<Self> {
+        let mut calendar = Self::read_file(file_path)?;
+        for symbol in symbols {
+            if let Some(instrument_info) = instruments_info.get_instrument_info(symbol)
+                && calendar.exchanges.contains_key(&instrument_info.exchange)
+            {
+                calendar.symbol_exchanges.insert(symbol.clone(), instrument_info.exchange.clone());
+            }
+        }
+        anyhow::Ok(calendar)
+    }
+
+    /// Reads and validates a session calendar file.
+    fn read_file(file_path: &std::path::Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read session calendar {}", file_path.display()))?;
+        let calendar: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse session calendar {}", file_path.display()))?;
+
+        for (exchange, exchange_calendar) in &calendar.exchanges {
+            exchange_calendar.validate(exchange)?;
+        }
+
+        anyhow::Ok(calendar)
+    }
+
+    /// Attaches to each strategy the calendars of the exchanges its symbols trade on,
+    /// and validates `data.session_filter` against them.
+    fn add_session_calendar_to_settings(
+        &self,
+        settings: &mut settings::Settings,
+        instruments_info: &instruments_info::InstrumentsInfoRegistry,
+    ) -> anyhow::Result<()> {
+        for (strategy_id, strategy_settings) in settings.portfolio.iter_mut() {
+            let mut exchanges: std::collections::HashMap<String, ExchangeCalendar> = std::collections::HashMap::new();
+            let mut symbol_exchanges: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+
+            for symbol in &strategy_settings.symbols {
+                let instrument_info = instruments_info.get_instrument_info(symbol)
+                    .ok_or_else(|| anyhow::anyhow!("Instrument info not found for symbol '{}'", symbol))?;
+                let exchange = &instrument_info.exchange;
+
+                match self.exchanges.get(exchange) {
+                    Some(exchange_calendar) => {
+                        if let Some(session_filter) = &strategy_settings.data.session_filter {
+                            for session_name in session_filter {
+                                if !exchange_calendar.sessions.iter().any(|session| &session.name == session_name) {
+                                    anyhow::bail!(
+                                        "Strategy '{}': session '{}' is not defined for exchange '{}'",
+                                        strategy_id, session_name, exchange,
+                                    );
+                                }
+                            }
+                        }
+                        exchanges.insert(exchange.clone(), exchange_calendar.clone());
+                        symbol_exchanges.insert(symbol.clone(), exchange.clone());
+                    },
+                    None => {
+                        if strategy_settings.data.session_filter.is_some() {
+                            anyhow::bail!(
+                                "Strategy '{}' uses data.session_filter, but exchange '{}' of '{}' has no session calendar",
+                                strategy_id, exchange, symbol,
+                            );
+                        }
+                    },
+                }
+            }
+
+            strategy_settings.session_calendar = Some(Self { exchanges, symbol_exchanges });
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Returns the calendar of an exchange.
+    pub fn for_exchange(&self, exchange: &str) -> Option<&ExchangeCalendar> {
+        self.exchanges.get(exchange)
+    }
+
+    /// Returns the calendar of the exchange a symbol trades on.
+    pub fn for_symbol(&self, symbol: &str) -> Option<&ExchangeCalendar> {
+        self.exchanges.get(self.symbol_exchanges.get(symbol)?)
+    }
+
+}
+
+/// Minutes from `datetime` until the close of the current session of `symbol`.
+/// Intended for strategies, e.g. to flatten positions before the close.
+/// Note that bars are stamped with their open time, so a bar ends `timeframe` minutes after `datetime`.
+/// # Arguments
+/// * `strategy_settings` - The strategy settings with the attached session calendar.
+/// * `symbol` - The traded symbol (e.g., "Si-12.23").
+/// * `datetime` - The moment to measure from (usually the current bar datetime).
+/// # Returns
+/// * `Option<i64>` - Minutes to the close, or `None` without a calendar or outside sessions.
+pub fn minutes_to_session_close(
+    strategy_settings: &settings::StrategySettings,
+    symbol: &str,
+    datetime: chrono::DateTime<chrono::Utc>,
+) -> Option<i64> {
+    strategy_settings.session_calendar
+        .as_ref()?
+        .for_symbol(symbol)?
+        .minutes_to_session_close(datetime)
+}
+
+/// Returns `true` if the bar at `datetime` should be kept under `data.session_filter`.
+/// Without a filter every bar is kept.
+pub fn is_in_selected_sessions(
+    strategy_settings: &settings::StrategySettings,
+    symbol: &str,
+    datetime: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    let Some(session_filter) = &strategy_settings.data.session_filter else {
+        return true;
+    };
+
+    strategy_settings.session_calendar
+        .as_ref()
+        .and_then(|calendar| calendar.for_symbol(symbol))
+        .and_then(|exchange_calendar| exchange_calendar.session_at(datetime))
+        .map(|session| session_filter.contains(&session.name))
+        .unwrap_or(false)
+}
+
+// --- Deserialization Helpers ---
+
+fn default_trading_weekdays() -> Vec<chrono::Weekday> {
+    vec![
+        chrono::Weekday::Mon,
+        chrono::Weekday::Tue,
+        chrono::Weekday::Wed,
+        chrono::Weekday::Thu,
+        chrono::Weekday::Fri,
+    ]
+}
+
+/// Deserializes a time of day given as "HH:MM" or "HH:MM:SS".
+fn deserialize_time<'de, D>(deserializer: D) -> Result<chrono::NaiveTime, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw: String = serde::Deserialize::deserialize(deserializer)?;
+    chrono::NaiveTime::parse_from_str(&raw, "%H:%M")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(&raw, "%H:%M:%S"))
+        .map_err(|e| serde::de::Error::custom(format!("Invalid time '{}': {}", raw, e)))
+}
@@ -8,6 +8,8 @@ use std::fs;
 use serde::Deserialize;
 
 use crate::commission_plans;
+use crate::sessions;
+use crate::param_schema;
 
 /// Type of optimizer to use.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -17,6 +19,14 @@ pub enum OptimizerType {
     GridSearch,
     #[serde(rename = "Genetic")]
     Genetic { ga_params: GAParams },
+    #[serde(rename = "Random_Search")]
+    RandomSearch { sampling_params: SamplingParams },
+    #[serde(rename = "Latin_Hypercube")]
+    LatinHypercube { sampling_params: SamplingParams },
+    #[serde(rename = "Bayesian")]
+    Bayesian { bayesian_params: BayesianParams },
+    #[serde(rename = "NSGA_II")]
+    Nsga2 { nsga2_params: Nsga2Params },
 }
 
 /// Type of fitness metric to optimize.
@@ -56,6 +66,67 @@ pub struct GAParams {
     pub p_mutation: f64,
     pub max_generations: usize,
     pub fitness_params: FitnessParams,
+    /// Best chromosomes carried unchanged into the next generation. Default 1.
+    pub elite_count: Option<usize>,
+    /// Standard deviation, in range steps, of the Gaussian mutation of a value. Default 1.0.
+    pub mutation_sigma: Option<f64>,
+    /// Raise the mutation rate while the best fitness stagnates and lower it when it improves. Default `false`.
+    pub adaptive_mutation: Option<bool>,
+    /// Stop after this many generations in a row without improving the best fitness. Runs `max_generations` when absent.
+    pub patience: Option<usize>,
+    /// Smallest fitness gain counted as an improvement by `patience` and `adaptive_mutation`. Default 0.0.
+    pub min_improvement: Option<f64>,
+    /// Seed of the GA; the same seed gives the same run. A random seed is drawn (and printed) when absent.
+    pub seed: Option<u64>,
+}
+
+/// Parameters of the sampling optimizers (`Random_Search`, `Latin_Hypercube`).
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SamplingParams {
+    /// Number of parameter sets to evaluate.
+    pub budget: usize,
+    /// Seed of the sampler. A random seed is drawn (and printed) when absent.
+    pub seed: Option<u64>,
+}
+
+/// Parameters of the Bayesian optimizer (tree-structured Parzen estimator).
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BayesianParams {
+    /// Maximum number of parameter sets to evaluate.
+    pub budget: usize,
+    /// Parameter sets proposed (and evaluated in parallel) per batch. Defaults to `threads`.
+    pub batch_size: Option<usize>,
+    /// Latin hypercube samples evaluated before the model is used. Defaults to a fifth of the budget (at least one batch).
+    pub initial_samples: Option<usize>,
+    /// Share of the evaluated parameter sets modelled as "good". Default 0.25.
+    pub gamma: Option<f64>,
+    /// Candidates drawn from the model for every proposed parameter set. Default 64.
+    pub candidates: Option<usize>,
+    /// Stop after this many batches in a row without improving the best fitness. Runs the whole budget when absent.
+    pub patience: Option<usize>,
+    /// Smallest fitness gain counted as an improvement by `patience`. Default 0.0.
+    pub min_improvement: Option<f64>,
+    /// Seed of the sampler. A random seed is drawn (and printed) when absent.
+    pub seed: Option<u64>,
+    pub fitness_params: FitnessParams,
+}
+
+/// Parameters of the NSGA-II multi-objective optimizer.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Nsga2Params {
+    pub population_size: usize,
+    /// Probability that a child mixes the values of both parents (uniform crossover) instead of copying the first one.
+    pub p_crossover: f64,
+    /// Probability that each value of a child is replaced by a random value of its range.
+    pub p_mutation: f64,
+    pub max_generations: usize,
+    /// Objectives optimized together, each with its own direction.
+    pub objectives: Vec<FitnessParams>,
+    /// Seed of the optimizer. A random seed is drawn (and printed) when absent.
+    pub seed: Option<u64>,
 }
 
 /// Fitness function parameters for the Genetic Algorithm.
@@ -92,6 +163,144 @@ pub struct PosSizer {
 pub struct DataSettings {
     pub data_path: String,
     pub timeframe: String,
+    /// Optional data-quality check executed before the strategy is run.
+    pub check_data: Option<DataCheckSettings>,
+    /// Optional list of session names (from the session calendar) to keep; bars outside them are dropped.
+    pub session_filter: Option<Vec<String>>,
+    /// What to do when a symbol has no bar for a timeline timestamp. Defaults to `forward_fill`.
+    pub gap_policy: Option<GapPolicy>,
+    /// Per-symbol overrides of `gap_policy`.
+    pub symbol_gap_policies: Option<std::collections::HashMap<String, GapPolicy>>,
+}
+
+impl DataSettings {
+    /// Returns the gap policy for `symbol`: the per-symbol override, then `gap_policy`, then `forward_fill`.
+    pub fn gap_policy_for(&self, symbol: &str) -> GapPolicy {
+        self.symbol_gap_policies
+            .as_ref()
+            .and_then(|policies| policies.get(symbol))
+            .or(self.gap_policy.as_ref())
+            .copied()
+            .unwrap_or(GapPolicy::ForwardFill)
+    }
+
+}
+
+/// Policy for timeline timestamps where a symbol has no bar of its own.
+/// The combined timeline is the union of all symbols' timestamps.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub enum GapPolicy {
+    /// Repeat the last bar (OHLC of the last real bar, zero volume), marked `is_filled`.
+    #[serde(rename = "forward_fill")]
+    ForwardFill,
+    /// Emit nothing: the latest bar stays the last real bar.
+    #[serde(rename = "skip")]
+    Skip,
+    /// Emit a flat bar at the last close (O=H=L=C, zero volume), marked `is_filled`.
+    #[serde(rename = "synthetic")]
+    Synthetic,
+}
+
+/// Thresholds for the data integrity checker (`check-data` command and pre-run check).
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DataCheckSettings {
+    /// Intraday gap between consecutive bars (in minutes) reported as a warning.
+    pub max_gap_minutes: u64,
+    /// Volume above `volume_spike_factor` × rolling mean volume is reported as a spike.
+    pub volume_spike_factor: f64,
+    /// Bar move above `price_outlier_factor` × rolling std of close-to-close returns is reported as an outlier.
+    pub price_outlier_factor: f64,
+    /// Number of previous bars used for the rolling volume / return statistics.
+    pub rolling_window: usize,
+    /// Abort the run if any symbol has errors (warnings never abort).
+    pub abort_on_errors: bool,
+}
+
+impl Default for DataCheckSettings {
+    fn default() -> Self {
+        DataCheckSettings {
+            max_gap_minutes: 15,
+            volume_spike_factor: 50.0,
+            price_outlier_factor: 10.0,
+            rolling_window: 100,
+            abort_on_errors: true,
+        }
+    }
+}
+
+/// Event journal of every backtest (MARKET, SIGNAL, ORDER, FILL) written as JSONL.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EventJournalSettings {
+    /// Directory for journal files. Defaults to `<exit_results_path>/journals`.
+    pub path: Option<String>,
+}
+
+/// Hot reload of the strategy library (or script) during an optimization.
+/// `strategy_path` is checked for a new build before every Grid Search chunk and every GA generation;
+/// the remaining work runs with the new build.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HotReloadSettings {
+    /// Grid Search combinations per chunk. Defaults to 4 per thread.
+    pub grid_chunk_size: Option<usize>,
+    /// Re-runs the combinations evaluated by a replaced build with the newest build,
+    /// so that every result of the optimization comes from one build.
+    #[serde(default)]
+    pub rerun_stale: bool,
+}
+
+/// Checkpoints of an optimization, so that an interrupted run can continue with `--resume`.
+/// Every completed backtest is appended to the checkpoint as soon as it ends; the Genetic Algorithm
+/// also saves its population, fitness cache and random generator after every generation.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CheckpointSettings {
+    /// Directory of the checkpoint files. Defaults to `<exit_results_path>/checkpoint`.
+    pub path: Option<String>,
+}
+
+/// Persistent cache of backtest results, shared by optimization runs.
+/// A backtest is reused when its parameter set, the strategy build, the data files, the instrument, commission and
+/// session settings, the initial capital and the other backtest settings are the same as in an earlier run.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ResultCacheSettings {
+    /// Cache file. Defaults to `<exit_results_path>/result_cache.jsonl`; strategies and configs may share one file.
+    pub path: Option<String>,
+}
+
+/// Vectorized pre-filter of a Grid Search.
+/// Every combination is first ranked by the vectorized backtester; only the best ones run the event-driven backtest.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PrefilterSettings {
+    /// Signal whose position targets are backtested.
+    pub signal: PrefilterSignal,
+    /// Share of the combinations kept for the event-driven backtests, in (0, 1].
+    pub keep: f64,
+    /// Contracts per position. Defaults to 1.
+    pub quantity: Option<f64>,
+    /// Ranking of the combinations. Defaults to maximizing APR/DD_factor.
+    pub fitness_params: Option<FitnessParams>,
+}
+
+/// Signals the pre-filter can backtest.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum PrefilterSignal {
+    /// Crossover of two moving averages, as traded by `MA_cross`.
+    #[serde(rename = "ma_cross")]
+    MaCross {
+        /// Moving average: "sma" (default), "ema" or "wma".
+        indicator: Option<String>,
+        /// Strategy parameter holding the period of the fast average.
+        fast_param: String,
+        /// Strategy parameter holding the period of the slow average.
+        slow_param: String,
+    },
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -126,7 +335,8 @@ pub struct PortfolioSettingsForStrategy {
 pub struct StrategySettings {
     pub threads: Option<usize>,
     pub strategy_name: String,
-    pub strategy_path: String,
+    /// Strategy library (.so/.dylib/.dll) or Rhai script (.rhai); without it `strategy_name` is looked up among the strategies compiled into the engine.
+    pub strategy_path: Option<String>,
     pub exit_results_path: String,
     pub strategy_weight: f64,
     
@@ -143,8 +353,25 @@ pub struct StrategySettings {
     pub margin_params: MarginParams,
     pub portfolio_settings_for_strategy: PortfolioSettingsForStrategy,
     pub optimizer_type: OptimizerType,
-    #[serde(skip_deserializing)]
-    pub commission_plans: Option<commission_plans::CommissionPlans>
+    /// Optional event journal; one `.jsonl` file per backtest.
+    pub event_journal: Option<EventJournalSettings>,
+    /// Optional hot reload of `strategy_path` during the optimization.
+    pub hot_reload: Option<HotReloadSettings>,
+    /// Optional vectorized pre-filter of the Grid Search combinations.
+    pub prefilter: Option<PrefilterSettings>,
+    /// Optional checkpoints of the optimization, for `--resume`.
+    pub checkpoint: Option<CheckpointSettings>,
+    /// Optional persistent cache of backtest results across runs.
+    pub result_cache: Option<ResultCacheSettings>,
+    /// Loaded from `commission_plans_path`, not from the strategy settings.
+    #[serde(skip)]
+    pub commission_plans: Option<commission_plans::CommissionPlans>,
+    /// Loaded from `sessions_path`, not from the strategy settings.
+    #[serde(skip)]
+    pub session_calendar: Option<sessions::SessionCalendar>,
+    /// Declared by the strategy library, not by the strategy settings.
+    #[serde(skip)]
+    pub param_schema: Option<param_schema::ParamSchema>,
 }
 
 /// Common settings applicable to the entire platform.
@@ -155,6 +382,8 @@ pub struct CommonSettings {
     pub initial_capital: f64,
     pub commission_plans_path: String,
     pub instrument_info_path: String,
+    /// Session calendar file. Defaults to `sessions.json` next to `instrument_info_path`.
+    pub sessions_path: Option<String>,
 }
 
 /// Top-level settings structure.
@@ -244,46 +473,70 @@ fn check_args(
                         if ga_params.max_generations == 0 {
                             anyhow::bail!("GA max_generations must be greater than 0");
                         }
+                        if ga_params.elite_count.is_some_and(|elite_count| elite_count > ga_params.population_size) {
+                            anyhow::bail!("GA elite_count must not exceed population_size");
+                        }
+                        if ga_params.mutation_sigma.is_some_and(|mutation_sigma| !(mutation_sigma > 0.0 && mutation_sigma.is_finite())) {
+                            anyhow::bail!("GA mutation_sigma must be positive");
+                        }
+                        if ga_params.patience == Some(0) {
+                            anyhow::bail!("GA patience must be greater than 0");
+                        }
+                        if ga_params.min_improvement.is_some_and(|min_improvement| min_improvement < 0.0) {
+                            anyhow::bail!("GA min_improvement must not be negative");
+                        }
 
-                        // check fitness_direction
-                        let dir_str = &ga_params.fitness_params.fitness_direction;
-                        if dir_str != "max" && dir_str != "min" {
-                            anyhow::bail!("fitness_direction must be 'max' or 'min'");
+                        check_fitness_params(&ga_params.fitness_params)?;
+                    },
+                    OptimizerType::RandomSearch { sampling_params } | OptimizerType::LatinHypercube { sampling_params } => {
+                        if sampling_params.budget == 0 {
+                            anyhow::bail!("sampling_params budget must be greater than 0");
+                        }
+                    },
+                    OptimizerType::Bayesian { bayesian_params } => {
+                        if bayesian_params.budget == 0 {
+                            anyhow::bail!("bayesian_params budget must be greater than 0");
+                        }
+                        if bayesian_params.batch_size == Some(0) {
+                            anyhow::bail!("bayesian_params batch_size must be greater than 0");
+                        }
+                        if bayesian_params.initial_samples.is_some_and(|initial_samples| initial_samples == 0 || initial_samples > bayesian_params.budget) {
+                            anyhow::bail!("bayesian_params initial_samples must be between 1 and budget");
+                        }
+                        if bayesian_params.gamma.is_some_and(|gamma| !(gamma > 0.0 && gamma < 1.0)) {
+                            anyhow::bail!("bayesian_params gamma must be between 0.0 and 1.0 (exclusive)");
+                        }
+                        if bayesian_params.candidates == Some(0) {
+                            anyhow::bail!("bayesian_params candidates must be greater than 0");
+                        }
+                        if bayesian_params.patience == Some(0) {
+                            anyhow::bail!("bayesian_params patience must be greater than 0");
+                        }
+                        if bayesian_params.min_improvement.is_some_and(|min_improvement| min_improvement < 0.0) {
+                            anyhow::bail!("bayesian_params min_improvement must not be negative");
                         }
 
-                        // check fitness_value
-                        match &ga_params.fitness_params.fitness_value {
-                            FitnessValue::Composite { metrics } => {
-                                const VALID_COMPOSITE_METRICS: &[&str] = &[
-                                    "Total_Return",
-                                    "Total_Return_%",
-                                    "APR",
-                                    "max_DD", 
-                                    "max_DD_%",
-                                    "APR/DD_factor",
-                                    "Recovery_Factor",
-                                    "Recovery_Factor_%",
-                                    "Deals_Count",
-                                ];
-
-                                if metrics.is_empty() {
-                                    anyhow::bail!(
-                                        "Composite fitness must have at least one metric. One of {:?}",
-                                        VALID_COMPOSITE_METRICS
-                                    );
-                                }
+                        check_fitness_params(&bayesian_params.fitness_params)?;
+                    },
+                    OptimizerType::Nsga2 { nsga2_params } => {
+                        if nsga2_params.population_size == 0 {
+                            anyhow::bail!("NSGA-II population_size must be greater than 0");
+                        }
+                        if nsga2_params.p_crossover < 0.0 || nsga2_params.p_crossover > 1.0 {
+                            anyhow::bail!("NSGA-II p_crossover must be between 0.0 and 1.0");
+                        }
+                        if nsga2_params.p_mutation < 0.0 || nsga2_params.p_mutation > 1.0 {
+                            anyhow::bail!("NSGA-II p_mutation must be between 0.0 and 1.0");
+                        }
+                        if nsga2_params.max_generations == 0 {
+                            anyhow::bail!("NSGA-II max_generations must be greater than 0");
+                        }
+                        if nsga2_params.objectives.len() < 2 {
+                            anyhow::bail!("NSGA-II needs at least two objectives; use the Genetic optimizer for one");
+                        }
 
-                                for metric in metrics {
-                                    if !VALID_COMPOSITE_METRICS.contains(&metric.as_str()) {
-                                        anyhow::bail!(
-                                            "Invalid composite metric '{}'. Must be one of: {:?}",
-                                            metric,
-                                            VALID_COMPOSITE_METRICS
-                                        );
-                                    }
-                                }
-                            },
-                            _ => {}
+                        for objective in &nsga2_params.objectives {
+                            check_fitness_params(objective)?;
                         }
                     },
                     OptimizerType::GridSearch => {}
@@ -298,6 +551,44 @@ fn check_args(
                 }
             }
 
+            // check data quality settings
+            {
+                if let Some(check_data) = &strategy_settings.data.check_data {
+                    if check_data.max_gap_minutes == 0 {
+                        anyhow::bail!("check_data max_gap_minutes must be greater than 0");
+                    }
+                    if check_data.volume_spike_factor <= 1.0 {
+                        anyhow::bail!("check_data volume_spike_factor must be greater than 1.0");
+                    }
+                    if check_data.price_outlier_factor <= 0.0 {
+                        anyhow::bail!("check_data price_outlier_factor must be positive");
+                    }
+                    if check_data.rolling_window < 2 {
+                        anyhow::bail!("check_data rolling_window must be at least 2");
+                    }
+                }
+            }
+
+            // check session filter
+            {
+                if let Some(session_filter) = &strategy_settings.data.session_filter
+                    && session_filter.is_empty()
+                {
+                    anyhow::bail!("data.session_filter cannot be empty! Remove it to keep all bars");
+                }
+            }
+
+            // check gap policy overrides
+            {
+                if let Some(symbol_gap_policies) = &strategy_settings.data.symbol_gap_policies {
+                    for symbol in symbol_gap_policies.keys() {
+                        if !strategy_settings.symbols.contains(symbol) {
+                            anyhow::bail!("data.symbol_gap_policies has unknown symbol '{}'", symbol);
+                        }
+                    }
+                }
+            }
+
             // check symbols
             {
                 if strategy_settings.symbols.is_empty() {
@@ -363,6 +654,49 @@ fn check_args(
                 }
             }
 
+            // check hot reload
+            {
+                if let Some(hot_reload) = &strategy_settings.hot_reload {
+                    if strategy_settings.strategy_path.is_none() {
+                        anyhow::bail!("hot_reload needs a strategy_path: built-in strategies cannot be reloaded");
+                    }
+                    if hot_reload.grid_chunk_size == Some(0) {
+                        anyhow::bail!("hot_reload grid_chunk_size must be greater than 0");
+                    }
+                }
+            }
+
+            // check prefilter
+            {
+                if let Some(prefilter) = &strategy_settings.prefilter {
+                    if !matches!(strategy_settings.optimizer_type, OptimizerType::GridSearch) {
+                        anyhow::bail!("prefilter is only supported by the Grid_Search optimizer");
+                    }
+                    if !(prefilter.keep > 0.0 && prefilter.keep <= 1.0) {
+                        anyhow::bail!("prefilter keep must be greater than 0.0 and at most 1.0");
+                    }
+                    if prefilter.quantity.is_some_and(|quantity| quantity <= 0.0) {
+                        anyhow::bail!("prefilter quantity must be positive");
+                    }
+                    if let Some(fitness_params) = &prefilter.fitness_params {
+                        check_fitness_params(fitness_params)?;
+                    }
+                    match &prefilter.signal {
+                        PrefilterSignal::MaCross { indicator, fast_param, slow_param } => {
+                            const VALID_MOVING_AVERAGES: &[&str] = &["sma", "ema", "wma"];
+                            if indicator.as_ref().is_some_and(|indicator| !VALID_MOVING_AVERAGES.contains(&indicator.as_str())) {
+                                anyhow::bail!("prefilter indicator must be one of {:?}", VALID_MOVING_AVERAGES);
+                            }
+                            for param_name in [fast_param, slow_param] {
+                                if !strategy_settings.strategy_params.contains_key(param_name) {
+                                    anyhow::bail!("prefilter uses unknown strategy parameter '{}'", param_name);
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+
             // check exit path of results
             {
                 let normalized_path = strategy_settings.exit_results_path
@@ -385,6 +719,54 @@ fn check_args(
     anyhow::Ok(())
 }
 
+// --- Validation Helpers ---
+
+/// Checks the fitness direction and, for composite fitness, the metric names.
+fn check_fitness_params(fitness_params: &FitnessParams) -> anyhow::Result<()> {
+    // check fitness_direction
+    let dir_str = &fitness_params.fitness_direction;
+    if dir_str != "max" && dir_str != "min" {
+        anyhow::bail!("fitness_direction must be 'max' or 'min'");
+    }
+
+    // check fitness_value
+    match &fitness_params.fitness_value {
+        FitnessValue::Composite { metrics } => {
+            const VALID_COMPOSITE_METRICS: &[&str] = &[
+                "Total_Return",
+                "Total_Return_%",
+                "APR",
+                "max_DD", 
+                "max_DD_%",
+                "APR/DD_factor",
+                "Recovery_Factor",
+                "Recovery_Factor_%",
+                "Deals_Count",
+            ];
+
+            if metrics.is_empty() {
+                anyhow::bail!(
+                    "Composite fitness must have at least one metric. One of {:?}",
+                    VALID_COMPOSITE_METRICS
+                );
+            }
+
+            for metric in metrics {
+                if !VALID_COMPOSITE_METRICS.contains(&metric.as_str()) {
+                    anyhow::bail!(
+                        "Invalid composite metric '{}'. Must be one of: {:?}",
+                        metric,
+                        VALID_COMPOSITE_METRICS
+                    );
+                }
+            }
+        },
+        _ => {}
+    }
+
+    anyhow::Ok(())
+}
+
 // --- Deserialization Helpers ---
 
 /// Deserializes strategy parameters from JSON.
@@ -26,46 +26,204 @@ pub trait Strategy {
         symbol_list: &[String],
     ) -> anyhow::Result<()>;
 
+    // --- Lifecycle hooks (optional, default: do nothing) ---
+
+    /// Called once before the first bar. Timers scheduled here are registered on the first bar.
+    /// # Returns
+    /// * `anyhow::Result<()>` - An error aborts the backtest.
+    fn on_start(&mut self) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Called on every bar right before `calculate_signals` (e.g. to update state that does not trade).
+    /// # Arguments
+    /// * `data_handler` - Interface to market data.
+    /// * `current_positions` - Current positions for all symbols.
+    /// * `latest_holdings` - Latest holdings snapshot.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn on_bar(
+        &mut self,
+        _data_handler: &dyn data_handler::DataHandler,
+        _current_positions: &std::collections::HashMap<String, portfolio::PositionState>,
+        _latest_holdings: &portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Called after the portfolio has applied a fill of one of the strategy's orders.
+    /// `current_positions` already include the fill; `latest_holdings` are updated on the next mark-to-market.
+    /// # Arguments
+    /// * `fill_event` - The fill (price, quantity, commission).
+    /// * `data_handler` - Interface to market data.
+    /// * `current_positions` - Current positions for all symbols.
+    /// * `latest_holdings` - Latest holdings snapshot.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn on_fill(
+        &mut self,
+        _fill_event: &event::FillEvent,
+        _data_handler: &dyn data_handler::DataHandler,
+        _current_positions: &std::collections::HashMap<String, portfolio::PositionState>,
+        _latest_holdings: &portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Called when the portfolio refuses to turn a signal into an order (e.g. not enough initial margin).
+    /// # Arguments
+    /// * `rejected_event` - The rejected signal and the reason.
+    /// * `data_handler` - Interface to market data.
+    /// * `current_positions` - Current positions for all symbols.
+    /// * `latest_holdings` - Latest holdings snapshot.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn on_order_rejected(
+        &mut self,
+        _rejected_event: &event::OrderRejectedEvent,
+        _data_handler: &dyn data_handler::DataHandler,
+        _current_positions: &std::collections::HashMap<String, portfolio::PositionState>,
+        _latest_holdings: &portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Called on a margin call, before the forced EXIT orders for every position are processed.
+    /// # Arguments
+    /// * `margin_call_event` - Capital and required minimal margin.
+    /// * `data_handler` - Interface to market data.
+    /// * `current_positions` - Current positions for all symbols.
+    /// * `latest_holdings` - Latest holdings snapshot.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn on_margin_call(
+        &mut self,
+        _margin_call_event: &event::MarginCallEvent,
+        _data_handler: &dyn data_handler::DataHandler,
+        _current_positions: &std::collections::HashMap<String, portfolio::PositionState>,
+        _latest_holdings: &portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Called once after the last bar, before the final metrics are calculated.
+    /// Signals sent here are not processed.
+    /// # Arguments
+    /// * `data_handler` - Interface to market data.
+    /// * `current_positions` - Final positions for all symbols.
+    /// * `latest_holdings` - Final holdings snapshot.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn on_end(
+        &mut self,
+        _data_handler: &dyn data_handler::DataHandler,
+        _current_positions: &std::collections::HashMap<String, portfolio::PositionState>,
+        _latest_holdings: &portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Called when a timer scheduled with `schedule_timer` comes due.
+    /// Runs on the bar the timer fired on, after `calculate_signals`; signals sent here are processed with the bar's own signals.
+    /// The default implementation does nothing.
+    /// # Arguments
+    /// * `timer_event` - The timer that fired.
+    /// * `data_handler` - Interface to market data.
+    /// * `current_positions` - Current positions for all symbols.
+    /// * `latest_holdings` - Latest holdings snapshot.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn on_timer(
+        &mut self,
+        _timer_event: &event::TimerEvent,
+        _data_handler: &dyn data_handler::DataHandler,
+        _current_positions: &std::collections::HashMap<String, portfolio::PositionState>,
+        _latest_holdings: &portfolio::HoldingSnapshot,
+    ) -> anyhow::Result<()> {
+        anyhow::Ok(())
+    }
+
+    /// Schedules a timer; `on_timer` is called with `tag` when it comes due.
+    /// The engine registers the timer when it drains the event queue at the start of the next bar,
+    /// so a timer already due by then fires on that bar. Scheduling an existing tag replaces the timer.
+    /// # Arguments
+    /// * `event_sender` - Sender for events.
+    /// * `tag` - Name of the timer, passed back in `TimerEvent::tag`.
+    /// * `schedule` - When the timer fires.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn schedule_timer(
+        &self,
+        event_sender: &event::EventQueue,
+        tag: &str,
+        schedule: event::TimerSchedule,
+    ) -> anyhow::Result<()> {
+        event_sender.send(event::TimerCommand::Schedule {
+            tag: tag.to_string(),
+            schedule,
+        })?;
+
+        anyhow::Ok(())
+    }
+
+    /// Cancels the timer with `tag` (no-op if there is none).
+    /// # Arguments
+    /// * `event_sender` - Sender for events.
+    /// * `tag` - Name of the timer.
+    /// # Returns
+    /// * `anyhow::Result<()>` indicating success or failure.
+    fn cancel_timer(
+        &self,
+        event_sender: &event::EventQueue,
+        tag: &str,
+    ) -> anyhow::Result<()> {
+        event_sender.send(event::TimerCommand::Cancel {
+            tag: tag.to_string(),
+        })?;
+
+        anyhow::Ok(())
+    }
+
     /// Opens a position by sending a limit order.
     /// # Arguments
     /// * `event_sender` - Sender for events.
     /// * `current_bar_datetime` - Current bar datetime.
     /// * `symbol` - Symbol to trade.
-    /// * `signal_name` - Signal name (e.g., "LONG", "SHORT").
+    /// * `signal_name` - Signal name (e.g., `SignalName::Long`).
     /// * `quantity` - Quantity to trade.
     /// * `limit_price` - Limit price.
     /// # Returns
     /// * `anyhow::Result<()>` indicating success or failure.
     fn open_by_limit(
         &self,
-        event_sender: &std::sync::mpsc::Sender<Box<dyn event::Event>>,
+        event_sender: &event::EventQueue,
         current_bar_datetime: chrono::DateTime<chrono::Utc>,
         symbol: &String,
-        signal_name: &str,
+        signal_name: event::SignalName,
         quantity: Option<f64>,
         limit_price: Option<f64>,
     ) -> anyhow::Result<()> {
-        event_sender.send(Box::new(event::SignalEvent::new(
+        event_sender.send(event::SignalEvent::new(
             current_bar_datetime,
             symbol.clone(),
-            signal_name.to_string(),
-            "LMT".to_string(),
+            signal_name,
+            event::OrderType::Limit,
             quantity,
             limit_price,
-        )))?;
+        ))?;
 
         anyhow::Ok(())
     }
 
     /// Sends a `SIGNAL` event to close a position using a **limit order**.
-    /// This method creates a `SignalEvent` with the specified parameters and sends it through the event channel.
-    /// The order type is hardcoded as "LMT".
+    /// This method creates a `SignalEvent` with the specified parameters and pushes it into the event queue.
+    /// The order type is always `OrderType::Limit`.
     ///
     /// # Arguments
-    /// * `event_sender` - The channel sender for broadcasting events.
+    /// * `event_sender` - The event queue shared with the engine.
     /// * `current_bar_datetime` - The timestamp associated with this signal.
     /// * `symbol` - The trading symbol (e.g., "Si-12.23").
-    /// * `signal_name` - The name of the signal (e.g., "EXIT").
+    /// * `signal_name` - The name of the signal (e.g., `SignalName::Exit`).
     /// * `quantity` - The number of contracts to trade (use `None` if not applicable or to use current position size).
     /// * `limit_price` - The specific price at which the limit order should be placed.
     ///
@@ -73,21 +231,21 @@ pub trait Strategy {
     /// * `anyhow::Result<()>` - `Ok(())` on successful sending, `Err` if sending fails.
     fn close_by_limit(
         &self,
-        event_sender: &std::sync::mpsc::Sender<Box<dyn event::Event>>,
+        event_sender: &event::EventQueue,
         current_bar_datetime: chrono::DateTime<chrono::Utc>,
         symbol: &String,
-        signal_name: &str,
+        signal_name: event::SignalName,
         quantity: Option<f64>,
         limit_price: Option<f64>,
     ) -> anyhow::Result<()> {
-        event_sender.send(Box::new(event::SignalEvent::new(
+        event_sender.send(event::SignalEvent::new(
             current_bar_datetime,
             symbol.clone(),
-            signal_name.to_string(),
-            "LMT".to_string(),
+            signal_name,
+            event::OrderType::Limit,
             quantity,
             limit_price,
-        )))?;
+        ))?;
 
         anyhow::Ok(())
     }
@@ -97,26 +255,26 @@ pub trait Strategy {
     /// * `event_sender` - Sender for events.
     /// * `current_bar_datetime` - Current bar datetime.
     /// * `symbol` - Symbol to trade.
-    /// * `signal_name` - Signal name (e.g., "LONG", "SHORT").
+    /// * `signal_name` - Signal name (e.g., `SignalName::Long`).
     /// * `quantity` - Quantity to trade.
     /// # Returns
     /// * `anyhow::Result<()>` indicating success or failure.
     fn open_by_market(
         &self,
-        event_sender: &std::sync::mpsc::Sender<Box<dyn event::Event>>,
+        event_sender: &event::EventQueue,
         current_bar_datetime: chrono::DateTime<chrono::Utc>,
         symbol: &String,
-        signal_name: &str,
+        signal_name: event::SignalName,
         quantity: Option<f64>,
     ) -> anyhow::Result<()> {
-        event_sender.send(Box::new(event::SignalEvent::new(
+        event_sender.send(event::SignalEvent::new(
             current_bar_datetime,
             symbol.clone(),
-            signal_name.to_string(),
-            "MKT".to_string(),
+            signal_name,
+            event::OrderType::Market,
             quantity,
             None,
-        )))?;
+        ))?;
 
         anyhow::Ok(())
     }
@@ -126,26 +284,26 @@ pub trait Strategy {
     /// * `event_sender` - Sender for events.
     /// * `current_bar_datetime` - Current bar datetime.
     /// * `symbol` - Symbol to trade.
-    /// * `signal_name` - Signal name (e.g., "EXIT").
+    /// * `signal_name` - Signal name (e.g., `SignalName::Exit`).
     /// * `quantity` - Quantity to trade.
     /// # Returns
     /// * `anyhow::Result<()>` indicating success or failure.
     fn close_by_market(
         &self,
-        event_sender: &std::sync::mpsc::Sender<Box<dyn event::Event>>,
+        event_sender: &event::EventQueue,
         current_bar_datetime: chrono::DateTime<chrono::Utc>,
         symbol: &String,
-        signal_name: &str,
+        signal_name: event::SignalName,
         quantity: Option<f64>,
     ) -> anyhow::Result<()> {
-        event_sender.send(Box::new(event::SignalEvent::new(
+        event_sender.send(event::SignalEvent::new(
             current_bar_datetime,
             symbol.clone(),
-            signal_name.to_string(),
-            "MKT".to_string(),
+            signal_name,
+            event::OrderType::Market,
             quantity,
             None,
-        )))?;
+        ))?;
 
         anyhow::Ok(())
     }
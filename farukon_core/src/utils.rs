@@ -96,6 +96,7 @@ pub fn parse_optimization_config(
         .with_slippage_range(slippage_range)
         .with_pos_sizer_name(pos_sizer_name)
         .with_pos_sizer_additional_params(pos_sizer_additional_params)
+        .with_param_schema(strategy_settings.param_schema.clone())
 }
 
 /// Creates a new strategy settings object from a parameter set.
@@ -0,0 +1,370 @@
+// farukon_core/src/vectorized.rs
+
+//! Vectorized fast-path backtester.
+//!
+//! Instead of replaying MARKET/SIGNAL/ORDER/FILL events bar by bar, the vectorized backtester takes the
+//! position each symbol should hold after every bar (a precomputed column of targets) and computes fills,
+//! commissions and PnL over whole columns with `wide::f64x4`. The equity curve and the metrics then come from
+//! the same `PerformanceManager` as the event-driven backtest, and commissions from `calculate_forts_comission`.
+//!
+//! The accounting follows the event-driven portfolio and execution handler: per-bar PnL is
+//! `(close - last_close) * cost_of_step_price * position` rounded to cents, no PnL is computed on filled bars,
+//! market orders are filled at the high (buy) or the low (sell) of their bar, limit orders at the limit or a better open,
+//! and every entry counts as a deal. With the same fills the results are the same as the event-driven backtest;
+//! what it leaves out is what cannot be expressed as columns:
+//! * position sizes are the given targets: no position sizer, margin or available capital checks;
+//! * the strategy logic is reduced to the target and limit price columns, built beforehand (see `crossover_targets`).
+//!
+//! Typical use is a pre-filter: rank every combination of a grid with the vectorized backtester and run
+//! the event-driven backtest only on the best ones.
+
+use crate::commission_plans;
+use crate::data_handler;
+use crate::instruments_info;
+use crate::performance;
+use crate::settings;
+use crate::utils;
+
+// --- Targets ---
+
+/// Position targets of one symbol: after bar `i` the strategy holds `targets[i]` contracts (negative for short).
+pub struct SymbolTargets<'a> {
+    /// Symbol name, used to look up its instrument info.
+    pub symbol: &'a str,
+    /// Bars of the symbol, oldest first.
+    pub bars: &'a [data_handler::MarketBar],
+    /// Target position after each bar; same length as `bars`.
+    pub targets: Vec<f64>,
+    /// Limit price of the order filled on each bar, NaN for a market order; empty if every order is a market order.
+    /// A market order is filled at the high (buy) or the low (sell) of its bar; a limit order at the open
+    /// if the bar opens beyond the limit, otherwise at the limit. Targets only change on bars where the order is filled.
+    pub limit_prices: Vec<f64>,
+}
+
+/// Builds the targets and orders of the moving average crossover of `MA_cross`.
+/// A long position of `quantity` contracts is opened while `fast > slow` and a short one while `fast < slow`,
+/// with a limit order at the close of the signal bar. An open position is closed by a market order when the averages
+/// cross or at the expiration of the contract, and the opposite position is opened on the next signal.
+/// Positions are opened only between `trade_from_date` (inclusive) and `expiration_date` (exclusive);
+/// bars where an average is NaN (warm-up) send no order.
+/// As in the event-driven backtest, the order of a signal is filled on the next bar, and a limit order
+/// that is not reached there is dropped (the signal is re-evaluated on that bar).
+/// # Arguments
+/// * `symbol` - Symbol name.
+/// * `bars` - Bars of the symbol.
+/// * `fast` - Fast moving average, one value per bar.
+/// * `slow` - Slow moving average, one value per bar.
+/// * `quantity` - Contracts per position.
+/// * `instrument_info` - Instrument metadata with the trading window of the contract.
+/// # Returns
+/// * `anyhow::Result<SymbolTargets>` - Targets and limit prices, or an error if the trading window cannot be parsed.
+pub fn crossover_targets<'a>(
+    symbol: &'a str,
+    bars: &'a [data_handler::MarketBar],
+    fast: &[f64],
+    slow: &[f64],
+    quantity: f64,
+    instrument_info: &instruments_info::InstrumentInfo,
+) -> anyhow::Result<SymbolTargets<'a>> {
+    if fast.len() != bars.len() || slow.len() != bars.len() {
+        anyhow::bail!(
+            "Moving averages have {} and {} values for {} bars",
+            fast.len(), slow.len(), bars.len(),
+        );
+    }
+
+    let trade_from_date = utils::string_to_date_time(&instrument_info.trade_from_date, "%Y-%m-%d %H:%M:%S")?;
+    let expiration_date = utils::string_to_date_time(&instrument_info.expiration_date, "%Y-%m-%d %H:%M:%S")?;
+
+    let mut targets = Vec::with_capacity(bars.len());
+    let mut limit_prices = Vec::with_capacity(bars.len());
+    let mut position = 0.0;
+    // (target, limit price) of the order sent on the previous bar
+    let mut pending_order: Option<(f64, f64)> = None;
+    for ((bar, &fast), &slow) in bars.iter().zip(fast).zip(slow) {
+        // Fill the order of the previous bar
+        let mut limit_price = f64::NAN;
+        if let Some((target, order_limit_price)) = pending_order.take() {
+            let reached = if order_limit_price.is_nan() {
+                true
+            } else if target > position {
+                bar.low <= order_limit_price
+            } else {
+                bar.high >= order_limit_price
+            };
+            if reached {
+                position = target;
+                limit_price = order_limit_price;
+            }
+        }
+        targets.push(position);
+        limit_prices.push(limit_price);
+
+        // Signals of this bar
+        if !fast.is_nan() && !slow.is_nan() {
+            let tradable = bar.datetime >= trade_from_date && bar.datetime < expiration_date;
+            pending_order = if position > 0.0 {
+                (fast < slow || bar.datetime >= expiration_date).then_some((0.0, f64::NAN))
+            } else if position < 0.0 {
+                (fast > slow || bar.datetime >= expiration_date).then_some((0.0, f64::NAN))
+            } else if fast > slow && tradable {
+                Some((quantity, bar.close))
+            } else if fast < slow && tradable {
+                Some((-quantity, bar.close))
+            } else {
+                None
+            };
+        }
+    }
+
+    anyhow::Ok(SymbolTargets {
+        symbol,
+        bars,
+        targets,
+        limit_prices,
+    })
+}
+
+// --- Backtester ---
+
+/// PnL of one symbol per bar, net of commissions.
+#[derive(Debug, Clone)]
+pub struct SymbolPnl {
+    /// PnL of each bar of the symbol.
+    pub pnl: Vec<f64>,
+    /// Number of entries (deals).
+    pub deals_count: usize,
+}
+
+/// Backtests position targets over whole columns.
+pub struct VectorizedBacktester<'a> {
+    /// Starting capital of the strategy.
+    initial_capital: f64,
+    /// Strategy settings: commission plans and metrics calculation mode.
+    strategy_settings: &'a settings::StrategySettings,
+    /// Instrument metadata (step, step price, exchange) for every traded symbol.
+    instruments_info: &'a std::collections::HashMap<String, instruments_info::InstrumentInfo>,
+}
+
+impl<'a> VectorizedBacktester<'a> {
+    /// Creates a new `VectorizedBacktester`.
+    /// # Arguments
+    /// * `initial_capital` - Starting capital of the strategy.
+    /// * `strategy_settings` - Strategy settings with commission plans and metrics calculation mode.
+    /// * `instruments_info` - Instrument metadata for the traded symbols.
+    pub fn new(
+        initial_capital: f64,
+        strategy_settings: &'a settings::StrategySettings,
+        instruments_info: &'a std::collections::HashMap<String, instruments_info::InstrumentInfo>,
+    ) -> Self {
+        Self {
+            initial_capital,
+            strategy_settings,
+            instruments_info,
+        }
+    }
+
+    /// Backtests the targets of every symbol and calculates the performance metrics of the combined equity.
+    /// # Arguments
+    /// * `symbols` - Targets of each traded symbol.
+    /// # Returns
+    /// * `anyhow::Result<PerformanceMetrics>` - The metrics, or an error for unknown symbols or mismatched columns.
+    pub fn run(&self, symbols: &[SymbolTargets]) -> anyhow::Result<performance::PerformanceMetrics> {
+        let mut columns = Vec::with_capacity(symbols.len());
+        let mut deals_count = 0;
+        for symbol_targets in symbols {
+            let symbol_pnl = self.symbol_pnl(symbol_targets)?;
+            deals_count += symbol_pnl.deals_count;
+            columns.push((symbol_targets.bars, symbol_pnl.pnl));
+        }
+
+        let (datetimes, pnl) = merge_pnl(columns);
+        let (Some(&start_date), Some(&end_date)) = (datetimes.first(), datetimes.last()) else {
+            return anyhow::Ok(performance::PerformanceMetrics::default());
+        };
+
+        // Equity after each step of the combined timeline
+        let mut capital = self.initial_capital;
+        let equity_series: Vec<f64> = pnl
+            .iter()
+            .map(|pnl| {
+                capital += pnl;
+                capital
+            })
+            .collect();
+
+        let mut performance_manager = performance::PerformanceManager::new(self.initial_capital, self.strategy_settings);
+        match self.strategy_settings.portfolio_settings_for_strategy.metrics_calculation_mode {
+            settings::MetricsMode::Offline => {
+                performance_manager.calculate_final(&equity_series, start_date, end_date, deals_count);
+            },
+            settings::MetricsMode::RealTime { .. } => {
+                for (datetime, capital) in datetimes.iter().zip(&equity_series) {
+                    performance_manager.update_incremental(*capital, start_date, *datetime, deals_count);
+                }
+            },
+        }
+
+        anyhow::Ok(performance_manager.get_current_performance_metrics().clone())
+    }
+
+    /// Calculates the PnL of one symbol per bar.
+    /// Holding PnL, fill prices and fill PnL are computed four bars at a time;
+    /// commissions only on the bars where the target changes.
+    /// # Arguments
+    /// * `symbol_targets` - Bars, targets and limit prices of the symbol.
+    /// # Returns
+    /// * `anyhow::Result<SymbolPnl>` - PnL per bar and number of deals.
+    pub fn symbol_pnl(&self, symbol_targets: &SymbolTargets) -> anyhow::Result<SymbolPnl> {
+        let bars = symbol_targets.bars;
+        let targets = &symbol_targets.targets;
+        if targets.len() != bars.len() {
+            anyhow::bail!(
+                "{} has {} targets for {} bars",
+                symbol_targets.symbol, targets.len(), bars.len(),
+            );
+        }
+        let limit_prices = if symbol_targets.limit_prices.is_empty() {
+            vec![f64::NAN; bars.len()]
+        } else if symbol_targets.limit_prices.len() == bars.len() {
+            symbol_targets.limit_prices.clone()
+        } else {
+            anyhow::bail!(
+                "{} has {} limit prices for {} bars",
+                symbol_targets.symbol, symbol_targets.limit_prices.len(), bars.len(),
+            );
+        };
+        let instrument_info = self.instruments_info
+            .get(symbol_targets.symbol)
+            .ok_or_else(|| anyhow::anyhow!("No instrument info for {}", symbol_targets.symbol))?;
+
+        let n = bars.len();
+        let cost_of_step_price = ((instrument_info.step_price / instrument_info.step) * 100_000.0).round() / 100_000.0;
+
+        // --- Columns ---
+        // Bars before the first bar of a symbol have NaN prices; they are zeroed and never traded.
+        let price = |value: f64| if value.is_finite() { value } else { 0.0 };
+        let open: Vec<f64> = bars.iter().map(|bar| price(bar.open)).collect();
+        let close: Vec<f64> = bars.iter().map(|bar| price(bar.close)).collect();
+        let high: Vec<f64> = bars.iter().map(|bar| price(bar.high)).collect();
+        let low: Vec<f64> = bars.iter().map(|bar| price(bar.low)).collect();
+        let last_close: Vec<f64> = std::iter::once(close.first().copied().unwrap_or_default())
+            .chain(close.iter().copied())
+            .take(n)
+            .collect();
+        // Holding PnL only on real bars whose close and previous close are known
+        let traded: Vec<f64> = bars
+            .iter()
+            .enumerate()
+            .map(|(i, bar)| {
+                let known = bar.close.is_finite() && (i == 0 || bars[i - 1].close.is_finite());
+                if !bar.is_filled && known { 1.0 } else { 0.0 }
+            })
+            .collect();
+        let last_target: Vec<f64> = std::iter::once(0.0)
+            .chain(targets.iter().copied())
+            .take(n)
+            .collect();
+
+        // --- Holding PnL and fills ---
+        // holding = (close - last_close) * cost * last_target on traded bars
+        // fill = (close - execution_price) * cost * delta
+        let mut holding_pnl = vec![0.0; n];
+        let mut fill_pnl = vec![0.0; n];
+        let mut execution_prices = vec![0.0; n];
+        let cost = wide::f64x4::splat(cost_of_step_price);
+        let zero = wide::f64x4::splat(0.0);
+        let simd_end = n - n % 4;
+        for start in (0..simd_end).step_by(4) {
+            let close_lanes = lanes(&close, start);
+            let open_lanes = lanes(&open, start);
+            let limit_lanes = lanes(&limit_prices, start);
+            let target_lanes = lanes(targets, start);
+            let last_target_lanes = lanes(&last_target, start);
+            let delta = target_lanes - last_target_lanes;
+            let buy = wide::CmpGt::cmp_gt(delta, zero);
+            let market_price = buy.blend(lanes(&high, start), lanes(&low, start));
+            let limit_price = buy.blend(open_lanes.min(limit_lanes), open_lanes.max(limit_lanes));
+            let execution_price = limit_lanes.is_nan().blend(market_price, limit_price);
+
+            let holding = (close_lanes - lanes(&last_close, start)) * cost * last_target_lanes * lanes(&traded, start);
+            let fill = (close_lanes - execution_price) * cost * delta;
+            holding_pnl[start..start + 4].copy_from_slice(&holding.to_array());
+            fill_pnl[start..start + 4].copy_from_slice(&fill.to_array());
+            execution_prices[start..start + 4].copy_from_slice(&execution_price.to_array());
+        }
+        for i in simd_end..n {
+            let delta = targets[i] - last_target[i];
+            execution_prices[i] = match (limit_prices[i].is_nan(), delta > 0.0) {
+                (true, true) => high[i],
+                (true, false) => low[i],
+                (false, true) => open[i].min(limit_prices[i]),
+                (false, false) => open[i].max(limit_prices[i]),
+            };
+            holding_pnl[i] = (close[i] - last_close[i]) * cost_of_step_price * last_target[i] * traded[i];
+            fill_pnl[i] = (close[i] - execution_prices[i]) * cost_of_step_price * delta;
+        }
+
+        // --- Commissions and deals, on the bars where the target changes ---
+        let mut pnl: Vec<f64> = holding_pnl
+            .iter()
+            .map(|holding| (holding * 100.0).round() / 100.0)
+            .collect();
+        let mut deals_count = 0;
+        for i in 0..n {
+            let delta = targets[i] - last_target[i];
+            if delta == 0.0 {
+                continue;
+            }
+            let commission = commission_plans::calculate_forts_comission(
+                Some(execution_prices[i]),
+                instrument_info,
+                self.strategy_settings,
+            ).unwrap_or(0.0);
+            pnl[i] += (fill_pnl[i] * 100.0).round() / 100.0 - commission * delta.abs();
+
+            if targets[i] != 0.0 && (last_target[i] == 0.0 || targets[i].signum() != last_target[i].signum()) {
+                deals_count += 1;
+            }
+        }
+
+        anyhow::Ok(SymbolPnl { pnl, deals_count })
+    }
+}
+
+// --- Helpers ---
+
+/// Loads four consecutive values starting at `start`.
+fn lanes(values: &[f64], start: usize) -> wide::f64x4 {
+    wide::f64x4::from([values[start], values[start + 1], values[start + 2], values[start + 3]])
+}
+
+/// Sums the PnL of every symbol per datetime, over the union of their datetimes.
+/// # Arguments
+/// * `columns` - Bars and PnL per bar of each symbol.
+/// # Returns
+/// * The combined timeline and the PnL of each of its steps.
+fn merge_pnl(columns: Vec<(&[data_handler::MarketBar], Vec<f64>)>) -> (Vec<chrono::DateTime<chrono::Utc>>, Vec<f64>) {
+    if columns.len() == 1 {
+        let (bars, pnl) = columns.into_iter().next().unwrap();
+        return (bars.iter().map(|bar| bar.datetime).collect(), pnl);
+    }
+
+    let mut steps: Vec<(chrono::DateTime<chrono::Utc>, f64)> = columns
+        .iter()
+        .flat_map(|(bars, pnl)| bars.iter().map(|bar| bar.datetime).zip(pnl.iter().copied()))
+        .collect();
+    steps.sort_by_key(|(datetime, _)| *datetime);
+
+    let mut datetimes: Vec<chrono::DateTime<chrono::Utc>> = Vec::with_capacity(steps.len());
+    let mut pnl: Vec<f64> = Vec::with_capacity(steps.len());
+    for (datetime, step_pnl) in steps {
+        if datetimes.last() == Some(&datetime) {
+            *pnl.last_mut().unwrap() += step_pnl;
+        } else {
+            datetimes.push(datetime);
+            pnl.push(step_pnl);
+        }
+    }
+    (datetimes, pnl)
+}
@@ -0,0 +1,24 @@
+// farukon_core/tests/common/mod.rs
+
+//! Fixtures shared by the integration tests.
+
+use farukon_core::settings::StrategySettings;
+
+/// Settings of a single-threaded strategy trading `Si-3.24`, with offline metrics and no commission plans.
+pub fn strategy_settings() -> StrategySettings {
+    serde_json::from_value(serde_json::json!({
+        "threads": 1,
+        "strategy_name": "MA_cross",
+        "exit_results_path": "results",
+        "strategy_weight": 1,
+        "slippage": [0.0],
+        "data": { "data_path": "data", "timeframe": "5min" },
+        "symbols": ["Si-3.24"],
+        "strategy_params": { "short_window": [10], "long_window": [100] },
+        "pos_sizer_params": { "pos_sizer_name": "mpr", "pos_sizer_params": {}, "pos_sizer_value": [1] },
+        "margin_params": { "min_margin": 0.5, "margin_call_type": "close_deal" },
+        "portfolio_settings_for_strategy": { "metrics_calculation_mode": "offline" },
+        "optimizer_type": "Grid_Search"
+    }))
+    .unwrap()
+}
@@ -0,0 +1,303 @@
+// farukon_core/tests/indicators.rs
+
+//! Reference tests of `farukon_core::indicators`.
+//! The expected values were computed independently from the textbook definitions
+//! (Wilder's smoothing for RSI/ATR/ADX, SMA-seeded EMAs, population deviation for Bollinger)
+//! on the fixed 40-bar series below; each batch function must match them,
+//! and each streaming indicator must match its batch function.
+
+use farukon_core::indicators;
+use farukon_core::indicators::streaming::{self, StreamingIndicator};
+
+/// (open, high, low, close, volume), one 5min bar each.
+const BARS: [(f64, f64, f64, f64, u64); 40] = [
+    (99.65, 100.63, 98.14, 98.25, 648),
+    (97.44, 99.13, 97.12, 97.77, 188),
+    (97.64, 97.78, 95.28, 95.92, 946),
+    (96.05, 98.79, 95.18, 97.84, 163),
+    (97.99, 99.45, 97.51, 97.58, 979),
+    (96.85, 97.66, 95.67, 96.53, 673),
+    (97.16, 98.03, 94.92, 95.88, 481),
+    (95.07, 96.77, 94.14, 95.92, 608),
+    (96.28, 96.75, 95.11, 95.99, 564),
+    (95.71, 95.98, 93.53, 94.70, 183),
+    (94.85, 96.26, 93.76, 94.95, 394),
+    (95.17, 95.94, 93.21, 93.46, 450),
+    (92.76, 92.82, 91.72, 92.72, 882),
+    (92.84, 95.23, 92.33, 94.00, 458),
+    (94.19, 95.19, 92.93, 94.51, 376),
+    (94.46, 95.21, 93.41, 95.12, 762),
+    (95.28, 96.67, 94.21, 96.00, 784),
+    (95.69, 97.98, 94.77, 97.45, 605),
+    (96.57, 97.83, 96.20, 97.64, 500),
+    (98.47, 98.72, 97.86, 98.46, 384),
+    (99.23, 101.81, 98.81, 100.51, 525),
+    (101.48, 102.78, 101.13, 102.21, 184),
+    (101.56, 101.91, 99.76, 100.49, 703),
+    (99.85, 100.07, 98.18, 98.98, 724),
+    (99.11, 101.96, 98.34, 100.92, 732),
+    (101.23, 102.87, 99.92, 102.19, 995),
+    (102.55, 103.39, 101.96, 102.79, 593),
+    (103.06, 103.16, 101.00, 101.31, 266),
+    (100.53, 101.08, 99.68, 100.93, 649),
+    (100.13, 100.17, 98.27, 99.58, 728),
+    (99.33, 101.30, 98.43, 99.87, 585),
+    (99.12, 102.01, 98.42, 100.52, 595),
+    (100.14, 101.26, 97.61, 98.72, 590),
+    (99.38, 99.41, 96.60, 98.03, 640),
+    (97.75, 99.88, 96.61, 98.51, 405),
+    (99.47, 101.96, 99.08, 100.92, 475),
+    (101.74, 102.07, 100.35, 101.16, 614),
+    (100.82, 102.04, 98.23, 99.71, 973),
+    (99.10, 99.70, 96.86, 98.06, 304),
+    (98.10, 98.14, 97.48, 97.52, 386),
+];
+
+const EPSILON: f64 = 1e-9;
+
+fn bars() -> Vec<farukon_core::data_handler::MarketBar> {
+    let start = chrono::DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z").unwrap().with_timezone(&chrono::Utc);
+    BARS.iter()
+        .enumerate()
+        .map(|(idx, &(open, high, low, close, volume))| farukon_core::data_handler::MarketBar {
+            datetime: start + chrono::Duration::minutes(5 * idx as i64),
+            open,
+            high,
+            low,
+            close,
+            volume,
+            is_filled: false,
+        })
+        .collect()
+}
+
+fn closes() -> Vec<f64> {
+    BARS.iter().map(|bar| bar.3).collect()
+}
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.expect("indicator has no value");
+    assert!((actual - expected).abs() < EPSILON, "expected {}, got {}", expected, actual);
+}
+
+fn assert_bands(actual: Option<indicators::Bands>, upper: f64, middle: f64, lower: f64) {
+    let actual = actual.expect("indicator has no value");
+    assert_close(Some(actual.upper), upper);
+    assert_close(Some(actual.middle), middle);
+    assert_close(Some(actual.lower), lower);
+}
+
+/// Feeds all bars to a streaming indicator and returns its last value.
+fn stream<T: StreamingIndicator>(mut indicator: T) -> Option<T::Output> {
+    bars().iter().fold(None, |_, bar| indicator.update(bar))
+}
+
+// --- Hand-checked values ---
+
+#[test]
+fn simple_series() {
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_close(indicators::sma(&values, 3), 4.0);
+    // EMA(3): seed (1 + 2 + 3) / 3 = 2, then 2 + 0.5 * (4 - 2) = 3, then 3 + 0.5 * (5 - 3) = 4
+    assert_close(indicators::ema(&values, 3), 4.0);
+    // WMA(3): (1 * 3 + 2 * 4 + 3 * 5) / 6
+    assert_close(indicators::wma(&values, 3), 26.0 / 6.0);
+    // Only gains: RSI 100; no change at all: RSI 50
+    assert_close(indicators::rsi(&values, 3), 100.0);
+    assert_close(indicators::rsi(&[2.0, 2.0, 2.0, 2.0], 3), 50.0);
+    // Population deviation of [3, 4, 5] is sqrt(2 / 3)
+    let std = (2.0f64 / 3.0).sqrt();
+    assert_bands(indicators::bollinger(&values, 3, 1.0), 4.0 + std, 4.0, 4.0 - std);
+
+    assert_eq!(indicators::ema(&values, 6), None);
+    assert_eq!(indicators::wma(&values, 0), None);
+    assert_eq!(indicators::rsi(&values, 5), None);
+}
+
+// --- Reference values ---
+
+#[test]
+fn moving_averages() {
+    let closes = closes();
+    assert_close(indicators::sma(&closes, 10), 99.30199999999999);
+    assert_close(indicators::ema(&closes, 10), 99.20957849749101);
+    assert_close(indicators::wma(&closes, 10), 99.10545454545455);
+
+    assert_close(stream(streaming::Sma::new(10)), 99.30199999999999);
+    assert_close(stream(streaming::Ema::new(10)), 99.20957849749101);
+    assert_close(stream(streaming::Wma::new(10)), 99.10545454545455);
+}
+
+#[test]
+fn rsi() {
+    assert_close(indicators::rsi(&closes(), 14), 43.54261922536416);
+    assert_close(stream(streaming::Rsi::new(14)), 43.54261922536416);
+}
+
+#[test]
+fn atr() {
+    let bars = bars();
+    assert_close(indicators::atr(&bars, 14), 2.5716915906612403);
+    assert_close(indicators::atr(&bars[..30], 14), 2.365132396961103);
+    assert_close(stream(streaming::Atr::new(14)), 2.5716915906612403);
+    assert_eq!(indicators::atr(&bars[..14], 14), None);
+}
+
+#[test]
+fn bollinger() {
+    let (upper, middle, lower) = (103.05841534904434, 100.1465, 97.23458465095567);
+    assert_bands(indicators::bollinger(&closes(), 20, 2.0), upper, middle, lower);
+    assert_bands(stream(streaming::Bollinger::new(20, 2.0)), upper, middle, lower);
+}
+
+#[test]
+fn macd() {
+    let (line, signal, histogram) = (0.538211924558226, 1.0937668367044069, -0.5555549121461809);
+    for actual in [indicators::macd(&closes(), 12, 26, 9), stream(streaming::Macd::new(12, 26, 9))] {
+        let actual = actual.expect("MACD has no value");
+        assert_close(Some(actual.macd), line);
+        assert_close(Some(actual.signal), signal);
+        assert_close(Some(actual.histogram), histogram);
+    }
+    // 26 + 9 - 1 values are needed
+    assert!(indicators::macd(&closes()[..33], 12, 26, 9).is_none());
+    assert!(indicators::macd(&closes()[..34], 12, 26, 9).is_some());
+}
+
+#[test]
+fn stochastic() {
+    for actual in [indicators::stochastic(bars(), 14, 3), stream(streaming::Stochastic::new(14, 3))] {
+        let actual = actual.expect("Stochastic has no value");
+        assert_close(Some(actual.k), 13.54933726067748);
+        assert_close(Some(actual.d), 26.951399116347588);
+    }
+}
+
+#[test]
+fn adx() {
+    for actual in [indicators::adx(bars(), 14), stream(streaming::Adx::new(14))] {
+        let actual = actual.expect("ADX has no value");
+        assert_close(Some(actual.adx), 11.151426993130563);
+        assert_close(Some(actual.plus_di), 18.04499383269478);
+        assert_close(Some(actual.minus_di), 23.043250091125195);
+    }
+    assert!(indicators::adx(&bars()[..27], 14).is_none());
+    assert!(indicators::adx(&bars()[..28], 14).is_some());
+}
+
+#[test]
+fn channels() {
+    assert_bands(indicators::donchian(bars(), 20), 103.39, 99.995, 96.6);
+    assert_bands(stream(streaming::Donchian::new(20)), 103.39, 99.995, 96.6);
+
+    let (upper, middle, lower) = (104.34265712775938, 99.1729989107836, 94.00334069380783);
+    assert_bands(indicators::keltner(bars(), 20, 10, 2.0), upper, middle, lower);
+    assert_bands(stream(streaming::Keltner::new(20, 10, 2.0)), upper, middle, lower);
+}
+
+#[test]
+fn parabolic_sar() {
+    let bars = bars();
+    assert_close(indicators::parabolic_sar(&bars[..11], 0.02, 0.2), 97.64521481047143);
+    assert_close(indicators::parabolic_sar(&bars, 0.02, 0.2), 96.86);
+    assert_close(stream(streaming::ParabolicSar::new(0.02, 0.2)), 96.86);
+    assert_eq!(indicators::parabolic_sar(&bars[..1], 0.02, 0.2), None);
+}
+
+#[test]
+fn vwap() {
+    assert_close(indicators::vwap(bars()), 98.11038894274549);
+    assert_close(stream(streaming::Vwap::new()), 98.11038894274549);
+}
+
+// --- Streaming behaviour ---
+
+#[test]
+fn rolling_extremes_match_batch() {
+    let bars = bars();
+    let highs: Vec<Option<f64>> = bars.iter().map(|bar| Some(bar.high)).collect();
+    let lows: Vec<Option<f64>> = bars.iter().map(|bar| Some(bar.low)).collect();
+    let mut highest = streaming::RollingMax::new(7);
+    let mut lowest = streaming::RollingMin::new(7);
+    for (idx, bar) in bars.iter().enumerate() {
+        assert_eq!(highest.update(bar), indicators::highest(&highs[..=idx], 7, 0));
+        assert_eq!(lowest.update(bar), indicators::lowest(&lows[..=idx], 7, 0));
+    }
+}
+
+#[test]
+fn symbol_indicators_feed_each_bar_once() {
+    let bars = bars();
+    let symbols = vec!["A".to_string(), "B".to_string()];
+    let mut sma = streaming::SymbolIndicators::for_symbols(&symbols, || streaming::Sma::new(3));
+
+    // "B" sees every bar twice, as on steps where only another symbol got a new bar.
+    for bar in &bars[..5] {
+        sma.update("A", bar);
+        sma.update("B", bar);
+        sma.update("B", bar);
+    }
+    assert_close(sma.value("A"), indicators::sma(&closes()[..5], 3).unwrap());
+    assert_eq!(sma.value("A"), sma.value("B"));
+    assert_eq!(sma.update("C", &bars[0]), None);
+
+    sma.reset();
+    assert_eq!(sma.value("A"), None);
+}
+
+#[test]
+fn vwap_daily_reset() {
+    let mut bars = bars();
+    // Move the second half to the next day.
+    for bar in &mut bars[20..] {
+        bar.datetime += chrono::Duration::days(1);
+    }
+    let mut vwap = streaming::Vwap::new().with_daily_reset();
+    let last = bars.iter().fold(None, |_, bar| vwap.update(bar));
+    assert_close(last, indicators::vwap(&bars[20..]).unwrap());
+}
+
+// --- Indicator cache ---
+
+#[test]
+fn cached_series_match_batch() {
+    use farukon_core::indicator_cache::IndicatorSpec;
+
+    let bars = bars();
+    let closes = closes();
+    let highs: Vec<Option<f64>> = bars.iter().map(|bar| Some(bar.high)).collect();
+    let cache = farukon_core::indicator_cache::IndicatorCache::new("5min");
+
+    // Every bar index, so both the SIMD lanes and the scalar remainder are compared.
+    for idx in 0..bars.len() {
+        let value = |spec: IndicatorSpec| cache.value_at("A", &spec, &bars, idx);
+        // Same summation order as the batch SMA: identical, not only close.
+        assert_eq!(value(IndicatorSpec::sma(7)), indicators::sma(&closes[..=idx], 7));
+        assert_eq!(value(IndicatorSpec::highest(7)), indicators::highest(&highs[..=idx], 7, 0));
+        for (cached, batch) in [
+            (value(IndicatorSpec::wma(10)), indicators::wma(&closes[..=idx], 10)),
+            (value(IndicatorSpec::ema(10)), indicators::ema(&closes[..=idx], 10)),
+            (value(IndicatorSpec::rsi(14)), indicators::rsi(&closes[..=idx], 14)),
+            (value(IndicatorSpec::atr(14)), indicators::atr(&bars[..=idx], 14)),
+            (value(IndicatorSpec::std(20)), indicators::bollinger(&closes[..=idx], 20, 1.0).map(|bands| bands.upper - bands.middle)),
+        ] {
+            assert_eq!(cached.is_some(), batch.is_some(), "bar {}", idx);
+            if let Some(batch) = batch {
+                assert_close(cached, batch);
+            }
+        }
+    }
+    // Each series is computed once, whatever the number of lookups.
+    assert_eq!(cache.len(), 7);
+    assert_eq!(cache.value_at("A", &IndicatorSpec::sma(7), &bars, bars.len()), None);
+
+    // Handlers without a cache compute the same value from the published bars.
+    let spec = IndicatorSpec::lowest(5).with_source(streaming::PriceSource::Close);
+    assert_eq!(spec.last_value(&bars), cache.value_at("A", &spec, &bars, bars.len() - 1));
+
+    // They read only the last `lookback + shift` bars of a window indicator.
+    assert_eq!(IndicatorSpec::ema(10).lookback(), None);
+    let (lookback, shift) = (spec.lookback().unwrap(), 3);
+    let latest = &bars[bars.len() - lookback - shift..bars.len() - shift];
+    assert_eq!(spec.last_value(latest), cache.value_at("A", &spec, &bars, bars.len() - 1 - shift));
+}
@@ -0,0 +1,311 @@
+// farukon_core/tests/optimization.rs
+
+//! Tests of the optimizers of `farukon_core::optimization` on small parameter spaces,
+//! with fitness functions simple enough to know the expected answer.
+
+mod common;
+
+use farukon_core::optimization::{
+    self, BayesianConfig, BayesianOptimizer, GAConfig, GeneticAlgorythm, Nsga2Config, Nsga2Optimizer, OptimizationConfig, ParameterSet, SamplingMethod, SamplingOptimizer,
+};
+use farukon_core::param_schema::{CompareOp, ParamConstraint, ParamKind, ParamSchema, ParamSpec};
+use farukon_core::settings::{BayesianParams, GAParams, Nsga2Params};
+
+/// `short_window` and `long_window` from 1 to `values`, one position sizer value and one slippage.
+fn optimization_config(values: i64) -> OptimizationConfig {
+    let range: Vec<serde_json::Value> = (1..=values).map(serde_json::Value::from).collect();
+    OptimizationConfig::new()
+        .with_strategy_params_ranges(std::collections::HashMap::from([
+            ("short_window".to_string(), range.clone()),
+            ("long_window".to_string(), range),
+        ]))
+        .with_pos_sizer_name("mpr".to_string())
+        .with_pos_sizer_value_ranges(vec![1.0])
+        .with_slippage_range(vec![0.0])
+}
+
+fn param(params: &ParameterSet, name: &str) -> f64 {
+    params.get_strategy_params()
+        .iter()
+        .find(|(param_name, _)| param_name == name)
+        .and_then(|(_, value)| value.as_f64())
+        .unwrap()
+}
+
+/// (short_window, long_window) of every parameter set.
+fn windows(samples: &[ParameterSet]) -> Vec<(i64, i64)> {
+    samples.iter().map(|params| (param(params, "short_window") as i64, param(params, "long_window") as i64)).collect()
+}
+
+/// Schema with the constraint `short_window < long_window`.
+fn short_below_long() -> ParamSchema {
+    let window = || ParamKind::Int { min: Some(1), max: None };
+    ParamSchema::new()
+        .with_param(ParamSpec::new("short_window", window()))
+        .with_param(ParamSpec::new("long_window", window()))
+        .with_constraint(ParamConstraint::new("short_window", CompareOp::Less, "long_window"))
+}
+
+// --- Sampling ---
+
+#[test]
+fn sampling_is_reproducible_from_its_seed() {
+    for method in [SamplingMethod::Random, SamplingMethod::LatinHypercube] {
+        let samples = |seed: u64| windows(
+            &SamplingOptimizer::new()
+                .with_optimization_config(optimization_config(30))
+                .with_method(method)
+                .with_budget(25)
+                .with_seed(seed)
+                .generate_samples(),
+        );
+        assert_eq!(samples(11).len(), 25);
+        assert_eq!(samples(11), samples(11), "{:?}", method);
+        assert_ne!(samples(11), samples(12), "{:?}", method);
+    }
+}
+
+#[test]
+fn latin_hypercube_covers_each_stratum_once() {
+    let samples = |budget: usize| windows(
+        &SamplingOptimizer::new()
+            .with_optimization_config(optimization_config(20))
+            .with_method(SamplingMethod::LatinHypercube)
+            .with_budget(budget)
+            .with_seed(3)
+            .generate_samples(),
+    );
+    let sorted = |mut values: Vec<i64>| {
+        values.sort();
+        values
+    };
+
+    // As many strata as values: every value is drawn exactly once on each axis
+    let samples_20 = samples(20);
+    assert_eq!(sorted(samples_20.iter().map(|(short, _)| *short).collect()), (1..=20).collect::<Vec<i64>>());
+    assert_eq!(sorted(samples_20.iter().map(|(_, long)| *long).collect()), (1..=20).collect::<Vec<i64>>());
+
+    // 10 strata of two values each: one value of every stratum on each axis
+    let samples_10 = samples(10);
+    assert_eq!(sorted(samples_10.iter().map(|(short, _)| (short - 1) / 2).collect()), (0..10).collect::<Vec<i64>>());
+    assert_eq!(sorted(samples_10.iter().map(|(_, long)| (long - 1) / 2).collect()), (0..10).collect::<Vec<i64>>());
+}
+
+#[test]
+fn sampling_replaces_invalid_and_duplicate_sets() {
+    for method in [SamplingMethod::Random, SamplingMethod::LatinHypercube] {
+        let sampler = |budget: usize| SamplingOptimizer::new()
+            .with_optimization_config(optimization_config(6).with_param_schema(Some(short_below_long())))
+            .with_method(method)
+            .with_budget(budget)
+            .with_seed(5);
+
+        // 36 combinations, 15 of them with short_window < long_window
+        let samples = windows(&sampler(12).generate_samples());
+        assert_eq!(samples.len(), 12, "{:?}", method);
+        assert!(samples.iter().all(|(short, long)| short < long), "{:?}: {:?}", method, samples);
+        let distinct: std::collections::HashSet<&(i64, i64)> = samples.iter().collect();
+        assert_eq!(distinct.len(), 12, "{:?}: {:?}", method, samples);
+
+        // A budget above the valid combinations returns each of them once
+        let mut samples = windows(&sampler(30).generate_samples());
+        samples.sort();
+        let all_valid: Vec<(i64, i64)> = (1..=6).flat_map(|short| (short + 1..=6).map(move |long| (short, long))).collect();
+        assert_eq!(samples, all_valid, "{:?}", method);
+    }
+}
+
+#[test]
+fn sampling_a_space_within_the_budget_returns_it_whole() {
+    let samples = SamplingOptimizer::new()
+        .with_optimization_config(optimization_config(3))
+        .with_method(SamplingMethod::Random)
+        .with_budget(9)
+        .generate_samples();
+    // Index order: axes sorted by name, long_window first
+    let expected: Vec<(i64, i64)> = (1..=3).flat_map(|long| (1..=3).map(move |short| (short, long))).collect();
+    assert_eq!(windows(&samples), expected);
+}
+
+// --- Bayesian ---
+
+#[test]
+fn bayesian_forgets_the_scores_of_a_replaced_fitness_function() {
+    let bayesian_params: BayesianParams = serde_json::from_value(serde_json::json!({
+        "budget": 15,
+        "batch_size": 4,
+        "initial_samples": 4,
+        "seed": 3,
+        "fitness_params": { "fitness_direction": "max", "fitness_value": "Total_Return" }
+    }))
+    .unwrap();
+    let optimizer = BayesianOptimizer::new()
+        .with_bayesian_config(BayesianConfig::from_settings(&bayesian_params, 1))
+        .with_optimization_config(optimization_config(4));
+    let fitness = |batch: &[ParameterSet]| batch.iter().map(|params| param(params, "short_window") - param(params, "long_window")).collect();
+    let distinct = |history: &[(ParameterSet, f64)]| {
+        let evaluated = windows(&history.iter().map(|(params, _)| params.clone()).collect::<Vec<_>>());
+        evaluated.into_iter().collect::<std::collections::HashSet<_>>().len()
+    };
+
+    // Every parameter set is evaluated once.
+    let history = optimizer.run(fitness).unwrap();
+    assert_eq!((history.len(), distinct(&history)), (15, 15));
+
+    // A fitness function replaced before the second batch makes the sets scored by the old one worth proposing again.
+    let history = optimizer.run_with_batch_hook(fitness, |batch_idx| batch_idx == 1).unwrap();
+    assert_eq!(history.len(), 15);
+    assert!(distinct(&history) < 15);
+}
+
+// --- Genetic Algorithm ---
+
+#[test]
+fn genetic_algorithm_min_direction_keeps_the_lowest_metric() {
+    let ga_params: GAParams = serde_json::from_value(serde_json::json!({
+        "population_size": 10,
+        "p_crossover": 0.5,
+        "p_mutation": 0.3,
+        "max_generations": 8,
+        "elite_count": 2,
+        "seed": 7,
+        "fitness_params": { "fitness_direction": "min", "fitness_value": "Deals_Count" }
+    }))
+    .unwrap();
+
+    // Raw metric to minimize: short_window + long_window. The score is the negated metric,
+    // as the engine scores "min" objectives, so a higher score is always better.
+    let evaluated = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let evaluate = {
+        let evaluated = evaluated.clone();
+        move |params: &ParameterSet| {
+            let metric = param(params, "short_window") + param(params, "long_window");
+            evaluated.lock().unwrap().push(metric);
+            -metric
+        }
+    };
+
+    let stats = GeneticAlgorythm::new()
+        .with_ga_config(GAConfig::from_settings(&ga_params))
+        .with_optimization_config(optimization_config(30))
+        .run(&common::strategy_settings(), evaluate)
+        .unwrap();
+
+    let lowest_metric = evaluated.lock().unwrap().iter().fold(f64::INFINITY, |a, &b| a.min(b));
+    let best_scores: Vec<f64> = stats.iter().map(|stat| stat.get_best_fitness()).collect();
+    assert_eq!(best_scores.len(), 8);
+    // Elitism carries the best chromosome over: the best score never falls.
+    assert!(best_scores.windows(2).all(|pair| pair[1] >= pair[0]), "{:?}", best_scores);
+    assert_eq!(*best_scores.last().unwrap(), -lowest_metric);
+    // Selection pushes the population towards low metrics.
+    assert!(best_scores[7] > best_scores[0], "{:?}", best_scores);
+}
+
+// --- NSGA-II ---
+
+/// Two maximized objectives. Points 1 and 3 are the same point.
+fn pareto_points() -> Vec<Vec<f64>> {
+    vec![
+        vec![4.0, 1.0],
+        vec![3.0, 3.0],
+        vec![1.0, 4.0],
+        vec![3.0, 3.0],
+        vec![2.0, 2.0],
+        vec![1.0, 1.0],
+        vec![2.0, 1.0],
+    ]
+}
+
+#[test]
+fn dominates_needs_no_worse_and_one_better_objective() {
+    assert!(optimization::dominates(&[3.0, 3.0], &[2.0, 2.0]));
+    assert!(optimization::dominates(&[3.0, 3.0], &[3.0, 2.0]));
+    assert!(!optimization::dominates(&[3.0, 2.0], &[3.0, 3.0]));
+    // Equal points and trade-offs dominate neither way
+    assert!(!optimization::dominates(&[3.0, 3.0], &[3.0, 3.0]));
+    assert!(!optimization::dominates(&[4.0, 1.0], &[1.0, 4.0]));
+    assert!(!optimization::dominates(&[1.0, 4.0], &[4.0, 1.0]));
+}
+
+#[test]
+fn non_dominated_fronts_of_a_known_set() {
+    let fronts = optimization::non_dominated_fronts(&pareto_points());
+    // Equal points share a front; (2, 1) is dominated by (2, 2) only once the first front is gone.
+    assert_eq!(fronts, vec![vec![0, 1, 2, 3], vec![4], vec![6], vec![5]]);
+    assert!(optimization::non_dominated_fronts(&[]).is_empty());
+}
+
+#[test]
+fn crowding_distances_of_a_known_front() {
+    let points = pareto_points();
+    // Objective 0 sorted: 1 (point 2), 3 (point 1), 3 (point 3), 4 (point 0); span 3.
+    // Objective 1 sorted: 1 (point 0), 3 (point 1), 3 (point 3), 4 (point 2); span 3.
+    // Point 1: (3 - 1) / 3 + (3 - 1) / 3; point 3: (4 - 3) / 3 + (4 - 3) / 3; points 0 and 2 are extremes.
+    let distances = optimization::crowding_distances(&points, &[0, 1, 2, 3]);
+    assert_eq!(distances[0], f64::INFINITY);
+    assert!((distances[1] - 4.0 / 3.0).abs() < 1e-12, "{:?}", distances);
+    assert_eq!(distances[2], f64::INFINITY);
+    assert!((distances[3] - 2.0 / 3.0).abs() < 1e-12, "{:?}", distances);
+
+    // Fronts of two or fewer points are all extremes
+    assert_eq!(optimization::crowding_distances(&points, &[4]), vec![f64::INFINITY]);
+    assert_eq!(optimization::crowding_distances(&points, &[0, 2]), vec![f64::INFINITY; 2]);
+    assert!(optimization::crowding_distances(&points, &[]).is_empty());
+
+    // An objective with the same value everywhere adds nothing
+    let flat = vec![vec![1.0, 5.0], vec![2.0, 5.0], vec![3.0, 5.0]];
+    assert_eq!(optimization::crowding_distances(&flat, &[0, 1, 2]), vec![f64::INFINITY, 1.0, f64::INFINITY]);
+}
+
+#[test]
+fn nsga2_drops_the_scores_of_a_replaced_objective_function() {
+    let nsga2_params: Nsga2Params = serde_json::from_value(serde_json::json!({
+        "population_size": 8,
+        "p_crossover": 0.9,
+        "p_mutation": 0.3,
+        "max_generations": 4,
+        "objectives": [
+            { "fitness_direction": "max", "fitness_value": "Total_Return" },
+            { "fitness_direction": "max", "fitness_value": "APR" }
+        ],
+        "seed": 9
+    }))
+    .unwrap();
+
+    // Before generation 2 the windows are maximized, from then on minimized (e.g. a rebuilt strategy).
+    let replaced = std::cell::Cell::new(false);
+    let evaluated: std::cell::RefCell<Vec<((i64, i64), bool)>> = std::cell::RefCell::new(Vec::new());
+    let score = |params: &ParameterSet, replaced: bool| {
+        let sign = if replaced { -1.0 } else { 1.0 };
+        vec![sign * param(params, "short_window"), sign * param(params, "long_window")]
+    };
+
+    let pareto_front = Nsga2Optimizer::new()
+        .with_nsga2_config(Nsga2Config::from_settings(&nsga2_params))
+        .with_optimization_config(optimization_config(20))
+        .run_with_generation_hook(
+            |batch| {
+                evaluated.borrow_mut().extend(windows(batch).into_iter().map(|window| (window, replaced.get())));
+                batch.iter().map(|params| score(params, replaced.get())).collect()
+            },
+            |gen_idx| {
+                if gen_idx == 2 {
+                    replaced.set(true);
+                }
+                gen_idx == 2
+            },
+        )
+        .unwrap();
+
+    // The population of generation 1 is evaluated again with the new function.
+    let evaluated = evaluated.into_inner();
+    let evaluated_before: std::collections::HashSet<(i64, i64)> = evaluated.iter().filter(|(_, replaced)| !replaced).map(|(window, _)| *window).collect();
+    let evaluated_again = evaluated.iter().filter(|(window, replaced)| *replaced && evaluated_before.contains(window)).count();
+    assert_eq!(evaluated_again, 8);
+
+    // The front only holds scores of the new function.
+    assert!(!pareto_front.is_empty());
+    for point in &pareto_front {
+        assert_eq!(point.get_objectives(), score(point.get_parameters(), true).as_slice());
+    }
+}
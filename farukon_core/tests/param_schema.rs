@@ -0,0 +1,206 @@
+// farukon_core/tests/param_schema.rs
+
+//! Tests of `farukon_core::param_schema`: value checks, defaults of the configured ranges,
+//! constraints between parameters and the filtering of the Grid Search combinations.
+
+use farukon_core::optimization::OptimizationConfig;
+use farukon_core::param_schema::{CompareOp, ParamConstraint, ParamKind, ParamSchema, ParamSpec};
+
+/// `MA_cross`-like schema: two bounded windows with `short_window < long_window`, and an optional mode.
+fn schema() -> ParamSchema {
+    ParamSchema::new()
+        .with_param(ParamSpec::new("short_window", ParamKind::Int { min: Some(1), max: Some(500) }))
+        .with_param(ParamSpec::new("long_window", ParamKind::Int { min: Some(1), max: Some(500) }))
+        .with_param(
+            ParamSpec::new("mode", ParamKind::Enum { values: vec!["trend".to_string(), "revert".to_string()] })
+                .with_default("trend"),
+        )
+        .with_constraint(ParamConstraint::new("short_window", CompareOp::Less, "long_window"))
+}
+
+fn error_of(result: anyhow::Result<()>) -> String {
+    format!("{:#}", result.expect_err("expected an error"))
+}
+
+fn strategy_params(params: &[(&str, Vec<serde_json::Value>)]) -> std::collections::HashMap<String, Vec<serde_json::Value>> {
+    params.iter().map(|(name, values)| (name.to_string(), values.clone())).collect()
+}
+
+fn combination(params: &[(&str, serde_json::Value)]) -> Vec<(String, serde_json::Value)> {
+    params.iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+}
+
+// --- Values ---
+
+#[test]
+fn int_accepts_whole_floats_only() {
+    let kind = ParamKind::Int { min: Some(1), max: Some(100) };
+    assert!(kind.check_value(&serde_json::json!(10)).is_ok());
+    // `start/end/step` ranges produce floats
+    assert!(kind.check_value(&serde_json::json!(10.0)).is_ok());
+    assert!(error_of(kind.check_value(&serde_json::json!(10.5))).contains("expected an integer, got 10.5"));
+    assert!(error_of(kind.check_value(&serde_json::json!("10"))).contains("expected an integer"));
+    assert!(error_of(kind.check_value(&serde_json::json!(0))).contains("0 is below the minimum 1"));
+    assert!(error_of(kind.check_value(&serde_json::json!(101.0))).contains("101 is above the maximum 100"));
+}
+
+#[test]
+fn float_bool_and_enum_values() {
+    let float = ParamKind::Float { min: Some(0.0), max: None };
+    assert!(float.check_value(&serde_json::json!(2)).is_ok());
+    assert!(float.check_value(&serde_json::json!(0.5)).is_ok());
+    assert!(error_of(float.check_value(&serde_json::json!(-0.5))).contains("below the minimum"));
+    assert!(error_of(float.check_value(&serde_json::json!(true))).contains("expected a number"));
+
+    assert!(ParamKind::Bool.check_value(&serde_json::json!(false)).is_ok());
+    assert!(error_of(ParamKind::Bool.check_value(&serde_json::json!(1))).contains("expected true or false"));
+
+    let enumeration = ParamKind::Enum { values: vec!["trend".to_string()] };
+    assert!(enumeration.check_value(&serde_json::json!("trend")).is_ok());
+    assert!(error_of(enumeration.check_value(&serde_json::json!("revert"))).contains("expected one of"));
+    assert!(error_of(enumeration.check_value(&serde_json::json!(1))).contains("expected one of"));
+}
+
+// --- Configured ranges ---
+
+#[test]
+fn defaults_fill_missing_optional_params() {
+    let mut params = strategy_params(&[
+        ("short_window", vec![serde_json::json!(10), serde_json::json!(20.0)]),
+        ("long_window", vec![serde_json::json!(100)]),
+    ]);
+    schema().apply_to_strategy_params(&mut params).unwrap();
+    assert_eq!(params["mode"], vec![serde_json::json!("trend")]);
+    // Configured values are kept as they are
+    assert_eq!(params["short_window"], vec![serde_json::json!(10), serde_json::json!(20.0)]);
+}
+
+#[test]
+fn missing_required_param_is_an_error() {
+    let mut params = strategy_params(&[("short_window", vec![serde_json::json!(10)])]);
+    let error = error_of(schema().apply_to_strategy_params(&mut params));
+    assert!(error.contains("Missing strategy parameter 'long_window' (it has no default)"), "{}", error);
+}
+
+#[test]
+fn unknown_param_is_an_error() {
+    let mut params = strategy_params(&[
+        ("short_window", vec![serde_json::json!(10)]),
+        ("long_window", vec![serde_json::json!(100)]),
+        ("long_windw", vec![serde_json::json!(100)]),
+    ]);
+    let error = error_of(schema().apply_to_strategy_params(&mut params));
+    assert!(error.contains("Unknown strategy parameter 'long_windw'"), "{}", error);
+    assert!(error.contains("short_window, long_window, mode"), "{}", error);
+}
+
+#[test]
+fn invalid_value_names_the_param() {
+    let mut params = strategy_params(&[
+        ("short_window", vec![serde_json::json!(10), serde_json::json!(12.5)]),
+        ("long_window", vec![serde_json::json!(100)]),
+    ]);
+    let error = error_of(schema().apply_to_strategy_params(&mut params));
+    assert!(error.contains("Invalid value of strategy parameter 'short_window': expected an integer, got 12.5"), "{}", error);
+}
+
+// --- Constraints ---
+
+#[test]
+fn check_combination_reports_the_broken_constraint() {
+    let schema = schema();
+    assert!(schema.check_combination(&combination(&[
+        ("short_window", serde_json::json!(10)),
+        ("long_window", serde_json::json!(100.0)),
+    ])).is_ok());
+
+    let error = error_of(schema.check_combination(&combination(&[
+        ("short_window", serde_json::json!(100)),
+        ("long_window", serde_json::json!(100)),
+    ])));
+    assert_eq!(error, "Constraint 'short_window < long_window' does not hold for short_window = 100, long_window = 100");
+
+    // Constraints on parameters missing from the combination are not checked
+    assert!(schema.is_valid_combination(&combination(&[("short_window", serde_json::json!(100))])));
+}
+
+#[test]
+fn non_numeric_values_are_only_compared_for_equality() {
+    let enum_kind = || ParamKind::Enum { values: vec!["a".to_string(), "b".to_string()] };
+    let equal = ParamSchema::new()
+        .with_param(ParamSpec::new("entry", enum_kind()))
+        .with_param(ParamSpec::new("exit", enum_kind()))
+        .with_constraint(ParamConstraint::new("entry", CompareOp::NotEqual, "exit"));
+    assert!(equal.validate().is_ok());
+    assert!(equal.is_valid_combination(&combination(&[("entry", serde_json::json!("a")), ("exit", serde_json::json!("b"))])));
+    assert!(!equal.is_valid_combination(&combination(&[("entry", serde_json::json!("a")), ("exit", serde_json::json!("a"))])));
+
+    // Ordering non-numeric parameters is rejected by the schema validation, and never holds
+    let ordered = ParamSchema::new()
+        .with_param(ParamSpec::new("entry", enum_kind()))
+        .with_param(ParamSpec::new("exit", enum_kind()))
+        .with_constraint(ParamConstraint::new("entry", CompareOp::Less, "exit"));
+    let error = error_of(ordered.validate());
+    assert!(error.contains("constraint 'entry < exit' orders non-numeric parameter 'entry'"), "{}", error);
+    assert!(!ordered.is_valid_combination(&combination(&[("entry", serde_json::json!("a")), ("exit", serde_json::json!("b"))])));
+}
+
+#[test]
+fn validate_rejects_broken_schemas() {
+    assert!(schema().validate().is_ok());
+
+    let duplicate = schema().with_param(ParamSpec::new("mode", ParamKind::Bool));
+    assert!(error_of(duplicate.validate()).contains("declares 'mode' more than once"));
+
+    let undeclared = schema().with_constraint(ParamConstraint::new("short_window", CompareOp::Less, "slow"));
+    assert!(error_of(undeclared.validate()).contains("uses undeclared parameter 'slow'"));
+
+    let invalid_default = ParamSchema::new()
+        .with_param(ParamSpec::new("window", ParamKind::Int { min: Some(1), max: None }).with_default(0));
+    let error = error_of(invalid_default.validate());
+    assert!(error.contains("invalid default of 'window': 0 is below the minimum 1"), "{}", error);
+}
+
+// --- Grid Search combinations ---
+
+#[test]
+fn grid_combinations_skip_broken_constraints() {
+    let values = |values: &[i64]| values.iter().map(|&value| serde_json::json!(value)).collect::<Vec<_>>();
+    let optimization_config = |schema: ParamSchema| {
+        OptimizationConfig::new()
+            .with_strategy_params_ranges(strategy_params(&[
+                ("short_window", values(&[10, 20, 30])),
+                ("long_window", values(&[20, 30])),
+            ]))
+            .with_pos_sizer_name("mpr".to_string())
+            .with_pos_sizer_value_ranges(vec![1.0, 2.0])
+            .with_slippage_range(vec![0.0])
+            .with_param_schema(Some(schema))
+    };
+
+    let config = optimization_config(schema());
+    let mut pairs: Vec<(i64, i64, f64)> = config
+        .generate_all_combinations_vec()
+        .iter()
+        .map(|params| {
+            let value = |name: &str| params.get_strategy_params()
+                .iter()
+                .find(|(param_name, _)| param_name == name)
+                .and_then(|(_, value)| value.as_i64())
+                .unwrap();
+            (value("short_window"), value("long_window"), *params.get_pos_sizer_value())
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(pairs, vec![
+        (10, 20, 1.0), (10, 20, 2.0),
+        (10, 30, 1.0), (10, 30, 2.0),
+        (20, 30, 1.0), (20, 30, 2.0),
+    ]);
+    assert!(config.has_valid_combinations());
+
+    let impossible = schema().with_constraint(ParamConstraint::new("short_window", CompareOp::Greater, "long_window"));
+    let config = optimization_config(impossible);
+    assert!(config.generate_all_combinations_vec().is_empty());
+    assert!(!config.has_valid_combinations());
+}
@@ -0,0 +1,153 @@
+// farukon_core/tests/vectorized.rs
+
+//! Reference tests of `farukon_core::vectorized`.
+//! The expected PnL, deals and commissions were worked out by hand, bar by bar, with the accounting of the
+//! event-driven portfolio and execution handler: a Si-like contract (step 1, step price 1), a commission of 1% of
+//! the fill price per contract, market orders filled at the high (buy) or the low (sell), limit orders at the limit
+//! or a better open. The series have bar counts not divisible by 4, so the scalar remainder loop is covered too.
+
+mod common;
+
+use farukon_core::data_handler::MarketBar;
+use farukon_core::instruments_info::InstrumentInfo;
+use farukon_core::vectorized::{self, SymbolTargets, VectorizedBacktester};
+
+const SYMBOL: &str = "Si-3.24";
+const INITIAL_CAPITAL: f64 = 100_000.0;
+
+fn instruments_info() -> std::collections::HashMap<String, InstrumentInfo> {
+    let instrument_info: InstrumentInfo = serde_json::from_value(serde_json::json!({
+        "exchange": "FORTS",
+        "type": "futures",
+        "contract_precision": 0,
+        "margin": 10.0,
+        "commission_type": "currency",
+        "trade_from_date": "2024-01-01 10:00:00",
+        "expiration_date": "2024-03-18 10:00:00",
+        "marginal_costs": 0,
+        "step": 1,
+        "step_price": 1
+    }))
+    .unwrap();
+    std::collections::HashMap::from([(SYMBOL.to_string(), instrument_info)])
+}
+
+/// Strategy settings with a commission of 1% of the price per contract, or without commission plans.
+fn strategy_settings(with_commission: bool) -> farukon_core::settings::StrategySettings {
+    let mut strategy_settings = common::strategy_settings();
+    if with_commission {
+        strategy_settings.commission_plans = Some(serde_json::from_value(serde_json::json!({
+            "exchanges": { "FORTS": { "exchange": { "currency": 1.0 } } }
+        })).unwrap());
+    }
+    strategy_settings
+}
+
+/// 5min bars from 2024-02-01 10:00, one per (open, high, low, close).
+fn bars(prices: &[(f64, f64, f64, f64)]) -> Vec<MarketBar> {
+    let start = chrono::NaiveDate::from_ymd_opt(2024, 2, 1).unwrap().and_hms_opt(10, 0, 0).unwrap().and_utc();
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &(open, high, low, close))| MarketBar {
+            datetime: start + chrono::Duration::minutes(5 * i as i64),
+            open,
+            high,
+            low,
+            close,
+            volume: 100,
+            is_filled: false,
+        })
+        .collect()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len(), "{:?} != {:?}", actual, expected);
+    for (actual_value, expected_value) in actual.iter().zip(expected) {
+        assert!((actual_value - expected_value).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn crossover_matches_hand_computed_fills() {
+    let nan = f64::NAN;
+    let bars = bars(&[
+        (100.0, 101.0, 99.0, 100.0),
+        (100.0, 102.0, 99.0, 101.0),
+        (101.0, 103.0, 100.0, 102.0), // fast > slow: buy 2 at a limit of 102
+        (102.0, 104.0, 101.0, 103.0), // limit reached: long at 102
+        (103.0, 106.0, 103.0, 105.0), // fast < slow: sell at market
+        (105.0, 106.0, 104.0, 104.0), // flat at the low, 104; sell 2 at a limit of 104
+        (104.0, 105.0, 103.0, 103.0), // limit reached: short at 104
+        (103.0, 104.0, 100.0, 101.0), // fast > slow: buy at market
+        (101.0, 102.0, 99.0, 100.0),  // flat at the high, 102; buy 2 at a limit of 100
+        (100.5, 101.0, 100.5, 101.0), // limit not reached: the order is dropped
+    ]);
+    let fast = [nan, nan, 2.0, 2.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0];
+    let slow = [nan, nan, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 1.0, 1.0];
+    let instruments_info = instruments_info();
+
+    let symbol_targets = vectorized::crossover_targets(SYMBOL, &bars, &fast, &slow, 2.0, &instruments_info[SYMBOL]).unwrap();
+    assert_eq!(symbol_targets.targets, vec![0.0, 0.0, 0.0, 2.0, 2.0, 0.0, -2.0, -2.0, 0.0, 0.0]);
+    let limit_prices: Vec<Option<f64>> = symbol_targets.limit_prices.iter().map(|price| (!price.is_nan()).then_some(*price)).collect();
+    assert_eq!(limit_prices, vec![None, None, None, Some(102.0), None, None, Some(104.0), None, None, None]);
+
+    // Bar 3: fill (103 - 102) * 2 = 2, commission 2 * 1.02. Bar 4: holding (105 - 103) * 2 = 4.
+    // Bar 5: holding (104 - 105) * 2 = -2, fill 0, commission 2 * 1.04. Bar 6: fill (103 - 104) * -2 = 2, commission 2 * 1.04.
+    // Bar 7: holding (101 - 103) * -2 = 4. Bar 8: holding (100 - 101) * -2 = 2, fill (100 - 102) * 2 = -4, commission 2 * 1.02.
+    let expected_pnl = [0.0, 0.0, 0.0, -0.04, 4.0, -4.08, -0.08, 4.0, -4.04, 0.0];
+    let expected_commission = 2.0 * 1.02 + 2.0 * 1.04 + 2.0 * 1.04 + 2.0 * 1.02;
+
+    let with_commission = strategy_settings(true);
+    let backtester = VectorizedBacktester::new(INITIAL_CAPITAL, &with_commission, &instruments_info);
+    let symbol_pnl = backtester.symbol_pnl(&symbol_targets).unwrap();
+    assert_close(&symbol_pnl.pnl, &expected_pnl);
+    assert_eq!(symbol_pnl.deals_count, 2);
+
+    let metrics = backtester.run(std::slice::from_ref(&symbol_targets)).unwrap();
+    assert!((metrics.get_total_return() - -0.24).abs() < 1e-6, "{}", metrics.get_total_return());
+    assert_eq!(*metrics.get_deals_count(), 2);
+
+    let without_commission = strategy_settings(false);
+    let metrics_without_commission = VectorizedBacktester::new(INITIAL_CAPITAL, &without_commission, &instruments_info)
+        .run(std::slice::from_ref(&symbol_targets))
+        .unwrap();
+    let commission = metrics_without_commission.get_total_return() - metrics.get_total_return();
+    assert!((commission - expected_commission).abs() < 1e-6, "{}", commission);
+}
+
+#[test]
+fn leading_nan_bars_are_never_traded() {
+    let nan = f64::NAN;
+    // The symbol starts trading on bar 2 of the combined timeline; 7 bars leave 3 to the remainder loop.
+    let bars = bars(&[
+        (nan, nan, nan, nan),
+        (nan, nan, nan, nan),
+        (100.0, 101.0, 99.0, 100.0),
+        (100.0, 102.0, 100.0, 101.0), // buy 1 at market: 102
+        (101.0, 103.0, 101.0, 103.0),
+        (103.0, 104.0, 101.0, 102.0), // sell 2 at market: 101
+        (102.0, 102.0, 100.0, 100.0), // buy 1 at market: 102
+    ]);
+    let instruments_info = instruments_info();
+    let symbol_targets = SymbolTargets {
+        symbol: SYMBOL,
+        bars: &bars,
+        targets: vec![0.0, 0.0, 0.0, 1.0, 1.0, -1.0, 0.0],
+        limit_prices: Vec::new(),
+    };
+
+    // Bar 3: fill (101 - 102) * 1 = -1, commission 1.02. Bar 4: holding (103 - 101) * 1 = 2.
+    // Bar 5: holding (102 - 103) * 1 = -1, fill (102 - 101) * -2 = -2, commission 2 * 1.01.
+    // Bar 6: holding (100 - 102) * -1 = 2, fill (100 - 102) * 1 = -2, commission 1.02.
+    let expected_pnl = [0.0, 0.0, 0.0, -2.02, 2.0, -5.02, -1.02];
+
+    let with_commission = strategy_settings(true);
+    let backtester = VectorizedBacktester::new(INITIAL_CAPITAL, &with_commission, &instruments_info);
+    let symbol_pnl = backtester.symbol_pnl(&symbol_targets).unwrap();
+    assert_close(&symbol_pnl.pnl, &expected_pnl);
+    assert_eq!(symbol_pnl.deals_count, 2);
+
+    let metrics = backtester.run(std::slice::from_ref(&symbol_targets)).unwrap();
+    assert!((metrics.get_total_return() - -6.06).abs() < 1e-6, "{}", metrics.get_total_return());
+}
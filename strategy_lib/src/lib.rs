@@ -1,7 +1,5 @@
 // strategy_lib/src/lib.rs
 
-use farukon_core::{self, strategy::Strategy};
-
 /// A simple moving average crossover strategy.
 /// This strategy generates buy/sell signals based on the crossing of two SMAs.
 /// It also handles position exits based on SMA crossover or contract expiration.
@@ -12,12 +10,12 @@ pub struct MovingAverageCrossStrategy {
     strategy_settings: farukon_core::settings::StrategySettings,
     /// Instrument metadata for all symbols traded by this strategy.
     strategy_instruments_info: std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
-    /// The event sender channel used to communicate signals to other components.
-    event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
-    /// The window size for the short-term Simple Moving Average (SMA).
-    short_window: usize,
-    /// The window size for the long-term Simple Moving Average (SMA).
-    long_window: usize,
+    /// The event queue used to communicate signals to other components.
+    event_sender: farukon_core::event::EventQueue,
+    /// Short SMA of the close, read from the indicator cache shared by the backtests of an optimization.
+    short_sma: farukon_core::indicator_cache::IndicatorSpec,
+    /// Long SMA of the close, read from the indicator cache.
+    long_sma: farukon_core::indicator_cache::IndicatorSpec,
 }
 
 impl MovingAverageCrossStrategy {
@@ -29,7 +27,7 @@ impl MovingAverageCrossStrategy {
     /// * `mode` - The operational mode (e.g., "Debug", "Optimize").
     /// * `strategy_settings` - The settings for this strategy, loaded from the JSON config.
     /// * `strategy_instruments_info` - Instrument metadata for all symbols traded by this strategy.
-    /// * `event_sender` - The event sender channel used to communicate signals.
+    /// * `event_sender` - The event queue used to communicate signals.
     ///
     /// # Returns
     /// * `anyhow::Result<Self>` - A new instance of the strategy or an error if initialization fails.
@@ -37,7 +35,7 @@ impl MovingAverageCrossStrategy {
         mode: String,
         strategy_settings: farukon_core::settings::StrategySettings,
         strategy_instruments_info: std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
-        event_sender: std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
+        event_sender: farukon_core::event::EventQueue,
     ) -> anyhow::Result<Self> {
         // Extract the short and long window sizes from the strategy settings.
         let short_window = get_param_as_usize(&strategy_settings.strategy_params, "short_window")?;
@@ -48,15 +46,20 @@ impl MovingAverageCrossStrategy {
             anyhow::bail!("'short_window' ({}) must be less than 'long_window' ({}).", short_window, long_window);
         }
 
+        // The SMAs are looked up by bar in the shared indicator cache: every parameter set
+        // of an optimization with the same window reuses the series computed once.
+        let short_sma = farukon_core::indicator_cache::IndicatorSpec::sma(short_window);
+        let long_sma = farukon_core::indicator_cache::IndicatorSpec::sma(long_window);
+
         // Create and return the new strategy instance.
         anyhow::Ok(
             MovingAverageCrossStrategy {
                 mode,
                 strategy_settings,
                 strategy_instruments_info,
-                short_window: short_window as usize,
-                long_window: long_window as usize,
                 event_sender,                          
+                short_sma,
+                long_sma,
             }
         )
     }
@@ -68,7 +71,7 @@ impl MovingAverageCrossStrategy {
 impl farukon_core::strategy::Strategy for MovingAverageCrossStrategy {
     /// Calculates trading signals based on market data and portfolio state.
     /// This function iterates through each symbol in the symbol list, calculates SMAs,
-    /// checks for crossovers, and sends appropriate signals (LONG, SHORT, EXIT) via the event channel.
+    /// checks for crossovers, and sends appropriate signals (LONG, SHORT, EXIT) via the event queue.
     ///
     /// # Arguments
     /// * `data_handler` - Interface to access market data (OHLCV, timestamps).
@@ -93,8 +96,11 @@ impl farukon_core::strategy::Strategy for MovingAverageCrossStrategy {
             // Get the current capital from the equity point.
             let strategy_instruments_info_for_symbol = self.strategy_instruments_info.get(symbol).unwrap();
 
-            // Get the current datetime for the symbol
-            let current_bar_datetime = data_handler.get_latest_bar_datetime(symbol).unwrap();
+            // Get the current datetime for the symbol.
+            // A symbol may have no bars yet (e.g. with the "skip" gap policy before its first bar).
+            let Some(current_bar_datetime) = data_handler.get_latest_bar_datetime(symbol) else {
+                continue;
+            };
             // Get the latest close price for the symbol.
             let close = Some(data_handler.get_latest_bar_value(symbol, "close").unwrap());
  
@@ -110,14 +116,11 @@ impl farukon_core::strategy::Strategy for MovingAverageCrossStrategy {
             let current_position_state = current_positions.get(symbol).unwrap();
             let current_position_quantity = current_position_state.position;
 
-            // Calculate signals
-            let short_sma_bars = data_handler.get_latest_bars_values(symbol, "close", self.short_window);
-            let long_sma_bars = data_handler.get_latest_bars_values(symbol, "close", self.long_window);
+            // Calculate signals: the SMAs at the latest bar, from the indicator cache.
+            let short_sma = data_handler.get_indicator_value(symbol, &self.short_sma, 0);
+            let long_sma = data_handler.get_indicator_value(symbol, &self.long_sma, 0);
 
-            if let (Some(short_sma), Some(long_sma)) = (
-                farukon_core::indicators::sma(&short_sma_bars, self.short_window),
-                farukon_core::indicators::sma(&long_sma_bars, self.long_window),
-            ) {
+            if let (Some(short_sma), Some(long_sma)) = (short_sma, long_sma) {
                 // Print debug information if in Debug mode.
                 if self.mode == "Debug".to_string() {
                     println!("Start event, Indicators, {}, {}, short_sma: {}, long_sma: {}, current_position: {}", symbol, current_bar_datetime, short_sma, long_sma, current_position_quantity);
@@ -126,7 +129,7 @@ impl farukon_core::strategy::Strategy for MovingAverageCrossStrategy {
                                 
                 // if position exist
                 if current_position_quantity != 0.0 {
-                    let signal_name = "EXIT";
+                    let signal_name = farukon_core::event::SignalName::Exit;
                     // if long position
                     if current_position_quantity > 0.0 {
                         // EXIT LONG
@@ -181,7 +184,7 @@ impl farukon_core::strategy::Strategy for MovingAverageCrossStrategy {
                     current_bar_datetime < expiration_date_dt &&
                     current_bar_datetime >= trade_from_date_dt 
                     {
-                        let signal_name = "LONG";
+                        let signal_name = farukon_core::event::SignalName::Long;
                         let quantity = farukon_core::pos_sizers::get_pos_sizer_from_settings(
                             &self.mode,
                             capital,
@@ -210,7 +213,7 @@ impl farukon_core::strategy::Strategy for MovingAverageCrossStrategy {
                     current_bar_datetime < expiration_date_dt &&
                     current_bar_datetime >= trade_from_date_dt 
                     {
-                        let signal_name = "SHORT";
+                        let signal_name = farukon_core::event::SignalName::Short;
                         let quantity = farukon_core::pos_sizers::get_pos_sizer_from_settings(
                             &self.mode,
                             capital,
@@ -271,131 +274,45 @@ fn get_param_as_usize(params: &std::collections::HashMap<String, Vec<serde_json:
     }
 }
 
-/// C function exported for dynamic loading.
-/// Creates a new instance of the MovingAverageCrossStrategy.
-/// This function is called by the main application when loading the strategy library.
-///
-/// # Arguments
-/// * `mode_cstr` - A C string representing the operational mode.
-/// * `strategy_settings_ptr` - A pointer to the strategy settings struct.
-/// * `strategy_instruments_info_ptr` - A pointer to the instrument info map.
-/// * `event_sender_ptr` - A pointer to the event sender channel.
-///
-/// # Returns
-/// * A raw pointer to the newly created MovingAverageCrossStrategy instance, or null on error.
-#[unsafe(no_mangle)]
-pub extern "C" fn create_strategy(
-    mode_cstr: *const std::os::raw::c_char,
-    strategy_settings_ptr: *const farukon_core::settings::StrategySettings,
-    strategy_instruments_info_ptr: *const std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
-    event_sender_ptr: *const std::sync::mpsc::Sender<Box<dyn farukon_core::event::Event>>,
-) -> *mut MovingAverageCrossStrategy {
-    // Check for null pointers to prevent crashes.
-    if mode_cstr.is_null() || strategy_settings_ptr.is_null() || strategy_settings_ptr.is_null() || event_sender_ptr.is_null() {
-        return std::ptr::null_mut();
+/// Lets the engine construct the strategy through the plugin ABI.
+impl farukon_core::abi::PluginStrategy for MovingAverageCrossStrategy {
+    fn create(
+        mode: String,
+        strategy_settings: farukon_core::settings::StrategySettings,
+        strategy_instruments_info: std::collections::HashMap<String, farukon_core::instruments_info::InstrumentInfo>,
+        event_sender: farukon_core::event::EventQueue,
+    ) -> anyhow::Result<Self> {
+        MovingAverageCrossStrategy::new(mode, strategy_settings, strategy_instruments_info, event_sender)
     }
-    // Convert the C string to a Rust String.
-    let mode = unsafe { std::ffi::CStr::from_ptr(mode_cstr) }.to_string_lossy().into_owned();
-    // Dereference the raw pointers to get the actual values.
-    let strategy_settings_ref = unsafe { &*strategy_settings_ptr }.clone();
-    let strategy_instruments_info_ref = unsafe { &*strategy_instruments_info_ptr }.clone();
-    let event_sender_ref = unsafe { &*event_sender_ptr }.clone();
-    
-    // Attempt to create a new strategy instance.
-    match MovingAverageCrossStrategy::new(
-        mode,
-        strategy_settings_ref,
-        strategy_instruments_info_ref,
-        event_sender_ref,
-    ) {
-        // If successful, box the strategy and return a raw pointer to it.
-        Ok(strategy) => Box::into_raw(Box::new(strategy)),
-        // If an error occurs, return a null pointer.
-        Err(_) => std::ptr::null_mut(),
+
+    /// Both windows are required bar counts; the short one must stay below the long one.
+    fn param_schema() -> Option<farukon_core::param_schema::ParamSchema> {
+        Some(
+            farukon_core::param_schema::ParamSchema::new()
+                .with_param(
+                    farukon_core::param_schema::ParamSpec::new("short_window", farukon_core::param_schema::ParamKind::Int { min: Some(1), max: None })
+                        .with_description("Window of the short SMA, in bars")
+                )
+                .with_param(
+                    farukon_core::param_schema::ParamSpec::new("long_window", farukon_core::param_schema::ParamKind::Int { min: Some(2), max: None })
+                        .with_description("Window of the long SMA, in bars")
+                )
+                .with_constraint(
+                    farukon_core::param_schema::ParamConstraint::new("short_window", farukon_core::param_schema::CompareOp::Less, "long_window")
+                )
+        )
     }
 
 }
 
-/// C function exported for dynamic loading.
-/// Destroys an instance of the MovingAverageCrossStrategy.
-/// This function is called by the main application when unloading the strategy library.
-///
-/// # Arguments
-/// * `strategy` - A raw pointer to the MovingAverageCrossStrategy instance to be destroyed.
-#[unsafe(no_mangle)]
-pub extern "C" fn destroy_strategy(strategy: *mut MovingAverageCrossStrategy) {
-    if !strategy.is_null() {
-        // Reconstruct the Box from the raw pointer and let it go out of scope, triggering the Drop trait.
-        unsafe {
-            let _ = Box::from_raw(strategy);
-        }
-    }
+// Exports the `farukon_plugin_manifest` symbol read by the engine's strategy loader.
+// The name is matched against `strategy_name` in the settings.
+farukon_core::export_strategies! {
+    "MA_cross" => MovingAverageCrossStrategy,
 }
 
-/// C function exported for dynamic loading.
-/// Calls the calculate_signals method on the MovingAverageCrossStrategy instance.
-/// This function is called by the main application during the backtest loop.
-///
-/// # Arguments
-/// * `strategy_ptr` - A raw pointer to the MovingAverageCrossStrategy instance.
-/// * `data_handler_vtable` - A pointer to the VTable for the DataHandler trait object.
-/// * `data_handler_ptr` - A pointer to the DataHandler trait object data.
-/// * `current_positions_ptr` - A pointer to the map of current positions.
-/// * `latest_equity_point_ptr` - A pointer to the latest equity point.
-/// * `symbol_list_ptr` - A pointer to an array of C string pointers representing the symbol list.
-/// * `symbol_list_size` - The size of the symbol list array.
-///
-/// # Returns
-/// * `i32` - 0 on success, -1 on error.
-#[unsafe(no_mangle)]
-pub extern "C" fn calculate_signals(
-    strategy_ptr: *mut std::ffi::c_void,
-    data_handler_vtable: *const farukon_core::DataHandlerVTable,
-    data_handler_ptr: *const (),
-    current_positions_ptr: *mut std::collections::HashMap<String, farukon_core::portfolio::PositionState>,
-    latest_holdings_ptr: *mut farukon_core::portfolio::HoldingSnapshot,
-    symbol_list_ptr: *const *const std::os::raw::c_char,
-    symbol_list_size: usize,
-) -> i32 {
-    if strategy_ptr.is_null() || current_positions_ptr.is_null() || /*latest_holdings_ptr.is_null() ||*/symbol_list_ptr.is_null() {
-        return -1;
-    }
-    // Cast the void pointer to the correct type and get a mutable reference to the strategy.
-    let strategy = unsafe { &mut *(strategy_ptr as *mut MovingAverageCrossStrategy) };
-
-    // Reconstruct the DataHandler trait object from the VTable and data pointers.
-    let data_handler: &dyn farukon_core::data_handler::DataHandler = unsafe {
-        std::mem::transmute::<(*const (), *const ()), &dyn farukon_core::data_handler::DataHandler>((
-            data_handler_ptr,
-            data_handler_vtable as *const(),
-        ))
-    };
-
-    // Get mutable references to the current positions and latest equity point.
-    let current_positions = unsafe { &mut *current_positions_ptr };
-    let latest_holdings = unsafe { &mut *latest_holdings_ptr };
-
-    // Convert the C string array to a Vec<String>.
-    let symbols: Vec<String> = (0..symbol_list_size)
-        .filter_map(|i| unsafe {
-            let str_ptr = *symbol_list_ptr.add(i);
-            if str_ptr.is_null() { return None; }
-            std::ffi::CStr::from_ptr(str_ptr)
-                .to_str()
-                .ok()
-                .map(|s| s.to_string())
-        })
-        .collect();
-
-    // Call the Rust calculate_signals method and return 0 on success or -1 on error.
-    match strategy.calculate_signals(
-        data_handler,
-        current_positions,
-        latest_holdings,
-        &symbols,
-    ) {
-        Ok(_) => 0,
-        Err(_) => -1,
-    }
-    
+// Registers the same strategy in the engine binary, which links this crate:
+// settings without `strategy_path` use it without loading the library.
+farukon_core::register_strategies! {
+    "MA_cross" => MovingAverageCrossStrategy,
 }